@@ -1,5 +1,5 @@
-use crate::ast::type_wrapper::TypeWrapper;
-use crate::lexer::token::Token;
+use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+use crate::lexer::token::{Operator, Tk, Token};
 
 /// AstNode
 ///
@@ -10,6 +10,8 @@ pub enum AstNode {
     ArrayDeclNode(Box<AstNodeWrapper>, Token, Box<AstNodeWrapper>),
     // BinaryNode: Token of operator, expression left and expression right
     BinaryNode(Token, Box<AstNodeWrapper>, Box<AstNodeWrapper>),
+    // CaseNode: Label of the case (null for default) and list of statements
+    CaseNode(Box<AstNodeWrapper>, Vec<AstNodeWrapper>),
     // CastNode: Type of casting and expression to cast
     CastNode(Box<AstNodeWrapper>, Box<AstNodeWrapper>),
     // CompoundNode: List of statements
@@ -36,6 +38,8 @@ pub enum AstNode {
     ProcedureNode(Box<AstNodeWrapper>, Vec<AstNodeWrapper>),
     // SelectorNode: Expression to dereference and expression to be used as selector value
     SelectorNode(Box<AstNodeWrapper>, Box<AstNodeWrapper>),
+    // SwitchNode: Expression to be used as discriminant and list of cases
+    SwitchNode(Box<AstNodeWrapper>, Vec<AstNodeWrapper>),
     // TypeNode: Type to be used
     TypeNode(TypeWrapper),
     // VarDeclNode: Type of the declaration, identifier and (optional) expression
@@ -67,6 +71,7 @@ pub struct AstNodeWrapper {
     pub source_ref: SourceReference,
     pub type_ref: TypeWrapper,
     pub is_lvalue: bool,
+    pub is_parenthesized: bool,
 }
 
 use AstNode::*;
@@ -202,6 +207,26 @@ impl AstNode {
         ForNode(Box::new(an1.clone()), Box::new(an2.clone()), Box::new(an3.clone()), Box::new(an4.clone()))
     }
 
+    /// AstNode::new_switch
+    ///
+    /// Create a SwitchNode
+    ///
+    /// @in [...] What is necessary to build the node
+    /// @return [AstNode] Built node
+    pub fn new_switch(an1: &AstNodeWrapper, an2: &[AstNodeWrapper]) -> AstNode {
+        SwitchNode(Box::new(an1.clone()), an2.to_vec())
+    }
+
+    /// AstNode::new_case
+    ///
+    /// Create a CaseNode
+    ///
+    /// @in [...] What is necessary to build the node
+    /// @return [AstNode] Built node
+    pub fn new_case(an1: &AstNodeWrapper, an2: &[AstNodeWrapper]) -> AstNode {
+        CaseNode(Box::new(an1.clone()), an2.to_vec())
+    }
+
     /// AstNode::new_jump
     ///
     /// Create a JumpNode
@@ -278,6 +303,47 @@ impl AstNodeWrapper {
     /// @in indent[u32] How much to indent
     /// @return [String] Result of the string conversion
     pub fn to_string(&self, indent: u32) -> String {
+        self.format(indent, false)
+    }
+
+    /// AstNodeWrapper::to_string_typed
+    ///
+    /// Same as AstNodeWrapper::to_string, but each expression is followed by its type between
+    /// angle brackets, e.g. `(a<u32> + 1<u32>)<u32>`
+    ///
+    /// @in indent[u32] How much to indent
+    /// @return [String] Result of the string conversion
+    pub fn to_string_typed(&self, indent: u32) -> String {
+        self.format(indent, true)
+    }
+
+    /// AstNodeWrapper::eval_const_expr
+    ///
+    /// Evaluate an integer constant expression, as required by the labels of a case and used for
+    /// the size of an array. The literals can be in any of the forms supported by the lexer
+    /// (decimal, hexadecimal, binary, octal or characters), possibly negated
+    ///
+    /// @return [Option<u32>]: value of the expression, None if it is not constant
+    pub fn eval_const_expr(&self) -> Option<u32> {
+        match &self.node {
+            PrimaryNode(token) => match token.tk {
+                Tk::IntegerLiteral(num, _) => Some(num as u32),
+                Tk::Char(c) => Some(c as u32),
+                _ => None,
+            },
+            PrefixNode(token, expr) if token.tk == Tk::Operator(Operator::Minus) => expr.eval_const_expr().map(|v| (v as i32).wrapping_neg() as u32),
+            _ => None,
+        }
+    }
+
+    /// AstNodeWrapper::format
+    ///
+    /// Implementation of the string conversion
+    ///
+    /// @in indent[u32] How much to indent
+    /// @in show_types[bool] Whether to print the type of the expressions
+    /// @return [String] Result of the string conversion
+    fn format(&self, indent: u32, show_types: bool) -> String {
         let mut result = String::from("");
         match self.node {
             AstNode::CompoundNode(_) => {}
@@ -289,24 +355,24 @@ impl AstNodeWrapper {
             BinaryNode(tk, expr1, expr2) => {
                 result += &format!(
                     "({} {} {})",
-                    expr1.to_string(0).as_str(),
+                    expr1.format(0, show_types).as_str(),
                     tk.tk.to_string().as_str(),
-                    expr2.to_string(0).as_str()
+                    expr2.format(0, show_types).as_str()
                 );
             }
             PrefixNode(tk, expr) => {
-                result += &format!("({}{})", tk.tk.to_string().as_str(), expr.to_string(0).as_str());
+                result += &format!("({}{})", tk.tk.to_string().as_str(), expr.format(0, show_types).as_str());
             }
             CastNode(cn, expr) => {
-                result += &format!("(({}){})", cn.to_string(0).as_str(), expr.to_string(0).as_str());
+                result += &format!("(({}){})", cn.format(0, show_types).as_str(), expr.format(0, show_types).as_str());
             }
             TypeNode(t) => {
                 result += &format!("{}", t.to_string());
             }
             ProcedureNode(expr, args) => {
-                result += &format!("({})(", expr.to_string(0).as_str(),);
+                result += &format!("({})(", expr.format(0, show_types).as_str(),);
                 for i in 0..args.len() {
-                    result += &format!("{}", args[i].to_string(0).as_str(),);
+                    result += args[i].format(0, show_types).as_str();
                     if i != args.len() - 1 {
                         result += ",";
                     }
@@ -314,8 +380,8 @@ impl AstNodeWrapper {
                 result += ")";
             }
             SelectorNode(expr, args) => {
-                result += &format!("(({})", expr.to_string(0).as_str(),);
-                result += &format!("[{}])", args.to_string(0).as_str(),);
+                result += &format!("(({})", expr.format(0, show_types).as_str(),);
+                result += &format!("[{}])", args.format(0, show_types).as_str(),);
             }
             CompoundNode(value) => {
                 if value.len() == 0 {
@@ -323,7 +389,7 @@ impl AstNodeWrapper {
                 } else {
                     result += "{\n";
                     for s in value {
-                        result += &s.to_string(indent + 1);
+                        result += &s.format(indent + 1, show_types);
                     }
                     result += &self.get_indent(indent);
                     result += "}\n";
@@ -331,50 +397,76 @@ impl AstNodeWrapper {
             }
             ExprStatementNode(expr) => {
                 if expr.node != NullNode {
-                    result += &format!("{}", expr.to_string(0).as_str(),);
+                    result += expr.format(0, show_types).as_str();
                 }
                 result += ";\n";
             }
             IfNode(expr, statements_if, statements_else) => {
-                result += &format!("if({}){}", &expr.to_string(0).as_str(), &statements_if.to_string(indent).as_str());
-                let else_print = statements_else.to_string(indent);
+                result += &format!(
+                    "if({}){}",
+                    &expr.format(0, show_types).as_str(),
+                    &statements_if.format(indent, show_types).as_str()
+                );
+                let else_print = statements_else.format(indent, show_types);
                 if else_print.len() as u32 > (indent as u32) * 2 {
                     result += &self.get_indent(indent);
                     result += &format!("else{}", else_print);
                 }
             }
             WhileNode(expr, statements) => {
-                result += &format!("while({}){}", &expr.to_string(0).as_str(), &statements.to_string(indent).as_str());
+                result += &format!(
+                    "while({}){}",
+                    &expr.format(0, show_types).as_str(),
+                    &statements.format(indent, show_types).as_str()
+                );
             }
             ForNode(decl, expr, ass, statements) => {
                 result += &format!(
                     "for({}; {}; {}){}",
-                    &decl.to_string(0).as_str(),
-                    &expr.to_string(0).as_str(),
-                    &ass.to_string(0).as_str(),
-                    &statements.to_string(indent).as_str()
+                    &decl.format(0, show_types).as_str(),
+                    &expr.format(0, show_types).as_str(),
+                    &ass.format(0, show_types).as_str(),
+                    &statements.format(indent, show_types).as_str()
                 );
             }
+            SwitchNode(expr, cases) => {
+                result += &format!("switch({}){{\n", &expr.format(0, show_types).as_str());
+                for c in cases {
+                    result += &c.format(indent + 1, show_types);
+                }
+                result += &self.get_indent(indent);
+                result += "}\n";
+            }
+            CaseNode(label, statements) => {
+                if label.node == NullNode {
+                    result += "default:\n";
+                } else {
+                    result += &format!("case {}:\n", label.format(0, show_types).as_str());
+                }
+                for s in statements {
+                    result += &s.format(indent + 1, show_types);
+                }
+            }
             JumpNode(kw, expr) => match expr.node {
                 NullNode => {
                     result += &format!("{};\n", kw.tk.to_string().as_str(),);
                 }
                 _ => {
-                    result += &format!("{} {};\n", kw.tk.to_string().as_str(), expr.to_string(0).as_str());
+                    result += &format!("{} {};\n", kw.tk.to_string().as_str(), expr.format(0, show_types).as_str());
                 }
             },
             VarDeclNode(tt, id, expr) => {
-                result += &format!("{} {}", tt.to_string(0).as_str(), id.tk.to_string().as_str());
+                result += &format!("{} {}", tt.format(0, show_types).as_str(), id.tk.to_string().as_str());
                 if expr.node == NullNode {
                     result += &format!(";\n");
                 } else {
-                    result += &format!(" = {};\n", expr.to_string(0).as_str(),);
+                    result += &format!(" = {};\n", expr.format(0, show_types).as_str(),);
                 }
             }
             FuncDeclNode(tt, id, args, body) => {
-                result += &format!("{} {}(", tt.to_string(0).as_str(), id.tk.to_string().as_str());
+                result += &format!("{} {}(", tt.format(0, show_types).as_str(), id.tk.to_string().as_str());
                 for i in 0..args.len() {
-                    result += &format!("{}", args[i].to_string(0).as_str(),);
+                    result += args[i].format(0, show_types).as_str();
                     if i != args.len() - 1 {
                         result += &format!(",");
                     }
@@ -383,20 +475,30 @@ impl AstNodeWrapper {
                 if body.node == NullNode {
                     result += &format!(";\n");
                 } else {
-                    result += &format!("{}", body.to_string(indent));
+                    result += &body.format(indent, show_types);
                 }
             }
             ParameterNode(id, tt) => {
-                result += &format!("{} {}", tt.to_string(0).as_str(), id.tk.to_string().as_str());
+                result += &format!("{} {}", tt.format(0, show_types).as_str(), id.tk.to_string().as_str());
             }
             ArrayDeclNode(tt, id, arg) => {
-                result += &format!("{} {}", tt.to_string(0).as_str(), id.tk.to_string().as_str(),);
-                result += &format!("[{}];\n", arg.to_string(0).as_str(),);
+                result += &format!("{} {}", tt.format(0, show_types).as_str(), id.tk.to_string().as_str(),);
+                result += &format!("[{}];\n", arg.format(0, show_types).as_str(),);
             }
             DeclarationList(list) => {
                 for l in list {
-                    result += &format!("{}", l.to_string(indent).as_str())
+                    result += l.format(indent, show_types).as_str()
+                }
+            }
+        }
+
+        // Expressions are followed by their type
+        if show_types && self.type_ref.type_native != TypeNative::Null {
+            match self.node {
+                PrimaryNode(..) | BinaryNode(..) | PrefixNode(..) | CastNode(..) | ProcedureNode(..) | SelectorNode(..) => {
+                    result += &format!("<{}>", self.type_ref.to_string());
                 }
+                _ => {}
             }
         }
 
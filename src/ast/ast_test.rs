@@ -2,8 +2,9 @@ mod test {
 
     #[test]
     fn ast_test() {
-        use crate::ast::ast_impl::{AstNode, AstNodeWrapper, TypeNative, TypeWrapper};
-        use crate::lexer::lexer_impl::{Operator, Tk, Token};
+        use crate::ast::ast_node::{AstNode, AstNodeWrapper};
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::lexer::token::{Operator, Radix, Tk, Token};
 
         let ast_expr1 = AstNodeWrapper {
             node: AstNode::new_primary(&Token {
@@ -27,7 +28,7 @@ mod test {
 
         let ast_expr2 = AstNodeWrapper {
             node: AstNode::new_primary(&Token {
-                tk: Tk::IntegerLiteral(3),
+                tk: Tk::IntegerLiteral(3, Radix::Decimal),
                 line_number: 0,
                 last_character: 0,
                 first_character: 0,
@@ -37,7 +38,7 @@ mod test {
 
         let ast_expr5 = AstNodeWrapper {
             node: AstNode::new_primary(&Token {
-                tk: Tk::IntegerLiteral(6),
+                tk: Tk::IntegerLiteral(6, Radix::Decimal),
                 line_number: 0,
                 last_character: 0,
                 first_character: 0,
@@ -148,4 +149,116 @@ else{
 "
         );
     }
+
+    #[test]
+    fn ast_test_typed() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let input = String::from(
+            "
+            u8 g[4];
+
+            i32 f(u32* p, u8 c) {
+              i32 v = (i32)p[1];
+              if (c == 'x') {
+                v = -v;
+              } else {
+                g[0] = c;
+              }
+              return v;
+            }
+
+            void main() {
+              u32 a[2];
+              f(&a[0], (u8)3);
+              return;
+            }
+",
+        );
+
+        let expected = String::from(
+            "u8 g[4<u32>];
+i32 f(u32* p,u8 c){
+  i32 v = ((i32)((p<u32*>)[1<u32>])<u32>)<i32>;
+  if((c<u8> == 'x'<u8>)<u8>){
+    (v<i32> = (-v<i32>)<i32>)<i32>;
+  }
+  else{
+    (((g<u8*>)[0<u32>])<u8> = c<u8>)<u8>;
+  }
+  return v<i32>;
+}
+void main(){
+  u32 a[2<u32>];
+  (f<i32 (*)(u32*, u8)>)((&((a<u32*>)[0<u32>])<u32>)<u32*>,((u8)3<u32>)<u8>)<i32>;
+  return;
+}
+",
+        );
+
+        let mut l = Lexer::new(input, false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast_wrapped = p.parse();
+        assert!(ast_wrapped.is_some());
+        assert_eq!(expected, ast_wrapped.unwrap().to_string_typed(0));
+    }
+
+    #[test]
+    fn ast_test_type_size() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+
+        let new_type = |type_native: TypeNative, pointer: u32| TypeWrapper {
+            type_native,
+            pointer,
+            constant: false,
+        };
+
+        // Pointers are 4 bytes long and unsigned, whatever the pointed type is
+        assert_eq!(new_type(TypeNative::I32, 1).get_size(), 4);
+        assert!(!new_type(TypeNative::I32, 1).is_signed());
+        assert_eq!(new_type(TypeNative::I8, 1).get_size(), 4);
+        assert!(!new_type(TypeNative::I8, 1).is_signed());
+        assert_eq!(new_type(TypeNative::U8, 2).get_size(), 4);
+        assert!(!new_type(TypeNative::U8, 2).is_signed());
+        assert_eq!(new_type(TypeNative::Void, 1).get_size(), 4);
+        assert!(!new_type(TypeNative::Void, 1).is_signed());
+
+        // Non-pointer types
+        assert_eq!(new_type(TypeNative::I32, 0).get_size(), 4);
+        assert!(new_type(TypeNative::I32, 0).is_signed());
+        assert_eq!(new_type(TypeNative::I16, 0).get_size(), 2);
+        assert_eq!(new_type(TypeNative::U8, 0).get_size(), 1);
+        assert!(!new_type(TypeNative::U8, 0).is_signed());
+    }
+
+    #[test]
+    #[should_panic]
+    fn ast_test_void_size() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+
+        // `void` has no size
+        TypeWrapper {
+            type_native: TypeNative::Void,
+            pointer: 0,
+            constant: false,
+        }
+        .get_size();
+    }
+
+    #[test]
+    #[should_panic]
+    fn ast_test_void_sign() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+
+        // `void` has no sign
+        TypeWrapper {
+            type_native: TypeNative::Void,
+            pointer: 0,
+            constant: false,
+        }
+        .is_signed();
+    }
 }
@@ -5,13 +5,19 @@ use crate::lexer::token::{Keyword, Tk, Token};
 /// Possible list of native types in the language
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum TypeNative {
+    U64,
     U32,
     U16,
     U8,
+    I64,
     I32,
     I16,
     I8,
+    Bool,
     Void,
+    // Function, with its return type and the types of its arguments. It is only used behind a
+    // pointer, as a function is always accessed through its address
+    Function(Box<TypeWrapper>, Vec<TypeWrapper>),
     #[default]
     Null,
 }
@@ -37,13 +43,16 @@ impl TypeNative {
     pub fn from_token(tk: &Token) -> TypeNative {
         match &tk.tk {
             Tk::Keyword(k) => match k {
-                Keyword::U8 => return TypeNative::U8,
-                Keyword::U16 => return TypeNative::U16,
-                Keyword::U32 => return TypeNative::U32,
-                Keyword::I8 => return TypeNative::I8,
-                Keyword::I16 => return TypeNative::I16,
-                Keyword::I32 => return TypeNative::I32,
-                Keyword::Void => return TypeNative::Void,
+                Keyword::U8 => TypeNative::U8,
+                Keyword::U16 => TypeNative::U16,
+                Keyword::U64 => TypeNative::U64,
+                Keyword::U32 => TypeNative::U32,
+                Keyword::I8 => TypeNative::I8,
+                Keyword::I16 => TypeNative::I16,
+                Keyword::I64 => TypeNative::I64,
+                Keyword::I32 => TypeNative::I32,
+                Keyword::Bool => TypeNative::Bool,
+                Keyword::Void => TypeNative::Void,
                 _ => panic!("Cannot create type from non-type keyword"),
             },
             _ => {
@@ -56,7 +65,8 @@ impl TypeNative {
 impl TypeWrapper {
     /// TypeWrapper::to_string
     ///
-    /// Print the type with the const keyword, type and pointers
+    /// Print the type with the const keyword, type and pointers. A pointer to function is printed
+    /// as in C, without identifier: `u32 (*)(u32, u8*)`
     ///
     /// @return [String] Result
     pub fn to_string(&self) -> String {
@@ -66,15 +76,30 @@ impl TypeWrapper {
             result += "const ";
         }
 
+        if let TypeNative::Function(return_type, arguments) = &self.type_native {
+            let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+            return format!(
+                "{}{} ({})({})",
+                result,
+                return_type.to_string(),
+                "*".repeat(self.pointer as usize),
+                arguments.join(", ")
+            );
+        }
+
         match &self.type_native {
+            TypeNative::U64 => result += "u64",
             TypeNative::U32 => result += "u32",
             TypeNative::U16 => result += "u16",
             TypeNative::U8 => result += "u8",
+            TypeNative::I64 => result += "i64",
             TypeNative::I32 => result += "i32",
             TypeNative::I16 => result += "i16",
             TypeNative::I8 => result += "i8",
+            TypeNative::Bool => result += "bool",
             TypeNative::Void => result += "void",
             TypeNative::Null => result += "null",
+            TypeNative::Function(..) => {}
         };
 
         for _ in 0..self.pointer {
@@ -86,7 +111,9 @@ impl TypeWrapper {
 
     /// TypeWrapper::get_size
     ///
-    /// Get size in bytes of a give type
+    /// Get size in bytes of a give type. A pointer is always 4 bytes long, whatever the pointed
+    /// type is (`void*` included), while a non-pointer `void` has no size: asking for it is a
+    /// bug in the caller, thus the function panics
     ///
     /// @return [u32] size
     pub fn get_size(&self) -> u32 {
@@ -94,29 +121,75 @@ impl TypeWrapper {
             return 4;
         }
         match &self.type_native {
-            TypeNative::U8 | TypeNative::I8 => 1,
+            TypeNative::U8 | TypeNative::I8 | TypeNative::Bool => 1,
             TypeNative::U16 | TypeNative::I16 => 2,
             TypeNative::U32 | TypeNative::I32 => 4,
+            TypeNative::U64 | TypeNative::I64 => 8,
             _ => panic!("Cannot get size of non-sized type"),
         }
     }
 
     /// TypeWrapper::is_signed
     ///
-    /// Get if the type is signed or not
+    /// Get if the type is signed or not. Pointers are addresses, thus they are always compared,
+    /// divided and extended as unsigned values, whatever the pointed type is. A non-pointer
+    /// `void` has no sign: asking for it is a bug in the caller, thus the function panics
     ///
-    /// @return [u32] size
+    /// @return [bool] whether the type is signed
     pub fn is_signed(&self) -> bool {
         if self.pointer != 0 {
             return false;
         }
         match &self.type_native {
-            TypeNative::U8 | TypeNative::U16 | TypeNative::U32 => false,
-            TypeNative::I8 | TypeNative::I16 | TypeNative::I32 => true,
+            TypeNative::U8 | TypeNative::U16 | TypeNative::U32 | TypeNative::U64 | TypeNative::Bool => false,
+            TypeNative::I8 | TypeNative::I16 | TypeNative::I32 | TypeNative::I64 => true,
             _ => panic!("Cannot get sign of non-signed type"),
         }
     }
 
+    /// TypeWrapper::is_bool
+    ///
+    /// Get if the type is a non-pointer `bool`, whose values are always either 0 or 1
+    ///
+    /// @return [bool] whether the type is `bool`
+    pub fn is_bool(&self) -> bool {
+        self.pointer == 0 && self.type_native == TypeNative::Bool
+    }
+
+    /// TypeWrapper::is_wide
+    ///
+    /// Get if the type is a non-pointer integer of 64 bits, which the backend stores in a pair of
+    /// registers
+    ///
+    /// @return [bool] whether the type is `u64` or `i64`
+    pub fn is_wide(&self) -> bool {
+        self.pointer == 0 && (self.type_native == TypeNative::U64 || self.type_native == TypeNative::I64)
+    }
+
+    /// TypeWrapper::is_function_pointer
+    ///
+    /// Get if the type is a pointer to function, whose value is the address of the code of a
+    /// function. As the size of a function is unknown, it can only be called, compared and copied
+    ///
+    /// @return [bool] whether the type is a pointer to function
+    pub fn is_function_pointer(&self) -> bool {
+        self.pointer == 1 && matches!(self.type_native, TypeNative::Function(..))
+    }
+
+    /// TypeWrapper::discards_const
+    ///
+    /// Check whether using a value of type `src` where a value of type `dest` is expected would
+    /// discard the const qualifier, allowing a constant to be modified through a pointer (as in
+    /// `u32 *p = &x` with `x` declared as const). Non-pointer values are copies, thus they can
+    /// always be used
+    ///
+    /// @in dest [&TypeWrapper]: expected type
+    /// @in src [&TypeWrapper]: type of the value
+    /// @return [bool] whether the qualifier would be discarded
+    pub fn discards_const(dest: &TypeWrapper, src: &TypeWrapper) -> bool {
+        src.pointer != 0 && src.constant && !dest.constant
+    }
+
     /// TypeWrapper::are_compatible
     ///
     /// Check whether two types are compatible or not. This function can be expanded in order to
@@ -132,6 +205,53 @@ impl TypeWrapper {
         }
         return true;
     }
+
+    /// TypeWrapper::is_integer
+    ///
+    /// Get if the type is a non-pointer integer type (`bool` included)
+    ///
+    /// @return [bool] whether the type is an integer
+    pub fn is_integer(&self) -> bool {
+        self.pointer == 0 && self.type_native != TypeNative::Void && self.type_native != TypeNative::Null
+    }
+
+    /// TypeWrapper::are_comparable
+    ///
+    /// Check whether two types can be compared: either they are compatible, or they are both
+    /// integers, which are converted to their common type (see `common_type`)
+    ///
+    /// @in a [&TypeWrapper]: first type
+    /// @in b [&TypeWrapper]: second type
+    /// @return [bool] result of the check
+    pub fn are_comparable(a: &TypeWrapper, b: &TypeWrapper) -> bool {
+        TypeWrapper::are_compatible(a, b) || (a.is_integer() && b.is_integer())
+    }
+
+    /// TypeWrapper::common_type
+    ///
+    /// Get the type in which two comparable operands are compared, following the usual arithmetic
+    /// conversions of C: compatible operands keep their type, otherwise both are promoted to 32
+    /// bits (64 bits if one of them is that large), and the comparison is unsigned if one of
+    /// them is unsigned of that size
+    ///
+    /// @in a [&TypeWrapper]: first type
+    /// @in b [&TypeWrapper]: second type
+    /// @return [TypeWrapper] common type of the operands
+    pub fn common_type(a: &TypeWrapper, b: &TypeWrapper) -> TypeWrapper {
+        if TypeWrapper::are_compatible(a, b) {
+            return a.clone();
+        }
+        let (unsigned, signed) = if a.get_size() == 8 || b.get_size() == 8 {
+            (TypeNative::U64, TypeNative::I64)
+        } else {
+            (TypeNative::U32, TypeNative::I32)
+        };
+        let is_unsigned = a.type_native == unsigned || b.type_native == unsigned;
+        TypeWrapper {
+            type_native: if is_unsigned { unsigned } else { signed },
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for TypeWrapper {
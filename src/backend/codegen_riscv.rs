@@ -1,9 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
 
-use crate::ast::type_wrapper::TypeWrapper;
-use crate::backend::riscv_isa::{RiscvInstruction, RiscvInstructionType, A0, FP, GP, RA, SP, TP, X0};
+use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+use crate::backend::riscv_isa::{RiscvInstruction, RiscvInstructionType, A0, FP, RA, SP, TP, X0};
 use crate::lexer::token::Operator;
 use crate::lirgen::irnode::{CompareType, IrNode};
+use crate::lirgen::lirgen::EXIT_CODE;
+use crate::parser::symbol_table::Declaration;
 
 use IrNode::*;
 use RiscvInstructionType::*;
@@ -13,6 +16,25 @@ use RiscvInstructionType::*;
 /// Initial value of the stack pointer, to be used in the `init` function
 const SP_INIT_VALUE: i32 = 0x00010000;
 
+/// STACK_ALIGNMENT
+///
+/// Alignment of `sp` required by the ABI. The activation records, the arrays whose size is known at
+/// runtime and the space for the arguments of a call passed on the stack are all multiples of it,
+/// so that `sp` is aligned whenever a function is called
+const STACK_ALIGNMENT: u32 = 16;
+
+/// N_GENERAL_REGISTERS
+///
+/// Number of physical registers which can be used by the allocator with no restrictions: `t0..t6`
+/// and `s1..s9` (`s10` and `s11` are used for the virtual registers on the TP stack)
+const N_GENERAL_REGISTERS: usize = 16;
+
+/// N_ARGUMENT_REGISTERS
+///
+/// Number of argument registers `a0..a7`, which can be used by the allocator when they are not
+/// required by the calling convention
+const N_ARGUMENT_REGISTERS: usize = 8;
+
 /// struct StackOffset
 ///
 /// Struct to store the information of the variables allocated on the stack. At the beginning of
@@ -27,6 +49,44 @@ pub struct StackOffset {
     name: String, // name of the variable
 }
 
+/// struct Frame
+///
+/// Part of the activation record which depends on the register allocation: the slots used to save
+/// the registers (with respect to the frame pointer) and the space required on the TP stack for
+/// the spilled virtual registers
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+struct Frame {
+    saved: Vec<(i32, i32)>,        // physical registers with a save slot, with the offset of the slot
+    callee_saved: Vec<(i32, i32)>, // `s` registers to save in the preamble, with the offset of the slot
+    tp_size: i32,                  // bytes reserved on the TP stack
+}
+
+/// enum CodegenError
+///
+/// Errors which can be found while generating the code. Apart from `Unsupported`, all of them are
+/// due to an IR which is not valid, as lirgen and the optimizer are never expected to produce one
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CodegenError {
+    UnexpectedNode(String),     // Node of a kind which is not expected in its position
+    RegisterAllocation(String), // Virtual register which cannot be associated to a physical one
+    StackSize(u32),             // Size of an activation record which is not valid
+    Unsupported(String),        // Valid node which the backend cannot translate yet
+}
+
+impl fmt::Display for CodegenError {
+    /// CodegenError::fmt
+    ///
+    /// Write the message associated to an error
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodegenError::UnexpectedNode(message) => write!(f, "unexpected node: {}", message),
+            CodegenError::RegisterAllocation(message) => write!(f, "register allocation failed: {}", message),
+            CodegenError::StackSize(ssa) => write!(f, "activation record of {} bytes is not valid", ssa),
+            CodegenError::Unsupported(message) => write!(f, "not supported: {}", message),
+        }
+    }
+}
+
 /// struct Codegen
 ///
 /// Struct to handle the codegen process.
@@ -41,15 +101,45 @@ pub struct Codegen {
     // the immediate instructions
     constants_map: HashMap<u32, u32>,
 
-    // Stack used by the `init` function, containing the global variables
-    global_stack_position: Vec<StackOffset>,
+    // Map of the registers containing constant values, including the ones which do not fit in an
+    // immediate. This is used to initialize the global variables at compile time
+    constant_values: HashMap<u32, u32>,
+
+    // Map of the registers containing the address of a label
+    global_addresses: HashMap<u32, String>,
+
+    // Map of the registers of 64 bits values to the registers storing their upper 32 bits, the
+    // lower ones being in the register itself
+    high_registers: HashMap<u32, i32>,
+
+    // Content of the `.data` section, with the global variables initialized at compile time
+    data_section: Vec<RiscvInstruction>,
+
+    // Content of the `.bss` section, with the global variables initialized with zero or at runtime
+    bss_section: Vec<RiscvInstruction>,
+
+    // Required level of optimization
+    opt: u32,
+
+    // Whether the target supports the M extension (multiplication and division)
+    m_extension: bool,
+
+    // Software routines used in place of multiplications and divisions, to be added to the
+    // program, if the M extension is not available
+    software_routines: Vec<String>,
+
+    // Name of the source file, if the `.file`/`.loc` directives are to be emitted for debugging
+    debug_file: Option<String>,
 }
 
 impl Codegen {
     /// Codegen::new
     ///
     /// Create a new codegen object.
-    pub fn new() -> Codegen {
+    ///
+    /// @in opt [u32]: required level of optimization
+    /// @in m_extension [bool]: whether the target supports the M extension (rv32im) or not (rv32i)
+    pub fn new(opt: u32, m_extension: bool) -> Codegen {
         Codegen {
             // The value of current_new_register is initialized to a very large value, so that it
             // will not collide with the registers used by the linear IR
@@ -57,10 +147,37 @@ impl Codegen {
             // Initially empty
             constants_map: HashMap::new(),
             // Initially empty
-            global_stack_position: vec![],
+            constant_values: HashMap::new(),
+            // Initially empty
+            global_addresses: HashMap::new(),
+            // Initially empty
+            high_registers: HashMap::new(),
+            // Initially empty
+            data_section: vec![],
+            // Initially empty
+            bss_section: vec![],
+            // Provided by the user
+            opt,
+            // Provided by the target
+            m_extension,
+            // Initially empty
+            software_routines: vec![],
+            // Disabled by default
+            debug_file: None,
         }
     }
 
+    /// Codegen::set_debug_lines
+    ///
+    /// Enable the emission of the directives mapping the instructions back to the source: a
+    /// `.file` directive at the beginning of the program and a `.loc` directive each time the
+    /// line of the source the instructions come from changes
+    ///
+    /// @in file_name [String]: name of the source file
+    pub fn set_debug_lines(&mut self, file_name: String) {
+        self.debug_file = Some(file_name);
+    }
+
     /// Codegen::get_new_register
     ///
     /// Get a new register
@@ -72,20 +189,294 @@ impl Codegen {
         return result;
     }
 
+    /// Codegen::get_high_register
+    ///
+    /// Get the register storing the upper 32 bits of a 64 bits value, creating it the first time
+    /// the value is met
+    ///
+    /// @in register [u32]: register of the value, storing its lower 32 bits
+    /// @return [i32]: register storing its upper 32 bits
+    fn get_high_register(&mut self, register: u32) -> i32 {
+        if let Some(high) = self.high_registers.get(&register) {
+            return *high;
+        }
+        let high = self.get_new_register();
+        self.high_registers.insert(register, high);
+        high
+    }
+
+    /// Codegen::is_wide
+    ///
+    /// Check whether a type is an integer of 64 bits, whose values occupy a pair of registers
+    ///
+    /// @in tt [&TypeWrapper]: type to check
+    /// @return [bool]: true if the type is `u64` or `i64`
+    fn is_wide(tt: &TypeWrapper) -> bool {
+        tt.is_wide()
+    }
+
+    /// Codegen::is_immediate
+    ///
+    /// Check whether a constant can be used as immediate of an arithmetic instruction, that is
+    /// whether it is in the range of a signed 12 bits integer
+    ///
+    /// @in value [u32]: constant to check
+    /// @return [bool]: true if the constant fits in the immediate
+    fn is_immediate(value: u32) -> bool {
+        let value = value as i32;
+        (-(1 << 11)..(1 << 11)).contains(&value)
+    }
+
+    /// Codegen::load_constant
+    ///
+    /// Get the instructions to load a constant in a register. If the constant fits in the 12 bits
+    /// immediate of ADDI (negative values included), a single instruction is enough, and the
+    /// register is added to the list of registers storing constants, so that if it is found in an
+    /// arithmetic instruction it can be substituted with the correspondent constant. Otherwise, a
+    /// LUI is required for the upper 20 bits. Since the immediate of ADDI is sign extended, when
+    /// bit 11 of the constant is set the lower part is negative, and the upper part has to be
+    /// incremented by one to compensate
+    ///
+    /// @in dest [u32]: register to use
+    /// @in value [u32]: constant to load
+    /// @return [Vec<RiscvInstruction>]: list of instructions to use
+    fn load_constant(&mut self, dest: u32, value: u32) -> Vec<RiscvInstruction> {
+        let mut result: Vec<RiscvInstruction> = vec![];
+        self.constant_values.insert(dest, value);
+
+        if Codegen::is_immediate(value) {
+            self.constants_map.insert(dest, value);
+            result.push(RiscvInstruction {
+                tt: ADDI,
+                dest: dest as i32,
+                src1: X0,
+                immediate: value as i32,
+                comment: format!("# Load constant {} in register", value as i32),
+                ..Default::default()
+            });
+            return result;
+        }
+
+        let lower = ((value << 20) as i32) >> 20;
+        let upper = value.wrapping_sub(lower as u32) >> 12;
+        result.push(RiscvInstruction {
+            tt: LUI,
+            dest: dest as i32,
+            immediate: upper as i32,
+            comment: "# Constant larger than 2**11".to_string(),
+            ..Default::default()
+        });
+        if lower != 0 {
+            result.push(RiscvInstruction {
+                tt: ADDI,
+                dest: dest as i32,
+                src1: dest as i32,
+                immediate: lower,
+                comment: format!("# Load constant {} in register", value as i32),
+                ..Default::default()
+            });
+        }
+        result
+    }
+
+    /// Codegen::convert_global_alloc
+    ///
+    /// Global variables are not stored on the stack, but they are labeled data in the `.data`
+    /// section (if their initial value is known at compile time) or in the `.bss` section
+    /// (otherwise). In the `init` function, the destination register of the allocation gets the
+    /// address of the variable, and the initial value is stored there if it is computed at runtime.
+    /// The content of an array is labeled as `<name>.data`, while `<name>` is the pointer to it
+    /// (`.` is not allowed in an identifier, so no collision is possible).
+    ///
+    /// @in node [&IrNode]: Alloc node of a global variable
+    /// @return [Result<Option<Vec<RiscvInstruction>>, CodegenError>]: list of instructions for the
+    /// `init` function. None is returned if the node is about an array whose size is not known at
+    /// compile time, which then has to be allocated on the stack
+    fn convert_global_alloc(&mut self, node: &IrNode) -> Result<Option<Vec<RiscvInstruction>>, CodegenError> {
+        let (tt, dest, src, size, from_reg, name, radix) = if let Alloc(tt, dest, src, _, size, from_reg, name, radix, _) = node {
+            (tt, dest, src, size, from_reg, name, radix)
+        } else {
+            return Err(CodegenError::UnexpectedNode(format!(
+                "`{}` provided to `convert_global_alloc`",
+                node.to_string().trim()
+            )));
+        };
+        let mut result: Vec<RiscvInstruction> = vec![];
+        let size_bytes = tt.get_size();
+        let label = if *from_reg { format!("{}.data", name) } else { name.to_string() };
+        let data_type = match size_bytes {
+            4 => WORD,
+            2 => HALF,
+            _ => BYTE,
+        };
+
+        // Get the data of the variable, and whether it belongs to the `.bss` section or not
+        let (data, is_bss) = if *from_reg {
+            match self.constant_values.get(size) {
+                Some(length) => (
+                    RiscvInstruction {
+                        tt: ZERO,
+                        immediate: (length * size_bytes) as i32,
+                        ..Default::default()
+                    },
+                    true,
+                ),
+                None => return Ok(None),
+            }
+        } else {
+            match (self.constant_values.get(src), self.global_addresses.get(src)) {
+                (Some(value), _) => (
+                    RiscvInstruction {
+                        tt: data_type,
+                        immediate: *value as i32,
+                        is_unsigned: !tt.is_signed(),
+                        radix: radix.clone(),
+                        ..Default::default()
+                    },
+                    false,
+                ),
+                (_, Some(address)) => (
+                    RiscvInstruction {
+                        tt: data_type,
+                        name: address.to_string(),
+                        ..Default::default()
+                    },
+                    false,
+                ),
+                _ => (
+                    RiscvInstruction {
+                        tt: ZERO,
+                        immediate: size_bytes as i32,
+                        ..Default::default()
+                    },
+                    true,
+                ),
+            }
+        };
+
+        // If the variable has an initial value which was not known at compile time, it has to be
+        // stored at runtime
+        let is_dynamic = !*from_reg && *src != 0 && is_bss;
+
+        let section = if is_bss { &mut self.bss_section } else { &mut self.data_section };
+        // The exit code of the program is visible outside of the assembly, so that a runtime can
+        // read it once the execution is over
+        if name == EXIT_CODE {
+            section.push(RiscvInstruction {
+                tt: GLOBL,
+                name: label.clone(),
+                ..Default::default()
+            });
+        }
+        section.push(RiscvInstruction {
+            tt: ALIGN,
+            immediate: size_bytes.trailing_zeros() as i32,
+            ..Default::default()
+        });
+        section.push(RiscvInstruction {
+            tt: LABELDATA,
+            name: label.clone(),
+            ..Default::default()
+        });
+        section.push(data);
+
+        // The destination register gets the address of the variable
+        result.push(RiscvInstruction {
+            tt: LA,
+            dest: *dest as i32,
+            name: label.clone(),
+            comment: format!("# Load address of global variable {}", name),
+            ..Default::default()
+        });
+        self.global_addresses.insert(*dest, label);
+
+        if is_dynamic {
+            result.push(RiscvInstruction {
+                tt: match size_bytes {
+                    8 | 4 => SW,
+                    2 => SH,
+                    _ => SB,
+                },
+                src1: *dest as i32,
+                src2: *src as i32,
+                immediate: 0,
+                comment: format!("# Initialize global variable {}", name),
+                ..Default::default()
+            });
+            if size_bytes == 8 {
+                let high = self.get_high_register(*src);
+                result.push(RiscvInstruction {
+                    tt: SW,
+                    src1: *dest as i32,
+                    src2: high,
+                    immediate: 4,
+                    comment: format!("# Initialize upper bits of global variable {}", name),
+                    ..Default::default()
+                });
+            }
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Codegen::get_arguments_load
+    ///
+    /// At the beginning of a function, the arguments are moved in the virtual registers `v1..vn`.
+    /// The first 8 arguments are provided in the registers `a0..a7`, while the additional
+    /// arguments are provided on the stack, before the new FP
+    ///
+    /// @in [&[TypeWrapper]]: list of arguments of the function
+    /// @return [Vec<RiscvInstruction>]: list of instructions loading the arguments
+    fn get_arguments_load(&self, args: &[TypeWrapper]) -> Vec<RiscvInstruction> {
+        let mut result: Vec<RiscvInstruction> = vec![];
+        for i in 0..args.len() {
+            if i < 8 {
+                result.push(RiscvInstruction {
+                    tt: ADD,
+                    dest: i as i32 + 1,
+                    src1: A0 - i as i32,
+                    src2: X0,
+                    comment: format!("# Load argument {} from register", i + 1),
+                    ..Default::default()
+                });
+            } else {
+                result.push(RiscvInstruction {
+                    tt: LW,
+                    dest: i as i32 + 1,
+                    src1: FP,
+                    immediate: (i as i32 - 8) * 4,
+                    comment: format!("# Load argument {} from stack", i + 1),
+                    ..Default::default()
+                });
+            }
+        }
+        result
+    }
+
     /// Codegen::get_pre_function
     ///
     /// Each function has a preamble, containing some instruction to prepare the activation record
-    /// of the function
+    /// of the function: `ra` and `s0` are saved, `s0` gets the base of the activation record, the
+    /// `s` registers used by the function are saved and the space for the spilled registers is
+    /// reserved on the TP stack
     ///
     /// @in [&String]: name of the function
     /// @in [u32]: size of the activation record (which has to be a multiple of 16)
-    /// @in [&Vec<TypeWrapper>]: list of arguments of the function
-    /// @return [Vec<RiscvInstruction>]: list of instructions ofr the preamble
-    fn get_pre_function(&self, name: &String, ssa: u32, args: &Vec<TypeWrapper>) -> Vec<RiscvInstruction> {
+    /// @in [&Frame]: registers to save and space on the TP stack, as a result of the allocation
+    /// @in [&Vec<StackOffset>]: variables stored in the activation record
+    /// @return [Result<Vec<RiscvInstruction>, CodegenError>]: list of instructions ofr the
+    /// preamble, or an error if the size of the activation record is not valid
+    fn get_pre_function(
+        &self,
+        name: &String,
+        ssa: u32,
+        frame: &Frame,
+        stack_position: &Vec<StackOffset>,
+    ) -> Result<Vec<RiscvInstruction>, CodegenError> {
         let mut pre_function: Vec<RiscvInstruction> = vec![];
 
-        if ssa % 16 != 0 {
-            panic!("Increments of SP must be multiple of 16; {} provided", ssa);
+        if !ssa.is_multiple_of(STACK_ALIGNMENT) {
+            return Err(CodegenError::StackSize(ssa));
         }
 
         // Label of the function
@@ -95,6 +486,35 @@ impl Codegen {
             ..Default::default()
         });
 
+        // Position of each local variable with respect to the frame pointer, to be read along
+        // with the assembly (only printed with the comments)
+        for variable in stack_position {
+            pre_function.push(RiscvInstruction {
+                tt: COMMENT,
+                comment: format!("# Variable {} at {}(s0)", variable.name, variable.offset),
+                ..Default::default()
+            });
+        }
+
+        // If we hare handling the init function, we have to initialize the stack pointer to a
+        // picked value
+        if name == "init" {
+            pre_function.push(RiscvInstruction {
+                tt: LUI,
+                dest: TP,
+                immediate: SP_INIT_VALUE >> 13,
+                comment: "# Initialize TP".to_string(),
+                ..Default::default()
+            });
+            pre_function.push(RiscvInstruction {
+                tt: LUI,
+                dest: SP,
+                immediate: SP_INIT_VALUE >> 12,
+                comment: "# Initialize SP".to_string(),
+                ..Default::default()
+            });
+        }
+
         // Decrement of SP
         pre_function.push(RiscvInstruction {
             tt: ADDI,
@@ -136,78 +556,88 @@ impl Codegen {
             ..Default::default()
         });
 
-        // If the function has more than 8 arguments, the additional arguments are provided on the
-        // stack, before the new FP. These instructions load those values in the appropriate
-        // virtual registers.
-        for i in 0..args.len() {
-            if i < 8 {
-                pre_function.push(RiscvInstruction {
-                    tt: ADD,
-                    dest: i as i32 + 1,
-                    src1: A0 - i as i32,
-                    src2: X0,
-                    comment: format!("# Load argument {} from register", i + 1),
-                    ..Default::default()
-                });
-            } else {
-                pre_function.push(RiscvInstruction {
-                    tt: LW,
-                    dest: i as i32 + 1,
-                    src1: FP,
-                    immediate: (i as i32 - 8) * 4,
-                    comment: format!("# Load argument {} from stack", i + 1),
-                    ..Default::default()
-                });
-            }
+        // According to the ABI, the callee is in charge of saving the `s` registers it modifies
+        for (register, offset) in &frame.callee_saved {
+            pre_function.push(RiscvInstruction {
+                tt: SW,
+                src1: FP,
+                src2: *register,
+                immediate: *offset,
+                comment: "# Store register on stack as it must be preserved".to_string(),
+                ..Default::default()
+            });
         }
 
-        // If we hare handling the init function, we have to initialize the stack pointer to a
-        // picked value, and then use the frame pointer as global pointer, since global variables
-        // are initialized in the `init`, stored on the stack and referenced from there
-        if name == "init" {
-            pre_function.insert(
-                1,
-                RiscvInstruction {
-                    tt: LUI,
-                    dest: SP,
-                    immediate: SP_INIT_VALUE >> 12,
-                    comment: format!("# Initialize SP"),
-                    ..Default::default()
-                },
-            );
-            pre_function.insert(
-                1,
-                RiscvInstruction {
-                    tt: LUI,
-                    dest: TP,
-                    immediate: SP_INIT_VALUE >> 13,
-                    comment: format!("# Initialize TP"),
-                    ..Default::default()
-                },
-            );
+        // Space for the virtual registers spilled on the TP stack
+        if frame.tp_size != 0 {
             pre_function.push(RiscvInstruction {
                 tt: ADDI,
-                dest: GP,
-                src1: FP,
-                immediate: 0,
-                comment: format!("# Initialize GP"),
+                dest: TP,
+                src1: TP,
+                immediate: -frame.tp_size,
+                comment: "# Space for virtual registers on TP stack".to_string(),
                 ..Default::default()
             });
         }
 
-        return pre_function;
+        // The preamble only uses physical registers
+        for instruction in &mut pre_function {
+            instruction.register_allocated = true;
+        }
+
+        Ok(pre_function)
     }
 
     /// Codegen::get_post_function
     ///
     /// Each function has an epilogue, in which all the modified variables are brought back to the
-    /// previous values.
+    /// previous values. If the function allocated arrays whose size is known at runtime, SP is
+    /// first brought back to the bottom of the activation record using S0, as the arrays might
+    /// have been allocated any number of times (e.g. inside a loop)
     ///
     /// @in ssa[u32]: size of the activation record
+    /// @in [&Frame]: registers to restore and space on the TP stack, as a result of the allocation
+    /// @in has_runtime_arrays [bool]: whether SP was moved below the activation record
     /// @return [Vec<RiscvInstruction>]: list of instructions to use
-    fn get_post_function(&self, ssa: u32) -> Vec<RiscvInstruction> {
+    fn get_post_function(&self, ssa: u32, frame: &Frame, has_runtime_arrays: bool) -> Vec<RiscvInstruction> {
         let mut post_function: Vec<RiscvInstruction> = vec![];
 
+        // Get rid of the space allocated for the arrays below the activation record
+        if has_runtime_arrays {
+            post_function.push(RiscvInstruction {
+                tt: ADDI,
+                dest: SP,
+                src1: FP,
+                immediate: -(ssa as i32),
+                comment: "# Get rid of space allocated for vectors".to_string(),
+                ..Default::default()
+            });
+        }
+
+        // Restore the space on the TP stack
+        if frame.tp_size != 0 {
+            post_function.push(RiscvInstruction {
+                tt: ADDI,
+                dest: TP,
+                src1: TP,
+                immediate: frame.tp_size,
+                comment: "# Restore space for virtual registers on TP stack".to_string(),
+                ..Default::default()
+            });
+        }
+
+        // Restore the `s` registers, while s0 still points to the activation record
+        for (register, offset) in &frame.callee_saved {
+            post_function.push(RiscvInstruction {
+                tt: LW,
+                dest: *register,
+                src1: FP,
+                immediate: *offset,
+                comment: "# Restore register from stack".to_string(),
+                ..Default::default()
+            });
+        }
+
         // Restore return address
         post_function.push(RiscvInstruction {
             tt: LW,
@@ -240,15 +670,418 @@ impl Codegen {
 
         // Jump to the return address without saving the return address
         post_function.push(RiscvInstruction {
-            tt: JAL,
+            tt: JALR,
             dest: X0,
             src1: RA,
             ..Default::default()
         });
 
+        // The epilogue only uses physical registers
+        for instruction in &mut post_function {
+            instruction.register_allocated = true;
+        }
+
         return post_function;
     }
 
+    /// Codegen::get_activation_record_size
+    ///
+    /// Compute the size of the activation record of a function, made by its local variables, the
+    /// slots used to save the registers and the space for `ra` and `s0`. Check that none of these
+    /// elements overlap, that each of them is aligned to its size and that all of them are inside
+    /// the activation record. The arrays whose size is known at runtime are not part of it, as
+    /// they are allocated below it by moving `sp` of a multiple of 16
+    ///
+    /// @in locals_size [u32]: space used by the local variables, right below `s0`
+    /// @in stack_position [&[StackOffset]]: offset of each local variable
+    /// @in frame [&Frame]: slots used to save the registers
+    /// @return [Result<u32, CodegenError>]: size of the activation record, or an error if two
+    /// elements overlap
+    fn get_activation_record_size(locals_size: u32, stack_position: &[StackOffset], frame: &Frame) -> Result<u32, CodegenError> {
+        let ssa = (locals_size + frame.saved.len() as u32 * 4 + 8 + STACK_ALIGNMENT - 1) & !(STACK_ALIGNMENT - 1);
+
+        // Each element is a range of bytes with respect to `s0`: `ra` and `s0` are at the bottom
+        // of the activation record. Since `s0` is a multiple of 16, an element is aligned if its
+        // offset is a multiple of its size
+        let mut elements: Vec<(i32, i32)> = vec![(-(ssa as i32), -(ssa as i32) + 4), (-(ssa as i32) + 4, -(ssa as i32) + 8)];
+        for elem in stack_position {
+            elements.push((elem.offset, elem.offset + elem.size as i32));
+        }
+        for (_, offset) in &frame.saved {
+            elements.push((*offset, *offset + 4));
+        }
+
+        elements.sort();
+        for i in 0..elements.len() {
+            let is_outside = elements[i].0 < -(ssa as i32) || elements[i].1 > 0;
+            let is_overlapping = i + 1 < elements.len() && elements[i].1 > elements[i + 1].0;
+            let is_misaligned = elements[i].0 % (elements[i].1 - elements[i].0) != 0;
+            if is_outside || is_overlapping || is_misaligned {
+                return Err(CodegenError::StackSize(ssa));
+            }
+        }
+
+        Ok(ssa)
+    }
+
+    /// Codegen::convert_wide_node
+    ///
+    /// Convert an IR node working on values of 64 bits, which are stored in pairs of registers:
+    /// the register of the value holds its lower 32 bits, while the upper ones are in the
+    /// register given by `get_high_register`. Loads and stores use two words, additions and
+    /// subtractions propagate the carry between the two halves, and multiplications are
+    /// computed by the software routine `__muldi3`, which gets the operands in `a0..a3` and
+    /// returns the result in `a0` and `a1`. Comparisons, divisions and the other binary operators
+    /// are not supported yet, and the parser rejects the programs using them
+    ///
+    /// @in node [&IrNode]: node to translate
+    /// @in stack_position [&Vec<StackOffset>]: list of variables stored in the stack
+    /// @in i_function [u32]: function index used to create labels
+    /// @return [Result<Option<Vec<RiscvInstruction>>, CodegenError>]: list of instructions
+    /// translating the node, None if the node does not involve values of 64 bits. An error is
+    /// returned if the node cannot be translated
+    fn convert_wide_node(
+        &mut self,
+        node: &IrNode,
+        stack_position: &Vec<StackOffset>,
+        i_function: u32,
+    ) -> Result<Option<Vec<RiscvInstruction>>, CodegenError> {
+        let mut result: Vec<RiscvInstruction> = vec![];
+        // Address of a variable, either relative to `fp` if it is on the stack or given by the
+        // content of a register
+        let address = |reg: &u32| match stack_position.iter().find(|elem| elem.reg == *reg) {
+            Some(elem) => (FP, elem.offset, format!("variable {}", elem.name)),
+            None => (*reg as i32, 0, "value".to_string()),
+        };
+        let unsupported = || CodegenError::Unsupported(format!("`{}` on 64 bits", node.to_string().trim()));
+
+        match node {
+            // Variables are initialized with two stores, one for each half of the value
+            Alloc(tt, dest, src, false, _, false, ..) if Codegen::is_wide(tt) => {
+                if *src == 0 {
+                    return Ok(Some(result));
+                }
+                let (base, offset, name) = address(dest);
+                let high = self.get_high_register(*src);
+                for (src2, immediate) in [(*src as i32, offset), (high, offset + 4)] {
+                    result.push(RiscvInstruction {
+                        tt: SW,
+                        src1: base,
+                        src2,
+                        immediate,
+                        comment: format!("# Initialize {}", name),
+                        ..Default::default()
+                    });
+                }
+                if base == FP {
+                    result.push(RiscvInstruction {
+                        tt: ADDI,
+                        dest: *dest as i32,
+                        src1: FP,
+                        immediate: offset,
+                        ..Default::default()
+                    });
+                }
+            }
+            // The constants have 32 bits, thus the upper half is zero
+            MovC(tt, dest, value) if Codegen::is_wide(tt) => {
+                result.append(&mut self.load_constant(*dest, *value));
+                let high = self.get_high_register(*dest);
+                result.push(RiscvInstruction {
+                    tt: ADDI,
+                    dest: high,
+                    src1: X0,
+                    immediate: 0,
+                    ..Default::default()
+                });
+            }
+            Store(tt, dest, src, _) if Codegen::is_wide(tt) => {
+                let (base, offset, name) = address(dest);
+                let high = self.get_high_register(*src);
+                for (src2, immediate) in [(*src as i32, offset), (high, offset + 4)] {
+                    result.push(RiscvInstruction {
+                        tt: SW,
+                        src1: base,
+                        src2,
+                        immediate,
+                        comment: format!("# Store {}", name),
+                        ..Default::default()
+                    });
+                }
+            }
+            LoadR(tt, dest, src, _) if Codegen::is_wide(tt) => {
+                let (base, offset, name) = address(src);
+                let high = self.get_high_register(*dest);
+                for (dest, immediate) in [(*dest as i32, offset), (high, offset + 4)] {
+                    result.push(RiscvInstruction {
+                        tt: LW,
+                        dest,
+                        src1: base,
+                        immediate,
+                        comment: format!("# Load {}", name),
+                        ..Default::default()
+                    });
+                }
+            }
+            // A cast between two values of 64 bits copies both halves
+            Cast(ttd, tts, dest, src) if Codegen::is_wide(ttd) && Codegen::is_wide(tts) => {
+                let (high_dest, high_src) = (self.get_high_register(*dest), self.get_high_register(*src));
+                for (dest, src1) in [(*dest as i32, *src as i32), (high_dest, high_src)] {
+                    result.push(RiscvInstruction {
+                        tt: ADDI,
+                        dest,
+                        src1,
+                        immediate: 0,
+                        ..Default::default()
+                    });
+                }
+            }
+            // A value is widened by extending it to 32 bits, and then by filling the upper half
+            // with its sign (or with zero if it is unsigned)
+            Cast(ttd, tts, dest, src) if Codegen::is_wide(ttd) => {
+                let tt = TypeWrapper {
+                    type_native: if tts.is_signed() { TypeNative::I32 } else { TypeNative::U32 },
+                    ..Default::default()
+                };
+                let mut instructions = self.convert_node(&Cast(tt, tts.clone(), *dest, *src), stack_position, i_function)?;
+                result.append(&mut instructions);
+                let high = self.get_high_register(*dest);
+                result.push(if tts.is_signed() {
+                    RiscvInstruction {
+                        tt: SRLI,
+                        dest: high,
+                        src1: *dest as i32,
+                        immediate: 31,
+                        comment: "# Sign extension to 64 bits".to_string(),
+                        ..Default::default()
+                    }
+                } else {
+                    RiscvInstruction {
+                        tt: ADDI,
+                        dest: high,
+                        src1: X0,
+                        immediate: 0,
+                        comment: "# Zero extension to 64 bits".to_string(),
+                        ..Default::default()
+                    }
+                });
+            }
+            // A value is narrowed by ignoring its upper half
+            Cast(ttd, tts, dest, src) if Codegen::is_wide(tts) => {
+                let tt = TypeWrapper {
+                    type_native: if tts.is_signed() { TypeNative::I32 } else { TypeNative::U32 },
+                    ..Default::default()
+                };
+                let mut instructions = self.convert_node(&Cast(ttd.clone(), tt, *dest, *src), stack_position, i_function)?;
+                result.append(&mut instructions);
+            }
+            Unary(tt, op, dest, src) if Codegen::is_wide(tt) => {
+                let (high_dest, high_src) = (self.get_high_register(*dest), self.get_high_register(*src));
+                match op {
+                    // The opposite is `0 - src`, with a borrow if the lower half is not zero
+                    Operator::Minus => {
+                        let borrow = self.get_new_register();
+                        result.append(&mut vec![
+                            RiscvInstruction {
+                                tt: SLT,
+                                dest: borrow,
+                                src1: X0,
+                                src2: *src as i32,
+                                is_unsigned: true,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: *dest as i32,
+                                src1: X0,
+                                src2: *src as i32,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: high_dest,
+                                src1: X0,
+                                src2: high_src,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: high_dest,
+                                src1: high_dest,
+                                src2: borrow,
+                                ..Default::default()
+                            },
+                        ]);
+                    }
+                    Operator::Complement => {
+                        for (dest, src1) in [(*dest as i32, *src as i32), (high_dest, high_src)] {
+                            result.push(RiscvInstruction {
+                                tt: XORI,
+                                dest,
+                                src1,
+                                immediate: -1,
+                                ..Default::default()
+                            });
+                        }
+                    }
+                    // The value is zero if both its halves are zero
+                    Operator::Not => {
+                        result.append(&mut vec![
+                            RiscvInstruction {
+                                tt: OR,
+                                dest: *dest as i32,
+                                src1: *src as i32,
+                                src2: high_src,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SLTI,
+                                dest: *dest as i32,
+                                src1: *dest as i32,
+                                immediate: 1,
+                                is_unsigned: true,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: ADDI,
+                                dest: high_dest,
+                                src1: X0,
+                                immediate: 0,
+                                ..Default::default()
+                            },
+                        ]);
+                    }
+                    _ => return Err(unsupported()),
+                }
+            }
+            Binary(op, tt, dest, src1, src2, _) if Codegen::is_wide(tt) => {
+                let (high_dest, high_src1, high_src2) = (
+                    self.get_high_register(*dest),
+                    self.get_high_register(*src1),
+                    self.get_high_register(*src2),
+                );
+                match op {
+                    // The carry of the lower halves is set if their sum is smaller than one of
+                    // them, and it is added to the sum of the upper halves
+                    Operator::Plus => {
+                        let carry = self.get_new_register();
+                        result.append(&mut vec![
+                            RiscvInstruction {
+                                tt: ADD,
+                                dest: *dest as i32,
+                                src1: *src1 as i32,
+                                src2: *src2 as i32,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SLT,
+                                dest: carry,
+                                src1: *dest as i32,
+                                src2: *src1 as i32,
+                                is_unsigned: true,
+                                comment: "# Carry of the lower half".to_string(),
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: ADD,
+                                dest: high_dest,
+                                src1: high_src1,
+                                src2: high_src2,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: ADD,
+                                dest: high_dest,
+                                src1: high_dest,
+                                src2: carry,
+                                ..Default::default()
+                            },
+                        ]);
+                    }
+                    // The borrow of the lower halves is set if the first one is smaller than the
+                    // second one, and it is subtracted from the difference of the upper halves
+                    Operator::Minus => {
+                        let borrow = self.get_new_register();
+                        result.append(&mut vec![
+                            RiscvInstruction {
+                                tt: SLT,
+                                dest: borrow,
+                                src1: *src1 as i32,
+                                src2: *src2 as i32,
+                                is_unsigned: true,
+                                comment: "# Borrow of the lower half".to_string(),
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: *dest as i32,
+                                src1: *src1 as i32,
+                                src2: *src2 as i32,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: high_dest,
+                                src1: high_src1,
+                                src2: high_src2,
+                                ..Default::default()
+                            },
+                            RiscvInstruction {
+                                tt: SUB,
+                                dest: high_dest,
+                                src1: high_dest,
+                                src2: borrow,
+                                ..Default::default()
+                            },
+                        ]);
+                    }
+                    // The lower 64 bits of the product do not depend on the sign of the operands
+                    Operator::Asterisk => {
+                        let arguments = [*src1 as i32, high_src1, *src2 as i32, high_src2];
+                        for (i, argument) in arguments.iter().enumerate() {
+                            result.push(RiscvInstruction {
+                                tt: ADDI,
+                                dest: A0 - i as i32,
+                                src1: *argument,
+                                immediate: 0,
+                                comment: "# Load argument".to_string(),
+                                ..Default::default()
+                            });
+                        }
+                        result.push(RiscvInstruction {
+                            tt: JAL,
+                            dest: RA,
+                            name: "__muldi3".to_string(),
+                            ..Default::default()
+                        });
+                        for (dest, src1) in [(*dest as i32, A0), (high_dest, A0 - 1)] {
+                            result.push(RiscvInstruction {
+                                tt: ADDI,
+                                dest,
+                                src1,
+                                immediate: 0,
+                                comment: "# Move result of __muldi3".to_string(),
+                                ..Default::default()
+                            });
+                        }
+                        if !self.software_routines.contains(&"__muldi3".to_string()) {
+                            self.software_routines.push("__muldi3".to_string());
+                        }
+                    }
+                    _ => return Err(unsupported()),
+                }
+            }
+            // Comparisons would require to consider both halves
+            Binary(_, tt, ..) | Branch(_, tt, ..) if Codegen::is_wide(tt) => return Err(unsupported()),
+            _ => return Ok(None),
+        }
+
+        // The lower half of a value of 64 bits is never used as a 32 bits constant
+        self.constants_map.remove(&node.get_dest());
+        self.constant_values.remove(&node.get_dest());
+        Ok(Some(result))
+    }
+
     /// Codegen::convert_node
     ///
     /// Convert an IR node to a list of RV32IM instructions to handle it
@@ -256,18 +1089,22 @@ impl Codegen {
     /// @in node [&IrNode]: node to translate
     /// @in stack_position [&Vec<StackOffset>]: list of variables stored in the stack
     /// @in i_function [u32]: function index used to create labels
-    /// @return [(Vec<RiscvInstruction>, Vec<RiscvInstruction>)]: The first element is the list of
-    /// instructions which represents the functions. The second element is the list of instructions
-    /// to be added to the prologue of the function (mainly in order to restore the correct SP
-    /// value).
-    fn convert_node(&mut self, node: &IrNode, stack_position: &Vec<StackOffset>, i_function: u32) -> (Vec<RiscvInstruction>, Vec<RiscvInstruction>) {
+    /// @return [Result<Vec<RiscvInstruction>, CodegenError>]: list of instructions which
+    /// represents the node, or an error if the node cannot be translated
+    fn convert_node(&mut self, node: &IrNode, stack_position: &Vec<StackOffset>, i_function: u32) -> Result<Vec<RiscvInstruction>, CodegenError> {
         let mut in_function: Vec<RiscvInstruction> = vec![];
-        let mut post_function: Vec<RiscvInstruction> = vec![];
+
+        // Nodes on values of 64 bits are handled separately
+        if let Some(mut instructions) = self.convert_wide_node(node, stack_position, i_function)? {
+            in_function.append(&mut instructions);
+            return Ok(in_function);
+        }
+
         match node {
             // The return node is at the end of a function, and it consists in moving the value of
             // the register storing the return value into `a0` before running the epilogue of the
-            // function
-            Return(_, src) => {
+            // function. The upper 32 bits of a value of 64 bits are returned in `a1`
+            Return(tt, src, _) => {
                 if *src != 0 {
                     in_function.push(RiscvInstruction {
                         tt: ADDI,
@@ -275,6 +1112,15 @@ impl Codegen {
                         src1: *src as i32,
                         ..Default::default()
                     });
+                    if Codegen::is_wide(tt) {
+                        let high = self.get_high_register(*src);
+                        in_function.push(RiscvInstruction {
+                            tt: ADDI,
+                            dest: A0 - 1,
+                            src1: high,
+                            ..Default::default()
+                        });
+                    }
                 }
                 in_function.push(RiscvInstruction {
                     tt: J,
@@ -288,10 +1134,19 @@ impl Codegen {
             // fist case, the space for the pointer to the array is already in the activation
             // record, and the array is to be saved on top of the stack. Otherwise, the space of
             // the stack is to be initialized
-            Alloc(tt, dest, src, _, size, from_reg, ..) => {
+            Alloc(tt, dest, src, is_global, size, from_reg, ..) => {
+                // Global variables are handled separately, unless they are arrays whose size is
+                // not known at compile time
+                if *is_global {
+                    if let Some(mut instructions) = self.convert_global_alloc(node)? {
+                        in_function.append(&mut instructions);
+                        return Ok(in_function);
+                    }
+                }
+
                 if *from_reg {
                     // A new register is used to store the amount for which the stack is to be
-                    // modified. This must be a multiple of 15, while the size of the vector is
+                    // modified. This must be a multiple of 16, while the size of the vector is
                     // arbitrary.
                     let new_register = self.get_new_register();
 
@@ -301,7 +1156,7 @@ impl Codegen {
                         tt: SLLI,
                         dest: new_register,
                         src1: *size as i32,
-                        immediate: tt.get_size() as i32 / 2,
+                        immediate: tt.get_size().trailing_zeros() as i32,
                         comment: format!("# Size of array * Size of array type"),
                         ..Default::default()
                     });
@@ -311,14 +1166,14 @@ impl Codegen {
                         tt: ADDI,
                         dest: new_register,
                         src1: new_register,
-                        immediate: 15 as i32,
+                        immediate: (STACK_ALIGNMENT - 1) as i32,
                         ..Default::default()
                     });
                     in_function.push(RiscvInstruction {
                         tt: ANDI,
                         dest: new_register,
                         src1: new_register,
-                        immediate: (0xfffffff0 as u32) as i32,
+                        immediate: -(STACK_ALIGNMENT as i32),
                         comment: format!("# SP is always a multiple of 16"),
                         ..Default::default()
                     });
@@ -340,25 +1195,13 @@ impl Codegen {
                         comment: format!("# Store vector pointer"),
                         ..Default::default()
                     });
-                    // At the beginning of the epilogue, the SP has to be increased again in order
-                    // to compensate for the previous size.
-                    post_function.insert(
-                        0,
-                        RiscvInstruction {
-                            tt: ADD,
-                            dest: SP,
-                            src1: SP,
-                            src2: new_register,
-                            comment: format!("# Get rid of space allocated for vector"),
-                            ..Default::default()
-                        },
-                    );
+                    // The space is given back by the epilogue, which restores SP from S0
                 }
 
                 // If the source value is not set, in this case the space for the variable is not
                 // initialized. This is also the case for an array declaration
                 if *src == 0 {
-                    return (in_function, post_function);
+                    return Ok(in_function);
                 }
 
                 // A store instruction is required to initialize the space on the stack
@@ -387,7 +1230,7 @@ impl Codegen {
                             comment: format!("# Initialize variable {}", elem.name),
                             ..Default::default()
                         });
-                        return (in_function, post_function);
+                        return Ok(in_function);
                     }
                 }
                 // If the register is not found on the stack, then use the pointer register to
@@ -396,83 +1239,88 @@ impl Codegen {
                 store_instruction.comment = format!("# Initialize value using pointer");
                 in_function.push(store_instruction);
             }
-            MovC(_, dest, src) => {
-                // If the constant is larger than 2**12, a LUI is required
-                if *src > (1 << 12) {
-                    in_function.push(RiscvInstruction {
-                        tt: LUI,
-                        dest: *dest as i32,
-                        immediate: (src >> 12) as i32,
-                        comment: format!("# Constant larger than 2**12"),
-                        ..Default::default()
-                    });
-                } else {
-                    // Otherwise, add the register to the list of registers storing constants, so that
-                    // if it is found in an arithmetic instruction it can be substituted with the
-                    // correspondent constant
-                    self.constants_map.insert(*dest, *src);
-                }
-                // Move the constant value in the register
-                in_function.push(RiscvInstruction {
-                    tt: ADDI,
-                    dest: *dest as i32,
-                    src1: X0,
-                    immediate: (src % (1 << 12)) as i32,
-                    comment: format!("# Load constant {} in register", src),
-                    ..Default::default()
-                });
-            }
+            MovC(_, dest, src) => in_function.append(&mut self.load_constant(*dest, *src)),
 
             // A cast operation is done by first resizing the register from M to N bits, and then
             // possibly doing a signed extension. The first operation can be done with a bit mask.
             // The second operation can be done with a series of a left shift and signed right
             // shift
             Cast(ttd, tts, dest, src) => {
-                // Destination register is different from 4
-                // We clear the upper N bits of the register, which are 24 (size == 1) or 16 (size == 2)
-                let and_mask = match ttd.get_size() {
-                    1 => 0xff,
-                    2 => 0xffff,
-                    _ => -1,
+                // The value is truncated to the smallest of the two sizes and then extended. The
+                // extension is signed when a signed source is widened, or when the destination is
+                // signed and not larger than the source. In this way, the result does not depend
+                // on the upper bits of the source register
+                let bits = ttd.get_size().min(tts.get_size()) * 8;
+                let is_signed = if ttd.get_size() <= tts.get_size() {
+                    ttd.is_signed()
+                } else {
+                    tts.is_signed()
                 };
-                in_function.push(RiscvInstruction {
-                    tt: ANDI,
-                    dest: *dest as i32,
-                    src1: *src as i32,
-                    immediate: and_mask as i32,
-                    comment: format!(
-                        "# Clear upper bits of register due to cast from size {} to {}",
-                        tts.get_size(),
-                        ttd.get_size()
-                    ),
-                    ..Default::default()
-                });
-                // If the destination is signed and different form i32, we shift left until we have
+
+                // If the source is a constant, the result of the cast is computed at compile time
+                if let Some(value) = self.constant_values.get(src) {
+                    let mut value = *value;
+                    if bits != 32 {
+                        value &= (1 << bits) - 1;
+                        if is_signed {
+                            value = (((value << (32 - bits)) as i32) >> (32 - bits)) as u32;
+                        }
+                    }
+                    in_function.append(&mut self.load_constant(*dest, value));
+                    return Ok(in_function);
+                }
+
+                // If the result is signed and smaller than 32 bits, we shift left until we have
                 // the important bits on the leftmost side, and then shift right signed to adjust
-                // the sign
-                if ttd.is_signed() && ttd.get_size() != 4 {
-                    let shift_size = 32 - ttd.get_size() * 8;
+                // the sign. The left shift already discards the upper bits of the source, so no
+                // mask is required, and the right shift works on the destination. The same is done
+                // with an unsigned right shift for unsigned 16 bits, whose mask does not fit in
+                // the immediate of an `andi`
+                if (is_signed || bits == 16) && bits != 32 {
                     in_function.push(RiscvInstruction {
                         tt: SLLI,
                         dest: *dest as i32,
                         src1: *src as i32,
-                        immediate: shift_size as i32,
+                        immediate: (32 - bits) as i32,
+                        comment: format!("# Cast from size {} to {}", tts.get_size(), ttd.get_size()),
                         ..Default::default()
                     });
                     in_function.push(RiscvInstruction {
                         tt: SRLI,
                         dest: *dest as i32,
                         src1: *dest as i32,
-                        immediate: shift_size as i32,
-                        is_unsigned: false,
-                        comment: format!("# Sign extension"),
+                        immediate: (32 - bits) as i32,
+                        is_unsigned: !is_signed,
+                        comment: if is_signed {
+                            "# Sign extension".to_string()
+                        } else {
+                            "# Zero extension".to_string()
+                        },
+                        ..Default::default()
+                    });
+                // Otherwise, we clear the upper 24 bits (8 bits) of the register
+                } else {
+                    let and_mask = match bits {
+                        8 => 0xff,
+                        _ => -1,
+                    };
+                    in_function.push(RiscvInstruction {
+                        tt: ANDI,
+                        dest: *dest as i32,
+                        src1: *src as i32,
+                        immediate: and_mask,
+                        comment: format!(
+                            "# Clear upper bits of register due to cast from size {} to {}",
+                            tts.get_size(),
+                            ttd.get_size()
+                        ),
                         ..Default::default()
                     });
                 }
             }
             // A store instruction has a destination which is either a relative point to the fp or
             // the content of a register
-            Store(tt, dest, src) => {
+            Store(tt, dest, src, _) => {
                 let mut store_instruction = RiscvInstruction { ..Default::default() };
                 // Pick the size of the store
                 store_instruction.tt = match tt.get_size() {
@@ -490,29 +1338,29 @@ impl Codegen {
                         store_instruction.immediate = elem.offset;
                         store_instruction.comment = format!("# Store value of variable {}", elem.name);
                         in_function.push(store_instruction);
-                        return (in_function, post_function);
+                        return Ok(in_function);
                     }
                 }
                 store_instruction.comment = format!("# Store value");
                 in_function.push(store_instruction);
             }
-            // Load address to global variable. They are all referenced from an offset to GP, thus
-            // the destination gets the pointer to that variable
-            LoadA(_, dest, src) => {
-                let mut load_instruction = RiscvInstruction { ..Default::default() };
-                load_instruction.tt = ADDI;
-                load_instruction.dest = *dest as i32;
-                load_instruction.src1 = GP;
-                for elem in &self.global_stack_position {
-                    if elem.name == *src {
-                        load_instruction.immediate = elem.offset as i32;
-                    }
-                }
-                load_instruction.comment = format!("# Load global pointer");
-                in_function.push(load_instruction);
+            // Load address to global variable or to function, which is referenced through its label
+            LoadA(tt, dest, src) => {
+                in_function.push(RiscvInstruction {
+                    tt: LA,
+                    dest: *dest as i32,
+                    name: src.to_string(),
+                    comment: if tt.is_function_pointer() {
+                        "# Load address of function".to_string()
+                    } else {
+                        "# Load global pointer".to_string()
+                    },
+                    ..Default::default()
+                });
+                self.global_addresses.insert(*dest, src.to_string());
             }
             // Load having the pointer of the variable to load in a register
-            LoadR(tt, dest, src) => {
+            LoadR(tt, dest, src, _) => {
                 let mut load_instruction = RiscvInstruction { ..Default::default() };
                 // Size of the load
                 load_instruction.tt = match tt.get_size() {
@@ -520,6 +1368,8 @@ impl Codegen {
                     2 => LH,
                     _ => LB,
                 };
+                // Values of unsigned types smaller than 32 bits are zero extended
+                load_instruction.is_unsigned = !tt.is_signed();
                 // Destination is fixed
                 load_instruction.dest = *dest as i32;
                 // The address might also be on the stack, if we are referring to a local variable
@@ -529,7 +1379,7 @@ impl Codegen {
                         load_instruction.immediate = elem.offset as i32;
                         load_instruction.comment = format!("# Load variable {} from stack", elem.name);
                         in_function.push(load_instruction);
-                        return (in_function, post_function);
+                        return Ok(in_function);
                     }
                 }
                 load_instruction.comment = format!("# Load from register");
@@ -544,12 +1394,20 @@ impl Codegen {
                 ..Default::default()
             }),
             // Call to a function, which requires to handle the load of the arguments in the proper
-            // registers, and possibly handling the extra arguments with the stack
-            Call(name, _, arguments, ret) => {
+            // registers, and possibly handling the extra arguments with the stack. The function is
+            // either referenced by its label or, for an indirect call, by its address in a register
+            Call(_, tt, arguments, ret, _) | CallIndirect(tt, _, arguments, ret, _) => {
+                // The code of a built-in function is added once, if it is called
+                if let Call(name, ..) = node {
+                    if Declaration::builtins().iter().any(|b| &b.name == name) && !self.software_routines.contains(name) {
+                        self.software_routines.push(name.to_string());
+                    }
+                }
+
                 // How many extra arguments
                 let extra_arguments: i32 = arguments.len() as i32 - 8;
                 // Space required on the stack to store the extra arguments
-                let extra_space: i32 = ((extra_arguments * 4) + 15) & -16;
+                let extra_space: i32 = ((extra_arguments * 4) + STACK_ALIGNMENT as i32 - 1) & -(STACK_ALIGNMENT as i32);
 
                 // Move the SP if required to add the extra arguments
                 if extra_arguments > 0 {
@@ -586,13 +1444,24 @@ impl Codegen {
                         });
                     }
                 }
-                // Add a jump to the function
-                in_function.push(RiscvInstruction {
-                    tt: JAL,
-                    dest: RA,
-                    name: name.to_string(),
-                    ..Default::default()
-                });
+                // Add a jump to the function, or to the address of the function
+                if let CallIndirect(_, target, ..) = node {
+                    in_function.push(RiscvInstruction {
+                        tt: JALR,
+                        dest: RA,
+                        src1: *target as i32,
+                        immediate: 0,
+                        comment: "# Indirect call".to_string(),
+                        ..Default::default()
+                    });
+                } else if let Call(name, ..) = node {
+                    in_function.push(RiscvInstruction {
+                        tt: JAL,
+                        dest: RA,
+                        name: name.to_string(),
+                        ..Default::default()
+                    });
+                }
                 // Mov the return value to the correct register
                 if *ret != 0 {
                     in_function.push(RiscvInstruction {
@@ -603,6 +1472,17 @@ impl Codegen {
                         comment: format!("# Move return value from functino"),
                         ..Default::default()
                     });
+                    if Codegen::is_wide(tt) {
+                        let high = self.get_high_register(*ret);
+                        in_function.push(RiscvInstruction {
+                            tt: ADDI,
+                            dest: high,
+                            src1: A0 - 1,
+                            immediate: 0,
+                            comment: "# Move upper bits of return value from function".to_string(),
+                            ..Default::default()
+                        });
+                    }
                 }
                 // Put the stack value back
                 if extra_arguments > 0 {
@@ -616,6 +1496,31 @@ impl Codegen {
                     });
                 }
             }
+            // A system call has its arguments in the argument registers and its number in `a7`
+            Syscall(number, arguments) => {
+                for (i, argument) in arguments.iter().enumerate() {
+                    in_function.push(RiscvInstruction {
+                        tt: ADDI,
+                        dest: A0 - i as i32,
+                        src1: *argument as i32,
+                        immediate: 0,
+                        comment: "# Load argument of the system call".to_string(),
+                        ..Default::default()
+                    });
+                }
+                in_function.push(RiscvInstruction {
+                    tt: ADDI,
+                    dest: A0 - 7,
+                    src1: X0,
+                    immediate: *number as i32,
+                    comment: "# Number of the system call".to_string(),
+                    ..Default::default()
+                });
+                in_function.push(RiscvInstruction {
+                    tt: ECALL,
+                    ..Default::default()
+                });
+            }
             // Handle a branch
             Branch(ct, tt, src1, src2, name) => {
                 let mut branch_instruction = RiscvInstruction { ..Default::default() };
@@ -678,16 +1583,24 @@ impl Codegen {
                 in_function.push(branch_instruction);
             }
             // Unary operation
-            Unary(_, tk, dest, src) => {
+            Unary(tt, tk, dest, src) => {
                 match tk {
                     // dest = 0 - source
-                    Operator::Minus => in_function.push(RiscvInstruction {
-                        tt: SUB,
-                        dest: *dest as i32,
-                        src1: X0,
-                        src2: *src as i32,
-                        ..Default::default()
-                    }),
+                    // If the source is a constant whose opposite fits in an immediate, the result
+                    // is a constant as well (this is the case of negative literals)
+                    Operator::Minus => match self.constants_map.get(src) {
+                        Some(v) if Codegen::is_immediate(v.wrapping_neg()) => {
+                            let value = v.wrapping_neg();
+                            in_function.append(&mut self.load_constant(*dest, value));
+                        }
+                        _ => in_function.push(RiscvInstruction {
+                            tt: SUB,
+                            dest: *dest as i32,
+                            src1: X0,
+                            src2: *src as i32,
+                            ..Default::default()
+                        }),
+                    },
                     // dest = source ^ 0xffffffff
                     Operator::Complement => in_function.push(RiscvInstruction {
                         tt: XORI,
@@ -696,20 +1609,41 @@ impl Codegen {
                         immediate: (0xffffffff as u32) as i32,
                         ..Default::default()
                     }),
-                    // dest = if source < 1 {1} else {0} => set if zero, clear if not zero
-                    Operator::Not => in_function.push(RiscvInstruction {
-                        tt: SLTI,
-                        dest: *dest as i32,
-                        src1: *src as i32,
-                        immediate: 1,
-                        is_unsigned: true,
-                        ..Default::default()
-                    }),
-                    _ => panic!("Invalid binary operator {:#?}", tk),
+                    // dest = if source < 1 {1} else {0} => set if zero, clear if not zero. The
+                    // upper bits of a source smaller than 32 bits are not relevant (they might be
+                    // set by an operation which overflowed), so they are shifted out first
+                    Operator::Not => {
+                        let mut to_compare = *src as i32;
+                        if tt.get_size() != 4 {
+                            in_function.push(RiscvInstruction {
+                                tt: SLLI,
+                                dest: *dest as i32,
+                                src1: *src as i32,
+                                immediate: (32 - tt.get_size() * 8) as i32,
+                                comment: format!("# Only the lower {} bits are relevant", tt.get_size() * 8),
+                                ..Default::default()
+                            });
+                            to_compare = *dest as i32;
+                        }
+                        in_function.push(RiscvInstruction {
+                            tt: SLTI,
+                            dest: *dest as i32,
+                            src1: to_compare,
+                            immediate: 1,
+                            is_unsigned: true,
+                            ..Default::default()
+                        });
+                    }
+                    _ => {
+                        return Err(CodegenError::UnexpectedNode(format!(
+                            "`{}` has an invalid binary operator",
+                            node.to_string().trim()
+                        )))
+                    }
                 }
             }
             // Binary operation
-            Binary(tk, tt, dest, src1, src2) => {
+            Binary(tk, tt, dest, src1, src2, _) => {
                 let mut binary_instruction = RiscvInstruction { ..Default::default() };
                 binary_instruction.dest = *dest as i32;
                 binary_instruction.src1 = *src1 as i32;
@@ -717,98 +1651,62 @@ impl Codegen {
                 binary_instruction.is_unsigned = !tt.is_signed();
                 let mut to_add = true;
                 match tk {
-                    // vx == vy can be implemented by computing the subtraction between the two
-                    // operands and setting the destination to 1 if the result is 0
-                    Operator::EqualCompare => {
-                        in_function.push(RiscvInstruction {
-                            tt: SUB,
-                            dest: *dest as i32,
-                            src1: *src1 as i32,
-                            src2: *src2 as i32,
-                            ..Default::default()
-                        });
+                    // Without the M extension, multiplications, divisions and remainders are
+                    // implemented as calls to software routines. The operands are moved in `a0`
+                    // and `a1`, while the result is in `a0`
+                    Operator::Asterisk | Operator::Slash | Operator::Module if !self.m_extension => {
+                        let routine = match (tk, tt.is_signed()) {
+                            (Operator::Asterisk, _) => "__mulsi3",
+                            (Operator::Slash, true) => "__divsi3",
+                            (Operator::Slash, false) => "__udivsi3",
+                            (_, true) => "__modsi3",
+                            (_, false) => "__umodsi3",
+                        };
                         in_function.push(RiscvInstruction {
-                            tt: SLTI,
-                            dest: *dest as i32,
-                            src1: *dest as i32,
-                            immediate: 1,
-                            is_unsigned: true,
-                            ..Default::default()
-                        });
-                        to_add = false;
-                    }
-                    // vx == vy can be implemented by computing the subtraction between the two
-                    // operands and setting the destination to 1 if the result is not 0
-                    Operator::DiffCompare => {
-                        in_function.push(RiscvInstruction {
-                            tt: SUB,
-                            dest: *dest as i32,
+                            tt: ADDI,
+                            dest: A0,
                             src1: *src1 as i32,
-                            src2: *src2 as i32,
-                            ..Default::default()
-                        });
-                        in_function.push(RiscvInstruction {
-                            tt: SLT,
-                            dest: *dest as i32,
-                            src1: X0,
-                            src2: *dest as i32,
-                            is_unsigned: true,
+                            immediate: 0,
+                            comment: "# Load argument".to_string(),
                             ..Default::default()
                         });
-                        to_add = false;
-                    }
-                    // First we set dest if src1 > src2, then we do the opposite of the result
-                    // dest = if src2 < src1 {1} else {0}
-                    // dest = if dest == 1 {0} else {1}
-                    Operator::LECompare => {
                         in_function.push(RiscvInstruction {
-                            tt: SLT,
-                            dest: *dest as i32,
+                            tt: ADDI,
+                            dest: A0 - 1,
                             src1: *src2 as i32,
-                            src2: *src1 as i32,
-                            ..Default::default()
-                        });
-                        in_function.push(RiscvInstruction {
-                            tt: SLTI,
-                            dest: *dest as i32,
-                            src1: *dest as i32,
-                            immediate: 1,
-                            is_unsigned: true,
+                            immediate: 0,
+                            comment: "# Load argument".to_string(),
                             ..Default::default()
                         });
-                        to_add = false;
-                    }
-                    // First we set dest if src1 < src2, then we do the opposite of the result
-                    // dest = if src2 < src1 {1} else {0}
-                    // dest = if dest == 1 {0} else {1}
-                    Operator::GECompare => {
                         in_function.push(RiscvInstruction {
-                            tt: SLT,
-                            dest: *dest as i32,
-                            src1: *src1 as i32,
-                            src2: *src2 as i32,
+                            tt: JAL,
+                            dest: RA,
+                            name: routine.to_string(),
                             ..Default::default()
                         });
                         in_function.push(RiscvInstruction {
-                            tt: SLTI,
+                            tt: ADDI,
                             dest: *dest as i32,
-                            src1: *dest as i32,
-                            immediate: 1 as i32,
-                            is_unsigned: true,
+                            src1: A0,
+                            immediate: 0,
+                            comment: format!("# Move result of {}", routine),
                             ..Default::default()
                         });
+                        if !self.software_routines.contains(&routine.to_string()) {
+                            self.software_routines.push(routine.to_string());
+                        }
                         to_add = false;
                     }
-                    // First we set dest if src2 < src1, then we do the opposite of the result
-                    // dest = if src2 < src1 {1} else {0}
-                    // dest = if dest == 1 {0} else {1}
-                    Operator::GTCompare => {
-                        binary_instruction.src2 = *src1 as i32;
-                        binary_instruction.src1 = *src2 as i32;
-                        binary_instruction.tt = SLT;
+                    // Each comparison produces either 0 or 1
+                    Operator::EqualCompare
+                    | Operator::DiffCompare
+                    | Operator::LTCompare
+                    | Operator::GTCompare
+                    | Operator::LECompare
+                    | Operator::GECompare => {
+                        in_function.append(&mut Codegen::get_comparison(tk, tt, *dest as i32, *src1 as i32, *src2 as i32));
+                        to_add = false;
                     }
-                    // dest = if src1 < src2 {1} else {0}
-                    Operator::LTCompare => binary_instruction.tt = SLT,
                     Operator::Minus => binary_instruction.tt = SUB,
                     // Implement the plus operator, by checking if either the left operand or the
                     // right operand is a constant
@@ -896,65 +1794,534 @@ impl Codegen {
                     },
                     // Remainder
                     Operator::Module => binary_instruction.tt = REM,
-                    // Left shift (right operand might be immediate)
+                    // Left shift (right operand might be immediate). Only the lower 5 bits of the
+                    // amount are used, as the hardware does when the amount is in a register
                     Operator::LShift => match self.constants_map.get(src2) {
                         Some(v) => {
                             binary_instruction.tt = SLLI;
                             binary_instruction.src2 = 0;
-                            binary_instruction.immediate = *v as i32;
+                            binary_instruction.immediate = (*v & 31) as i32;
                         }
-                        None => {
+                        _ => {
                             binary_instruction.tt = SLL;
                         }
                     },
-                    // Right shift (right operand might be immediate)
+                    // Right shift (right operand might be immediate). Only the lower 5 bits of the
+                    // amount are used, as the hardware does when the amount is in a register
                     Operator::RShift => match self.constants_map.get(src2) {
                         Some(v) => {
                             binary_instruction.tt = SRLI;
                             binary_instruction.src2 = 0;
-                            binary_instruction.immediate = *v as i32;
+                            binary_instruction.immediate = (*v & 31) as i32;
                         }
-                        None => {
+                        _ => {
                             binary_instruction.tt = SRL;
                         }
                     },
-                    _ => panic!("Invalid binary operator {:#?}", tk),
+                    _ => {
+                        return Err(CodegenError::UnexpectedNode(format!(
+                            "`{}` has an invalid binary operator",
+                            node.to_string().trim()
+                        )))
+                    }
                 }
                 if to_add {
                     in_function.push(binary_instruction);
                 }
             }
-            _ => panic!("Non valid node"),
+            _ => {
+                return Err(CodegenError::UnexpectedNode(format!(
+                    "`{}` cannot be translated",
+                    node.to_string().trim()
+                )))
+            }
+        }
+        Ok(in_function)
+    }
+
+    /// Codegen::get_comparison
+    ///
+    /// Get the instructions setting the destination to 1 if a comparison between two registers
+    /// holds, to 0 otherwise. The comparison is reduced to a first instruction and an optional
+    /// second one, both writing the destination:
+    /// - `<` and `>=` compute `src1 < src2`, `>` and `<=` compute `src2 < src1` (signed or
+    ///   unsigned depending on the type of the operands);
+    /// - `==` and `!=` compute `src1 - src2`;
+    /// - `>=`, `<=` and `==` then set the destination if the first result is zero, while `!=` sets
+    ///   it if the first result is not zero.
+    ///
+    /// @in op [&Operator]: comparison operator
+    /// @in tt [&TypeWrapper]: type of the operands
+    /// @in dest [i32]: destination register
+    /// @in src1 [i32]: left operand
+    /// @in src2 [i32]: right operand
+    /// @return [Vec<RiscvInstruction>]: instructions implementing the comparison
+    fn get_comparison(op: &Operator, tt: &TypeWrapper, dest: i32, src1: i32, src2: i32) -> Vec<RiscvInstruction> {
+        let is_unsigned = !tt.is_signed();
+        let first = match op {
+            Operator::LTCompare | Operator::GECompare => RiscvInstruction {
+                tt: SLT,
+                dest,
+                src1,
+                src2,
+                is_unsigned,
+                ..Default::default()
+            },
+            Operator::GTCompare | Operator::LECompare => RiscvInstruction {
+                tt: SLT,
+                dest,
+                src1: src2,
+                src2: src1,
+                is_unsigned,
+                ..Default::default()
+            },
+            _ => RiscvInstruction {
+                tt: SUB,
+                dest,
+                src1,
+                src2,
+                ..Default::default()
+            },
+        };
+
+        let mut result = vec![first];
+        match op {
+            // dest = if dest == 0 {1} else {0}
+            Operator::GECompare | Operator::LECompare | Operator::EqualCompare => result.push(RiscvInstruction {
+                tt: SLTI,
+                dest,
+                src1: dest,
+                immediate: 1,
+                is_unsigned: true,
+                ..Default::default()
+            }),
+            // dest = if 0 < dest {1} else {0}
+            Operator::DiffCompare => result.push(RiscvInstruction {
+                tt: SLT,
+                dest,
+                src1: X0,
+                src2: dest,
+                is_unsigned: true,
+                ..Default::default()
+            }),
+            _ => {}
+        }
+        result
+    }
+
+    /// Codegen::get_software_routine
+    ///
+    /// Get the instructions of a software routine implementing a multiplication (`__mulsi3`), a
+    /// division (`__divsi3`, `__udivsi3`) or a remainder (`__modsi3`, `__umodsi3`) without the M
+    /// extension, the multiplication of 64 bits values (`__muldi3`), the built-in comparison of
+    /// strings (`__strcmp`) or the built-in stop on a runtime error (`__trap`). The operands are in `a0` and `a1` (`a0..a3` for `__muldi3`), the
+    /// result is returned in `a0` (`a0` and `a1` for `__muldi3`). Only temporary registers are
+    /// used, so there is nothing to save. The registers are already allocated.
+    ///
+    /// @in name [&str]: name of the routine
+    /// @in i_function [u32]: function index used to create labels
+    /// @return [Vec<RiscvInstruction>]: instructions of the routine
+    fn get_software_routine(name: &str, i_function: u32) -> Vec<RiscvInstruction> {
+        let (a0, a1, t0, t1, t2, t3, t4) = (A0, A0 - 1, 0, 1, 2, 3, 4);
+        // Helpers to build the instructions
+        let op = |tt: RiscvInstructionType, dest: i32, src1: i32, src2: i32, immediate: i32| RiscvInstruction {
+            tt,
+            dest,
+            src1,
+            src2,
+            immediate,
+            is_unsigned: true,
+            label_function: i_function,
+            register_allocated: true,
+            ..Default::default()
+        };
+        let label = |tt: RiscvInstructionType, src1: i32, label: u32| RiscvInstruction {
+            tt,
+            src1,
+            src2: X0,
+            label,
+            label_function: i_function,
+            register_allocated: true,
+            ..Default::default()
+        };
+
+        let mut result: Vec<RiscvInstruction> = vec![RiscvInstruction {
+            tt: LABELFUNCTION,
+            name: name.to_string(),
+            comment: "# Software routine".to_string(),
+            ..Default::default()
+        }];
+
+        // Multiplication: for each bit of `a1` which is set, add `a0` shifted by the position of
+        // the bit to the result
+        if name == "__mulsi3" {
+            result.append(&mut vec![
+                op(ADDI, t0, a0, 0, 0),
+                op(ADDI, a0, X0, 0, 0),
+                label(LABEL, 0, 1),
+                label(BEQ, a1, 2),
+                op(ANDI, t1, a1, 0, 1),
+                label(BEQ, t1, 3),
+                op(ADD, a0, a0, t0, 0),
+                label(LABEL, 0, 3),
+                op(SLLI, t0, t0, 0, 1),
+                op(SRLI, a1, a1, 0, 1),
+                label(J, 0, 1),
+                label(LABEL, 0, 2),
+                op(JALR, X0, RA, 0, 0),
+            ]);
+            return result;
+        }
+
+        // Multiplication of 64 bits values, whose halves are in `a0`/`a1` and `a2`/`a3`: for each
+        // bit of the second operand which is set, add the first operand (shifted by the position
+        // of the bit) to the result, propagating the carry between the halves
+        if name == "__muldi3" {
+            let (a2, a3) = (A0 - 2, A0 - 3);
+            result.append(&mut vec![
+                op(ADDI, t0, a0, 0, 0),
+                op(ADDI, t1, a1, 0, 0),
+                op(ADDI, a0, X0, 0, 0),
+                op(ADDI, a1, X0, 0, 0),
+                label(LABEL, 0, 1),
+                op(OR, t2, a2, a3, 0),
+                label(BEQ, t2, 2),
+                op(ANDI, t2, a2, 0, 1),
+                label(BEQ, t2, 3),
+                op(ADD, a0, a0, t0, 0),
+                op(SLT, t2, a0, t0, 0),
+                op(ADD, a1, a1, t1, 0),
+                op(ADD, a1, a1, t2, 0),
+                label(LABEL, 0, 3),
+                op(SRLI, t2, t0, 0, 31),
+                op(SLLI, t1, t1, 0, 1),
+                op(OR, t1, t1, t2, 0),
+                op(SLLI, t0, t0, 0, 1),
+                op(SLLI, t2, a3, 0, 31),
+                op(SRLI, a2, a2, 0, 1),
+                op(OR, a2, a2, t2, 0),
+                op(SRLI, a3, a3, 0, 1),
+                label(J, 0, 1),
+                label(LABEL, 0, 2),
+                op(JALR, X0, RA, 0, 0),
+            ]);
+            return result;
+        }
+
+        // Runtime error: the execution stops in an endless loop, as it does at the end of `init`
+        if name == "__trap" {
+            result.append(&mut vec![label(LABEL, 0, 1), label(J, 0, 1)]);
+            return result;
+        }
+
+        // Comparison of strings: the bytes are compared until they differ or the end of the first
+        // string is reached. The result is the difference of the last compared bytes
+        if name == "__strcmp" {
+            result.append(&mut vec![
+                label(LABEL, 0, 1),
+                op(LB, t0, a0, 0, 0),
+                op(LB, t1, a1, 0, 0),
+                RiscvInstruction {
+                    src2: t1,
+                    ..label(BNE, t0, 2)
+                },
+                label(BEQ, t0, 2),
+                op(ADDI, a0, a0, 0, 1),
+                op(ADDI, a1, a1, 0, 1),
+                label(J, 0, 1),
+                label(LABEL, 0, 2),
+                op(SUB, a0, t0, t1, 0),
+                op(JALR, X0, RA, 0, 0),
+            ]);
+            return result;
+        }
+
+        // In case of signed operations, the operands are made positive. The sign of the quotient
+        // is the xor of the signs of the operands, while the sign of the remainder is the sign of
+        // the dividend. It is stored in the most significant bit of `t4`
+        let is_signed = name == "__divsi3" || name == "__modsi3";
+        let is_division = name == "__divsi3" || name == "__udivsi3";
+        if is_signed {
+            result.append(&mut vec![
+                if is_division { op(XOR, t4, a0, a1, 0) } else { op(ADDI, t4, a0, 0, 0) },
+                op(SRLI, t3, a0, 0, 31),
+                label(BEQ, t3, 3),
+                op(SUB, a0, X0, a0, 0),
+                label(LABEL, 0, 3),
+                op(SRLI, t3, a1, 0, 31),
+                label(BEQ, t3, 4),
+                op(SUB, a1, X0, a1, 0),
+                label(LABEL, 0, 4),
+            ]);
+        }
+
+        // Unsigned division, one bit at a time: the dividend is shifted in the remainder `t1`,
+        // and when the remainder is larger than the divisor, the divisor is subtracted and the
+        // bit of the quotient `a0` is set
+        result.append(&mut vec![
+            op(ADDI, t0, a0, 0, 0),
+            op(ADDI, a0, X0, 0, 0),
+            op(ADDI, t1, X0, 0, 0),
+            op(ADDI, t2, X0, 0, 32),
+            label(LABEL, 0, 1),
+            op(SLLI, t1, t1, 0, 1),
+            op(SRLI, t3, t0, 0, 31),
+            op(OR, t1, t1, t3, 0),
+            op(SLLI, t0, t0, 0, 1),
+            op(SLLI, a0, a0, 0, 1),
+            op(SLT, t3, t1, a1, 0),
+            label(BNE, t3, 2),
+            op(SUB, t1, t1, a1, 0),
+            op(ORI, a0, a0, 0, 1),
+            label(LABEL, 0, 2),
+            op(ADDI, t2, t2, 0, -1),
+            label(BNE, t2, 1),
+        ]);
+
+        // The remainder is the result
+        if !is_division {
+            result.push(op(ADDI, a0, t1, 0, 0));
+        }
+
+        // Restore the sign of the result. As required by the ISA, a division by zero gives -1
+        // whatever the sign of the dividend is, so the sign of the quotient is not restored
+        if is_signed {
+            if is_division {
+                result.push(label(BEQ, a1, 5));
+            }
+            result.append(&mut vec![
+                op(SRLI, t4, t4, 0, 31),
+                label(BEQ, t4, 5),
+                op(SUB, a0, X0, a0, 0),
+                label(LABEL, 0, 5),
+            ]);
         }
-        return (in_function, post_function);
+
+        result.push(op(JALR, X0, RA, 0, 0));
+        result
     }
 
-    /// Codegen::remove_load_constant
+    /// Codegen::remove_dead_instructions
     ///
     /// If a constant was loaded in a register, then the register might be substituted with a
     /// constant if the instruction support the `immediate` version. Due to this, some loads of
-    /// constants on a register might be useless. They are all removed, freeing virtual registers
+    /// constants on a register might be useless. More in general, any instruction without side
+    /// effects (arithmetic instructions, loads of constants and of addresses) whose destination
+    /// virtual register is never read can be removed, freeing virtual registers. As removing an
+    /// instruction might make its sources useless, the process is repeated until nothing changes
+    ///
+    /// @in nodes [Vec<RiscvInstruction>]: instructions of a function
+    /// @return [Vec<RiscvInstruction>]: instructions without the dead ones
+    fn remove_dead_instructions(&self, mut nodes: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
+        let is_pure = |n: &RiscvInstruction| {
+            matches!(
+                n.tt,
+                ADDI | SLTI | ANDI | ORI | XORI | SLLI | SRLI | LUI | ADD | SLT | AND | OR | XOR | SLL | SRL | SUB | DIV | REM | MUL | LA
+            )
+        };
+
+        loop {
+            // Collect the registers which are read. A register read by an instruction without side
+            // effects which also overwrites it (such as the ADDI completing a LUI) does not count,
+            // as that instruction is dead as well if nothing else reads the register
+            let mut read_registers: HashSet<i32> = HashSet::new();
+            for n in &nodes {
+                for src in [n.src1, n.src2] {
+                    if src > 0 && !(is_pure(n) && n.dest == src) {
+                        read_registers.insert(src);
+                    }
+                }
+            }
+
+            let len_before = nodes.len();
+            nodes.retain(|n| !(is_pure(n) && n.dest > 0 && !read_registers.contains(&n.dest)));
+
+            if nodes.len() == len_before {
+                return nodes;
+            }
+        }
+    }
+
+    /// Codegen::get_read_registers
+    ///
+    /// Get the registers read by an arithmetic or load instruction. Since the physical register 0
+    /// is a valid register once the registers are allocated, the fields of the instruction cannot
+    /// be used without taking into account its type
+    ///
+    /// @in node [&RiscvInstruction]: instruction to analyze
+    /// @return [Option<Vec<i32>>]: registers read by the instruction, None if the instruction is
+    /// not an arithmetic or load one
+    fn get_read_registers(node: &RiscvInstruction) -> Option<Vec<i32>> {
+        match node.tt {
+            ADDI | SLTI | ANDI | ORI | XORI | SLLI | SRLI | LB | LH | LW => Some(vec![node.src1]),
+            ADD | SLT | AND | OR | XOR | SLL | SRL | SUB | DIV | REM | MUL => Some(vec![node.src1, node.src2]),
+            _ => None,
+        }
+    }
+
+    /// Codegen::peephole
+    ///
+    /// Once the registers are allocated, some simple patterns of adjacent instructions can be
+    /// simplified:
+    /// - `mv rd, rd` is removed;
+    /// - `mv rx, ry` followed by an instruction reading `rx` and overwriting it is removed,
+    ///   using `ry` in the following instruction;
+    /// - `lui rd, hi` followed by `addi rd, rd, lo` is fused into `li rd, value`;
+    /// - `sw rs, off(rb)` followed by `lw rd, off(rb)` becomes `sw` followed by `mv rd, rs`
+    ///   (the load is removed if `rd` is `rs`).
+    ///
+    /// As only adjacent instructions are considered, and labels, branches and jumps are never
+    /// part of a pattern, the control flow is not affected. The process is repeated until no
+    /// pattern is found.
     ///
-    /// @in [&Vec<RiscvInstruction>]: List of function nodes
-    /// @in [&Vec<TypeWrapper>]: List of arguments to the function
-    /// @return [Vec<RiscvInstruction>]: List of modified functions
-    fn remove_load_constant(&self, mut nodes: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
-        let copy_nodes = nodes.clone();
-        nodes.retain(|node| {
-            if node.tt == ADDI && node.src1 == X0 && node.dest > 0 {
-                // Keep the register if there is a node having that same register as source
-                for n in &copy_nodes {
-                    if n.src1 == node.dest || n.src2 == node.dest {
-                        return true;
+    /// @in nodes [Vec<RiscvInstruction>]: instructions of a function, with allocated registers
+    /// @return [Vec<RiscvInstruction>]: simplified instructions
+    fn peephole(&self, nodes: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
+        let is_move = |n: &RiscvInstruction| (n.tt == ADDI && n.immediate == 0 && n.src1 != X0) || (n.tt == ADD && n.src2 == X0);
+
+        let mut current = nodes;
+        loop {
+            let mut result: Vec<RiscvInstruction> = vec![];
+            let mut changed = false;
+            let mut i = 0;
+
+            while i < current.len() {
+                let node = current[i].clone();
+
+                // mv rd, rd
+                if is_move(&node) && node.dest == node.src1 {
+                    changed = true;
+                    i += 1;
+                    continue;
+                }
+
+                if i + 1 < current.len() {
+                    let next = &current[i + 1];
+
+                    // mv rx, ry; op rx, rx, ... -> op rx, ry, ...
+                    if is_move(&node) && next.dest == node.dest {
+                        if let Some(read) = Codegen::get_read_registers(next) {
+                            if read.contains(&node.dest) {
+                                let mut new_node = next.clone();
+                                if new_node.src1 == node.dest {
+                                    new_node.src1 = node.src1;
+                                }
+                                if read.len() > 1 && new_node.src2 == node.dest {
+                                    new_node.src2 = node.src1;
+                                }
+                                result.push(new_node);
+                                changed = true;
+                                i += 2;
+                                continue;
+                            }
+                        }
+                    }
+
+                    // lui rd, hi; addi rd, rd, lo -> li rd, value
+                    if node.tt == LUI && next.tt == ADDI && next.dest == node.dest && next.src1 == node.dest {
+                        result.push(RiscvInstruction {
+                            tt: LI,
+                            dest: node.dest,
+                            immediate: (node.immediate << 12).wrapping_add(next.immediate),
+                            register_allocated: true,
+                            comment: next.comment.clone(),
+                            ..Default::default()
+                        });
+                        changed = true;
+                        i += 2;
+                        continue;
+                    }
+
+                    // sw rs, off(rb); lw rd, off(rb) -> sw rs, off(rb); mv rd, rs
+                    if node.tt == SW && next.tt == LW && next.src1 == node.src1 && next.immediate == node.immediate {
+                        result.push(node.clone());
+                        if next.dest != node.src2 {
+                            result.push(RiscvInstruction {
+                                tt: ADDI,
+                                dest: next.dest,
+                                src1: node.src2,
+                                immediate: 0,
+                                register_allocated: true,
+                                comment: next.comment.clone(),
+                                ..Default::default()
+                            });
+                        }
+                        changed = true;
+                        i += 2;
+                        continue;
                     }
                 }
-                // If none was found, remove it
-                return false;
+
+                result.push(node);
+                i += 1;
+            }
+
+            current = result;
+            if !changed {
+                return current;
             }
-            // Keep all the other instructions
-            return true;
+        }
+    }
+
+    /// Codegen::add_function_directives
+    ///
+    /// Surround the instructions of a function with the directives required by the linker: the
+    /// function is global (so that the entry point `init` and `main` can be found by a runtime),
+    /// its symbol is marked as a function and its size is provided at the end
+    ///
+    /// @in name [&str]: name of the function
+    /// @in instructions [Vec<RiscvInstruction>]: instructions of the function
+    /// @return [Vec<RiscvInstruction>]: instructions of the function with the directives
+    fn add_function_directives(name: &str, mut instructions: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
+        let mut result: Vec<RiscvInstruction> = vec![
+            RiscvInstruction {
+                tt: GLOBL,
+                name: name.to_string(),
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: TYPE,
+                name: name.to_string(),
+                ..Default::default()
+            },
+        ];
+        result.append(&mut instructions);
+        result.push(RiscvInstruction {
+            tt: SIZE,
+            name: name.to_string(),
+            ..Default::default()
         });
-        return nodes;
+        result
+    }
+
+    /// Codegen::add_line_directives
+    ///
+    /// Add a `.loc` directive before the first instruction of a function coming from the source,
+    /// and then before each instruction coming from a different line than the previous one, so
+    /// that each statement is mapped to its position. Instructions without a position (preamble,
+    /// epilogue, labels) do not change the current one
+    ///
+    /// @in instructions [Vec<RiscvInstruction>]: instructions of a function
+    /// @return [Vec<RiscvInstruction>]: instructions with the directives
+    fn add_line_directives(instructions: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
+        let mut result: Vec<RiscvInstruction> = vec![];
+        let mut current_line: Option<u32> = None;
+
+        for instruction in instructions {
+            if let Some(source_ref) = &instruction.source_ref {
+                if current_line != Some(source_ref.init_line) {
+                    current_line = Some(source_ref.init_line);
+                    result.push(RiscvInstruction {
+                        tt: LOC,
+                        source_ref: Some(source_ref.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+            result.push(instruction);
+        }
+
+        result
     }
 
     /// Codegen::generate_code
@@ -962,16 +2329,35 @@ impl Codegen {
     /// Starting from the list of instruction, transform it into assembly code
     ///
     /// @in ir [&IrNode]: input linear IR of the program
-    /// @return [Vec<RiscvInstruction>]: list of instructions
-    pub fn generate_code(&mut self, ir: &IrNode) -> Vec<RiscvInstruction> {
+    /// @return [Result<Vec<RiscvInstruction>, CodegenError>]: list of instructions, or the error
+    /// found in the IR
+    pub fn generate_code(&mut self, ir: &IrNode) -> Result<Vec<RiscvInstruction>, CodegenError> {
         // Vector containing the result of the instruction
-        let mut code: Vec<RiscvInstruction> = vec![];
+        let mut code: Vec<RiscvInstruction> = vec![RiscvInstruction {
+            tt: SECTION,
+            name: ".text".to_string(),
+            ..Default::default()
+        }];
+
+        if let Some(file_name) = &self.debug_file {
+            code.insert(
+                0,
+                RiscvInstruction {
+                    tt: FILE,
+                    name: file_name.clone(),
+                    ..Default::default()
+                },
+            );
+        }
 
         // We expect the input IrNode to be a Program type, with the list of functions
         let functions_list = if let Program(functions_list) = ir {
             functions_list
         } else {
-            panic!("Provided node to `control_flow_removal` not of type Program")
+            return Err(CodegenError::UnexpectedNode(format!(
+                "`{}` provided to `generate_code`, expected a program",
+                ir.to_string().trim()
+            )));
         };
 
         // For each function
@@ -979,168 +2365,311 @@ impl Codegen {
             let mut in_function: Vec<RiscvInstruction> = vec![];
             let mut result: Vec<RiscvInstruction> = vec![];
             self.constants_map.clear();
+            self.constant_values.clear();
+            self.global_addresses.clear();
+            self.high_registers.clear();
 
             // We expect the element to be a function declaration
             let (name, _, args, nodes) = if let FunctionDeclaration(name, tt, args, nodes) = function {
                 (name, tt, args, nodes)
             } else {
-                panic!("Provided node to `control_flow_removal` not of type FunctionDeclaration")
+                return Err(CodegenError::UnexpectedNode(format!(
+                    "`{}` found in place of a function",
+                    function.to_string().trim()
+                )));
             };
 
-            // Some variables are to be allocated on the stack. We obtain the size of the stack
-            // activation required for the allocation and the offset of each variable on the stack
-            let (ssa, stack_position) = self.get_alloc_stack_offset(nodes);
+            // The arguments are passed in one register each, which cannot hold a value of 64 bits
+            if args.iter().any(Codegen::is_wide) {
+                return Err(CodegenError::Unsupported(format!("arguments of 64 bits in function `{}`", name)));
+            }
 
-            // Get prelude and postlude of the function
-            let mut pre_function = self.get_pre_function(&name, ssa, &args);
-            let mut post_function = self.get_post_function(ssa);
+            // Some variables are to be allocated on the stack. We obtain the space required for
+            // them and the offset of each variable on the stack
+            let (locals_size, stack_position) = self.get_alloc_stack_offset(nodes);
 
-            // If the function is `init`, then its stack is used as global stack
-            if name == "init" {
-                self.global_stack_position = stack_position.clone();
-            }
+            // Move the arguments into virtual registers
+            result.append(&mut self.get_arguments_load(args));
 
             // Convert each node
             for node in nodes {
-                let (mut to_add_in, mut to_add_post) = self.convert_node(node, &stack_position, i_function as u32);
-                in_function.append(&mut to_add_in);
-                to_add_post.append(&mut post_function);
-                post_function = to_add_post;
+                let mut to_add = self.convert_node(node, &stack_position, i_function as u32)?;
+                // Keep track of the source code the instructions come from, for error messages
+                if let Some(source_ref) = node.get_source_ref() {
+                    for instruction in to_add.iter_mut() {
+                        instruction.source_ref = Some(source_ref.clone());
+                    }
+                }
+                in_function.append(&mut to_add);
             }
 
-            // Create the functions by using pre_function and in_function
-            result.append(&mut pre_function);
+            // Create the body of the function, followed by the label the returns jump to
             result.append(&mut in_function);
             if name != "init" {
-                post_function.insert(
-                    0,
-                    RiscvInstruction {
-                        tt: LABEL,
-                        label_function: i_function as u32,
-                        label: 0,
-                        ..Default::default()
-                    },
-                );
-                result.append(&mut post_function);
+                result.push(RiscvInstruction {
+                    tt: LABEL,
+                    label_function: i_function as u32,
+                    label: 0,
+                    ..Default::default()
+                });
+            }
+
+            result = self.remove_dead_instructions(result);
+
+            let (mut body, frame) = self.register_allocation(result, locals_size)?;
+
+            // Once the registers are allocated, the size of the activation record is known, so
+            // that preamble and epilogue can be added
+            let ssa = Codegen::get_activation_record_size(locals_size, &stack_position, &frame)?;
+            result = self.get_pre_function(name, ssa, &frame, &stack_position)?;
+            result.append(&mut body);
+            if name != "init" {
+                let has_runtime_arrays = nodes.iter().any(|node| matches!(node, Alloc(_, _, _, _, _, true, ..)));
+                result.append(&mut self.get_post_function(ssa, &frame, has_runtime_arrays));
+            }
+
+            if self.opt > 0 {
+                result = self.peephole(result);
             }
 
-            result = self.remove_load_constant(result);
+            if self.debug_file.is_some() {
+                result = Codegen::add_line_directives(result);
+            }
+
+            code.append(&mut Codegen::add_function_directives(name, result));
+        }
 
-            result = self.register_allocation(result);
+        // Add the software routines which were used, each one once
+        for (i, routine) in self.software_routines.iter().enumerate() {
+            let result = Codegen::get_software_routine(routine, (functions_list.len() + i) as u32);
+            code.append(&mut Codegen::add_function_directives(routine, result));
+        }
 
-            code.append(&mut result);
+        // Add the sections of the global variables, if any
+        for (name, section) in [(".data", &mut self.data_section), (".bss", &mut self.bss_section)] {
+            if !section.is_empty() {
+                code.push(RiscvInstruction {
+                    tt: SECTION,
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                code.append(section);
+            }
         }
 
-        return code;
+        Ok(code)
+    }
+
+    /// Codegen::source_location
+    ///
+    /// Get the position in the source code of the node an instruction was generated from, to be
+    /// added to an error message
+    ///
+    /// @in instruction [&RiscvInstruction]: instruction to consider
+    /// @return [String]: position of the node or an empty string if it is unknown
+    fn source_location(instruction: &RiscvInstruction) -> String {
+        match &instruction.source_ref {
+            Some(source_ref) => format!(" (line {}, column {})", source_ref.init_line, source_ref.init_char),
+            None => String::new(),
+        }
     }
 
-    /// Codegen::find_usage_register
+    /// Codegen::compute_liveness
     ///
-    /// Perform a BFS of the CFG to see if a virtual register is used again in the graph.
-    /// This function is called in order to decide whether to de-allocate the physical register
-    /// employed for the virtual register or not. Each time we find a branch or a jump, the piece
-    /// of code starting from the destination label has to be analyzed, since the register might be
-    /// used from that point on. However, if a re-definition of the virtual register is found, then
-    /// there is no need to continue using the register.
+    /// Compute, for each instruction, the set of virtual registers which are live after its
+    /// execution, that is the registers which might be used by one of the instructions reachable
+    /// from it before being re-defined. This is used in order to
+    /// decide whether to de-allocate the physical register employed for a virtual register or
+    /// not. The instructions are first split into basic blocks (a block starts with a label or
+    /// after a jump/branch), then the classic iterative dataflow analysis is run on the blocks
+    /// to get their LIVEOUT, which is finally propagated backwards inside each block. The sets are
+    /// ordered, so that covering them never depends on the hashing of the registers.
     ///
-    /// @in instructions: [&Vec<RiscvInstruction>]: List of instructions before the allocation of
+    /// @in instructions: [&[RiscvInstruction]]: List of instructions before the allocation of
     /// registers
-    /// @in starting_point [u32]: Index of the instruction we have to start cover the list
-    /// @in target [i32]: target virtual register
-    /// @result [bool]: whether it is used or not
-    fn find_usage_register(&self, instructions: &Vec<RiscvInstruction>, starting_point: u32, target: i32) -> bool {
-        // Labels we have found in the analysis, thus we do not have to cover them again
-        let mut labels_found: Vec<u32> = vec![];
-        // Labels we still have to analyze
-        let mut labels_to_analyze: Vec<u32> = vec![];
-
-        // Starting from the provided index, cover all the instructions until the end of the
-        // program. If a branch is found, then add the destination label to the labels to be
-        // cover. If a label is found, then add the label to the labels that have been covered. If
-        // the register is re-defined (used as destination) it can be deallocated. If the register
-        // is used as source, it cannot be deallocated
-        for i in starting_point as usize..instructions.len() {
-            let instr = instructions[i].clone();
+    /// @result [Vec<BTreeSet<i32>>]: set of live registers after each instruction
+    fn compute_liveness(&self, instructions: &[RiscvInstruction]) -> Vec<BTreeSet<i32>> {
+        let is_branch = |instr: &RiscvInstruction| instr.tt != LABEL && instr.label > 0 || instr.tt == J;
+        let is_return = |instr: &RiscvInstruction| instr.tt == JALR && instr.dest == X0;
+
+        // Index of the first instruction of each block, and block starting with each label
+        let mut blocks_start: Vec<usize> = vec![];
+        let mut label_to_block: HashMap<u32, usize> = HashMap::new();
+        for i in 0..instructions.len() {
+            let instr = &instructions[i];
+            if i == 0 || instr.tt == LABEL || is_branch(&instructions[i - 1]) || is_return(&instructions[i - 1]) {
+                blocks_start.push(i);
+            }
             if instr.tt == LABEL {
-                labels_found.push(instr.label);
+                label_to_block.insert(instr.label, blocks_start.len() - 1);
             }
-            if instr.src1 == target || instr.src2 == target {
-                return true;
+        }
+        blocks_start.push(instructions.len());
+        let n_blocks = blocks_start.len() - 1;
+
+        // For each block, compute the successors, the registers used before being defined
+        // (UEVAR) and the registers defined (VARKILL)
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; n_blocks];
+        let mut uevar: Vec<BTreeSet<i32>> = vec![BTreeSet::new(); n_blocks];
+        let mut varkill: Vec<BTreeSet<i32>> = vec![BTreeSet::new(); n_blocks];
+        for b in 0..n_blocks {
+            for instr in &instructions[blocks_start[b]..blocks_start[b + 1]] {
+                for src in [instr.src1, instr.src2] {
+                    if src > 0 && !varkill[b].contains(&src) {
+                        uevar[b].insert(src);
+                    }
+                }
+                if instr.dest > 0 {
+                    varkill[b].insert(instr.dest);
+                }
             }
-            if instr.dest == target {
-                return false;
+            let last = &instructions[blocks_start[b + 1] - 1];
+            // The destination of a jump or a branch is a successor
+            if is_branch(last) {
+                if let Some(target) = label_to_block.get(&last.label) {
+                    successors[b].push(*target);
+                }
             }
-            if instr.label > 0 {
-                labels_to_analyze.push(instr.label);
+            // The next block is a successor, unless the block ends with a jump or a return
+            if last.tt != J && !is_return(last) && b + 1 < n_blocks {
+                successors[b].push(b + 1);
             }
         }
 
-        // For each label found which should be analyzed, cover all the instructions starting from
-        // that label up to the end of the program.
-        while labels_to_analyze.len() != 0 {
-            let current_label = labels_to_analyze.pop().unwrap();
-            // Do not proceed if the label was already covered
-            if labels_found.contains(&current_label) {
-                continue;
-            }
-            labels_found.push(current_label);
-
-            let mut label_is_found = false;
-            // For each instruction of the program, cover them. Start the anlaysis of the registers
-            // only if the target label was found (we are not interested in the blocks before)
-            for i in 0..instructions.len() {
-                let instr = instructions[i].clone();
-                if instr.tt == LABEL && instr.label == current_label {
-                    label_is_found = true;
-                    continue;
+        // LIVEOUT(b) = U_{s in succ(b)} (UEVAR(s) U (LIVEOUT(s) - VARKILL(s))), iterated until
+        // a fixed point is reached. Blocks are covered in reverse order to converge faster
+        let mut live_in: Vec<BTreeSet<i32>> = uevar.clone();
+        let mut live_out: Vec<BTreeSet<i32>> = vec![BTreeSet::new(); n_blocks];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for b in (0..n_blocks).rev() {
+                let mut new_live_out: BTreeSet<i32> = BTreeSet::new();
+                for s in &successors[b] {
+                    new_live_out.extend(live_in[*s].iter());
                 }
-                if !label_is_found {
-                    continue;
-                }
-                // Break if the label we are in was already found
-                if instr.tt == LABEL && instr.label > 0 && labels_found.contains(&instr.label) {
-                    break;
-                }
-                // It can be deallocated if it is found as target
-                if instr.dest == target {
-                    return false;
+                if new_live_out != live_out[b] {
+                    for reg in &new_live_out {
+                        if !varkill[b].contains(reg) {
+                            live_in[b].insert(*reg);
+                        }
+                    }
+                    live_out[b] = new_live_out;
+                    changed = true;
                 }
-                // It cannot be deallocated if it is found as source
-                if instr.src1 == target || instr.src2 == target {
-                    return true;
+            }
+        }
+
+        // Inside each block, starting from its LIVEOUT, go backwards: what is live before an
+        // instruction is live after the previous one. An instruction kills its destination and
+        // makes its sources live
+        let mut result: Vec<BTreeSet<i32>> = vec![BTreeSet::new(); instructions.len()];
+        for b in 0..n_blocks {
+            let mut live = live_out[b].clone();
+            for i in (blocks_start[b]..blocks_start[b + 1]).rev() {
+                let instr = &instructions[i];
+                result[i] = live.clone();
+                if instr.dest > 0 {
+                    live.remove(&instr.dest);
                 }
-                // Add a label to the covered ones
-                if instr.label > 0 && instr.tt == LABEL {
-                    labels_found.push(instr.label);
-                // Add a destination label to the ones to be analyzed
-                } else if instr.label > 0 {
-                    labels_to_analyze.push(instr.label);
+                for src in [instr.src1, instr.src2] {
+                    if src > 0 {
+                        live.insert(src);
+                    }
                 }
             }
         }
-        // If nothing was found, then the register can be deallocated
-        return false;
+
+        result
+    }
+
+    /// Codegen::get_physical_register
+    ///
+    /// Get the register associated to an entry of the allocator. The first entries are the
+    /// general registers, which are used as they are, while the last ones are the argument
+    /// registers, expressed as specific registers (so that `a0` is always `A0`)
+    ///
+    /// @in index [usize]: entry of the allocator
+    /// @return [i32]: physical register
+    fn get_physical_register(index: usize) -> i32 {
+        if index < N_GENERAL_REGISTERS {
+            return index as i32;
+        }
+        A0 - (index - N_GENERAL_REGISTERS) as i32
+    }
+
+    /// Codegen::get_argument_registers_usage
+    ///
+    /// An argument register can be allocated to a virtual register only if it is not used by the
+    /// calling convention while the virtual register is alive: the argument registers are
+    /// clobbered by a call, and they are explicitly written before a call (or a system call), read
+    /// at the beginning of a function and used for the return value. For each argument register, compute how many
+    /// instructions up to each position (excluded) either use it explicitly or are jumps and links
+    ///
+    /// @in instructions [&[RiscvInstruction]]: instructions of the function
+    /// @return [Vec<Vec<usize>>]: for each argument register, the cumulative count of instructions
+    /// preventing its usage
+    fn get_argument_registers_usage(instructions: &[RiscvInstruction]) -> Vec<Vec<usize>> {
+        let mut result: Vec<Vec<usize>> = vec![vec![0; instructions.len() + 1]; N_ARGUMENT_REGISTERS];
+        for (k, usage) in result.iter_mut().enumerate() {
+            let reg = A0 - k as i32;
+            for (i, instr) in instructions.iter().enumerate() {
+                let is_used = instr.tt == JAL || instr.tt == JALR || instr.tt == ECALL || instr.dest == reg || instr.src1 == reg || instr.src2 == reg;
+                usage[i + 1] = usage[i] + is_used as usize;
+            }
+        }
+        result
     }
 
     /// Codegen::register_allocation
     ///
-    /// Given a list of instructions, perform the register allocation
+    /// Given a list of instructions, perform the register allocation. The temporary registers are
+    /// used first, then the argument registers (when the calling convention allows it) and
+    /// finally the saved registers, which have to be preserved in the activation record
     ///
     /// @in instructions [Vec<RiscvInstruction>]: List of instructions before allocation
-    /// @result [Vec<RiscvInstruction>]: List of instructions after allocation
-    fn register_allocation(&self, instructions: Vec<RiscvInstruction>) -> Vec<RiscvInstruction> {
-        // Result of the allocation
+    /// @in locals_size [u32]: space used by the local variables, below which the registers are saved
+    /// @result [Result<(Vec<RiscvInstruction>, Frame), CodegenError>]: List of instructions after
+    /// allocation together with the save slots used, or an error if a virtual register is read
+    /// before being defined
+    fn register_allocation(&self, instructions: Vec<RiscvInstruction>, locals_size: u32) -> Result<(Vec<RiscvInstruction>, Frame), CodegenError> {
+        // Result of the allocation. The maps below are only used for lookups: the allocation
+        // covers the instructions and the physical registers in order, so that the output is the
+        // same at each run
         let mut result: Vec<RiscvInstruction> = vec![];
-        // List of physical registers associated to each virtual register
-        let mut virtual_register_allocation: HashMap<i32, i32> = HashMap::new();
+        // Entry of the allocator (see `get_physical_register`) associated to each virtual register
+        let mut virtual_register_allocation: HashMap<i32, usize> = HashMap::new();
         // Status of each physical register:
         // .0 -> is the register currently in use or not
         // .1 -> was the register used at least once
         // .2 -> which virtual register it is currently storing
-        let mut is_register_used: Vec<(bool, bool, i32)> = vec![(false, false, 0); 16];
+        let mut is_register_used: Vec<(bool, bool, i32)> = vec![(false, false, 0); N_GENERAL_REGISTERS + N_ARGUMENT_REGISTERS];
+        // Order in which the entries are tried: `t0..t6`, `a0..a7`, `s1..s9`
+        let allocation_order: Vec<usize> = (0..7)
+            .chain(N_GENERAL_REGISTERS..is_register_used.len())
+            .chain(7..N_GENERAL_REGISTERS)
+            .collect();
+        // Slots used to save the registers in the activation record
+        let mut frame = Frame::default();
         let mut current_offset_tp = 0;
         let mut offset_to_use_tp = 0;
         let mut virtual_registers_in_memory: HashMap<i32, i32> = HashMap::new();
+        // Set of live virtual registers after each instruction
+        let liveness = self.compute_liveness(&instructions);
+        // Last instruction in which each virtual register is either alive or read
+        let mut last_usage: HashMap<i32, usize> = HashMap::new();
+        for i in 0..instructions.len() {
+            for v in liveness[i].iter().chain([instructions[i].src1, instructions[i].src2].iter()) {
+                if *v > 0 {
+                    last_usage.insert(*v, i);
+                }
+            }
+        }
+        // A virtual register can be freed only if it is not required by any of the following
+        // instructions: being dead on the current path is not enough, as a block placed later
+        // in the code might still read it
+        let is_dead = |virtual_value: i32, i: usize| last_usage.get(&virtual_value).is_none_or(|last| i >= *last);
+        let argument_registers_usage = Codegen::get_argument_registers_usage(&instructions);
 
         // Cover each instruction in order
         for i in 0..instructions.len() {
@@ -1157,7 +2686,7 @@ impl Codegen {
                     if is_register_used[j].0 {
                         // Deallocate if the virtual register it is employing will not be used
                         // afterwards
-                        if !self.find_usage_register(&instructions, i as u32 + 1, is_register_used[j].2) {
+                        if is_dead(is_register_used[j].2, i) {
                             is_register_used[j].0 = false;
                         }
                     }
@@ -1185,14 +2714,21 @@ impl Codegen {
                         // Since the register are initialized before their usage, we expect a physical
                         // register to be already associated to the virtual register. If not, there is an
                         // error
-                        match virtual_register_allocation.get(&virtual_value) {
-                            Some(reg) => instr.src1 = *reg as i32,
-                            None => panic!("Virtual register {} has no associated physical register", instr.src1),
-                        }
+                        let index = match virtual_register_allocation.get(&virtual_value) {
+                            Some(index) => *index,
+                            None => {
+                                return Err(CodegenError::RegisterAllocation(format!(
+                                    "virtual register {} has no associated physical register{}",
+                                    instr.src1,
+                                    Codegen::source_location(&instr)
+                                )))
+                            }
+                        };
+                        instr.src1 = Codegen::get_physical_register(index);
                         // Deallocate if the virtual register it is employing will not be used
                         // afterwards
-                        if !self.find_usage_register(&instructions, i as u32 + 1, virtual_value) {
-                            is_register_used[instr.src1 as usize].0 = false;
+                        if is_dead(virtual_value, i) {
+                            is_register_used[index].0 = false;
                         }
                     }
                 }
@@ -1218,14 +2754,21 @@ impl Codegen {
                         // Since the register are initialized before their usage, we expect a physical
                         // register to be already associated to the virtual register. If not, there is an
                         // error
-                        match virtual_register_allocation.get(&virtual_value) {
-                            Some(reg) => instr.src2 = *reg as i32,
-                            None => panic!("Virtual register {} has no associated physical register", instr.src2),
-                        }
+                        let index = match virtual_register_allocation.get(&virtual_value) {
+                            Some(index) => *index,
+                            None => {
+                                return Err(CodegenError::RegisterAllocation(format!(
+                                    "virtual register {} has no associated physical register{}",
+                                    instr.src2,
+                                    Codegen::source_location(&instr)
+                                )))
+                            }
+                        };
+                        instr.src2 = Codegen::get_physical_register(index);
                         // Deallocate if the virtual register it is employing will not be used
                         // afterwards
-                        if !self.find_usage_register(&instructions, i as u32 + 1, virtual_value) {
-                            is_register_used[instr.src2 as usize].0 = false;
+                        if is_dead(virtual_value, i) {
+                            is_register_used[index].0 = false;
                         }
                     }
                 }
@@ -1236,28 +2779,38 @@ impl Codegen {
                 match virtual_register_allocation.get(&instr.dest) {
                     // It might happen that the destination register was used before as source. In that
                     // case, reuse the same physical register.
-                    Some(reg) => {
-                        instr.dest = *reg as i32;
+                    Some(index) => {
+                        let index = *index;
+                        instr.dest = Codegen::get_physical_register(index);
                         // Deallocate the register if not used afterwards
-                        if !self.find_usage_register(&instructions, i as u32 + 1, virtual_value) {
-                            is_register_used[instr.dest as usize].0 = false;
+                        if is_dead(virtual_value, i) {
+                            is_register_used[index].0 = false;
                         }
                     }
                     // Otherwise, allocate a new register
                     None => {
-                        let mut register_to_use: i32 = -1;
-                        for i in 0..is_register_used.len() {
-                            // If register `i` is currently free, use it
-                            if !is_register_used[i].0 {
-                                register_to_use = i as i32;
-                                is_register_used[i] = (true, true, instr.dest);
-                                virtual_register_allocation.insert(instr.dest, register_to_use);
-                                instr.dest = register_to_use;
+                        let last = *last_usage.get(&virtual_value).unwrap_or(&i);
+                        let mut register_to_use: Option<usize> = None;
+                        for &index in &allocation_order {
+                            // An argument register cannot be used if it is required by the calling
+                            // convention before the virtual register is dead
+                            if index >= N_GENERAL_REGISTERS {
+                                let usage = &argument_registers_usage[index - N_GENERAL_REGISTERS];
+                                if usage[last + 1] != usage[i] {
+                                    continue;
+                                }
+                            }
+                            // If register `index` is currently free, use it
+                            if !is_register_used[index].0 {
+                                register_to_use = Some(index);
+                                is_register_used[index] = (true, true, instr.dest);
+                                virtual_register_allocation.insert(instr.dest, index);
+                                instr.dest = Codegen::get_physical_register(index);
                                 break;
                             }
                         }
                         // Allocate virtual registers on the TP stack
-                        if register_to_use == -1 {
+                        if register_to_use.is_none() {
                             save_on_tp = true;
                             instr.dest = 16;
                             match virtual_registers_in_memory.get(&virtual_value) {
@@ -1267,7 +2820,7 @@ impl Codegen {
                                 None => {
                                     virtual_registers_in_memory.insert(virtual_value, current_offset_tp);
                                     offset_to_use_tp = current_offset_tp;
-                                    current_offset_tp -= 4;
+                                    current_offset_tp += 4;
                                 }
                             }
                         }
@@ -1277,17 +2830,17 @@ impl Codegen {
             // The instruction has its registers allocated
             instr.register_allocated = true;
 
-            // If we are handling a CALL instruction, we need to store in the activation record of
-            // the function the registers `t0..t6` which are currently in use, since the caller is
-            // in charge of storing them
-            if instr.tt == JAL && instr.src1 == 0 {
+            // If we are handling a CALL instruction (either direct with `jal` or indirect with `jalr`),
+            // we need to store in the activation record of the function the registers `t0..t6`
+            // which are currently in use, since the caller is in charge of storing them
+            if (instr.tt == JAL || instr.tt == JALR) && instr.dest == RA {
                 for i in 0..=6 {
                     if is_register_used[i].0 {
                         result.push(RiscvInstruction {
                             tt: SW,
                             src1: FP,
                             src2: i as i32,
-                            immediate: -4 - i as i32 * 4,
+                            immediate: Codegen::get_save_slot(&mut frame, locals_size, i as i32),
                             register_allocated: true,
                             comment: format!("# Save register on stack as it must be preserved"),
                             ..Default::default()
@@ -1302,7 +2855,7 @@ impl Codegen {
                             tt: LW,
                             dest: i as i32,
                             src1: FP,
-                            immediate: -4 - i as i32 * 4,
+                            immediate: Codegen::get_save_slot(&mut frame, locals_size, i as i32),
                             register_allocated: true,
                             comment: format!("# Restore register from stack"),
                             ..Default::default()
@@ -1325,165 +2878,86 @@ impl Codegen {
             }
         }
 
-        // If some of the `s` registers are used in the function, we have to store them in the
-        // activation record of the function in the `pre_function` block. This is required as,
-        // according to the ABI, the callee is the one saving those registers
-        for i in 7..is_register_used.len() {
-            if is_register_used[i].1 {
-                // Save them in the `pre_function`
-                result.insert(
-                    1, // After the function label
-                    RiscvInstruction {
-                        tt: SW,
-                        src1: SP,
-                        src2: i as i32,
-                        immediate: -4 - i as i32 * 4,
-                        register_allocated: true,
-                        comment: format!("# Store register on stack as it must be preserved"),
-                        ..Default::default()
-                    },
-                );
-                // Restore them before returning from the function
-                result.insert(
-                    result.len() - 1,
-                    RiscvInstruction {
-                        tt: LW,
-                        dest: i as i32,
-                        src1: SP,
-                        immediate: -4 - i as i32 * 4,
-                        register_allocated: true,
-                        comment: format!("# Restore register from stack"),
-                        ..Default::default()
-                    },
-                );
+        // If some of the `s` registers are used in the function, they have to be stored in the
+        // activation record by the preamble. This is required as, according to the ABI, the callee
+        // is the one saving those registers. s10 and s11 are used only when some registers have
+        // been spilled to memory
+        for (i, used) in is_register_used.iter().enumerate().take(N_GENERAL_REGISTERS).skip(7) {
+            if used.1 {
+                let offset = Codegen::get_save_slot(&mut frame, locals_size, i as i32);
+                frame.callee_saved.push((i as i32, offset));
             }
         }
-        // Save s10 and s11 if some registers have been spilled to memeory
         if current_offset_tp != 0 {
-            result.insert(
-                1, // After the function label
-                RiscvInstruction {
-                    tt: SW,
-                    src1: SP,
-                    src2: 16 as i32,
-                    immediate: -4 - 16 as i32 * 4,
-                    register_allocated: true,
-                    comment: format!("# Store register on stack as it must be preserved"),
-                    ..Default::default()
-                },
-            );
-            // Restore them before returning from the function
-            result.insert(
-                result.len() - 1,
-                RiscvInstruction {
-                    tt: LW,
-                    dest: 16 as i32,
-                    src1: SP,
-                    immediate: -4 - 16 as i32 * 4,
-                    register_allocated: true,
-                    comment: format!("# Restore register from stack"),
-                    ..Default::default()
-                },
-            );
-            result.insert(
-                1, // After the function label
-                RiscvInstruction {
-                    tt: SW,
-                    src1: SP,
-                    src2: 17 as i32,
-                    immediate: -4 - 17 as i32 * 4,
-                    register_allocated: true,
-                    comment: format!("# Store register on stack as it must be preserved"),
-                    ..Default::default()
-                },
-            );
-            // Restore them before returning from the function
-            result.insert(
-                result.len() - 1,
-                RiscvInstruction {
-                    tt: LW,
-                    dest: 17 as i32,
-                    src1: SP,
-                    immediate: -4 - 17 as i32 * 4,
-                    register_allocated: true,
-                    comment: format!("# Restore register from stack"),
-                    ..Default::default()
-                },
-            );
-            // Modify TP
-            result.insert(
-                1, // After the function label
-                RiscvInstruction {
-                    tt: ADDI,
-                    dest: TP,
-                    src1: TP,
-                    immediate: current_offset_tp,
-                    register_allocated: true,
-                    comment: format!("# Space for virtual registers on TP stack"),
-                    ..Default::default()
-                },
-            );
-            // Modify TP back
-            result.insert(
-                result.len() - 1,
-                RiscvInstruction {
-                    tt: ADDI,
-                    dest: TP,
-                    src1: TP,
-                    immediate: -current_offset_tp,
-                    register_allocated: true,
-                    comment: format!("# Restore space for virtual registers on TP stack"),
-                    ..Default::default()
-                },
-            );
+            for i in [16, 17] {
+                let offset = Codegen::get_save_slot(&mut frame, locals_size, i);
+                frame.callee_saved.push((i, offset));
+            }
         }
+        frame.tp_size = current_offset_tp;
 
-        return result;
+        Ok((result, frame))
+    }
+
+    /// Codegen::get_save_slot
+    ///
+    /// Get the slot in the activation record used to save a physical register. Slots are assigned
+    /// the first time a register requires one, right below the local variables, so that only the
+    /// registers actually saved take space in the activation record
+    ///
+    /// @in frame [&mut Frame]: slots assigned so far
+    /// @in locals_size [u32]: space used by the local variables
+    /// @in register [i32]: physical register to save
+    /// @return [i32]: offset of the slot with respect to the frame pointer
+    fn get_save_slot(frame: &mut Frame, locals_size: u32, register: i32) -> i32 {
+        if let Some((_, offset)) = frame.saved.iter().find(|(r, _)| *r == register) {
+            return *offset;
+        }
+        let offset = -(locals_size as i32) - 4 * (frame.saved.len() as i32 + 1);
+        frame.saved.push((register, offset));
+        offset
     }
 
     /// Codegen::get_alloc_stack_offset
     ///
     /// Given a function, reserve the space for all the declarations which are not about arrays.
-    /// Together with those, the space to store registers (both temporary and non-temporary), ra
-    /// and s0 has to be reserved as well.
+    /// The variables are placed right below `s0`; the space to save the registers, `ra` and `s0`
+    /// is reserved after the register allocation, once it is known how many of them are required.
     ///
     /// @in [&Vec<IrNode>]: nodes of the function
-    /// @result [(u32, Vec<StackOffset>)]: Size of the activation record, together with the offset
-    /// of each variable on the stack with respect to `s0`
+    /// @result [(u32, Vec<StackOffset>)]: Space used by the variables (multiple of 4), together
+    /// with the offset of each variable on the stack with respect to `s0`
     fn get_alloc_stack_offset(&self, ir: &Vec<IrNode>) -> (u32, Vec<StackOffset>) {
         let mut result: Vec<StackOffset> = vec![];
-        let mut current_offset = 76;
-        // Leave the space to store the registers (18 of them), `ra` and `s0`
-        let mut ssa = 20 * 4;
-        // First cover all the variables of size 4, then 2 and 1.
-        let available_sizes = vec![4, 2, 1];
+        let mut current_offset = 0;
+        // First cover all the variables of size 8, then 4, 2 and 1.
+        let available_sizes = vec![8, 4, 2, 1];
 
         for s in available_sizes {
-            // Look for elements of size `s` (in 4, 2 and 1) and allocate them
+            // Look for elements of size `s` (in 8, 4, 2 and 1) and allocate them
             for node in ir {
-                if let Alloc(tt, register, _, _, _, from_register, name) = node {
+                if let Alloc(tt, register, _, is_global, _, from_register, name, ..) = node {
                     // Allocation of the arrays happens on top of the stack, not in the activation
-                    // record
-                    if tt.get_size() == s && !from_register {
+                    // record, while global variables are in the `.data` or `.bss` sections
+                    if tt.get_size() == s && !from_register && !is_global {
+                        // Compute the offset of the variable (it is always aligned to the size of
+                        // the variable, since we cover them in order from the largest, starting
+                        // from `s0`, which is a multiple of 16)
+                        current_offset += s as i32;
                         result.push(StackOffset {
                             size: s,
                             reg: *register,
                             offset: -current_offset,
                             name: name.to_string(),
                         });
-                        // Compute the offset of the next variable to be used (it is always aligned
-                        // to the size of the variable, since we cover them in order)
-                        current_offset += s as i32;
-                        // Increment the allocation record size
-                        ssa += s as i32;
                     }
                 }
             }
         }
 
-        // The stack grows by multiple of 16
-        ssa = (ssa + 15) & -16;
+        // The slots to save the registers are aligned to 4
+        current_offset = (current_offset + 3) & -4;
 
-        return (ssa as u32, result);
+        (current_offset as u32, result)
     }
 }
@@ -0,0 +1,1557 @@
+mod test {
+
+    /// test::get_parser
+    ///
+    /// Get a parser for the tokens of the input, which must be tokenized without errors
+    ///
+    /// @in input [&str]: source code to parse
+    /// @return [Parser]: parser ready to parse the input
+    #[cfg(test)]
+    fn get_parser(input: &str) -> crate::parser::parser::Parser {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let mut l = Lexer::new(String::from(input), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        Parser::new(tokens.unwrap(), String::from(""))
+    }
+
+    /// test::get_ir
+    ///
+    /// Get the IR of the input, which must be parsed without errors, optimized as the compiler
+    /// does at the given level
+    ///
+    /// @in input [&str]: source code to compile
+    /// @in opt [u32]: level of optimization
+    /// @return [IrNode]: IR of the program
+    #[cfg(test)]
+    fn get_ir(input: &str, opt: u32) -> crate::lirgen::irnode::IrNode {
+        use crate::compiler::optimize;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let ast = get_parser(input).parse().unwrap();
+        optimize(Lirgen::new(opt).linearize_ast(&ast), opt)
+    }
+
+    /// test::get_code
+    ///
+    /// Get the assembly of the IR, one instruction per line
+    ///
+    /// @in ir [&IrNode]: IR of the program
+    /// @in opt [u32]: level of optimization of the codegen
+    /// @in m_extension [bool]: whether the M extension is available
+    /// @return [String]: assembly of the program
+    #[cfg(test)]
+    fn get_code(ir: &crate::lirgen::irnode::IrNode, opt: u32, m_extension: bool) -> String {
+        use crate::backend::codegen_riscv::Codegen;
+
+        Codegen::new(opt, m_extension)
+            .generate_code(ir)
+            .unwrap()
+            .iter()
+            .map(|i| i.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn codegen_riscv_test_large_constants() {
+        let input = String::from(
+            "
+            void main() {
+              u32 a = 0x7FF;
+              u32 b = 0x800;
+              u32 c = 0xFFF;
+              u32 d = 0x12345;
+              return;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+
+        // 0x7FF fits in the immediate of ADDI
+        assert!(code.contains("\tli\tt0, 2047\t"));
+        // 0x800 and 0xFFF have bit 11 set, so the upper immediate is incremented and the lower
+        // part is negative
+        assert!(code.contains("\tlui\tt0, 1\t"));
+        assert!(code.contains("\taddi\tt0, t0, -2048\t"));
+        assert!(code.contains("\tlui\tt0, 1\t"));
+        assert!(code.contains("\taddi\tt0, t0, -1\t"));
+        // 0x12345 = (18 << 12) + 837
+        assert!(code.contains("\tlui\tt0, 18\t"));
+        assert!(code.contains("\taddi\tt0, t0, 837\t"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_negative_immediates() {
+        let input = String::from(
+            "
+            i32 f(i32 x, u32 y) {
+              i32 a = x + (-1);
+              u32 b = y & 0xFFFFFFF0;
+              i32 c = x + (-3000);
+              return a;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+
+        // Negative constants in the range of a 12 bits signed integer are used as immediates
+        assert!(code.contains("\taddi\tt0, t0, -1\t"));
+        assert!(code.contains("\tandi\tt0, t0, -16\t"));
+        // Otherwise, they are loaded in a register
+        assert!(code.contains("\tlui\tt1, 1\t"));
+        assert!(code.contains("\taddi\tt1, t1, -1096\t"));
+        assert!(code.contains("\tsub\tt1, zero, t1\t"));
+        assert!(code.contains("\tadd\tt0, t0, t1\t"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_global_variables() {
+        let input = String::from(
+            "
+            u32 g = 0x12345;
+            u32 h;
+            i8 k = (i8)-3;
+            u32* p = &g;
+            u32 arr[4];
+
+            u32 f() {
+              return g + 1;
+            }
+
+            u32 w = f();
+
+            u32 main() {
+              arr[2] = g + h;
+              return arr[2] + w + *p;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+
+        // Variables initialized at compile time are in the `.data` section, the other ones in the
+        // `.bss` section
+        let expected_sections = String::from(
+            "
+\t.data
+\t.align\t2\t
+g:\t
+\t.word\t0x12345\t
+\t.align\t0\t
+k:\t
+\t.byte\t-3\t
+\t.align\t2\t
+p:\t
+\t.word\tg\t
+\t.align\t2\t
+arr:\t
+\t.word\tarr.data\t
+
+\t.bss
+\t.align\t2\t
+h:\t
+\t.zero\t4\t
+\t.align\t2\t
+arr.data:\t
+\t.zero\t16\t
+\t.align\t2\t
+w:\t
+\t.zero\t4\t
+
+\t.globl\tinit.exit_code
+\t.align\t2\t
+init.exit_code:\t
+\t.zero\t4\t
+",
+        );
+        assert!(code.ends_with(&expected_sections));
+
+        // Only the dynamic initializers are in `init`, and the global variables are referenced
+        // through their labels
+        assert!(code.contains("\tla\tt1, w\t# Load address of global variable w\n\tsw\tt0, 0(t1)\t"));
+        assert!(code.contains("\tla\tt0, g\t"));
+        assert!(!code.contains("Initialize global variable g"));
+        assert!(!code.contains("gp"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_data_radix() {
+        use crate::compiler::compile_str;
+
+        let input = String::from(
+            "
+            int g = 0x1234;
+            u8 b = 0b101;
+            u16 o = 017;
+            i32 n = -0x10;
+            u32 m = 0xFFFFFFFF;
+            i8 c = (i8) 0x7f;
+            u32 d = 12;
+            u8 l = 'a';
+
+            u32 main() {
+              return g;
+            }
+",
+        );
+
+        // The initial values are printed in the base used in the source, with the directive
+        // appropriate to the type of the variable
+        for opt in 0..=3 {
+            let code = compile_str(&input, opt, "rv32im").unwrap();
+            for data in [
+                "g:\t\n\t.word\t0x1234\t",
+                "b:\t\n\t.byte\t0b101\t",
+                "o:\t\n\t.half\t017\t",
+                "n:\t\n\t.word\t-0x10\t",
+                "m:\t\n\t.word\t0xffffffff\t",
+                "c:\t\n\t.byte\t0x7f\t",
+                "d:\t\n\t.word\t12\t",
+                "l:\t\n\t.byte\t97\t",
+            ] {
+                assert!(code.contains(data), "{}\n{}", data, code);
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_rv32i() {
+        let input = String::from(
+            "
+            i32 f(i32 a, i32 b, u32 c, u32 d) {
+              i32 x = a * b + a / b + a % b;
+              u32 y = c * d + c / d + c % d;
+              return x * (i32)y;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+
+        // With the M extension, the instructions are used directly
+        let code = get_code(&ir, 0, true);
+        for instruction in ["\tmul\t", "\tdiv\t", "\trem\t", "\tdivu\t", "\tremu\t"] {
+            assert!(code.contains(instruction));
+        }
+        assert!(!code.contains("__"));
+
+        // Without it, software routines are called, and each of them is added once
+        let code = get_code(&ir, 0, false);
+        for instruction in ["\tmul\t", "\tdiv\t", "\trem\t", "\tdivu\t", "\tremu\t"] {
+            assert!(!code.contains(instruction));
+        }
+        for routine in ["__mulsi3", "__divsi3", "__modsi3", "__udivsi3", "__umodsi3"] {
+            assert!(code.contains(&format!("\tcall\t{}\t", routine)));
+            assert_eq!(code.matches(&format!("\n{}:", routine)).count(), 1);
+        }
+        assert_eq!(code.matches("\tcall\t__mulsi3\t").count(), 3);
+    }
+
+    #[test]
+    fn codegen_riscv_test_peephole() {
+        let input = String::from(
+            "
+            u32 f(u32 a) { return a + 1; }
+            u32 main() {
+              u32 a = 0x12345;
+              u32 b = f(a) << 2;
+              return a + b;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 1);
+
+        let count_instructions = |code: &String| code.lines().filter(|line| line.starts_with("\t")).count();
+
+        let code_no_peephole = get_code(&ir, 0, true);
+        let code = get_code(&ir, 1, true);
+
+        // The two `lui`/`addi` pairs (the initialization of sp and the constant) become `li`,
+        // while the `lui` initializing tp is kept. The activation record of `init` only holds
+        // `ra` and `s0`, so sp is decremented by 16
+        assert_eq!(code_no_peephole.matches("\tlui\t").count(), 3);
+        assert_eq!(code.matches("\tlui\t").count(), 1);
+        assert!(code.contains("\tli\tt0, 74565\t"));
+        assert!(code.contains("\tli\tsp, 65520\t"));
+
+        // The move of the returned value is removed, using a0 directly (the move of the argument
+        // in `f` and the one of the exit code in `init`, which is not used right away, are kept)
+        assert_eq!(code_no_peephole.matches("\tmv\tt0, a0\t").count(), 3);
+        assert_eq!(code.matches("\tmv\tt0, a0\t").count(), 2);
+        assert!(code.contains("\tslli\tt0, a0, 2\t"));
+
+        assert_eq!(count_instructions(&code), count_instructions(&code_no_peephole) - 3);
+    }
+
+    #[test]
+    fn codegen_riscv_test_dead_instructions() {
+        let input = String::from(
+            "
+            u32 f(u32 x) {
+              u32 a = x + 1;
+              u32 b = a * 0x12345;
+              u32 c = b << 3;
+              return a;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+
+        // The addresses of the variables are computed but never read, as the variables are
+        // accessed through their offset from s0
+        assert!(!code.contains("# Initialize variable"));
+        // Each value is dead once stored, so two registers are enough
+        assert!(code.contains("\tmul\tt0, t0, t1\t"));
+        assert!(code.contains("\tslli\tt0, t0, 3\t"));
+        for register in ["t2", "t3", "t4"] {
+            assert!(!code.contains(register));
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_argument_registers_allocation() {
+        let input = String::from(
+            "
+            u32 g(u32 x) { return x; }
+
+            u32 f(u32 x) {
+              u32 v0 = x + 1; u32 v1 = x + 2; u32 v2 = x + 3; u32 v3 = x + 4; u32 v4 = x + 5;
+              u32 v5 = x + 6; u32 v6 = x + 7; u32 v7 = x + 8; u32 v8 = x + 9; u32 v9 = x + 10;
+              u32 v10 = x + 11; u32 v11 = x + 12; u32 v12 = x + 13; u32 v13 = x + 14; u32 v14 = x + 15;
+              u32 v15 = x + 16; u32 v16 = x + 17; u32 v17 = x + 18; u32 v18 = x + 19; u32 v19 = x + 20;
+              return v0 + v1 + v2 + v3 + v4 + v5 + v6 + v7 + v8 + v9 + v10 + v11 + v12 + v13 + v14 + v15 + v16 + v17 + v18 + v19;
+            }
+
+            u32 h(u32 x) {
+              return (x + 1) + ((x + 2) + ((x + 3) + ((x + 4) + ((x + 5) + ((x + 6) + ((x + 7) + ((x + 8) + g(x))))))));
+            }
+
+            u32 main() {
+              return f(7) + h(7);
+            }
+",
+        );
+
+        let ir = get_ir(&input, 1);
+        let code = get_code(&ir, 0, true);
+
+        let f_start = code.find("\nf:").unwrap();
+        let h_start = code.find("\nh:").unwrap();
+        let main_start = code.find("\nmain:").unwrap();
+        let f_code = &code[f_start..h_start];
+        let h_code = &code[h_start..main_start];
+
+        // The 20 values are alive at the same time: the argument registers are used after the
+        // temporary ones, so that nothing is spilled on the TP stack
+        assert!(!f_code.contains("tp"));
+        assert!(f_code.contains("\taddi\ta0, t0, 7\t"));
+        assert!(f_code.contains("\taddi\ta7, t0, 14\t"));
+        assert!(f_code.contains("\taddi\ts1, t0, 15\t"));
+
+        // The partial results are alive across a call, so the argument registers cannot be used:
+        // once the temporary registers are over, the saved ones are employed
+        assert!(h_code.contains("\taddi\tt6, t0, 6\t"));
+        assert!(h_code.contains("\taddi\ts1, t0, 7\t"));
+        assert!(h_code.contains("\taddi\ts2, t0, 8\t"));
+        for register in ["a1", "a2", "a3", "a4", "a5", "a6", "a7"] {
+            assert!(!h_code.contains(register));
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_function_directives() {
+        let input = String::from(
+            "
+            u32 f(u32 x) { return x * 3; }
+
+            u32 main() {
+              return f(2);
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, false);
+
+        assert!(code.starts_with("\n\t.text\n\n\t.globl\tinit\n\t.type\tinit, @function\ninit:"));
+        // Each function, software routines included, is global and has its size
+        for name in ["f", "main", "__mulsi3"] {
+            assert!(code.contains(&format!("\n\t.globl\t{}\n\t.type\t{}, @function\n{}:", name, name, name)));
+            assert!(code.contains(&format!("\tret\t\n\t.size\t{}, .-{}\n", name, name)));
+        }
+        // `init` never returns
+        assert!(code.contains("\tj\tL_0_0\t\n\t.size\tinit, .-init\n"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_errors() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::backend::codegen_riscv::{Codegen, CodegenError};
+        use crate::lirgen::irnode::IrNode;
+
+        let tt = TypeWrapper {
+            type_native: TypeNative::U32,
+            ..Default::default()
+        };
+        let function = |nodes: Vec<IrNode>| IrNode::Program(vec![IrNode::FunctionDeclaration("main".to_string(), tt.clone(), vec![], nodes)]);
+
+        // The input must be a program made of functions
+        let result = Codegen::new(0, true).generate_code(&IrNode::Label(1));
+        assert!(matches!(result, Err(CodegenError::UnexpectedNode(_))));
+        let result = Codegen::new(0, true).generate_code(&IrNode::Program(vec![IrNode::Label(1)]));
+        assert!(matches!(result, Err(CodegenError::UnexpectedNode(_))));
+
+        // A function cannot be declared inside of another one
+        let result = Codegen::new(0, true).generate_code(&function(vec![function(vec![])]));
+        assert!(matches!(result, Err(CodegenError::UnexpectedNode(_))));
+
+        // A register cannot be read before being defined
+        let result = Codegen::new(0, true).generate_code(&function(vec![IrNode::Return(tt.clone(), 5, None)]));
+        assert!(matches!(result, Err(CodegenError::RegisterAllocation(_))));
+    }
+
+    #[test]
+    fn codegen_riscv_test_narrow_values() {
+        let input = String::from(
+            "
+            u32 f(u8 a, i8 b, u16 c, i32 d) {
+              u8 x = a;
+              i8 y = b;
+              u16 z = c;
+              i32 w = (i32) y + (i32) x;
+              return (u32) (!x + (u8) !y + (u8) !z + (u8) !d + (u8) !w);
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+
+        // Unsigned values are zero extended when loaded, signed ones are sign extended
+        assert!(code.contains("\tlbu\tt0, -15(s0)\t# Load variable x from stack"));
+        assert!(code.contains("\tlb\tt0, -16(s0)\t# Load variable y from stack"));
+        assert!(code.contains("\tlhu\tt1, -12(s0)\t# Load variable z from stack"));
+
+        // Widening a signed value extends its sign, widening an unsigned one clears the upper bits
+        assert!(code.contains("\tslli\tt0, t0, 24\t# Cast from size 1 to 4\n\tsrai\tt0, t0, 24\t"));
+        assert!(code.contains("\tandi\tt1, t1, 255\t# Clear upper bits of register due to cast from size 1 to 4"));
+
+        // Only the lower bits of a value smaller than 32 bits are considered by `!`
+        assert_eq!(code.matches("\tsltiu\t").count(), 5);
+        assert_eq!(code.matches("# Only the lower 8 bits are relevant").count(), 2);
+        assert_eq!(code.matches("# Only the lower 16 bits are relevant").count(), 1);
+    }
+
+    #[test]
+    fn codegen_riscv_test_narrow_casts() {
+        let input = String::from(
+            "
+            i8 f(i32 a) { return (i8) a; }
+            u8 g(i32 a) { return (u8) a; }
+            i32 h() { i32 a = (i32) 0x1FF; return (i32) (i8) a; }
+            i32 k() { i32 a = (i32) 0x1FF; return (i32) (u8) a; }
+            i32 main() { return (i32) f((i32) 0x1FF) + (i32) g((i32) 0x1FF) + h() + k(); }
+",
+        );
+
+        let get_function = |code: &String, name: &str| {
+            let start = code.find(&format!("\n{}:", name)).unwrap();
+            code[start..start + code[start..].find("\t.size\t").unwrap()].to_string()
+        };
+
+        // At runtime, the sign extension shifts the value loaded in the destination register, so
+        // that `0x1FF` becomes `-1` as `i8`, while the cast to `u8` masks it, giving `255`
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+        let f = get_function(&code, "f");
+        assert!(f.contains("\tlw\tt0, -4(s0)\t# Load variable a from stack\n\tslli\tt0, t0, 24\t# Cast from size 4 to 1\n\tsrai\tt0, t0, 24\t"));
+        assert!(!f.contains("\tandi\t"));
+        let g = get_function(&code, "g");
+        assert!(g.contains("\tlw\tt0, -4(s0)\t# Load variable a from stack\n\tandi\tt0, t0, 255\t"));
+
+        // The same values are obtained when the cast is computed at compile time
+        let ir = get_ir(&input, 2);
+        let code = get_code(&ir, 2, true);
+        assert!(get_function(&code, "h").contains("\tli\tt0, -1\t"));
+        assert!(get_function(&code, "k").contains("\tli\tt0, 255\t"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_pipeline() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::lirgen::irnode::IrNode;
+
+        // Every kind of allocation and call produced by lirgen (global, local, array, parameter
+        // and variable-length array) is accepted by the backend, at each level of optimization
+        let input = String::from(
+            "
+            u32 g = 3;
+            u32 garr[4];
+            u32 n = f(2);
+            u32 f(u32 a) { u32 b = a + g; u8 c = (u8) b; return b + (u32) c; }
+            void v() { return; }
+            u32 main() {
+              u32 arr[3];
+              u32 dyn[n];
+              arr[1] = f(1);
+              dyn[0] = garr[2];
+              v();
+              return arr[1] + dyn[0];
+            }
+",
+        );
+
+        for opt in 0..=3 {
+            let ir = get_ir(&input, opt);
+
+            // The allocations of global variables, local variables, arrays and arguments are
+            // all part of the IR
+            if let IrNode::Program(functions) = &ir {
+                let nodes: Vec<&IrNode> = functions
+                    .iter()
+                    .flat_map(|function| match function {
+                        IrNode::FunctionDeclaration(_, _, _, nodes) => nodes.iter(),
+                        _ => panic!("Invalid program"),
+                    })
+                    .collect();
+                assert!(nodes.iter().any(|node| matches!(node, IrNode::Alloc(_, _, _, true, ..))));
+                assert!(nodes.iter().any(|node| matches!(node, IrNode::Alloc(_, _, _, false, _, true, ..))));
+                assert!(nodes.iter().any(|node| matches!(node, IrNode::Call(..))));
+            }
+
+            for m_extension in [true, false] {
+                assert!(Codegen::new(opt, m_extension).generate_code(&ir).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_loops_liveness() {
+        // A function made of many loops, each with its own counter. The liveness of the virtual
+        // registers is computed once per function, and it tells that each counter is dead after
+        // its loop, so that its register can be reused: neither the `s` registers nor the TP
+        // stack are required
+        let loops: String = (0..40)
+            .map(|k| {
+                format!(
+                    "u32 k{} = 0; while (k{} < (u32) {}) {{ s = s + k{}; k{} = k{} + 1; }}\n",
+                    k,
+                    k,
+                    k + 1,
+                    k,
+                    k,
+                    k
+                )
+            })
+            .collect();
+        let input = format!("u32 main() {{ u32 s = 0;\n{} return s; }}", loops);
+
+        for opt in 0..=3 {
+            let ir = get_ir(&input, opt);
+            let code = get_code(&ir, opt, true);
+            assert!(!code.contains("(tp)"));
+            assert!(!code.contains("\tsw\ts1, "));
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_spilling() {
+        use std::collections::HashMap;
+
+        // Each function keeps more values alive across the calls than the available registers, so
+        // that some of them are spilled to the TP stack. `g` spills more than `f`, which is called
+        // while the values of `g` are on the TP stack
+        let function = |name: &str, callee: &str, n: u32| {
+            let body: String = (0..n).map(|i| format!("  u32 v{} = {}(x + {});\n", i, callee, i)).collect();
+            let sum: Vec<String> = (0..n).map(|i| format!("v{} * {}", i, i + 1)).collect();
+            format!("u32 {}(u32 x) {{\n{}  return {};\n}}\n", name, body, sum.join(" + "))
+        };
+        let input = format!(
+            "u32 h(u32 a) {{ return a * 3; }}\n{}{}u32 main() {{ return g(2); }}\n",
+            function("f", "h", 28),
+            function("g", "f", 40)
+        );
+
+        // Offset in an operand such as `-8(s0)`
+        let get_offset = |line: &str, base: &str| -> Option<i32> {
+            let operand = line.split(['\t', ' ']).find(|op| op.ends_with(&format!("({})", base)))?;
+            operand.split('(').next().unwrap().parse().ok()
+        };
+
+        for opt in 1..=2 {
+            let ir = get_ir(&input, opt);
+            let code = get_code(&ir, opt, true);
+
+            let mut tp_sizes: HashMap<&str, i32> = HashMap::new();
+            for name in ["f", "g"] {
+                let start = code.find(&format!("\n{}:", name)).unwrap();
+                let end = start + code[start..].find("\t.size\t").unwrap();
+                let lines: Vec<&str> = code[start..end].lines().collect();
+
+                // Size of the activation record
+                let ssa: i32 = lines
+                    .iter()
+                    .find_map(|line| line.strip_prefix("\taddi\tsp, sp, -"))
+                    .unwrap()
+                    .split('\t')
+                    .next()
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                assert_eq!(ssa % 16, 0);
+
+                // The space reserved on the TP stack is given back before returning
+                let tp_adjust: Vec<&str> = lines.iter().filter(|line| line.starts_with("\taddi\ttp, tp, ")).cloned().collect();
+                assert_eq!(tp_adjust.len(), 2);
+                let tp_size: i32 = -tp_adjust[0].split(['\t', ' ']).nth(4).unwrap().parse::<i32>().unwrap();
+                assert!(tp_adjust[1].starts_with(&format!("\taddi\ttp, tp, {}\t", tp_size)));
+                tp_sizes.insert(name, tp_size);
+
+                // Both s10 and s11 are used to handle the spilled registers, so they are saved
+                assert!(lines.iter().any(|line| line.starts_with("\tsw\ts10, ")));
+                assert!(lines.iter().any(|line| line.starts_with("\tsw\ts11, ")));
+
+                // Each spilled register is in the space reserved on the TP stack
+                let tp_offsets: Vec<i32> = lines.iter().filter_map(|line| get_offset(line, "tp")).collect();
+                assert!(!tp_offsets.is_empty());
+                assert!(tp_offsets.iter().all(|offset| *offset >= 0 && *offset < tp_size && offset % 4 == 0));
+
+                // Each slot used to save a register is in the activation record, above `ra` and
+                // `s0`, and it is associated to one register only
+                let mut slots: HashMap<i32, &str> = HashMap::new();
+                for line in lines.iter().filter(|line| line.contains("must be preserved")) {
+                    let offset = get_offset(line, "s0").unwrap();
+                    assert!(offset < 0 && offset >= -ssa + 8);
+                    let register = line.split(['\t', ',']).nth(2).unwrap();
+                    assert_eq!(*slots.entry(offset).or_insert(register), register);
+                }
+            }
+
+            // `g` spills more registers than `f`
+            assert!(tp_sizes["g"] > tp_sizes["f"]);
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_strcmp() {
+        let input = String::from(
+            "
+            i32 f(u8 *a, u8 *b) { return __strcmp(a, b); }
+            i32 main() {
+              u8 a[2];
+              a[0] = (u8) 97;
+              a[1] = (u8) 0;
+              return __strcmp(a, a) + f(a, a);
+            }
+",
+        );
+
+        for opt in 0..=3 {
+            for m_ext in [true, false] {
+                let ir = get_ir(&input, opt);
+                let result = get_code(&ir, opt, m_ext);
+
+                // The built-in function is called as any other function, and its routine is
+                // added only once, comparing unsigned bytes
+                assert!(result.contains("call\t__strcmp"));
+                assert_eq!(result.matches("__strcmp:").count(), 1);
+                assert!(result.contains("lbu\tt0, 0(a0)"));
+                assert!(result.contains("lbu\tt1, 0(a1)"));
+                assert!(result.contains("sub\ta0, t0, t1"));
+            }
+        }
+
+        // The routine is not added if it is not called
+        let input = String::from("i32 main() { return (i32) 0; }");
+        let ir = get_ir(&input, 0);
+        let result = get_code(&ir, 0, true);
+        assert!(!result.contains("__strcmp"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_comparisons() {
+        // Each comparison is a `slt` (signed or unsigned depending on the type of the operands) or
+        // a `sub`, possibly followed by an instruction turning the result into 0 or 1, so that the
+        // value can be used in arithmetic, as in `(a < 3) + (a > 5)`
+        let cases = vec![
+            ("<", vec!["slt"], vec!["sltu"]),
+            (">", vec!["slt"], vec!["sltu"]),
+            ("<=", vec!["slt", "sltiu"], vec!["sltu", "sltiu"]),
+            (">=", vec!["slt", "sltiu"], vec!["sltu", "sltiu"]),
+            ("==", vec!["sub", "sltiu"], vec!["sub", "sltiu"]),
+            ("!=", vec!["sub", "sltu"], vec!["sub", "sltu"]),
+        ];
+
+        for (op, signed, unsigned) in cases {
+            for (tt, expected) in [("i32", signed), ("u32", unsigned)] {
+                let input = format!("{} f({} a, {} b) {{ return a {} b; }} void main() {{ return; }}", tt, tt, tt, op);
+                let ir = get_ir(&input, 0);
+                let code = get_code(&ir, 0, true);
+
+                let mnemonics: Vec<&str> = code
+                    .lines()
+                    .filter_map(|line| line.split('\t').nth(1))
+                    .filter(|m| m.starts_with("slt") || *m == "sub")
+                    .collect();
+                assert_eq!(mnemonics, expected, "{} on {}", op, tt);
+            }
+        }
+
+        let input = String::from("u32 f(u32 a) { return (a < 3) + (a > 5); } void main() { return; }");
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+        assert_eq!(code.matches("\tsltu\t").count(), 2);
+        assert_eq!(code.matches("\tadd\t").count(), 1);
+    }
+
+    #[test]
+    fn codegen_riscv_test_mixed_comparisons() {
+        // Integers of different types are compared in their common type: unsigned if one of them
+        // is `u32`, signed otherwise, both as a value and as the condition of a branch
+        let cases = vec![
+            ("u32 f(u32 a, i32 b) { return a > b; }", 0, "sltu"),
+            ("u32 f(i8 a, u32 b) { return a < b; }", 0, "sltu"),
+            ("u32 f(i32 b) { return 0xFFFFFFF0 > b; }", 0, "sltu"),
+            ("i32 f(u8 a, i16 b) { return a < b; }", 0, "slt"),
+            ("i32 f(i32 a, u16 b) { return a > b; }", 0, "slt"),
+            ("u32 f(u32 a) { if (a > (i32)1) { return 1; } return 0; }", 1, "bgeu"),
+            ("u32 f(u32 a) { if ((i32)1 < a) { return 1; } return 0; }", 1, "bgeu"),
+            ("u32 f(i32 a) { if (a > (i8)1) { return 1; } return 0; }", 1, "bge"),
+        ];
+
+        for (input, opt, expected) in cases {
+            let ir = get_ir(&format!("{} void main() {{ return; }}", input), opt);
+            let code = get_code(&ir, opt, true);
+
+            let mnemonics: Vec<&str> = code
+                .lines()
+                .filter_map(|line| line.split('\t').nth(1))
+                .filter(|m| m.starts_with("slt") || m.starts_with("bl") || m.starts_with("bg"))
+                .collect();
+            assert_eq!(mnemonics, vec![expected], "{}", input);
+        }
+
+        // Pointers cannot be compared with integers
+        assert!(get_parser("u32 f(u32 *a, u32 b) { return a == b; } void main() { return; }")
+            .parse()
+            .is_none());
+    }
+
+    #[test]
+    fn codegen_riscv_test_extra_arguments() {
+        let input = String::from(
+            "
+            u32 f(u32 a, u32 b, u32 c, u32 d, u32 e, u32 g, u32 h, u32 i, u32 j, u32 k, u32 l, u32 m) {
+              return a + b + c + d + e + g + h + i + j + k + l + m;
+            }
+            u32 main() {
+              u32 x = 12;
+              return f(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, x);
+            }
+",
+        );
+
+        for opt in 0..=3 {
+            let ir = get_ir(&input, opt);
+            let code = get_code(&ir, opt, true);
+
+            // The caller reserves 16 bytes below its stack pointer for the 4 extra arguments,
+            // storing them in order, and frees the space after the call
+            let call = code.find("\tcall\tf").unwrap();
+            let reserve = code.find("\taddi\tsp, sp, -16\t# Extra space required for arguments on stack").unwrap();
+            assert!(reserve < call);
+            for (i, offset) in [0, 4, 8, 12].iter().enumerate() {
+                let store = code.find(&format!(", {}(sp)\t# Move extra arguments on the stack", offset)).unwrap();
+                assert!(reserve < store && store < call, "argument {}", i + 9);
+            }
+            assert!(code[call..].contains("\taddi\tsp, sp, 16\t# Get rid of extra space required for arguments on stack"));
+
+            // The callee finds them at the same offsets from its frame pointer, which is the
+            // stack pointer of the caller
+            for (i, offset) in [0, 4, 8, 12].iter().enumerate() {
+                assert!(code.contains(&format!(", {}(s0)\t# Load argument {} from stack", offset, i + 9)));
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_nested_calls() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{A0, RA};
+
+        let input = String::from(
+            "
+            u32 g(u32 a) { return a * 3; }
+            u32 h(u32 a) { return a + 100; }
+            u32 f(u32 a, u32 b) { return a * 1000 + b; }
+            u32 main() {
+              u32 x = 5;
+              u32 y = 7;
+              u32 r = f(g(x), h(y));
+              return r + f(f(g(1), h(2)), g(h(x)));
+            }
+            u32 k(u32 a, u32 b) {
+              u32 c = a + b;
+              u32 d = a * b;
+              u32 e = a - b;
+              u32 m = a ^ b;
+              u32 n = a | b;
+              u32 o = a & b;
+              u32 q = a << b;
+              u32 s = a >> b;
+              u32 t = g(c) + h(d);
+              return a + b + c + d + e + m + n + o + q + s + t;
+            }
+",
+        );
+
+        let argument_registers: Vec<i32> = (0..8).map(|k| A0 - k).collect();
+
+        for opt in 0..=3 {
+            for m_ext in [true, false] {
+                let ir = get_ir(&input, opt);
+                let code = Codegen::new(opt, m_ext).generate_code(&ir).unwrap();
+
+                // A callee might overwrite any argument register, thus after a call only `a0`
+                // (the returned value) can be read before being written again: no value is kept
+                // in an argument register across a call. The functions have no branches, so the
+                // instructions can be followed in order
+                let mut clobbered: Vec<i32> = vec![];
+                for instr in &code {
+                    let is_call = (instr.tt == JAL || instr.tt == JALR) && instr.dest == RA;
+                    if instr.tt == LABELFUNCTION {
+                        clobbered = vec![];
+                    }
+                    if is_call {
+                        clobbered = argument_registers[1..].to_vec();
+                        continue;
+                    }
+                    let reads = match instr.tt {
+                        LUI | LA | LI | J | LABEL => vec![],
+                        _ => vec![instr.src1, instr.src2],
+                    };
+                    for src in reads {
+                        assert!(!clobbered.contains(&src), "`{}` reads a clobbered register", instr.to_string().trim());
+                    }
+                    clobbered.retain(|r| *r != instr.dest);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_shift_amount() {
+        // A constant amount is used as immediate, of which only the lower 5 bits are kept, so that
+        // `x << 32` behaves as `x << 0`, as it would with the amount in a register
+        let cases = vec![("<< 3", "slli", 3), ("<< 32", "slli", 0), ("<< 40", "slli", 8), (">> 33", "srli", 1)];
+
+        for (shift, mnemonic, amount) in cases {
+            let input = format!("u32 f(u32 x) {{ return x {}; }} void main() {{ return; }}", shift);
+            let ir = get_ir(&input, 0);
+            let code = get_code(&ir, 0, true);
+
+            let shifts: Vec<Vec<&str>> = code
+                .lines()
+                .map(|line| line.split('\t').collect::<Vec<&str>>())
+                .filter(|fields| fields.len() > 2 && (fields[1].starts_with("sll") || fields[1].starts_with("srl")))
+                .collect();
+            assert_eq!(shifts.len(), 1, "{}", shift);
+            assert_eq!(shifts[0][1], mnemonic, "{}", shift);
+            assert!(shifts[0][2].ends_with(&format!(", {}", amount)), "{}", shift);
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_right_shift() {
+        // A right shift is arithmetical on signed types, so that `-8 >> 1` is `-4`, and logical on
+        // unsigned types, so that `0x80000000 >> 1` is `0x40000000`, both with the amount as
+        // immediate and in a register. The operands of a type smaller than 32 bits are promoted
+        // first, so the shift is the last instruction
+        let cases = vec![
+            ("i32 f() { return -8 >> (i32) 1; }", "srai"),
+            ("u32 f() { return 0x80000000 >> 1; }", "srli"),
+            ("i32 f(i32 a, i32 b) { return a >> b; }", "sra"),
+            ("u32 f(u32 a, u32 b) { return a >> b; }", "srl"),
+            ("i8 f(i8 a, i8 b) { return a >> b; }", "sra"),
+            ("u8 f(u8 a, u8 b) { return a >> b; }", "srl"),
+        ];
+
+        for (function, mnemonic) in cases {
+            let input = format!("{} void main() {{ return; }}", function);
+            let ir = get_ir(&input, 0);
+            let code = get_code(&ir, 0, true);
+
+            let shifts: Vec<&str> = code
+                .lines()
+                .filter_map(|line| line.split('\t').nth(1))
+                .filter(|m| m.starts_with("sr"))
+                .collect();
+            assert_eq!(shifts.last(), Some(&mnemonic), "{}", function);
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_stack_frame() {
+        use crate::backend::codegen_riscv::Codegen;
+
+        let input = String::from(
+            "
+            u32 f(u32 n) {
+              u8 c = (u8) 1;
+              u32 x = 2;
+              u16 h = (u16) 3;
+              u8 d = (u8) 4;
+              u32 a[n];
+              u16 k = (u16) 5;
+              a[0] = x + (u32) c + (u32) h + (u32) d + (u32) k;
+              u32 r = a[0] + f(n - 1);
+              return r;
+            }
+            void main() { return; }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = get_code(&ir, 0, true);
+        let f = &code[code.find("\nf:").unwrap()..code.find("\n\t.size\tf").unwrap()];
+
+        // Size of the activation record, which is a multiple of 16
+        let ssa: i32 = f
+            .lines()
+            .find_map(|line| line.strip_prefix("\taddi\tsp, sp, -"))
+            .map(|line| line.split('\t').next().unwrap().parse().unwrap())
+            .unwrap();
+        assert_eq!(ssa % 16, 0);
+
+        // Every access relative to `s0` (locals and save slots) is aligned to its size and inside
+        // the activation record, and two different slots never overlap
+        let mut slots: Vec<(i32, i32)> = vec![];
+        for line in f.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 3 || !fields[2].ends_with("(s0)") {
+                continue;
+            }
+            let size = match fields[1] {
+                "sw" | "lw" => 4,
+                "sh" | "lh" | "lhu" => 2,
+                "sb" | "lb" | "lbu" => 1,
+                _ => continue,
+            };
+            let offset: i32 = fields[2].split(", ").nth(1).unwrap().trim_end_matches("(s0)").parse().unwrap();
+            assert_eq!(offset % size, 0, "{}", line);
+            assert!(offset >= -ssa + 8 && offset + size <= 0, "{}", line);
+            if !slots.contains(&(offset, size)) {
+                slots.push((offset, size));
+            }
+        }
+        slots.sort();
+        assert!(slots.len() >= 8);
+        for i in 1..slots.len() {
+            assert!(slots[i - 1].0 + slots[i - 1].1 <= slots[i].0, "{:?} and {:?}", slots[i - 1], slots[i]);
+        }
+
+        // The array is allocated below the activation record, of a multiple of 16 bytes
+        assert!(f.lines().any(|line| line.starts_with("\tandi\t") && line.contains(", -16\t")));
+        assert_eq!(f.matches("\tsub\tsp, sp, ").count(), 1);
+        assert!(f.contains(&format!("\taddi\tsp, s0, -{}\t", ssa)));
+
+        // When `g` returns, after allocating its array once per iteration, `sp` is back at the
+        // bottom of its activation record before `ra` and `s0` are restored, so that the frame
+        // of `main` (its locals and its return address) is unchanged after each call
+        let input = "
+            u32 g(u32 n) {
+              u32 i;
+              u32 s = 0;
+              for (i = 1; i <= n; i = i + 1) { u16 v[i]; v[i - 1] = (u16) i; s = s + (u32) v[i - 1]; }
+              return s;
+            }
+            u32 main() { u8 c = (u8) 7; u32 x = 100; u32 r = g(3) + g(4); return r + x + (u32) c; }
+";
+        for opt in 0..=3 {
+            let ir = get_ir(input, opt);
+            let code = get_code(&ir, opt, true);
+            let g = &code[code.find("\ng:").unwrap()..code.find("\n\t.size\tg").unwrap()];
+            let ssa = g
+                .lines()
+                .find_map(|line| line.strip_prefix("\taddi\tsp, sp, -"))
+                .unwrap()
+                .split('\t')
+                .next()
+                .unwrap();
+            let restore = g.find(&format!("\taddi\tsp, s0, -{}\t", ssa)).unwrap();
+            assert!(restore < g.find("\tlw\tra, 0(sp)").unwrap());
+            assert_eq!(run(&Codegen::new(opt, true).generate_code(&ir).unwrap()).0, 123, "opt {}", opt);
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_stack_alignment() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::compiler::compile_str;
+        use std::collections::HashSet;
+
+        let input = "
+            u32 g(u32 a, u32 b, u32 c, u32 d, u32 e, u32 k, u32 h, u32 i, u32 j) { return a + j; }
+            u32 f(u32 n) {
+              u8 a[n];
+              u32 b[n + 1];
+              a[0] = (u8)3;
+              b[0] = 2;
+              return g((u32)a[0], b[0], 2, 3, 4, 5, 6, 7, 8) + n;
+            }
+            u32 main() { return f(5); }
+";
+
+        for opt in 0..=2 {
+            for arch in ["rv32im", "rv32i"] {
+                let code = compile_str(input, opt, arch).unwrap();
+                let f = &code[code.find("\nf:").unwrap()..code.find("\n\t.size\tf").unwrap()];
+                // Only the instructions up to the last call matter
+                let last_call = f.rfind("\tcall\t").unwrap();
+                let f = &f[..last_call + f[last_call..].find('\n').unwrap()];
+
+                // Registers known to contain a multiple of 16, as the result of a mask
+                let mut aligned: HashSet<String> = HashSet::new();
+                let mut calls = 0;
+                for line in f.lines() {
+                    let fields: Vec<&str> = line.split('\t').collect();
+                    if fields.len() < 2 {
+                        continue;
+                    }
+                    let operands: Vec<&str> = fields.get(2).map_or(vec![], |operands| operands.split(", ").collect());
+
+                    // Each change of `sp` is a multiple of 16, either a constant or a masked
+                    // register, so that `sp` is aligned at each call
+                    if operands.first() == Some(&"sp") {
+                        match fields[1] {
+                            "addi" => assert_eq!(operands[2].parse::<i32>().unwrap() % 16, 0, "{}", line),
+                            "add" | "sub" => assert!(operands[1] == "sp" && aligned.contains(operands[2]), "{}", line),
+                            _ => panic!("Unexpected change of sp: {}", line),
+                        }
+                    }
+                    if fields[1] == "call" {
+                        calls += 1;
+                    }
+
+                    match fields[1] {
+                        "andi" if operands[2] == "-16" => {
+                            aligned.insert(operands[0].to_string());
+                        }
+                        "sw" | "sh" | "sb" | "call" | "j" | "ret" | "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu" | "beqz" | "bnez" => {}
+                        _ => {
+                            if let Some(dest) = operands.first() {
+                                aligned.remove(*dest);
+                            }
+                        }
+                    }
+                }
+                assert_eq!(calls, 1);
+                assert!(f.contains("\tsub\tsp, sp, "));
+            }
+        }
+
+        // An array allocated inside a loop moves `sp` once per iteration, so the epilogue
+        // restores it from `s0` rather than undoing the last allocation only
+        let input = "
+            u32 main() {
+              u32 i;
+              u32 s = 0;
+              for (i = 0; i < 5; i = i + 1) { u8 c[i + 1]; c[i] = (u8)i; s = s + (u32)c[i]; }
+              return s;
+            }
+";
+        for opt in 0..=3 {
+            for m_ext in [true, false] {
+                let code = Codegen::new(opt, m_ext).generate_code(&get_ir(input, opt)).unwrap();
+                assert_eq!(run(&code).0, 10, "opt {} m_ext {}", opt, m_ext);
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_debug_lines() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::backend::riscv_isa::Syntax;
+
+        let input = String::from(
+            "u32 f(u32 a, u32 b) {
+  u32 r = a * b;
+  if (a > b) { r = r + 1; }
+  return r;
+}
+u32 main() { return f(3, 4); }
+",
+        );
+
+        let ir = get_ir(&input, 1);
+
+        // Without the option, no directive is emitted
+        let code = get_code(&ir, 1, true);
+        assert!(!code.contains(".file") && !code.contains(".loc"));
+
+        let mut codegen = Codegen::new(1, true);
+        codegen.set_debug_lines("test.c".to_string());
+        let code = codegen.generate_code(&ir).unwrap();
+        let gas: String = code.iter().map(|i| i.format(&Syntax::Gas, false)).collect();
+        assert!(gas.starts_with("\t.file\t1 \"test.c\"\n"));
+
+        // Each line of `f` gets a single directive, before its first instruction, while `init`
+        // has no instruction coming from the source
+        let f = gas.split("\nf:\n").nth(1).unwrap().split(".size").next().unwrap();
+        for line in ["\t.loc\t1 1 7\n", "\t.loc\t1 2 11\n\tmul\t", "\t.loc\t1 3 20\n", "\t.loc\t1 4 10\n"] {
+            assert_eq!(f.matches(line).count(), 1, "{}", line);
+        }
+        assert_eq!(f.matches(".loc").count(), 4);
+        assert!(!gas.split("\nf:\n").next().unwrap().contains(".loc"));
+
+        // The raw syntax has no directives
+        let raw: String = code.iter().map(|i| i.format(&Syntax::Raw, false)).collect();
+        assert!(!raw.contains(".file") && !raw.contains(".loc"));
+    }
+
+    #[test]
+    fn codegen_riscv_test_locals_annotations() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::backend::riscv_isa::Syntax;
+
+        let input = String::from(
+            "u32 f(u32 a) {
+  u8 c = (u8)1;
+  u32 b = a + (u32)c;
+  return b;
+}
+u32 main() { return f(3); }
+",
+        );
+
+        let ir = get_ir(&input, 0);
+        let code = Codegen::new(0, true).generate_code(&ir).unwrap();
+
+        // Each local variable is listed right after the label of the function, and the offset is
+        // the one used to access it
+        let gas: String = code.iter().map(|i| i.format(&Syntax::Gas, true)).collect();
+        let f = gas.split("\nf:\t\n").nth(1).unwrap().split(".size").next().unwrap();
+        assert!(f.starts_with("\t# Variable a at -4(s0)\n\t# Variable b at -8(s0)\n\t# Variable c at -9(s0)\n\taddi\tsp, sp, "));
+        assert!(f.contains("\tsw\tt0, -4(s0)\t"));
+        assert!(f.contains("\tsb\tt0, -9(s0)\t"));
+
+        // The annotations are comments, which can be removed
+        let terse: String = code.iter().map(|i| i.format(&Syntax::Gas, false)).collect();
+        assert!(!terse.contains("Variable"));
+        assert!(terse.contains("\nf:\n\taddi\tsp, sp, "));
+    }
+
+    #[test]
+    fn codegen_riscv_test_putchar() {
+        use crate::compiler::compile_str;
+
+        let input = "u8 next(u8 c) { return c + (u8)1; } void main() { u8 c = 'a'; __putchar(c); __putchar(next(c)); return; }";
+
+        // Each `__putchar` is a system call with the character in `a0` and the number in `a7`,
+        // without any call to a routine
+        for opt in 0..=3 {
+            let code = compile_str(input, opt, "rv32im").unwrap();
+            assert_eq!(code.matches("\tecall\t").count(), 2, "{}", code);
+            assert_eq!(code.matches("\tli\ta7, 11\t").count(), 2, "{}", code);
+            assert!(!code.contains("__putchar"));
+            let lines: Vec<&str> = code.lines().collect();
+            for (i, _) in lines.iter().enumerate().filter(|(_, l)| l.starts_with("\tecall")) {
+                assert!(
+                    lines[i - 2].starts_with("\tmv\ta0, ") || lines[i - 2].starts_with("\tli\ta0, "),
+                    "{}",
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_indirect_call() {
+        use crate::compiler::compile_str;
+
+        let input = "
+            u32 add(u32 a, u32 b) { return a + b; }
+            u32 (*global_op)(u32, u32) = &add;
+            u32 apply(u32 (*op)(u32, u32), u32 x) { return (*op)(x, x + 1) + x; }
+            u32 main() { return apply(&add, 2) + (*global_op)(3, 4); }
+";
+
+        for opt in 0..=2 {
+            for arch in ["rv32im", "rv32i"] {
+                let code = compile_str(input, opt, arch).unwrap();
+
+                // The address of the function is loaded through its label, or stored in the data
+                // section for a global variable
+                assert!(code.contains(", add\t# Load address of function"), "{}", code);
+                assert!(code.contains("global_op:\t\n\t.word\tadd"), "{}", code);
+
+                // The indirect call links `ra`, and the address of the function cannot be in an
+                // argument register, which is overwritten by the arguments
+                let calls: Vec<&str> = code.lines().filter(|l| l.starts_with("\tjalr\tra, ")).collect();
+                assert_eq!(calls.len(), 2, "{}", code);
+                assert!(
+                    calls
+                        .iter()
+                        .all(|l| !l.starts_with("\tjalr\tra, a") && l.contains(", 0\t# Indirect call")),
+                    "{}",
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_indirect_call_ir() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{RiscvInstruction, A0, RA, X0};
+        use crate::lirgen::irnode::IrNode;
+
+        let tt = TypeWrapper {
+            type_native: TypeNative::U32,
+            ..Default::default()
+        };
+        let fp = TypeWrapper {
+            type_native: TypeNative::Function(Box::new(tt.clone()), vec![tt.clone()]),
+            pointer: 1,
+            ..Default::default()
+        };
+
+        // `main` calls `seven` through its address, passing it an argument which is ignored
+        let ir = IrNode::Program(vec![
+            IrNode::FunctionDeclaration(
+                "seven".to_string(),
+                tt.clone(),
+                vec![],
+                vec![IrNode::MovC(tt.clone(), 1, 7), IrNode::Return(tt.clone(), 1, None)],
+            ),
+            IrNode::FunctionDeclaration(
+                "main".to_string(),
+                tt.clone(),
+                vec![],
+                vec![
+                    IrNode::LoadA(fp.clone(), 1, "seven".to_string()),
+                    IrNode::MovC(tt.clone(), 2, 5),
+                    IrNode::CallIndirect(tt.clone(), 1, vec![2], 3, None),
+                    IrNode::Return(tt.clone(), 3, None),
+                ],
+            ),
+        ]);
+
+        for opt in 0..=2 {
+            for m_ext in [true, false] {
+                let code = Codegen::new(opt, m_ext).generate_code(&ir).unwrap();
+
+                // The call is a `jalr` linking `ra` with the register holding the address of the
+                // function, after the argument is moved into `a0`
+                let la = code.iter().position(|i| i.tt == LA && i.name == "seven").unwrap();
+                let calls: Vec<usize> = (0..code.len()).filter(|&i| code[i].tt == JALR && code[i].dest == RA).collect();
+                assert_eq!(calls.len(), 1);
+                let call = &code[calls[0]];
+                assert!(la < calls[0]);
+                assert_eq!(call.src1, code[la].dest);
+                assert_eq!(call.immediate, 0);
+                assert!(code[la..calls[0]].iter().any(|i| i.dest == A0 && i.tt == ADDI));
+                assert_eq!(
+                    call.to_string(),
+                    format!("\tjalr\tra, {}, 0\t# Indirect call\n", RiscvInstruction::reg_to_string(call.src1, true))
+                );
+
+                // No direct call is left, while both functions return with `jalr zero, ra, 0`
+                assert!(!code.iter().any(|i| i.tt == JAL));
+                let returns = code.iter().filter(|i| i.tt == JALR && i.dest == X0 && i.src1 == RA).count();
+                assert_eq!(returns, 2);
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_wide_integers() {
+        use crate::compiler::{compile_str, CompileError};
+
+        let input = "
+            u64 g = (u64)7;
+            u64 twice(u64 *p) { return *p + *p; }
+            i32 main() {
+              u64 a = (u64)4000000000;
+              u64 b = a - g;
+              u64 c = b * a;
+              u64 d = twice(&c);
+              return (i32)d;
+            }
+";
+
+        for opt in 0..=3 {
+            for arch in ["rv32im", "rv32i"] {
+                let code = compile_str(input, opt, arch).unwrap();
+                // Each variable uses two words on the stack
+                assert!(code.contains("\tsw\tt0, -8(s0)\t") && code.contains("\tsw\tt1, -4(s0)\t"), "{}", code);
+                // Additions and subtractions propagate the carry between the halves
+                assert!(code.contains("# Carry of the lower half"), "{}", code);
+                assert!(code.contains("# Borrow of the lower half"), "{}", code);
+                // Multiplications are computed by a software routine, even with the M extension
+                assert!(code.contains("\tcall\t__muldi3\t") && code.contains("__muldi3:"), "{}", code);
+                // The upper bits of the result are returned in `a1`
+                assert!(code.contains("\tmv\ta1, "), "{}", code);
+                // The global variable is initialized at runtime with both its halves
+                assert!(code.contains("# Initialize upper bits of global variable g"), "{}", code);
+            }
+        }
+
+        // Comparisons and arguments of 64 bits are not supported, so the parser rejects them before
+        // they reach the backend
+        let compare = "u32 main() { u64 a = (u64)1; if (a < a) { return (u32)1; } return (u32)0; }";
+        assert_eq!(compile_str(compare, 0, "rv32im"), Err(CompileError::Parser));
+        let argument = "u64 id(u64 a) { return a; } void main() { u64 a = id((u64)1); return; }";
+        assert_eq!(compile_str(argument, 0, "rv32im"), Err(CompileError::Parser));
+    }
+
+    #[test]
+    fn codegen_riscv_test_early_return() {
+        use crate::backend::codegen_riscv::Codegen;
+
+        // The branch returning early does not need `n` anymore, while the block placed after it
+        // still reads it: its physical register cannot be reused in between
+        let input = String::from(
+            "
+            u32 fib(u32 n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); }
+            u32 pick(u32 a, u32 b) { u32 c = a + b; if (a > b) { return a; } return c - a + b * 2; }
+            u32 main() { return fib(12) + pick(3, 5) * 1000; }
+",
+        );
+
+        for opt in 0..=3 {
+            for m_ext in [true, false] {
+                let ir = get_ir(&input, opt);
+                let code = Codegen::new(opt, m_ext).generate_code(&ir).unwrap();
+                assert_eq!(run(&code), (15144, String::new()), "opt {} m_ext {}", opt, m_ext);
+            }
+        }
+    }
+
+    #[test]
+    fn codegen_riscv_test_integer_promotion() {
+        use crate::backend::codegen_riscv::Codegen;
+
+        // Operations on types smaller than 32 bits are computed on `i32` when their result is
+        // converted to a larger type, either implicitly or explicitly
+        let inputs = vec![
+            ("u8 a = 200; u32 b = a + a; return b;", 400),
+            ("u8 a = 200; return (u32)(a + a);", 400),
+            ("u8 a = 200; u16 b = a * (u8) 3; return (u32) b;", 600),
+            ("u8 a = 200; u64 b = a + a; return (u32) b;", 400),
+            ("u8 a = 200; u8 b = a + a; return (u32) b;", 144),
+            ("i8 a = -6; i8 b = 4; i32 c = a / b; return (u32) c + 10;", 9),
+            ("u8 a = 200; u8 b = 100; u32 c = a + b - (u8) 250; return c;", 50),
+        ];
+
+        for (input, expected) in inputs {
+            let program = format!("u32 main() {{ {} }}", input);
+
+            for opt in 0..=3 {
+                for m_ext in [true, false] {
+                    let ir = get_ir(&program, opt);
+                    let code = Codegen::new(opt, m_ext).generate_code(&ir).unwrap();
+                    assert_eq!(run(&code).0, expected, "{} (opt {} m_ext {})", input, opt, m_ext);
+                }
+            }
+        }
+    }
+
+    /// Execute the instructions produced by the codegen, starting from `init` and stopping at its
+    /// final loop, an `ebreak` or an endless loop. Functions are addressed by the index of their
+    /// label, while global variables are placed in memory in order starting at `0x10000000`
+    ///
+    /// @in code [&[RiscvInstruction]]: instructions of the program
+    /// @return [(i32, String)]: exit code of the program and characters printed by `__putchar`
+    #[cfg(test)]
+    fn run(code: &[crate::backend::riscv_isa::RiscvInstruction]) -> (i32, String) {
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{A0, X0};
+        use std::collections::HashMap;
+
+        // Position of each label, address of each symbol and initial content of the memory
+        let mut labels: HashMap<(u32, u32), usize> = HashMap::new();
+        let mut symbols: HashMap<String, u32> = HashMap::new();
+        let mut memory: HashMap<u32, u8> = HashMap::new();
+        let mut address: u32 = 0x10000000;
+        for (i, instr) in code.iter().enumerate() {
+            match instr.tt {
+                LABEL => _ = labels.insert((instr.label_function, instr.label), i),
+                LABELFUNCTION => _ = symbols.insert(instr.name.clone(), i as u32),
+                LABELDATA => _ = symbols.insert(instr.name.clone(), address),
+                ALIGN => address = (address + (1 << instr.immediate) - 1) & !((1 << instr.immediate) - 1),
+                ZERO => address += instr.immediate as u32,
+                _ => {}
+            }
+        }
+        let mut pending: Vec<(u32, usize, &str)> = vec![];
+        address = 0x10000000;
+        for instr in code {
+            let size = match instr.tt {
+                WORD => 4,
+                HALF => 2,
+                BYTE => 1,
+                ALIGN => {
+                    address = (address + (1 << instr.immediate) - 1) & !((1 << instr.immediate) - 1);
+                    continue;
+                }
+                ZERO => {
+                    address += instr.immediate as u32;
+                    continue;
+                }
+                _ => continue,
+            };
+            if instr.name.is_empty() {
+                for k in 0..size {
+                    memory.insert(address + k as u32, (instr.immediate >> (8 * k)) as u8);
+                }
+            } else {
+                pending.push((address, size, &instr.name));
+            }
+            address += size as u32;
+        }
+        for (address, size, name) in pending {
+            for k in 0..size {
+                memory.insert(address + k as u32, (symbols[name] >> (8 * k)) as u8);
+            }
+        }
+
+        let load = |memory: &HashMap<u32, u8>, address: u32, size: u32, unsigned: bool| {
+            let mut value: u32 = 0;
+            for k in 0..size {
+                value |= (*memory.get(&(address + k)).unwrap_or(&0) as u32) << (8 * k);
+            }
+            if !unsigned && size < 4 && value >> (8 * size - 1) != 0 {
+                value |= u32::MAX << (8 * size);
+            }
+            value
+        };
+
+        let mut registers: HashMap<i32, u32> = HashMap::new();
+        let mut output = String::new();
+        let mut pc = symbols["init"] as usize;
+        for _ in 0..10_000_000 {
+            let instr = &code[pc];
+            pc += 1;
+            let get = |reg: i32| if reg == X0 { 0 } else { *registers.get(&reg).unwrap_or(&0) };
+            let (a, b, imm) = (get(instr.src1), get(instr.src2), instr.immediate as u32);
+            let result = match instr.tt {
+                ADDI => Some(a.wrapping_add(imm)),
+                SLTI if instr.is_unsigned => Some((a < imm) as u32),
+                SLTI => Some(((a as i32) < instr.immediate) as u32),
+                ANDI => Some(a & imm),
+                ORI => Some(a | imm),
+                XORI => Some(a ^ imm),
+                SLLI => Some(a << (imm & 31)),
+                SRLI if instr.is_unsigned => Some(a >> (imm & 31)),
+                SRLI => Some(((a as i32) >> (imm & 31)) as u32),
+                LUI => Some(imm << 12),
+                LI => Some(imm),
+                LA => Some(symbols[&instr.name]),
+                ADD => Some(a.wrapping_add(b)),
+                SUB => Some(a.wrapping_sub(b)),
+                SLT if instr.is_unsigned => Some((a < b) as u32),
+                SLT => Some(((a as i32) < (b as i32)) as u32),
+                AND => Some(a & b),
+                OR => Some(a | b),
+                XOR => Some(a ^ b),
+                SLL => Some(a << (b & 31)),
+                SRL if instr.is_unsigned => Some(a >> (b & 31)),
+                SRL => Some(((a as i32) >> (b & 31)) as u32),
+                MUL => Some(a.wrapping_mul(b)),
+                DIV if b == 0 => Some(u32::MAX),
+                DIV if instr.is_unsigned => Some(a / b),
+                DIV => Some((a as i32).wrapping_div(b as i32) as u32),
+                REM if b == 0 => Some(a),
+                REM if instr.is_unsigned => Some(a % b),
+                REM => Some((a as i32).wrapping_rem(b as i32) as u32),
+                LB => Some(load(&memory, a.wrapping_add(imm), 1, instr.is_unsigned)),
+                LH => Some(load(&memory, a.wrapping_add(imm), 2, instr.is_unsigned)),
+                LW => Some(load(&memory, a.wrapping_add(imm), 4, true)),
+                SB | SH | SW => {
+                    let size = match instr.tt {
+                        SB => 1,
+                        SH => 2,
+                        _ => 4,
+                    };
+                    for k in 0..size {
+                        memory.insert(a.wrapping_add(imm) + k, (b >> (8 * k)) as u8);
+                    }
+                    None
+                }
+                J | BEQ | BNE | BLT | BGE => {
+                    let taken = match instr.tt {
+                        BEQ => a == b,
+                        BNE => a != b,
+                        BLT if instr.is_unsigned => a < b,
+                        BLT => (a as i32) < (b as i32),
+                        BGE if instr.is_unsigned => a >= b,
+                        BGE => (a as i32) >= (b as i32),
+                        _ => true,
+                    };
+                    if taken {
+                        let target = labels[&(instr.label_function, instr.label)];
+                        // An endless loop ends the execution
+                        if instr.tt == J && target == pc - 2 {
+                            break;
+                        }
+                        pc = target;
+                    }
+                    None
+                }
+                JAL | JALR => {
+                    let link = pc as u32;
+                    pc = if instr.tt == JAL {
+                        symbols[&instr.name] as usize
+                    } else {
+                        a.wrapping_add(imm) as usize
+                    };
+                    Some(link)
+                }
+                ECALL => {
+                    if get(A0 - 7) == 11 {
+                        output.push(get(A0) as u8 as char);
+                    }
+                    None
+                }
+                EBREAK => break,
+                _ => None,
+            };
+            if let Some(value) = result {
+                registers.insert(instr.dest, value);
+            }
+        }
+
+        let exit_code = load(&memory, symbols["init.exit_code"], 4, true) as i32;
+        (exit_code, output)
+    }
+}
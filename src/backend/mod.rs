@@ -1,2 +1,4 @@
 pub mod codegen_riscv;
+pub mod codegen_riscv_test;
 pub mod riscv_isa;
+pub mod riscv_isa_test;
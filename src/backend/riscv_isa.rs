@@ -1,8 +1,13 @@
+use crate::ast::ast_node::SourceReference;
+use crate::lexer::token::Radix;
+use std::fmt;
+
 /// enum RiscvInstructionType
 ///
 /// List of all the instructions available on RV32IM, plus some pseudo instructions useful for
-/// prototyping purposes. The instruction AUIPC is not included in the list, as it was not used in
-/// the codegen
+/// prototyping purposes and the assembler directives used to describe the global variables. The
+/// instruction AUIPC is not included in the list, as addresses are loaded with the pseudo
+/// instruction LA
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum RiscvInstructionType {
     ADDI,  // add immediate
@@ -23,12 +28,13 @@ pub enum RiscvInstructionType {
     SUB,   // sub
     J,     // jump label
     JAL,   // jump and link
+    JALR,  // jump and link register
     BEQ,   // branch equal
     BNE,   // branch not equal
     BLT,   // branch less than
     BGE,   // branch greater equal
-    LB,    // load byte
-    LH,    // load half-word
+    LB,    // load byte (either signed or unsigned)
+    LH,    // load half-word (either signed or unsigned)
     LW,    // load word
     SB,    // store byte
     SH,    // store half-word
@@ -36,10 +42,27 @@ pub enum RiscvInstructionType {
     DIV,   // division (either signed or unsigned)
     REM,   // remainder (either signed or unsigned)
     MUL,   // multiplication
+    LA,    // load address of a label
+    LI,    // load a 32 bits immediate
     LABEL, // label
     #[default]
     NOP,
     LABELFUNCTION, // label function
+    LABELDATA,     // label global variable
+    SECTION,       // start of a section
+    ALIGN,         // alignment of the next data
+    WORD,          // word of data
+    HALF,          // half-word of data
+    BYTE,          // byte of data
+    ZERO,          // zero-initialized data
+    GLOBL,         // symbol visible to the linker
+    TYPE,          // symbol being a function
+    SIZE,          // size of a function
+    FILE,          // source file of the program
+    LOC,           // position in the source of the following instructions
+    ECALL,         // environment call (system call, whose number is in a7)
+    EBREAK,        // environment break (return the control to a debugger)
+    COMMENT,       // line made of the comment only
 }
 
 use RiscvInstructionType::*;
@@ -52,43 +75,71 @@ impl RiscvInstructionType {
     /// @return [String]: name of the instruction
     pub fn to_string(&self) -> String {
         match *self {
-            ADDI => format!("addi"),
-            SLTI => format!("slti"),
-            ANDI => format!("andi"),
-            ORI => format!("ori"),
-            XORI => format!("xori"),
-            SLLI => format!("slli"),
-            SRLI => format!("srli"),
-            LUI => format!("lui"),
-            ADD => format!("add"),
-            SLT => format!("slt"),
-            AND => format!("and"),
-            OR => format!("or"),
-            XOR => format!("xor"),
-            SLL => format!("sll"),
-            SRL => format!("srl"),
-            SUB => format!("sub"),
-            J => format!("j"),
-            JAL => format!("jal"),
-            BEQ => format!("beq"),
-            BNE => format!("bne"),
-            BLT => format!("blt"),
-            BGE => format!("bge"),
-            LB => format!("lb"),
-            LH => format!("lh"),
-            LW => format!("lw"),
-            SB => format!("sb"),
-            SH => format!("sh"),
-            SW => format!("sw"),
-            DIV => format!("div"),
-            REM => format!("rem"),
-            MUL => format!("mul"),
-            LABEL | LABELFUNCTION => format!(""),
-            NOP => format!("nop"),
+            ADDI => "addi".to_string(),
+            SLTI => "slti".to_string(),
+            ANDI => "andi".to_string(),
+            ORI => "ori".to_string(),
+            XORI => "xori".to_string(),
+            SLLI => "slli".to_string(),
+            SRLI => "srli".to_string(),
+            LUI => "lui".to_string(),
+            ADD => "add".to_string(),
+            SLT => "slt".to_string(),
+            AND => "and".to_string(),
+            OR => "or".to_string(),
+            XOR => "xor".to_string(),
+            SLL => "sll".to_string(),
+            SRL => "srl".to_string(),
+            SUB => "sub".to_string(),
+            J => "j".to_string(),
+            JAL => "jal".to_string(),
+            JALR => "jalr".to_string(),
+            BEQ => "beq".to_string(),
+            BNE => "bne".to_string(),
+            BLT => "blt".to_string(),
+            BGE => "bge".to_string(),
+            LB => "lb".to_string(),
+            LH => "lh".to_string(),
+            LW => "lw".to_string(),
+            SB => "sb".to_string(),
+            SH => "sh".to_string(),
+            SW => "sw".to_string(),
+            DIV => "div".to_string(),
+            REM => "rem".to_string(),
+            MUL => "mul".to_string(),
+            LA => "la".to_string(),
+            LI => "li".to_string(),
+            ECALL => "ecall".to_string(),
+            EBREAK => "ebreak".to_string(),
+            ALIGN => ".align".to_string(),
+            WORD => ".word".to_string(),
+            HALF => ".half".to_string(),
+            BYTE => ".byte".to_string(),
+            ZERO => ".zero".to_string(),
+            GLOBL => ".globl".to_string(),
+            TYPE => ".type".to_string(),
+            SIZE => ".size".to_string(),
+            FILE => ".file".to_string(),
+            LOC => ".loc".to_string(),
+            LABEL | LABELFUNCTION | LABELDATA | SECTION | COMMENT => "".to_string(),
+            NOP => "nop".to_string(),
         }
     }
 }
 
+/// enum Syntax
+///
+/// Syntax used to print the instructions: `Gas` is meant for the GNU assembler, with ABI register
+/// names, pseudo instructions and the directives describing the functions, while `Raw` is a bare
+/// dump of the instructions, with numeric register names (`x0` to `x31`) and no pseudo
+/// instructions, which is easier to compare
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Syntax {
+    #[default]
+    Gas,
+    Raw,
+}
+
 /// struct RiscvInstruction
 ///
 /// Each instruction is represented using a struct having all the necessary fields. A struct is
@@ -112,6 +163,8 @@ pub struct RiscvInstruction {
     pub name: String,
     pub register_allocated: bool,
     pub comment: String,
+    pub source_ref: Option<SourceReference>, // Part of the source code the instruction comes from
+    pub radix: Radix,                        // Base in which the value of a data directive is printed
 }
 
 /// Set of constants used to represent some specific registers of the ISA. These registers are to
@@ -126,11 +179,26 @@ pub const A0: i32 = -6; // first function argument / return value
                         // as the system is on 32 bits and no value is larger than that, a1 is
                         // never used as return address
 
+/// ABI names of the physical registers used by the register allocator, indexed by the number of
+/// the register once allocated. The temporary registers `t0..t6` come first, followed by the
+/// saved registers `s1..s11` (`s0` is the frame pointer). `s10` and `s11` are used to handle the
+/// spilled virtual registers
+pub const ALLOCATABLE_REGISTERS: [&str; 18] = [
+    "t0", "t1", "t2", "t3", "t4", "t5", "t6", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+];
+
+/// ABI names of the registers, indexed by their number
+pub const ABI_REGISTERS: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7",
+    "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
 impl RiscvInstruction {
     /// RiscvInstruction::reg_to_string
     ///
-    /// Get the name of a register. The specific registers are expressed with their names.
-    /// Allocated register are expressed with `x` as prefix, otherwise with `v`
+    /// Get the name of a register. The specific registers are expressed with their ABI names.
+    /// Allocated register are expressed with the ABI names of the physical registers used by the
+    /// allocator (see `ALLOCATABLE_REGISTERS`), otherwise with `r` as prefix.
     /// Registers used for arguments are expressed with integers from -6 to -13, with names from
     /// `a0` to `a7`
     ///
@@ -139,26 +207,18 @@ impl RiscvInstruction {
     /// @return [String]: representation of the register
     pub fn reg_to_string(reg: i32, allocated: bool) -> String {
         match reg {
-            SP => format!("sp"),
-            GP => format!("gp"),
-            FP => format!("s0"),
-            RA => format!("ra"),
-            X0 => format!("x0"),
-            TP => format!("tp"),
-            -6 => format!("a0"),
-            -7 => format!("a1"),
-            -8 => format!("a2"),
-            -9 => format!("a3"),
-            -10 => format!("a4"),
-            -11 => format!("a5"),
-            -12 => format!("a6"),
-            -13 => format!("a7"),
+            SP => "sp".to_string(),
+            GP => "gp".to_string(),
+            FP => "s0".to_string(),
+            RA => "ra".to_string(),
+            X0 => "zero".to_string(),
+            TP => "tp".to_string(),
+            _ if reg <= A0 && reg > A0 - 8 => format!("a{}", A0 - reg),
             _ => {
                 if allocated {
-                    if reg <= 6 {
-                        format!("t{}", reg)
-                    } else {
-                        format!("s{}", reg - 6)
+                    match ALLOCATABLE_REGISTERS.get(reg as usize) {
+                        Some(name) => name.to_string(),
+                        None => panic!("Physical register {} does not exist", reg),
                     }
                 } else {
                     format!("r{}", reg)
@@ -167,19 +227,98 @@ impl RiscvInstruction {
         }
     }
 
-    /// RiscvInstruction::to_string
+    /// RiscvInstruction::reg_to_number
+    ///
+    /// Get the numeric name of a register (`x0` to `x31`), used by the raw syntax. Virtual
+    /// registers are expressed with `r` as prefix, as in RiscvInstruction::reg_to_string
+    ///
+    /// @in reg [i32]: register number
+    /// @in allocated [bool]: is the register physical or virtual
+    /// @return [String]: representation of the register
+    pub fn reg_to_number(reg: i32, allocated: bool) -> String {
+        let name = RiscvInstruction::reg_to_string(reg, allocated);
+        match ABI_REGISTERS.iter().position(|abi| **abi == name) {
+            Some(number) => format!("x{}", number),
+            None => name,
+        }
+    }
+
+    /// RiscvInstruction::reg
+    ///
+    /// Get the name of a register of the instruction in the required syntax
     ///
-    /// Transforms an instruction to string
+    /// @in reg [i32]: register number
+    /// @in syntax [&Syntax]: syntax to use
+    /// @return [String]: representation of the register
+    fn reg(&self, reg: i32, syntax: &Syntax) -> String {
+        match syntax {
+            Syntax::Gas => RiscvInstruction::reg_to_string(reg, self.register_allocated),
+            Syntax::Raw => RiscvInstruction::reg_to_number(reg, self.register_allocated),
+        }
+    }
+
+    /// RiscvInstruction::to_pseudo_string
     ///
+    /// Some instructions are the expansion of a pseudo instruction (such as `addi rd, rs, 0`,
+    /// which is a `mv rd, rs`). In this case, the pseudo instruction is printed, since it is
+    /// easier to read. The semantic of the instruction is the same.
+    ///
+    /// @return [Option<String>]: string version of the pseudo instruction, None if the
+    /// instruction does not correspond to any pseudo instruction
+    fn to_pseudo_string(&self) -> Option<String> {
+        let dest = RiscvInstruction::reg_to_string(self.dest, self.register_allocated);
+        let src1 = RiscvInstruction::reg_to_string(self.src1, self.register_allocated);
+        match self.tt {
+            // addi x0, x0, 0 -> nop
+            ADDI if self.dest == X0 && self.src1 == X0 && self.immediate == 0 => Some(format!("\tnop\t{}\n", self.comment)),
+            // addi rd, x0, imm -> li rd, imm
+            ADDI if self.src1 == X0 => Some(format!("\tli\t{}, {}\t{}\n", dest, self.immediate, self.comment)),
+            // addi rd, rs, 0 -> mv rd, rs
+            ADDI if self.immediate == 0 => Some(format!("\tmv\t{}, {}\t{}\n", dest, src1, self.comment)),
+            // add rd, rs, x0 -> mv rd, rs
+            ADD if self.src2 == X0 => Some(format!("\tmv\t{}, {}\t{}\n", dest, src1, self.comment)),
+            // jal x0, label -> j label
+            J => Some(format!("\tj\tL_{}_{}\t{}\n", self.label_function, self.label, self.comment)),
+            // jalr x0, ra, 0 -> ret
+            JALR if self.dest == X0 && self.src1 == RA && self.immediate == 0 => Some(format!("\tret\t{}\n", self.comment)),
+            // jal ra, function -> call function
+            JAL if self.dest == RA => Some(format!("\tcall\t{}\t{}\n", self.name, self.comment)),
+            _ => None,
+        }
+    }
+
+    /// RiscvInstruction::format
+    ///
+    /// Transforms an instruction to string in the required syntax. Once the registers are
+    /// allocated, pseudo instructions are used whenever possible by the `Gas` syntax. Without
+    /// comment, the tab which separates it from the instruction is not printed either
+    ///
+    /// @in syntax [&Syntax]: syntax to use
+    /// @in keep_comment [bool]: whether to print the comment of the instruction
     /// @return [String]: string version of the instruction
-    pub fn to_string(&self) -> String {
+    pub fn format(&self, syntax: &Syntax, keep_comment: bool) -> String {
+        if !keep_comment {
+            if self.tt == COMMENT {
+                return String::new();
+            }
+            let instruction = RiscvInstruction {
+                comment: String::new(),
+                ..self.clone()
+            };
+            return instruction.format(syntax, true).replace("\t\n", "\n");
+        }
+        if self.register_allocated && *syntax == Syntax::Gas {
+            if let Some(pseudo) = self.to_pseudo_string() {
+                return pseudo;
+            }
+        }
         match self.tt {
             // Arithmetical instructions with immediate as argument
             ADDI | ANDI | ORI | XORI | SLLI => format!(
                 "\t{}\t{}, {}, {}\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
+                self.reg(self.dest, syntax),
+                self.reg(self.src1, syntax),
                 self.immediate,
                 self.comment
             ),
@@ -193,13 +332,14 @@ impl RiscvInstruction {
                 format!(
                     "{}\t{}, {}, {}\t{}\n",
                     opcode,
-                    RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                    RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
+                    self.reg(self.dest, syntax),
+                    self.reg(self.src1, syntax),
                     self.immediate,
                     self.comment
                 )
             }
 
+            // Right shift with immediate amount, either logical (unsigned) or arithmetical (signed)
             SRLI => {
                 let mut opcode = format!("\t");
                 if self.is_unsigned {
@@ -210,18 +350,37 @@ impl RiscvInstruction {
                 format!(
                     "{}\t{}, {}, {}\t{}\n",
                     opcode,
-                    RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                    RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
+                    self.reg(self.dest, syntax),
+                    self.reg(self.src1, syntax),
                     self.immediate,
                     self.comment
                 )
             }
 
+            // Right shift with amount in a register, either logical (unsigned) or arithmetical
+            // (signed)
+            SRL => {
+                let mut opcode = "\t".to_string();
+                if self.is_unsigned {
+                    opcode += "srl";
+                } else {
+                    opcode += "sra";
+                }
+                format!(
+                    "{}\t{}, {}, {}\t{}\n",
+                    opcode,
+                    self.reg(self.dest, syntax),
+                    self.reg(self.src1, syntax),
+                    self.reg(self.src2, syntax),
+                    self.comment
+                )
+            }
+
             // Load upper immediate
             LUI => format!(
                 "\t{}\t{}, {}\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
+                self.reg(self.dest, syntax),
                 self.immediate,
                 self.comment,
             ),
@@ -230,15 +389,15 @@ impl RiscvInstruction {
             ADD | AND | OR | XOR | SLL | SUB | MUL => format!(
                 "\t{}\t{}, {}, {}\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
-                RiscvInstruction::reg_to_string(self.src2, self.register_allocated),
+                self.reg(self.dest, syntax),
+                self.reg(self.src1, syntax),
+                self.reg(self.src2, syntax),
                 self.comment,
             ),
 
             // Arithmetical instruction with two registers as arguments and possible unsigned
             // version
-            SLT | DIV | REM | SRL => {
+            SLT | DIV | REM => {
                 let mut opcode = format!("\t{}", self.tt.to_string());
                 if self.is_unsigned {
                     opcode += &"u";
@@ -246,42 +405,47 @@ impl RiscvInstruction {
                 format!(
                     "{}\t{}, {}, {}\t{}\n",
                     opcode,
-                    RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                    RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
-                    RiscvInstruction::reg_to_string(self.src2, self.register_allocated),
+                    self.reg(self.dest, syntax),
+                    self.reg(self.src1, syntax),
+                    self.reg(self.src2, syntax),
                     self.comment
                 )
             }
 
             // Jump instruction
-            J => format!("\tjal\tx0, L_{}_{}\t{}\n", self.label_function, self.label, self.comment),
+            J => format!(
+                "\tjal\t{}, L_{}_{}\t{}\n",
+                self.reg(X0, syntax),
+                self.label_function,
+                self.label,
+                self.comment
+            ),
 
-            // Jump and link instruction, both with a register as destination or label
-            JAL => {
-                if self.src1 == 0 {
-                    format!(
-                        "\t{}\t{}, {}\t{}\n",
-                        self.tt.to_string(),
-                        RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                        self.name,
-                        self.comment
-                    )
-                } else {
-                    format!(
-                        "\tjalr\t{}, {}, 0\t{}\n",
-                        RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
-                        RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
-                        self.comment
-                    )
-                }
-            }
+            // Jump and link instruction, with a register as destination and a label
+            JAL => format!(
+                "\t{}\t{}, {}\t{}\n",
+                self.tt.to_string(),
+                self.reg(self.dest, syntax),
+                self.name,
+                self.comment
+            ),
+
+            // Jump and link instruction, with the address of the destination in a register
+            JALR => format!(
+                "\t{}\t{}, {}, {}\t{}\n",
+                self.tt.to_string(),
+                self.reg(self.dest, syntax),
+                self.reg(self.src1, syntax),
+                self.immediate,
+                self.comment
+            ),
 
             // Branch to label comparing two registers
             BEQ | BNE => format!(
                 "\t{}\t{}, {}, L_{}_{}\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
-                RiscvInstruction::reg_to_string(self.src2, self.register_allocated),
+                self.reg(self.src1, syntax),
+                self.reg(self.src2, syntax),
                 self.label_function,
                 self.label,
                 self.comment
@@ -290,27 +454,28 @@ impl RiscvInstruction {
             // Branch to label comparing two registers, possible unsigned version
             BLT | BGE => {
                 let mut opcode = format!("\t{}", self.tt.to_string());
-                if !self.is_unsigned {
+                if self.is_unsigned {
                     opcode += &"u";
                 }
                 format!(
                     "{}\t{}, {}, L_{}_{}\t{}\n",
                     opcode,
-                    RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
-                    RiscvInstruction::reg_to_string(self.src2, self.register_allocated),
+                    self.reg(self.src1, syntax),
+                    self.reg(self.src2, syntax),
                     self.label_function,
                     self.label,
                     self.comment
                 )
             }
 
-            // Load instruction
+            // Load instruction (loads of bytes and halfwords have an unsigned version)
             LH | LW | LB => format!(
-                "\t{}\t{}, {}({})\t{}\n",
+                "\t{}{}\t{}, {}({})\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.dest, self.register_allocated),
+                if self.is_unsigned && self.tt != LW { "u" } else { "" },
+                self.reg(self.dest, syntax),
                 self.immediate,
-                RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
+                self.reg(self.src1, syntax),
                 self.comment
             ),
 
@@ -318,17 +483,91 @@ impl RiscvInstruction {
             SB | SH | SW => format!(
                 "\t{}\t{}, {}({})\t{}\n",
                 self.tt.to_string(),
-                RiscvInstruction::reg_to_string(self.src2, self.register_allocated),
+                self.reg(self.src2, syntax),
+                self.immediate,
+                self.reg(self.src1, syntax),
+                self.comment
+            ),
+            // Load address of a label
+            LA => format!(
+                "\t{}\t{}, {}\t{}\n",
+                self.tt.to_string(),
+                self.reg(self.dest, syntax),
+                self.name,
+                self.comment
+            ),
+            // Load a 32 bits immediate
+            LI => format!(
+                "\t{}\t{}, {}\t{}\n",
+                self.tt.to_string(),
+                self.reg(self.dest, syntax),
                 self.immediate,
-                RiscvInstruction::reg_to_string(self.src1, self.register_allocated),
                 self.comment
             ),
             // Label
             LABEL => format!("L_{}_{}:\t{}\n", self.label_function, self.label, self.comment),
-            // NOP
-            NOP => format!("\t{}\t{}\n", self.tt.to_string(), self.comment),
+            // Instructions without operands
+            NOP | ECALL | EBREAK => format!("\t{}\t{}\n", self.tt.to_string(), self.comment),
             // Label function
-            LABELFUNCTION => format!("\n{}:\t{}\n", self.name, self.comment),
+            LABELFUNCTION => format!("{}:\t{}\n", self.name, self.comment),
+            // Label global variable
+            LABELDATA => format!("{}:\t{}\n", self.name, self.comment),
+            // Comment on its own line
+            COMMENT => format!("\t{}\n", self.comment),
+            // Section, whose name is stored in `name`
+            SECTION => format!("\n\t{}\n", self.name),
+            // Data, which is either a constant or the address of a label. A constant is printed in
+            // the base it was written in the source, as an unsigned value if its type is unsigned
+            WORD | HALF | BYTE => {
+                if self.name.is_empty() {
+                    let value = match self.radix {
+                        Radix::Decimal => self.immediate as i64,
+                        _ if self.is_unsigned => self.immediate as u32 as i64,
+                        _ => self.immediate as i64,
+                    };
+                    format!("\t{}\t{}\t{}\n", self.tt.to_string(), self.radix.format(value), self.comment)
+                } else {
+                    format!("\t{}\t{}\t{}\n", self.tt.to_string(), self.name, self.comment)
+                }
+            }
+            // Directives with an immediate as argument
+            ALIGN | ZERO => format!("\t{}\t{}\t{}\n", self.tt.to_string(), self.immediate, self.comment),
+            // Directives describing a function, whose name is stored in `name`. The first one
+            // separates the function from the previous one. They are not part of the raw syntax
+            GLOBL | TYPE | SIZE if *syntax == Syntax::Raw => String::new(),
+            GLOBL => format!("\n\t{}\t{}\n", self.tt.to_string(), self.name),
+            TYPE => format!("\t{}\t{}, @function\n", self.tt.to_string(), self.name),
+            SIZE => format!("\t{}\t{}, .-{}\n", self.tt.to_string(), self.name, self.name),
+            // Debugging directives: the name of the source file is stored in `name`, while the
+            // position of a `.loc` is the one in `source_ref`. They are not part of the raw syntax
+            FILE | LOC if *syntax == Syntax::Raw => String::new(),
+            FILE => format!(
+                "\t{}\t1 \"{}\"\n",
+                self.tt.to_string(),
+                self.name.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            LOC => match &self.source_ref {
+                Some(source_ref) => format!("\t{}\t1 {} {}\n", self.tt.to_string(), source_ref.init_line, source_ref.init_char),
+                None => String::new(),
+            },
         }
     }
+
+    /// RiscvInstruction::to_string_without_comment
+    ///
+    /// Same as the displayed instruction (see `fmt::Display`), but the comment of the instruction is not printed,
+    /// together with the tab which separates it from the instruction
+    ///
+    /// @return [String]: string version of the instruction
+    pub fn to_string_without_comment(&self) -> String {
+        self.format(&Syntax::Gas, false)
+    }
+}
+
+/// An instruction is displayed in the syntax of the GNU assembler with its comment, so that it can
+/// be used with `{}` in `format!`, `println!` or `write!` (and converted with `to_string`)
+impl fmt::Display for RiscvInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format(&Syntax::Gas, true))
+    }
 }
@@ -0,0 +1,247 @@
+mod test {
+
+    #[test]
+    fn riscv_isa_test_pseudo_instructions() {
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{RiscvInstruction, A0, RA, X0};
+
+        let instructions = vec![
+            RiscvInstruction {
+                tt: ADDI,
+                dest: 1,
+                src1: 2,
+                immediate: 0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ADD,
+                dest: 1,
+                src1: A0,
+                src2: X0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ADDI,
+                dest: 1,
+                src1: X0,
+                immediate: -5,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ADDI,
+                dest: X0,
+                src1: X0,
+                immediate: 0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: J,
+                label_function: 1,
+                label: 2,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: JALR,
+                dest: X0,
+                src1: RA,
+                immediate: 0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: JAL,
+                dest: RA,
+                name: String::from("main"),
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: JALR,
+                dest: RA,
+                src1: 3,
+                immediate: 0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ADDI,
+                dest: 1,
+                src1: 2,
+                immediate: 3,
+                ..Default::default()
+            },
+        ];
+
+        // Before the allocation of the registers, the instructions are printed as they are
+        let expected = String::from(
+            "\taddi\tr1, r2, 0\t
+\tadd\tr1, a0, zero\t
+\taddi\tr1, zero, -5\t
+\taddi\tzero, zero, 0\t
+\tjal\tzero, L_1_2\t
+\tjalr\tzero, ra, 0\t
+\tjal\tra, main\t
+\tjalr\tra, r3, 0\t
+\taddi\tr1, r2, 3\t
+",
+        );
+        let result: String = instructions.iter().map(|i| i.to_string()).collect();
+        assert_eq!(expected, result);
+
+        // Afterwards, pseudo instructions are used
+        let expected = String::from(
+            "\tmv\tt1, t2\t
+\tmv\tt1, a0\t
+\tli\tt1, -5\t
+\tnop\t
+\tj\tL_1_2\t
+\tret\t
+\tcall\tmain\t
+\tjalr\tra, t3, 0\t
+\taddi\tt1, t2, 3\t
+",
+        );
+        let result: String = instructions
+            .iter()
+            .map(|i| {
+                let mut i = i.clone();
+                i.register_allocated = true;
+                i.to_string()
+            })
+            .collect();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn riscv_isa_test_register_names() {
+        use crate::backend::riscv_isa::{RiscvInstruction, A0, FP, GP, RA, SP, TP, X0};
+
+        let specific = vec![
+            (X0, "zero"),
+            (RA, "ra"),
+            (SP, "sp"),
+            (GP, "gp"),
+            (TP, "tp"),
+            (FP, "s0"),
+            (A0, "a0"),
+            (A0 - 7, "a7"),
+        ];
+        for (reg, name) in specific {
+            assert_eq!(RiscvInstruction::reg_to_string(reg, true), name);
+            assert_eq!(RiscvInstruction::reg_to_string(reg, false), name);
+        }
+
+        let allocated = vec![(0, "t0"), (6, "t6"), (7, "s1"), (15, "s9"), (16, "s10"), (17, "s11")];
+        for (reg, name) in allocated {
+            assert_eq!(RiscvInstruction::reg_to_string(reg, true), name);
+        }
+
+        assert_eq!(RiscvInstruction::reg_to_string(20, false), "r20");
+    }
+
+    #[test]
+    fn riscv_isa_test_without_comment() {
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{RiscvInstruction, A0, X0};
+
+        let instructions = [
+            RiscvInstruction {
+                tt: ADDI,
+                dest: A0,
+                src1: X0,
+                immediate: 3,
+                comment: "# Load constant".to_string(),
+                register_allocated: true,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: LABELFUNCTION,
+                name: "__mulsi3".to_string(),
+                comment: "# Software routine".to_string(),
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: LW,
+                dest: A0,
+                src1: X0,
+                immediate: 8,
+                register_allocated: true,
+                ..Default::default()
+            },
+        ];
+
+        // The comment is dropped together with the tab separating it from the instruction
+        let expected = vec!["\tli\ta0, 3\n", "__mulsi3:\n", "\tlw\ta0, 8(zero)\n"];
+        for (instruction, expected) in instructions.iter().zip(expected) {
+            assert_eq!(instruction.to_string_without_comment(), expected);
+            assert!(instruction.to_string().starts_with(expected.trim_end()));
+        }
+    }
+
+    #[test]
+    fn riscv_isa_test_syntax() {
+        use crate::backend::riscv_isa::RiscvInstructionType::*;
+        use crate::backend::riscv_isa::{RiscvInstruction, Syntax, A0, SP, X0};
+
+        let instructions = [
+            RiscvInstruction {
+                tt: ADDI,
+                dest: 0,
+                src1: A0,
+                immediate: 0,
+                register_allocated: true,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: SW,
+                src1: SP,
+                src2: 4,
+                immediate: 8,
+                register_allocated: true,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ADD,
+                dest: 3,
+                src1: 17,
+                src2: X0,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: GLOBL,
+                name: "main".to_string(),
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: ECALL,
+                ..Default::default()
+            },
+            RiscvInstruction {
+                tt: EBREAK,
+                ..Default::default()
+            },
+        ];
+
+        // The raw syntax uses numeric register names, no pseudo instructions and no directives
+        // describing the functions, while virtual registers are printed as they are
+        let gas = vec![
+            "\tmv\tt0, a0\n",
+            "\tsw\tt4, 8(sp)\n",
+            "\tadd\tr3, r17, zero\n",
+            "\n\t.globl\tmain\n",
+            "\tecall\n",
+            "\tebreak\n",
+        ];
+        let raw = vec![
+            "\taddi\tx5, x10, 0\n",
+            "\tsw\tx29, 8(x2)\n",
+            "\tadd\tr3, r17, x0\n",
+            "",
+            "\tecall\n",
+            "\tebreak\n",
+        ];
+        for ((instruction, gas), raw) in instructions.iter().zip(gas).zip(raw) {
+            assert_eq!(instruction.format(&Syntax::Gas, false), gas);
+            assert_eq!(instruction.format(&Syntax::Raw, false), raw);
+            assert_eq!(instruction.format(&Syntax::Gas, true), instruction.to_string());
+            assert_eq!(instruction.format(&Syntax::Gas, true), format!("{}", instruction));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::backend::codegen_riscv::{Codegen, CodegenError};
+use crate::diagnostic::Reporter;
+use crate::lexer::lexer::Lexer;
+use crate::lirgen::irnode::IrNode;
+use crate::lirgen::lirgen::Lirgen;
+use crate::lirgen::verifier::verify;
+use crate::optimizer::optimizer::{Optimizer, MAX_OPTIMIZER_PASSES};
+use crate::parser::parser::Parser;
+use std::fmt;
+use std::fmt::Write;
+
+/// enum CompileError
+///
+/// Errors which can stop the compilation of a program. The errors of the lexer and of the parser
+/// are printed on stderr, with references to the source, when they are found, as the warnings of
+/// the parser are
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompileError {
+    Lexer,                 // The source cannot be tokenized
+    Parser,                // The program is not valid
+    Architecture(String),  // Target architecture which is not supported
+    Verifier(String),      // The IR does not satisfy the invariants required by the backend
+    Codegen(CodegenError), // The IR cannot be translated
+}
+
+impl fmt::Display for CompileError {
+    /// CompileError::fmt
+    ///
+    /// Write the message associated to an error
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::Lexer => write!(f, "failed lexing"),
+            CompileError::Parser => write!(f, "failed parsing"),
+            CompileError::Architecture(arch) => write!(f, "unsupported architecture: {}", arch),
+            CompileError::Verifier(message) => write!(f, "invalid IR: {}", message),
+            CompileError::Codegen(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// optimize
+///
+/// Optimize a program according to the optimization level: nothing is done below level 2, at level
+/// 2 the optimizer runs once, at level 3 it runs until the IR does not change anymore
+///
+/// @in ir [IrNode]: program to optimize
+/// @in opt [u32]: optimization level
+/// @return [IrNode]: optimized program
+pub fn optimize(mut ir: IrNode, opt: u32) -> IrNode {
+    if opt > 2 {
+        let mut optimizer = Optimizer::new(opt);
+        for _ in 0..MAX_OPTIMIZER_PASSES {
+            let is_changed: bool;
+            (ir, is_changed) = optimizer.optimize_pass(ir);
+            if !is_changed {
+                break;
+            }
+        }
+    } else if opt > 1 {
+        ir = Optimizer::new(opt).optimize(ir);
+    }
+    ir
+}
+
+/// compile_str
+///
+/// Compile a program to assembly in-process, running lexer, parser, lirgen, optimizer and codegen
+/// one after the other
+///
+/// @in src [&str]: source of the program
+/// @in opt [u32]: optimization level
+/// @in arch [&str]: target architecture (`rv32im` or `rv32i`)
+/// @return [Result<String, CompileError>]: assembly of the program
+pub fn compile_str(src: &str, opt: u32, arch: &str) -> Result<String, CompileError> {
+    let m_extension = match arch {
+        "rv32im" => true,
+        "rv32i" => false,
+        _ => return Err(CompileError::Architecture(arch.to_string())),
+    };
+
+    let mut l = Lexer::new_from_source("<string>".to_string(), src.to_string()).map_err(|_| CompileError::Lexer)?;
+    let tokens = l.tokenize().ok_or(CompileError::Lexer)?;
+
+    let mut p = Parser::new(tokens, "<string>".to_string());
+    p.set_source(src.to_string());
+    let ast = p.parse();
+    Reporter::new("<string>".to_string(), src, false).report(p.get_diagnostics());
+    let ast = ast.ok_or(CompileError::Parser)?;
+
+    let ir = optimize(Lirgen::new(opt).linearize_ast(&ast), opt);
+    verify(&ir).map_err(CompileError::Verifier)?;
+
+    let code = Codegen::new(opt, m_extension).generate_code(&ir).map_err(CompileError::Codegen)?;
+
+    // The assembly is built in a single string, reserving the space of an average instruction
+    // for each of them
+    let mut result = String::with_capacity(code.len() * 32);
+    for instruction in &code {
+        write!(result, "{}", instruction).unwrap();
+    }
+    Ok(result)
+}
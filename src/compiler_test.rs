@@ -0,0 +1,50 @@
+mod test {
+
+    #[test]
+    fn compiler_test_compile_str() {
+        use crate::compiler::{compile_str, CompileError};
+
+        let input = "u32 f(u32 a, u32 b) { return a * b; } u32 main() { return f(3, 4); }";
+
+        // The assembly is the same of the one produced step by step, at each level of optimization
+        for opt in 0..=3 {
+            let code = compile_str(input, opt, "rv32im").unwrap();
+            assert!(code.contains("\nmain:"));
+            assert!(code.contains("\nf:"));
+            assert!(code.contains("\tmul\t"));
+            assert!(!code.contains("__mulsi3"));
+
+            let code = compile_str(input, opt, "rv32i").unwrap();
+            assert!(code.contains("\n__mulsi3:"));
+            assert!(!code.contains("\tmul\t"));
+        }
+
+        // Each phase reports its failure
+        assert_eq!(compile_str(input, 0, "x86"), Err(CompileError::Architecture("x86".to_string())));
+        assert_eq!(compile_str("u32 main() { return $; }", 0, "rv32im"), Err(CompileError::Lexer));
+        assert_eq!(compile_str("u32 main() { return a; }", 0, "rv32im"), Err(CompileError::Parser));
+    }
+
+    #[test]
+    fn compiler_test_reproducible_output() {
+        use crate::compiler::compile_str;
+
+        let input = "
+            u32 g[4];
+            u32 f(u32 a, u32 b, u32 c) { u32 x = a * b + c; u32 y = x - a; if (y > b) { return x; } return y + c; }
+            u32 main() {
+                u32 s = 0;
+                for (u32 i = 0; i < 4; i = i + 1) { g[i] = f(i, s, 3); s = s + g[i] * f(s, i, g[i]); }
+                return s;
+            }";
+
+        // Compiling the same program twice produces the same assembly, byte for byte
+        for opt in 0..=3 {
+            for arch in ["rv32im", "rv32i"] {
+                let first = compile_str(input, opt, arch).unwrap();
+                let second = compile_str(input, opt, arch).unwrap();
+                assert_eq!(first, second);
+            }
+        }
+    }
+}
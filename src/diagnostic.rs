@@ -0,0 +1,166 @@
+use crate::ast::ast_node::SourceReference;
+
+/// enum Severity
+///
+/// Severity of a diagnostic: an error stops the compilation, while a warning does not, unless the
+/// warnings are treated as errors
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// struct Diagnostic
+///
+/// Message found by a phase of the compilation, possibly associated to a portion of the source.
+/// The diagnostics are collected while compiling, and printed at the end of the phase by a
+/// `Reporter`
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    pub severity: Severity,                  // Whether the diagnostic is an error or a warning
+    pub phase: String,                       // Phase which found it (`parser`, `codegen`, ...)
+    pub message: String,                     // Description of the problem
+    pub source_ref: Option<SourceReference>, // Portion of the source it refers to, if any
+}
+
+impl Diagnostic {
+    /// Diagnostic::warning
+    ///
+    /// Create a new warning
+    ///
+    /// @in phase [&str]: phase of the compilation which found the warning
+    /// @in message [String]: description of the warning
+    /// @in source_ref [Option<SourceReference>]: portion of the source it refers to
+    /// @return [Diagnostic]: new diagnostic
+    pub fn warning(phase: &str, message: String, source_ref: Option<SourceReference>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            phase: phase.to_string(),
+            message,
+            source_ref,
+        }
+    }
+
+    /// Diagnostic::error
+    ///
+    /// Create a new error
+    ///
+    /// @in phase [&str]: phase of the compilation which found the error
+    /// @in message [String]: description of the error
+    /// @in source_ref [Option<SourceReference>]: portion of the source it refers to
+    /// @return [Diagnostic]: new diagnostic
+    pub fn error(phase: &str, message: String, source_ref: Option<SourceReference>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            phase: phase.to_string(),
+            message,
+            source_ref,
+        }
+    }
+}
+
+/// struct Reporter
+///
+/// Print the diagnostics on the error stream, showing the lines of the source they refer to. With
+/// `werror`, each warning is printed and counted as an error
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Reporter {
+    file_name: String,  // Name of the source (to print the position of the diagnostics)
+    lines: Vec<String>, // Lines of the source
+    werror: bool,       // Whether the warnings are treated as errors
+}
+
+impl Reporter {
+    /// Reporter::new
+    ///
+    /// Create a new reporter for a source
+    ///
+    /// @in file_name [String]: name of the source
+    /// @in source [&str]: content of the source
+    /// @in werror [bool]: whether the warnings are treated as errors
+    /// @return [Reporter]: new reporter
+    pub fn new(file_name: String, source: &str, werror: bool) -> Reporter {
+        Reporter {
+            file_name,
+            lines: source.lines().map(|line| line.to_string()).collect(),
+            werror,
+        }
+    }
+
+    /// Reporter::get_severity
+    ///
+    /// Get the severity a diagnostic is reported with, which is always an error with `werror`
+    ///
+    /// @in diagnostic [&Diagnostic]: diagnostic to consider
+    /// @return [Severity]: severity of the diagnostic
+    pub fn get_severity(&self, diagnostic: &Diagnostic) -> Severity {
+        if self.werror {
+            return Severity::Error;
+        }
+        diagnostic.severity.clone()
+    }
+
+    /// Reporter::format
+    ///
+    /// Get the text of a diagnostic: its position, its message and the lines of the source it
+    /// refers to, with the interested portion underlined
+    ///
+    /// @in diagnostic [&Diagnostic]: diagnostic to format
+    /// @return [String]: text of the diagnostic, ending with a new line
+    pub fn format(&self, diagnostic: &Diagnostic) -> String {
+        let (color, kind) = match self.get_severity(diagnostic) {
+            Severity::Error => ("\x1b[91m", "error"),
+            Severity::Warning => ("\x1b[33m", "warning"),
+        };
+        let mut result = String::new();
+
+        if let Some(source_ref) = &diagnostic.source_ref {
+            result += &format!("\x1b[34m{}:{}:{}: \x1b[0m", self.file_name, source_ref.init_line, source_ref.init_char);
+        }
+        result += &format!("{}{} {}: \x1b[0m{}", color, kind, diagnostic.phase, diagnostic.message);
+        if self.werror && diagnostic.severity == Severity::Warning {
+            result += " (warning treated as error)";
+        }
+        result += "\n";
+
+        let source_ref = match &diagnostic.source_ref {
+            Some(source_ref) => source_ref,
+            None => return result,
+        };
+        for line_number in source_ref.init_line..=source_ref.last_line {
+            let line = match self.lines.get((line_number as usize).wrapping_sub(1)) {
+                Some(line) => line,
+                None => break,
+            };
+            result += &format!("{}\t| {}\n\t| ", line_number, line);
+            for i in 0..line.len() as u32 {
+                let is_first = line_number == source_ref.init_line;
+                let is_last = line_number == source_ref.last_line;
+                if (is_first && i + 1 < source_ref.init_char) || (is_last && i + 1 > source_ref.last_char) {
+                    result += " ";
+                } else {
+                    result += &format!("{}^\x1b[0m", color);
+                }
+            }
+            result += "\n";
+        }
+        result
+    }
+
+    /// Reporter::report
+    ///
+    /// Print a list of diagnostics
+    ///
+    /// @in diagnostics [&[Diagnostic]]: diagnostics to print
+    /// @return [u32]: number of diagnostics reported as errors
+    pub fn report(&self, diagnostics: &[Diagnostic]) -> u32 {
+        let mut errors = 0;
+        for diagnostic in diagnostics {
+            eprint!("{}", self.format(diagnostic));
+            if self.get_severity(diagnostic) == Severity::Error {
+                errors += 1;
+            }
+        }
+        errors
+    }
+}
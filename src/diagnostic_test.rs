@@ -0,0 +1,62 @@
+mod test {
+
+    #[test]
+    fn diagnostic_test_format() {
+        use crate::ast::ast_node::SourceReference;
+        use crate::diagnostic::{Diagnostic, Reporter, Severity};
+
+        let source = "u32 main() {\n  return 1 << 40;\n}\n";
+        let source_ref = SourceReference {
+            init_line: 2,
+            init_char: 10,
+            last_char: 16,
+            last_line: 2,
+        };
+        let warning = Diagnostic::warning("parser", "large shift".to_string(), Some(source_ref));
+
+        // The position, the message and the underlined portion of the line are printed
+        let reporter = Reporter::new("a.c".to_string(), source, false);
+        assert_eq!(reporter.get_severity(&warning), Severity::Warning);
+        let text = reporter.format(&warning);
+        assert!(text.starts_with("\x1b[34ma.c:2:10: \x1b[0m\x1b[33mwarning parser: \x1b[0mlarge shift\n2\t|   return 1 << 40;\n\t| "));
+        assert_eq!(text.matches('^').count(), 7);
+        assert_eq!(text.lines().last().unwrap().replace("\x1b[33m^\x1b[0m", "^"), "\t|          ^^^^^^^ ");
+
+        // With `werror`, the warning is an error
+        let reporter = Reporter::new("a.c".to_string(), source, true);
+        assert_eq!(reporter.get_severity(&warning), Severity::Error);
+        let text = reporter.format(&warning);
+        assert!(text.contains("\x1b[91merror parser: \x1b[0mlarge shift (warning treated as error)\n"));
+        assert!(!text.contains("\x1b[33m"));
+
+        // A diagnostic without a position is a single line
+        let error = Diagnostic::error("codegen", "not supported".to_string(), None);
+        assert_eq!(reporter.format(&error), "\x1b[91merror codegen: \x1b[0mnot supported\n");
+    }
+
+    #[test]
+    fn diagnostic_test_werror() {
+        use crate::diagnostic::{Reporter, Severity};
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let source = "u32 f(u32 a) { u32 b = 0; while (b = a) { b = b << 33; } } u32 main() { return f(1); }";
+        let mut l = Lexer::new(source.to_string(), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        assert!(p.parse().is_some());
+
+        // Each warning is collected once, even if its node is parsed again due to backtracking
+        let diagnostics = p.get_diagnostics();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning && d.phase == "parser"));
+        assert!(diagnostics[0].message.contains("assignment used as a condition"));
+        assert!(diagnostics[1].message.contains("shift amount 33"));
+        assert!(diagnostics[2].message.contains("Missing return statement"));
+
+        // Only with `werror` they are counted as errors
+        assert_eq!(Reporter::new("a.c".to_string(), source, false).report(diagnostics), 0);
+        assert_eq!(Reporter::new("a.c".to_string(), source, true).report(diagnostics), 3);
+    }
+}
@@ -1,6 +1,5 @@
-use crate::lexer::token::{Bracket, Keyword, Operator, Tk, Token};
+use crate::lexer::token::{Bracket, Keyword, Operator, Radix, Tk, Token};
 use std::fs;
-use std::fs::read_to_string;
 
 /// struct Lexer
 ///
@@ -38,8 +37,8 @@ impl Lexer {
         };
 
         Ok(Lexer {
-            // First character of the file
-            current_char: input_code[0],
+            // First character of the file, EOF if the file is empty
+            current_char: input_code.first().cloned().unwrap_or('\0'),
             // File
             input_code,
             // Index to 0
@@ -57,10 +56,25 @@ impl Lexer {
             // Handling a file?
             is_file,
             // First character number of the token under anaylsis
-            current_first_character: 0,
+            current_first_character: 1,
         })
     }
 
+    /// Lexer::new_from_source
+    ///
+    /// Create a new lexer given the content of a source which is not a file (such as the
+    /// standard input). Errors are printed as for a file, using the lines of the source
+    ///
+    /// @in [String]: Name of the source, used in the error messages
+    /// @in [String]: Content of the source
+    /// @return [Result<Lexer, Box<dyn std::error::Error + 'static>>]: Lexer built on the source
+    pub fn new_from_source(name: String, source: String) -> Result<Lexer, Box<dyn std::error::Error + 'static>> {
+        let mut lexer = Lexer::new(source, false)?;
+        lexer.file_name = name;
+        lexer.is_file = true;
+        Ok(lexer)
+    }
+
     /// Lexer::tokenize
     ///
     /// Tokenize the opened file
@@ -137,6 +151,11 @@ impl Lexer {
             return Some(Tk::Semicolon);
         }
 
+        // Colon token
+        if self.current_char == ':' {
+            return Some(Tk::Colon);
+        }
+
         // Bracket token
         let bracket = Bracket::from(&self.current_char.to_string());
         if bracket.is_some() {
@@ -183,12 +202,12 @@ impl Lexer {
         // String token: handle the parsing of a string
         if self.current_char == '\"' {
             self.advance_index();
-            let str = self.read_string();
-            if str.is_some() {
-                return Some(Tk::String(str.unwrap()));
-            } else {
-                self.lexer_error(format!("Line {}: String is not closed", self.current_line_number));
-                return None;
+            match self.read_string() {
+                Ok(str) => return Some(Tk::String(str)),
+                Err(error_str) => {
+                    self.lexer_error(error_str);
+                    return None;
+                }
             }
         }
 
@@ -203,8 +222,8 @@ impl Lexer {
             }
         }
 
-        // If the first character is alphabetic, then we have an identifier
-        if self.current_char.is_alphabetic() || self.current_char == '_' {
+        // If the first character is an ASCII letter or an underscore, then we have an identifier
+        if self.current_char.is_ascii_alphabetic() || self.current_char == '_' {
             let str = self.read_identifier();
 
             // the identifier might be a keyword
@@ -218,9 +237,9 @@ impl Lexer {
 
         // If the first charactrer is a number, then we have a number
         if self.current_char.is_numeric() {
-            let str = self.read_number();
-            if str.is_some() {
-                return Some(Tk::IntegerLiteral(str.unwrap()));
+            let number = self.read_number();
+            if let Some((value, radix)) = number {
+                return Some(Tk::IntegerLiteral(value, radix));
             } else {
                 return None;
             }
@@ -274,10 +293,10 @@ impl Lexer {
         loop {
             // A comment is found if the sequence // is found in the text
             if self.current_char == '/' && self.current_index + 1 < self.input_code.len() && self.get_char(self.current_index + 1) == '/' {
-                // Go ahead until an end of line is found
+                // Go ahead until an end of line (or the end of the file) is found
                 loop {
                     self.advance_index();
-                    if self.current_char == '\n' {
+                    if self.current_char == '\n' || self.current_char == '\0' {
                         self.skip_whitespaces();
                         break;
                     }
@@ -307,31 +326,64 @@ impl Lexer {
     /// Lexer::read_string
     ///
     /// When a quote is found, the next text is to be handled as a string: read until another quote
-    /// is found. If a new character (or EOF) is found before, then we have an error while
-    /// tokenizing it
+    /// is found. If a new line (or EOF) is found before, then we have an error while tokenizing it.
+    /// Escape sequences are replaced by the character they represent (see `read_escape`)
     ///
-    /// @return [Option<String>]: parsed string
-    fn read_string(&mut self) -> Option<String> {
+    /// @return [Result<String, String>]: parsed string, or the message of the error
+    fn read_string(&mut self) -> Result<String, String> {
         let mut str = String::from("");
-        let mut next_char = self.input_code[self.current_index];
 
         // Until and exit condition is found
         loop {
-            str.push(next_char);
-            self.advance_index();
-            next_char = self.input_code[self.current_index];
-            if next_char == '\"' {
-                return Some(str);
+            match self.current_char {
+                '\"' => return Ok(str),
+                '\n' | '\0' => return Err(format!("Line {}: String is not closed", self.current_line_number)),
+                '\\' => str.push(self.read_escape()?),
+                chr => str.push(chr),
             }
-            if next_char == '\n' || next_char == '\n' {
-                return None;
+            self.advance_index();
+        }
+    }
+
+    /// Lexer::read_escape
+    ///
+    /// Read an escape sequence of a character or string literal, starting from the backslash. The
+    /// hexadecimal (`\x41`) and octal (`\101`) escapes represent the byte with that value: as in
+    /// C, the former uses all the hexadecimal digits which follow, the latter up to three octal
+    /// digits. At the end, the current character is the last one of the sequence
+    ///
+    /// @return [Result<char, String>]: character represented by the sequence, or the message of the
+    /// error if the sequence is not valid or its value does not fit in a byte
+    fn read_escape(&mut self) -> Result<char, String> {
+        self.advance_index();
+        let (radix, max_digits, mut digits) = match self.current_char {
+            'x' => (16, usize::MAX, String::from("")),
+            chr if chr.is_digit(8) => (8, 3, chr.to_string()),
+            '\0' | '\n' => return Err("Unterminated escape sequence".to_string()),
+            chr => return Err(format!("Unknown escape sequence \\{}", chr)),
+        };
+
+        while digits.len() < max_digits && self.get_char(self.current_index + 1).is_digit(radix) {
+            self.advance_index();
+            digits.push(self.current_char);
+        }
+
+        if digits.is_empty() {
+            return Err("Missing digits in escape sequence \\x".to_string());
+        }
+        match u32::from_str_radix(&digits, radix) {
+            Ok(value) if value <= 0xff => Ok(char::from(value as u8)),
+            _ => {
+                let prefix = if radix == 16 { "x" } else { "" };
+                Err(format!("Escape sequence \\{}{} is out of range", prefix, digits))
             }
         }
     }
 
     /// Lexer::read_identifier
     ///
-    /// Read until a non alphabetic character is found, and mark it as identifer
+    /// Read until a character which is not an ASCII letter, a digit or an underscore is found, and
+    /// mark it as identifer. As in C, other characters (such as `é`) cannot be part of it
     ///
     /// @return [String]: parsed identifier
     fn read_identifier(&mut self) -> String {
@@ -339,8 +391,8 @@ impl Lexer {
         str.push(self.input_code[self.current_index]);
 
         loop {
-            let next_char = self.input_code[self.current_index + 1];
-            if next_char.is_alphanumeric() || next_char == '_' {
+            let next_char = self.get_char(self.current_index + 1);
+            if next_char.is_ascii_alphanumeric() || next_char == '_' {
                 self.advance_index();
                 str.push(next_char);
             } else {
@@ -351,19 +403,39 @@ impl Lexer {
 
     /// Lexer::read_char
     ///
-    /// Read character between two quotations
+    /// Read character between two quotations, which might be an escape sequence (see
+    /// `read_escape`)
     ///
     /// @return [Option<char>]: parse char
     fn read_char(&mut self) -> Option<char> {
+        // Error if the file ends before the closing quotation
+        if self.current_char == '\0' {
+            self.lexer_error("Unterminated character literal".to_string());
+            return None;
+        }
         // Error if there is a quotation followed by a new line
         if self.current_char == '\n' {
             self.lexer_error("Can't parse character".to_string());
             return None;
         }
-        let chr = self.current_char;
+        let chr = if self.current_char == '\\' {
+            match self.read_escape() {
+                Ok(chr) => chr,
+                Err(error_str) => {
+                    self.lexer_error(error_str);
+                    return None;
+                }
+            }
+        } else {
+            self.current_char
+        };
         self.advance_index();
 
         // Erorr if there is not the closing in the expected position
+        if self.current_char == '\0' {
+            self.lexer_error("Unterminated character literal".to_string());
+            return None;
+        }
         if self.current_char != '\'' {
             self.lexer_error("Can't parse character".to_string());
             return None;
@@ -376,8 +448,8 @@ impl Lexer {
     ///
     /// Read a number
     ///
-    /// @return [Option<char>]: parse char
-    fn read_number(&mut self) -> Option<u64> {
+    /// @return [Option<(u64, Radix)>]: parsed number, together with the base it is written in
+    fn read_number(&mut self) -> Option<(u64, Radix)> {
         let mut str = String::from("");
         str.push(self.input_code[self.current_index]);
 
@@ -399,27 +471,27 @@ impl Lexer {
         if str.contains("x") {
             let without_prefix = str.trim_start_matches("0x");
             match u64::from_str_radix(without_prefix, 16) {
-                Ok(parsed_int) => return Some(parsed_int),
+                Ok(parsed_int) => return Some((parsed_int, Radix::Hexadecimal)),
                 _ => self.lexer_error(format!("Can't parse hexadecimal number {}", str).to_string()),
             };
         // If the string has an 'b', parse it as binary
         } else if str.contains("b") {
             let without_prefix = str.trim_start_matches("0b");
             match u64::from_str_radix(without_prefix, 2) {
-                Ok(parsed_int) => return Some(parsed_int),
+                Ok(parsed_int) => return Some((parsed_int, Radix::Binary)),
                 _ => self.lexer_error(format!("Can't parse binary number {}", str).to_string()),
             };
         // If the string starts with 0, parse it as octal
         } else if str.chars().nth(0).unwrap() == '0' && str.len() != 1 {
             let without_prefix = str.trim_start_matches("0");
             match u64::from_str_radix(without_prefix, 8) {
-                Ok(parsed_int) => return Some(parsed_int),
+                Ok(parsed_int) => return Some((parsed_int, Radix::Octal)),
                 _ => self.lexer_error(format!("Can't parse octal number {}", str).to_string()),
             };
         // Parse the number as decimal in all the other cases
         } else {
             match u64::from_str_radix(&str, 10) {
-                Ok(parsed_int) => return Some(parsed_int),
+                Ok(parsed_int) => return Some((parsed_int, Radix::Decimal)),
                 _ => self.lexer_error(format!("Can't parse decimal number {}", str).to_string()),
             };
         }
@@ -440,7 +512,7 @@ impl Lexer {
 
         let line_number = self.current_line_number;
         let character_number = self.current_character_number;
-        let file_lines = self.read_lines(&self.file_name);
+        let file_lines = self.read_lines();
 
         eprint!("\x1b[34m{}:{}:{}: \x1b[0m", self.file_name, line_number, self.current_first_character);
         eprintln!("\x1b[91merror lexer: \x1b[34m{}\x1b[0m", error_str);
@@ -459,10 +531,15 @@ impl Lexer {
         eprintln!("");
     }
 
-    fn read_lines(&self, filename: &str) -> Vec<String> {
+    /// Lexer::read_lines
+    ///
+    /// Get all the lines of the source under analysis
+    ///
+    /// @return [Vec<String>]: vector of all the lines in the source
+    fn read_lines(&self) -> Vec<String> {
         let mut result = Vec::new();
 
-        for line in read_to_string(filename).unwrap().lines() {
+        for line in self.input_code.iter().collect::<String>().lines() {
             result.push(line.to_string())
         }
 
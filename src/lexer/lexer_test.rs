@@ -2,7 +2,8 @@ mod test {
 
     #[test]
     fn lx_test_comment() {
-        use crate::lexer::lexer_impl::{Bracket, Keyword, Lexer, Tk, Token};
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::{Bracket, Keyword, Tk, Token};
 
         let input_code = "u16
             // This comment will be ignored
@@ -63,7 +64,8 @@ mod test {
 
     #[test]
     fn lx_test_operators() {
-        use crate::lexer::lexer_impl::{Lexer, Operator, Tk, Token};
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::{Operator, Tk, Token};
 
         let input_code = "==+-/>=k&^!=
 ";
@@ -137,7 +139,7 @@ mod test {
 
     #[test]
     fn lx_test_open_string() {
-        use crate::lexer::lexer_impl::Lexer;
+        use crate::lexer::lexer::Lexer;
 
         let input_code = "
             \"daje
@@ -149,7 +151,7 @@ mod test {
 
     #[test]
     fn lx_test_numbers() {
-        use crate::lexer::lexer_impl::Lexer;
+        use crate::lexer::lexer::Lexer;
 
         let input_code = "
             0x10
@@ -167,7 +169,8 @@ mod test {
 
     #[test]
     fn lx_test_characters() {
-        use crate::lexer::lexer_impl::{Lexer, Tk, Token};
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::{Tk, Token};
 
         let input_code = "
     'd'
@@ -201,9 +204,85 @@ mod test {
         );
     }
 
+    #[test]
+    fn lx_test_escapes() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::Tk;
+
+        // Hexadecimal escapes use all the following digits, octal ones at most three of them
+        let input_code = "'\\x41' '\\101' '\\0' '\\xff' \"a\\x42\\1034\\x0d\";";
+        let tokens: Vec<Tk> = Lexer::new(input_code.to_string(), false)
+            .unwrap()
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.tk)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Tk::Char('A'),
+                Tk::Char('A'),
+                Tk::Char('\0'),
+                Tk::Char('\u{ff}'),
+                Tk::String(String::from("aBC4\r")),
+                Tk::Semicolon,
+                Tk::EOF,
+            ]
+        );
+
+        // Values which do not fit in a byte, sequences without digits and unknown sequences are
+        // errors
+        for input_code in ["'\\x1FF'", "'\\777'", "'\\x'", "'\\q'", "'\\", "\"\\x100\""] {
+            assert!(Lexer::new(input_code.to_string(), false).unwrap().tokenize().is_none());
+        }
+    }
+
+    #[test]
+    fn lx_test_identifiers() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::Tk;
+
+        // Identifiers are made of ASCII letters, digits and underscores, not starting with a digit
+        let input_code = "_x x_1 __ a1b2_ _9 u32_ x";
+        let tokens: Vec<Tk> = Lexer::new(input_code.to_string(), false)
+            .unwrap()
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.tk)
+            .collect();
+        let expected: Vec<Tk> = ["_x", "x_1", "__", "a1b2_", "_9", "u32_", "x"]
+            .iter()
+            .map(|id| Tk::Identifier(id.to_string()))
+            .chain([Tk::EOF])
+            .collect();
+        assert_eq!(tokens, expected);
+
+        // Other letters are not accepted, neither at the beginning nor inside an identifier
+        for input_code in ["\u{e9}", "caf\u{e9}", "u32 \u{e9}t\u{e9} = 0;", "x\u{3b1}"] {
+            assert!(Lexer::new(input_code.to_string(), false).unwrap().tokenize().is_none());
+        }
+    }
+
+    #[test]
+    fn lx_test_unterminated_character() {
+        use crate::lexer::lexer::Lexer;
+
+        // A source ending right after the opening quotation, or after the character, is an error,
+        // which is reported with the line of the source
+        for input_code in ["u8 c = '", "u8 c = 'a", "'", "u8 c;\n'"] {
+            assert!(Lexer::new(input_code.to_string(), false).unwrap().tokenize().is_none());
+            assert!(Lexer::new_from_source(String::from("<stdin>"), input_code.to_string())
+                .unwrap()
+                .tokenize()
+                .is_none());
+        }
+    }
+
     #[test]
     fn lx_test_invalid() {
-        use crate::lexer::lexer_impl::Lexer;
+        use crate::lexer::lexer::Lexer;
 
         let input_code = "
     'd'
@@ -214,4 +293,111 @@ mod test {
 
         assert!(Lexer::new(input_code.to_string(), false).unwrap().tokenize().is_none());
     }
+
+    #[test]
+    fn lx_test_json() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::{Tk, Token};
+
+        let input_code = "x = 10;";
+        let tokens = Lexer::new(input_code.to_string(), false).unwrap().tokenize().unwrap();
+
+        assert_eq!(
+            Token::list_to_json(&tokens),
+            "[
+  {\"tk\": {\"type\": \"Identifier\", \"value\": \"x\"}, \"line_number\": 1, \"first_character\": 1, \"last_character\": 1},
+  {\"tk\": {\"type\": \"Operator\", \"value\": \"=\"}, \"line_number\": 1, \"first_character\": 3, \"last_character\": 3},
+  {\"tk\": {\"type\": \"IntegerLiteral\", \"value\": 10}, \"line_number\": 1, \"first_character\": 5, \"last_character\": 6},
+  {\"tk\": {\"type\": \"Semicolon\"}, \"line_number\": 1, \"first_character\": 7, \"last_character\": 7},
+  {\"tk\": {\"type\": \"EOF\"}, \"line_number\": 1, \"first_character\": 7, \"last_character\": 8}
+]"
+        );
+
+        // Characters which cannot be used as they are in JSON are escaped
+        let token = Token {
+            tk: Tk::String(String::from("a\"b\\c\n")),
+            line_number: 2,
+            first_character: 3,
+            last_character: 10,
+        };
+        assert_eq!(
+            token.to_json(),
+            "{\"tk\": {\"type\": \"String\", \"value\": \"a\\\"b\\\\c\\n\"}, \"line_number\": 2, \"first_character\": 3, \"last_character\": 10}"
+        );
+        assert_eq!(Token::list_to_json(&[]), "[]");
+    }
+
+    #[test]
+    fn lx_test_from_source() {
+        use crate::lexer::lexer::Lexer;
+
+        let input_code = "u32 main() {\n  return 0;\n}\n";
+
+        // A lexer built over a source which is not a file produces the same tokens
+        assert_eq!(
+            Lexer::new_from_source(String::from("<stdin>"), input_code.to_string())
+                .unwrap()
+                .tokenize(),
+            Lexer::new(input_code.to_string(), false).unwrap().tokenize()
+        );
+
+        // Errors are found as well
+        assert!(Lexer::new_from_source(String::from("<stdin>"), "u32 a = $;".to_string())
+            .unwrap()
+            .tokenize()
+            .is_none());
+    }
+
+    #[test]
+    fn lx_test_comments_only() {
+        use crate::ast::ast_node::{AstNode, AstNodeWrapper};
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::Tk;
+        use crate::lirgen::lirgen::Lirgen;
+        use crate::lirgen::verifier::verify;
+        use crate::parser::parser::Parser;
+
+        // A source made of comments and whitespaces only, even without a final new line,
+        // produces the EOF token only. The program is empty, thus the entry function is reported
+        // as missing
+        for input_code in ["// first\n\n   // second\n", "  \n\t\n", "// no new line"] {
+            let tokens = Lexer::new(input_code.to_string(), false).unwrap().tokenize().unwrap();
+            assert_eq!(tokens.len(), 1);
+            assert_eq!(tokens[0].tk, Tk::EOF);
+            assert!(Parser::new(tokens, String::from("")).parse().is_none());
+        }
+
+        // A comment can end the file after some code
+        let tokens = Lexer::new(String::from("u32 a; // end"), false).unwrap().tokenize().unwrap();
+        assert_eq!(tokens.len(), 4);
+
+        // An empty program is still linearized into a valid `init` calling the entry function
+        let empty = AstNodeWrapper {
+            node: AstNode::new_declaration_list(&vec![]),
+            ..Default::default()
+        };
+        let ir = Lirgen::new(0).linearize_ast(&empty);
+        assert!(verify(&ir).is_ok());
+        assert_eq!(ir.functions().len(), 1);
+        assert_eq!(ir.functions()[0].get_name(), Some("init"));
+    }
+
+    #[test]
+    fn lx_test_empty() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lexer::token::Tk;
+
+        // An empty source produces the EOF token only, at the beginning of the first line
+        let tokens = Lexer::new(String::new(), false).unwrap().tokenize().unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].tk, Tk::EOF);
+        assert_eq!(tokens[0].line_number, 1);
+        assert_eq!(tokens[0].first_character, 1);
+
+        let tokens = Lexer::new_from_source(String::from("<stdin>"), String::new())
+            .unwrap()
+            .tokenize()
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+    }
 }
@@ -17,12 +17,18 @@ pub enum Keyword {
     False,
     Continue,
     Break,
+    Switch,
+    Case,
+    DefaultCase,
     U8,
     U16,
     U32,
+    U64,
     I8,
     I16,
     I32,
+    I64,
+    Bool,
     Void,
 }
 
@@ -46,12 +52,18 @@ impl Keyword {
             "false" => Some(Keyword::False),
             "continue" => Some(Keyword::Continue),
             "break" => Some(Keyword::Break),
+            "switch" => Some(Keyword::Switch),
+            "case" => Some(Keyword::Case),
+            "default" => Some(Keyword::DefaultCase),
             "u8" => Some(Keyword::U8),
             "u16" => Some(Keyword::U16),
             "u32" | "int" => Some(Keyword::U32),
             "i8" | "char" => Some(Keyword::I8),
             "i16" => Some(Keyword::I16),
             "i32" => Some(Keyword::I32),
+            "u64" => Some(Keyword::U64),
+            "i64" => Some(Keyword::I64),
+            "bool" => Some(Keyword::Bool),
             "void" => Some(Keyword::Void),
             _ => None,
         }
@@ -150,6 +162,39 @@ impl Operator {
     }
 }
 
+/// enum Radix
+///
+/// Base in which an integer literal is written in the source, so that its value can be printed
+/// back in the same base
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Radix {
+    #[default]
+    Decimal,
+    Hexadecimal,
+    Binary,
+    Octal,
+}
+
+impl Radix {
+    /// Radix::format
+    ///
+    /// Print a value in the base, with the prefix used in the source (`0x`, `0b`, `0`). A negative
+    /// value is printed as its absolute value preceded by `-`
+    ///
+    /// @in value [i64]: value to print
+    /// @return [String]: value in the base
+    pub fn format(&self, value: i64) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        let value = value.unsigned_abs();
+        match self {
+            Radix::Decimal => format!("{}{}", sign, value),
+            Radix::Hexadecimal => format!("{}0x{:x}", sign, value),
+            Radix::Binary => format!("{}0b{:b}", sign, value),
+            Radix::Octal => format!("{}0{:o}", sign, value),
+        }
+    }
+}
+
 /// enum Tk
 ///
 /// Enum associated to the tokens in the language
@@ -158,9 +203,10 @@ pub enum Tk {
     Bracket(Bracket),
     Keyword(Keyword),
     Semicolon,
+    Colon,
     Operator(Operator),
     Identifier(String),
-    IntegerLiteral(u64),
+    IntegerLiteral(u64, Radix),
     String(String),
     Char(char),
     EOF,
@@ -203,13 +249,98 @@ impl Tk {
         panic!("Cannot extract operator from non-identifier token: {:#?}", self);
     }
 
-    /// Tk::to_string
+    pub fn is_type(&self) -> bool {
+        match self {
+            Tk::Keyword(Keyword::U8)
+            | Tk::Keyword(Keyword::I8)
+            | Tk::Keyword(Keyword::U16)
+            | Tk::Keyword(Keyword::I16)
+            | Tk::Keyword(Keyword::U32)
+            | Tk::Keyword(Keyword::I32)
+            | Tk::Keyword(Keyword::U64)
+            | Tk::Keyword(Keyword::I64)
+            | Tk::Keyword(Keyword::Bool)
+            | Tk::Keyword(Keyword::Void) => {
+                return true;
+            }
+            _ => return false,
+        }
+    }
+}
+
+impl Token {
+    /// Token::to_json
+    ///
+    /// Serialize the token in JSON. The content of the token is an object with its `type` (name of
+    /// the variant of `Tk`) and, if any, its `value`, which is a number for integer literals and
+    /// the text of the token otherwise
+    ///
+    /// @return [String]: JSON object
+    pub fn to_json(&self) -> String {
+        let tk = match &self.tk {
+            Tk::Bracket(_) => format!("{{\"type\": \"Bracket\", \"value\": {}}}", json_string(&self.tk.to_string())),
+            Tk::Keyword(_) => format!("{{\"type\": \"Keyword\", \"value\": {}}}", json_string(&self.tk.to_string())),
+            Tk::Operator(_) => format!("{{\"type\": \"Operator\", \"value\": {}}}", json_string(&self.tk.to_string())),
+            Tk::Identifier(id) => format!("{{\"type\": \"Identifier\", \"value\": {}}}", json_string(id)),
+            Tk::IntegerLiteral(num, _) => format!("{{\"type\": \"IntegerLiteral\", \"value\": {}}}", num),
+            Tk::String(str) => format!("{{\"type\": \"String\", \"value\": {}}}", json_string(str)),
+            Tk::Char(chr) => format!("{{\"type\": \"Char\", \"value\": {}}}", json_string(&chr.to_string())),
+            Tk::Semicolon => "{\"type\": \"Semicolon\"}".to_string(),
+            Tk::Colon => "{\"type\": \"Colon\"}".to_string(),
+            Tk::EOF => "{\"type\": \"EOF\"}".to_string(),
+            Tk::ERROR => "{\"type\": \"ERROR\"}".to_string(),
+        };
+        format!(
+            "{{\"tk\": {}, \"line_number\": {}, \"first_character\": {}, \"last_character\": {}}}",
+            tk, self.line_number, self.first_character, self.last_character
+        )
+    }
+
+    /// Token::list_to_json
+    ///
+    /// Serialize a list of tokens as a JSON array, with one token per line
     ///
-    /// Convert to string
+    /// @in tokens [&[Token]]: tokens to serialize
+    /// @return [String]: JSON array
+    pub fn list_to_json(tokens: &[Token]) -> String {
+        let elements: Vec<String> = tokens.iter().map(|t| format!("  {}", t.to_json())).collect();
+        if elements.is_empty() {
+            return "[]".to_string();
+        }
+        format!("[\n{}\n]", elements.join(",\n"))
+    }
+}
+
+/// json_string
+///
+/// Quote a string to be used in JSON, escaping the characters which cannot be represented as
+/// they are
+///
+/// @in input [&str]: string to quote
+/// @return [String]: quoted string
+fn json_string(input: &str) -> String {
+    let mut result = String::from("\"");
+    for c in input.chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\r' => result += "\\r",
+            '\t' => result += "\\t",
+            c if (c as u32) < 0x20 => result += &format!("\\u{:04x}", c as u32),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+impl fmt::Display for Tk {
+    /// Tk::fmt
     ///
-    /// @return [String]: conversion
-    pub fn to_string(&self) -> String {
-        return match self {
+    /// Convert to string, as the token is written in the source
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let string = match self {
             Tk::Bracket(br) => match br {
                 Bracket::LCurly => "{".to_string(),
                 Bracket::RCurly => "}".to_string(),
@@ -232,12 +363,18 @@ impl Tk {
                 Keyword::False => "false".to_string(),
                 Keyword::Continue => "continue".to_string(),
                 Keyword::Break => "break".to_string(),
+                Keyword::Switch => "switch".to_string(),
+                Keyword::Case => "case".to_string(),
+                Keyword::DefaultCase => "default".to_string(),
                 Keyword::U8 => "u8".to_string(),
                 Keyword::U16 => "u16".to_string(),
                 Keyword::U32 => "u32".to_string(),
+                Keyword::U64 => "u64".to_string(),
                 Keyword::I8 => "i8".to_string(),
                 Keyword::I16 => "i16".to_string(),
                 Keyword::I32 => "i32".to_string(),
+                Keyword::I64 => "i64".to_string(),
+                Keyword::Bool => "bool".to_string(),
             },
             Tk::Operator(operator) => match operator {
                 Operator::Assign => "=".to_string(),
@@ -262,33 +399,14 @@ impl Tk {
                 Operator::RShift => ">>".to_string(),
             },
             Tk::Semicolon => ";".to_string(),
+            Tk::Colon => ":".to_string(),
             Tk::Identifier(id) => id.to_string(),
-            Tk::IntegerLiteral(num) => num.to_string(),
+            Tk::IntegerLiteral(num, _) => num.to_string(),
             Tk::String(str) => format!("\"{}\"", str).to_string(),
             Tk::Char(chr) => format!("\'{}\'", chr).to_string(),
             Tk::EOF => "EOF".to_string(),
             Tk::ERROR => "ERROR".to_string(),
         };
-    }
-
-    pub fn is_type(&self) -> bool {
-        match self {
-            Tk::Keyword(Keyword::U8)
-            | Tk::Keyword(Keyword::I8)
-            | Tk::Keyword(Keyword::U16)
-            | Tk::Keyword(Keyword::I16)
-            | Tk::Keyword(Keyword::U32)
-            | Tk::Keyword(Keyword::I32)
-            | Tk::Keyword(Keyword::Void) => {
-                return true;
-            }
-            _ => return false,
-        }
-    }
-}
-
-impl fmt::Display for Tk {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string().as_str())
+        write!(f, "{}", string)
     }
 }
@@ -0,0 +1,12 @@
+pub mod ast;
+pub mod backend;
+pub mod compiler;
+pub mod compiler_test;
+pub mod diagnostic;
+pub mod diagnostic_test;
+pub mod lexer;
+pub mod lirgen;
+pub mod optimizer;
+pub mod parser;
+
+pub use compiler::{compile_str, CompileError};
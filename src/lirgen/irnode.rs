@@ -1,5 +1,6 @@
+use crate::ast::ast_node::SourceReference;
 use crate::ast::type_wrapper::TypeWrapper;
-use crate::lexer::token::{Operator, Tk, Token};
+use crate::lexer::token::{Operator, Radix, Tk, Token};
 
 /// enum CompareType
 ///
@@ -29,51 +30,92 @@ pub enum IrNode {
     // name of the function, return type, argument types, list of nodes
     FunctionDeclaration(String, TypeWrapper, Vec<TypeWrapper>, Vec<IrNode>),
     // type of the allocated data, destination register, source register, is global, size in bytes,
-    // from register, name
-    Alloc(TypeWrapper, u32, u32, bool, u32, bool, String),
-    // type of the returned value, source register
-    Return(TypeWrapper, u32),
+    // from register, name, base of the initial value in the source, source reference
+    Alloc(TypeWrapper, u32, u32, bool, u32, bool, String, Radix, Option<SourceReference>),
+    // type of the returned value, source register, source reference
+    Return(TypeWrapper, u32, Option<SourceReference>),
     // type of the data, destination register, constant value
     MovC(TypeWrapper, u32, u32),
     // destination type, source type, destination register, source register
     Cast(TypeWrapper, TypeWrapper, u32, u32),
-    // type of the allocated data, destination address register, source register
-    Store(TypeWrapper, u32, u32),
+    // type of the allocated data, destination address register, source register, source reference
+    Store(TypeWrapper, u32, u32, Option<SourceReference>),
     // type of the allocated data, destination register, source label
     LoadA(TypeWrapper, u32, String),
-    // type of the allocated data, destination register, source address register
-    LoadR(TypeWrapper, u32, u32),
+    // type of the allocated data, destination register, source address register, source reference
+    LoadR(TypeWrapper, u32, u32, Option<SourceReference>),
     // label
     Label(u32),
-    // name of the function, return type, register arguments, return register
-    Call(String, TypeWrapper, Vec<u32>, u32),
+    // name of the function, return type, register arguments, return register, source reference
+    Call(String, TypeWrapper, Vec<u32>, u32, Option<SourceReference>),
+    // return type, register with the address of the function, register arguments, return
+    // register, source reference
+    CallIndirect(TypeWrapper, u32, Vec<u32>, u32, Option<SourceReference>),
     // compare operation to use, type to use, source1, source2, label to jump to
     Branch(CompareType, TypeWrapper, u32, u32, u32),
-    // operator, type, destination, source1, source2
-    Binary(Operator, TypeWrapper, u32, u32, u32),
+    // operator, type, destination, source1, source2, source reference
+    Binary(Operator, TypeWrapper, u32, u32, u32, Option<SourceReference>),
     // operator, type, destination, source
     Unary(TypeWrapper, Operator, u32, u32),
+    // number of the system call, register arguments
+    Syscall(u32, Vec<u32>),
 }
 
 use IrNode::*;
 
 impl IrNode {
+    /// IrNode::functions
+    ///
+    /// Given a Program, get its functions in order (the `init` function first); an empty slice is
+    /// returned for any other node
+    /// @return [&[IrNode]]: functions of the program
+    pub fn functions(&self) -> &[IrNode] {
+        match &self {
+            Program(functions_list) => functions_list,
+            _ => &[],
+        }
+    }
+
+    /// IrNode::instructions
+    ///
+    /// Given a FunctionDeclaration, get its nodes in order; an empty slice is returned for any
+    /// other node
+    /// @return [&[IrNode]]: nodes of the function
+    pub fn instructions(&self) -> &[IrNode] {
+        match &self {
+            FunctionDeclaration(_, _, _, nodes) => nodes,
+            _ => &[],
+        }
+    }
+
+    /// IrNode::get_name
+    ///
+    /// Given a FunctionDeclaration, get the name of the function, None for any other node
+    /// @return [Option<&str>]: name of the function
+    pub fn get_name(&self) -> Option<&str> {
+        match &self {
+            FunctionDeclaration(name, ..) => Some(name),
+            _ => None,
+        }
+    }
+
     /// IrNode::get_dest
     ///
     /// Given an IrNode, get its destination register, 0 if the node has no destination register.
     /// @return [u32]: destination register
     pub fn get_dest(&self) -> u32 {
         match &self {
-            Alloc(_, dest, ..) => return *dest,
-            MovC(_, dest, ..) => return *dest,
-            Cast(_, _, dest, ..) => return *dest,
-            Store(_, dest, ..) => return *dest,
-            LoadA(_, dest, ..) => return *dest,
-            LoadR(_, dest, ..) => return *dest,
-            Call(_, _, _, ret) => return *ret,
-            Unary(_, _, dest, ..) => return *dest,
-            Binary(_, _, dest, ..) => return *dest,
-            _ => return 0,
+            Alloc(_, dest, ..) => *dest,
+            MovC(_, dest, ..) => *dest,
+            Cast(_, _, dest, ..) => *dest,
+            Store(_, dest, ..) => *dest,
+            LoadA(_, dest, ..) => *dest,
+            LoadR(_, dest, ..) => *dest,
+            Call(_, _, _, ret, _) => *ret,
+            CallIndirect(_, _, _, ret, _) => *ret,
+            Unary(_, _, dest, ..) => *dest,
+            Binary(_, _, dest, ..) => *dest,
+            _ => 0,
         }
     }
 
@@ -84,22 +126,44 @@ impl IrNode {
     /// @return [Vec<u32>]: source registers
     pub fn get_src(&self) -> Vec<u32> {
         match &self {
-            Return(_, src) => return vec![*src],
-            Alloc(_, _, src, ..) => return vec![*src],
-            Cast(_, _, _, src) => return vec![*src],
-            Store(_, _, src) => return vec![*src],
-            LoadR(_, _, src) => return vec![*src],
-            Call(_, _, arguments, _) => return arguments.clone(),
-            Branch(_, _, src1, src2, _) => return vec![*src1, *src2],
-            Unary(_, _, _, src) => return vec![*src],
-            Binary(_, _, _, src1, src2) => return vec![*src1, *src2],
-            _ => return vec![],
+            Return(_, src, _) => vec![*src],
+            Alloc(_, _, src, _, size, true, ..) => vec![*src, *size],
+            Alloc(_, _, src, ..) => vec![*src],
+            Cast(_, _, _, src) => vec![*src],
+            Store(_, _, src, _) => vec![*src],
+            LoadR(_, _, src, _) => vec![*src],
+            Call(_, _, arguments, ..) => arguments.clone(),
+            CallIndirect(_, target, arguments, ..) => [vec![*target], arguments.clone()].concat(),
+            Branch(_, _, src1, src2, _) => vec![*src1, *src2],
+            Unary(_, _, _, src) => vec![*src],
+            Binary(_, _, _, src1, src2, _) => vec![*src1, *src2],
+            Syscall(_, arguments) => arguments.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// IrNode::get_source_ref
+    ///
+    /// Given an IrNode, get the reference to the part of the source code it was generated from,
+    /// if the node carries it
+    /// @return [Option<SourceReference>]: source reference
+    pub fn get_source_ref(&self) -> Option<SourceReference> {
+        match &self {
+            Alloc(.., source_ref) => source_ref.clone(),
+            Return(.., source_ref) => source_ref.clone(),
+            Store(.., source_ref) => source_ref.clone(),
+            LoadR(.., source_ref) => source_ref.clone(),
+            Call(.., source_ref) => source_ref.clone(),
+            CallIndirect(.., source_ref) => source_ref.clone(),
+            Binary(.., source_ref) => source_ref.clone(),
+            _ => None,
         }
     }
 
     /// IrNode::to_string
     ///
-    /// Get a string out of an IrNode
+    /// Get a string out of an IrNode, in the textual format described in `lir.md`. The format is
+    /// the one printed with `--print-lir`, and it does not depend on the source references
     ///
     /// @return [String]: result of the conversion
     pub fn to_string(&self) -> String {
@@ -130,7 +194,7 @@ impl IrNode {
 
                 return result + &format!("}}\n");
             }
-            Return(tt, src) => {
+            Return(tt, src, _) => {
                 // If the source register of a return statement is zero, then the statement is
                 // associated to a void function
                 if *src != 0 {
@@ -138,7 +202,7 @@ impl IrNode {
                 }
                 return format!("\treturn\n");
             }
-            Alloc(tt, dest, src, is_global, size, from_reg, name) => {
+            Alloc(tt, dest, src, is_global, size, from_reg, name, ..) => {
                 let mut result = format!("\tv{} = alloc<{}> ", dest, tt.to_string());
                 // No initizialization register
                 if *src != 0 {
@@ -161,19 +225,19 @@ impl IrNode {
             Cast(ttd, tts, dest, src) => {
                 return format!("\tv{} = <{}><{}> v{}\n", dest, ttd.to_string(), tts.to_string(), src);
             }
-            Store(tt, dest, src) => {
+            Store(tt, dest, src, _) => {
                 return format!("\tstore<{}> v{}, v{}\n", tt.to_string(), dest, src);
             }
             LoadA(tt, dest, src) => {
                 return format!("\tv{} = load<{}> @{}\n", dest, tt.to_string(), src);
             }
-            LoadR(tt, dest, src) => {
+            LoadR(tt, dest, src, _) => {
                 return format!("\tv{} = load<{}> v{}\n", dest, tt.to_string(), src);
             }
             Label(s) => {
                 return format!("\n\t%L_{}:\n", s);
             }
-            Call(name, tt, arguments, ret) => {
+            Call(name, tt, arguments, ret, _) => {
                 let mut result = format!("\tv{} = call<{}> {}(", ret, tt.to_string(), name);
                 for i in 0..arguments.len() {
                     result += &format!("v{}", arguments[i]);
@@ -184,6 +248,14 @@ impl IrNode {
 
                 return result + &format!(")\n");
             }
+            CallIndirect(tt, target, arguments, ret, _) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| format!("v{}", a)).collect();
+                format!("\tv{} = call<{}> *v{}({})\n", ret, tt.to_string(), target, arguments.join(", "))
+            }
+            Syscall(number, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| format!("v{}", a)).collect();
+                format!("\tsyscall ${}({})\n", number, arguments.join(", "))
+            }
             Branch(ct, tt, src1, src2, name) => {
                 let mut result = format!("\tj{}", ct.to_string());
 
@@ -210,7 +282,7 @@ impl IrNode {
                 result += &format!("<{}> v{}\n", tt.to_string(), src);
                 return result;
             }
-            Binary(tk, tt, dest, src1, src2) => {
+            Binary(tk, tt, dest, src1, src2, _) => {
                 let mut result = format!("\tv{} = ", dest);
 
                 match tk {
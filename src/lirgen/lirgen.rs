@@ -1,9 +1,17 @@
 use crate::ast::ast_node::{AstNode, AstNodeWrapper};
 use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
-use crate::lexer::token::{Keyword, Operator, Tk};
+use crate::lexer::token::{Keyword, Operator, Radix, Tk, Token};
 use crate::lirgen::irnode::{CompareType, IrNode};
+use crate::parser::symbol_table::Declaration;
 use std::collections::HashMap;
 
+/// EXIT_CODE
+///
+/// Label of the global variable where `init` stores the value returned by the entry function
+/// (`main` by default), that is the exit code of the program. `.` is not allowed in an
+/// identifier, so no collision is possible
+pub const EXIT_CODE: &str = "init.exit_code";
+
 /// struct LirgenResult
 ///
 /// Stores the result of a linearization of an ast node. In particular, as an ast node is
@@ -37,6 +45,11 @@ pub struct Lirgen {
     computed_binary: Vec<(Operator, u32, u32, u32)>,
     // Whether we are currently in the global scope or not
     is_global: bool,
+    // Names of the global variables, the only identifiers which can be accessed through a label
+    global_variables: Vec<String>,
+    // Signature of each function of the program (return type and types of the arguments), collected
+    // before linearizing any of them so that a call can target a function defined later
+    functions: HashMap<String, (TypeWrapper, Vec<TypeWrapper>)>,
     // If we encounter branches, the last information stored in the arrays `variable_pointers` and
     // `variable_values` are to be invalidated at the end of them. To pointer are never to be
     // invalidated
@@ -45,6 +58,15 @@ pub struct Lirgen {
     to_invalidate_variable: Vec<String>,
     // Optimization level to use
     opt: u32,
+    // Whether each access to an array has to be checked against the length of the array
+    bounds_check: bool,
+    // For each array declared in the current function, it keeps track of the register containing
+    // its length, using the register of its pointer (as stored in `variable_pointers`) as key
+    array_lengths: HashMap<u32, u32>,
+    // Length of each global array whose size is a constant, known before linearizing any function
+    global_array_lengths: HashMap<String, u32>,
+    // Name of the function called by `init`
+    entry: String,
 }
 
 use AstNode::*;
@@ -65,11 +87,38 @@ impl Lirgen {
             computed_binary: vec![],
             to_invalidate_variable: vec![],
             is_global: false,
+            global_variables: vec![],
+            functions: HashMap::new(),
             to_invalidate: false,
             opt,
+            bounds_check: false,
+            array_lengths: HashMap::new(),
+            global_array_lengths: HashMap::new(),
+            entry: "main".to_string(),
         };
     }
 
+    /// Lirgen::set_entry
+    ///
+    /// Set the name of the function called by `init` after the initialization of the global
+    /// variables (`main` by default)
+    ///
+    /// @in entry [String]: name of the entry function
+    pub fn set_entry(&mut self, entry: String) {
+        self.entry = entry;
+    }
+
+    /// Lirgen::set_bounds_check
+    ///
+    /// Enable or disable the runtime checks of the array accesses. When enabled, each access to an
+    /// array whose length is known checks the index against the length, jumping to an endless
+    /// loop in case it is out of range
+    ///
+    /// @in bounds_check [bool]: whether the checks are to be done
+    pub fn set_bounds_check(&mut self, bounds_check: bool) {
+        self.bounds_check = bounds_check;
+    }
+
     /// Lirgen::get_pointer_variable
     ///
     /// Add a stored pointer to variable
@@ -204,6 +253,7 @@ impl Lirgen {
         self.variable_values.clear();
         self.constant_values.clear();
         self.computed_binary.clear();
+        self.array_lengths.clear();
     }
 
     /// Lirgen::get_register
@@ -270,6 +320,32 @@ impl Lirgen {
         self.to_invalidate_variable = hv;
     }
 
+    /// Lirgen::start_scope
+    ///
+    /// Function to call when entering a new scope, in which variables can be declared (possibly
+    /// shadowing the ones of the outer scopes)
+    ///
+    /// @return [HashMap<String, u32>]: old value of self.variable_pointers, to be provided to
+    /// `end_scope`
+    fn start_scope(&mut self) -> HashMap<String, u32> {
+        self.variable_pointers.clone()
+    }
+
+    /// Lirgen::end_scope
+    ///
+    /// Function to call when exiting a scope. The variables declared in the scope are forgotten,
+    /// together with their values, and the variables they shadowed are visible again
+    ///
+    /// @in old_pointers[HashMap<String, u32>]: self.variable_pointers to restore
+    fn end_scope(&mut self, old_pointers: HashMap<String, u32>) {
+        for (name, pointer) in &self.variable_pointers {
+            if old_pointers.get(name) != Some(pointer) {
+                self.variable_values.remove(name);
+            }
+        }
+        self.variable_pointers = old_pointers;
+    }
+
     /// Lirgen::add_branch_condition
     ///
     /// Add a branch to the list of instructions. The branch is taken if the condition in
@@ -282,17 +358,23 @@ impl Lirgen {
     fn add_branch_condition(&mut self, expr: &AstNodeWrapper, label: u32) -> Vec<IrNode> {
         let mut instructions: Vec<IrNode> = vec![];
         let mut found_compare = false;
+        // A missing condition (for loop) is always true, thus the branch is never taken
+        if expr.node == AstNode::NullNode {
+            return instructions;
+        }
         if let BinaryNode(tk, exp1, exp2) = &expr.node {
             let compare_type = CompareType::from_token(&tk);
             if compare_type.is_some() && self.opt > 0 {
                 found_compare = true;
-                let mut expr1_lin = self.linearize(exp1, false, 0, 0);
-                let mut expr2_lin = self.linearize(exp2, false, 0, 0);
+                let expr1_lin = self.linearize(exp1, false, 0, 0);
+                let mut expr1_lin = self.convert_operand(exp1, expr1_lin, &expr.type_ref);
+                let expr2_lin = self.linearize(exp2, false, 0, 0);
+                let mut expr2_lin = self.convert_operand(exp2, expr2_lin, &expr.type_ref);
                 instructions.append(&mut expr1_lin.ir_list);
                 instructions.append(&mut expr2_lin.ir_list);
                 instructions.push(IrNode::Branch(
                     compare_type.unwrap().opposite(),
-                    exp1.type_ref.clone(),
+                    expr.type_ref.clone(),
                     expr1_lin.result_register,
                     expr2_lin.result_register,
                     label,
@@ -301,7 +383,8 @@ impl Lirgen {
         }
 
         if !found_compare {
-            let mut expr_lin = self.linearize(expr, false, 0, 0);
+            let expr_lin = self.linearize(expr, false, 0, 0);
+            let mut expr_lin = self.promote_operand(expr, expr_lin);
             instructions.append(&mut expr_lin.ir_list);
             instructions.push(IrNode::Branch(CompareType::NS, expr.type_ref.clone(), expr_lin.result_register, 0, label));
         }
@@ -321,27 +404,30 @@ impl Lirgen {
     /// @return [LirgenResult]: result of the conversion
     fn linearize(&mut self, ast: &AstNodeWrapper, get_address: bool, break_dest: u32, continue_dest: u32) -> LirgenResult {
         match ast.node {
-            DeclarationList(..) => return self.linearize_declaration_list(ast),
-            VarDeclNode(..) => return self.linearize_var_decl_node(ast),
-            PrimaryNode(..) => return self.linearize_primary_node(ast, get_address),
-            JumpNode(..) => return self.linearize_jump_node(ast, get_address, break_dest, continue_dest),
-            CompoundNode(..) => return self.linearize_compound_node(ast, get_address, break_dest, continue_dest),
-            FuncDeclNode(..) => return self.linearize_func_decl_node(ast),
-            BinaryNode(..) => return self.linearize_binary_node(ast, get_address),
-            CastNode(..) => return self.linearize_cast_node(ast, get_address),
-            ExprStatementNode(..) => return self.linearize_expr_statement_node(ast, get_address),
-            ArrayDeclNode(..) => return self.linearize_array_decl_node(ast, get_address),
-            ProcedureNode(..) => return self.linearize_procedure_node(ast),
-            PrefixNode(..) => return self.linearize_prefix_node(ast, get_address),
-            SelectorNode(..) => return self.linearize_selector_node(ast, get_address),
-            IfNode(..) => return self.linearize_if_node(ast, get_address, break_dest, continue_dest),
-            WhileNode(..) => return self.linearize_while_node(ast, get_address),
-            ForNode(..) => return self.linearize_for_node(ast, get_address),
+            DeclarationList(..) => self.linearize_declaration_list(ast),
+            VarDeclNode(..) => self.linearize_var_decl_node(ast),
+            PrimaryNode(..) => self.linearize_primary_node(ast, get_address),
+            JumpNode(..) => self.linearize_jump_node(ast, get_address, break_dest, continue_dest),
+            CompoundNode(..) => self.linearize_compound_node(ast, get_address, break_dest, continue_dest),
+            FuncDeclNode(..) => self.linearize_func_decl_node(ast),
+            BinaryNode(..) => self.linearize_binary_node(ast, get_address),
+            CastNode(..) => self.linearize_cast_node(ast, get_address),
+            ExprStatementNode(..) => self.linearize_expr_statement_node(ast, get_address),
+            ArrayDeclNode(..) => self.linearize_array_decl_node(ast, get_address),
+            ProcedureNode(..) => self.linearize_procedure_node(ast),
+            PrefixNode(..) => self.linearize_prefix_node(ast, get_address),
+            SelectorNode(..) => self.linearize_selector_node(ast, get_address),
+            IfNode(..) => self.linearize_if_node(ast, get_address, break_dest, continue_dest),
+            SwitchNode(..) => self.linearize_switch_node(ast, get_address, continue_dest),
+            WhileNode(..) => self.linearize_while_node(ast, get_address),
+            ForNode(..) => self.linearize_for_node(ast, get_address),
+            // An empty statement, or an omitted clause of a for loop, produces no IR
+            NullNode => LirgenResult { ..Default::default() },
             // Some nodes cannot be linearized, and in a correct ast construction they should never
             // be provided to this function
             TypeNode(..) => panic!("TypeNode cannot be linearized!"),
-            NullNode => panic!("NullNode cannot be linearized!"),
             ParameterNode(..) => panic!("ParameterNode cannot be linearized!"),
+            CaseNode(..) => panic!("CaseNode cannot be linearized!"),
         }
     }
 
@@ -367,6 +453,53 @@ impl Lirgen {
             result.ir_list.append(&mut l_lin.ir_list);
             result.ir_list.append(&mut r_lin.ir_list);
 
+            // If required, check the index against the length of the array. This is possible only
+            // if the left side is an array declared in the current function or a global array of
+            // constant size (unless a local variable hides it). As the comparison is unsigned,
+            // negative indexes are out of range as well. An index out of range calls `__trap`,
+            // which never returns
+            if self.bounds_check {
+                if let AstNode::PrimaryNode(tk) = &left.node {
+                    let id = tk.tk.get_identifier();
+                    let length = match self.get_pointer_variable(&id) {
+                        Some(p) => self.array_lengths.get(&p).cloned(),
+                        None => self.global_array_lengths.get(&id).cloned().map(|length| {
+                            let u32_type = TypeWrapper {
+                                type_native: TypeNative::U32,
+                                ..Default::default()
+                            };
+                            let mut length_lin = self.load_constant(&u32_type, length);
+                            result.ir_list.append(&mut length_lin.ir_list);
+                            length_lin.result_register
+                        }),
+                    };
+                    if let Some(length_register) = length {
+                        let in_range_label = self.get_label();
+                        result.ir_list.push(Branch(
+                            CompareType::LT,
+                            TypeWrapper {
+                                type_native: TypeNative::U32,
+                                ..Default::default()
+                            },
+                            r_lin.result_register,
+                            length_register,
+                            in_range_label,
+                        ));
+                        result.ir_list.push(IrNode::Call(
+                            String::from("__trap"),
+                            TypeWrapper {
+                                type_native: TypeNative::Void,
+                                ..Default::default()
+                            },
+                            vec![],
+                            0,
+                            Some(ast.source_ref.clone()),
+                        ));
+                        result.ir_list.push(Label(in_range_label));
+                    }
+                }
+            }
+
             // The array is a pointer, while in the ast it is saved as a scalar
             let mut tt = ast.type_ref.clone();
             tt.pointer += 1;
@@ -375,32 +508,36 @@ impl Lirgen {
 
             let offset_register;
 
-            // If size is different from 1, the offset has to be multplied by either 2 or 4. Since
-            // a left shift is used instead of a multiplication, the right value should be size / 2
+            // If size is different from 1, the offset has to be multiplied by the size of an
+            // element. When the size is a power of two, a left shift by log2(size) is used instead
+            // of a multiplication
             if size != 1 {
+                let (operator, amount) = if size.is_power_of_two() {
+                    (Operator::LShift, size.trailing_zeros())
+                } else {
+                    (Operator::Asterisk, size)
+                };
+
                 // Get the value of the constant to use, either from a register if it was already
                 // computed or by inserting it into a register
-                let constant_register;
-                match self.get_constant(size / 2) {
-                    Some(l) => {
-                        constant_register = l;
-                    }
-                    None => {
-                        let result_register = self.get_register();
-                        let store_constant_node = MovC(tt.clone(), result_register, size / 2);
-                        result.ir_list.push(store_constant_node);
-                        constant_register = result_register;
-                        self.add_constant(result_register, size / 2);
-                    }
-                }
+                let mut constant_lin = self.load_constant(&tt, amount);
+                result.ir_list.append(&mut constant_lin.ir_list);
+                let constant_register = constant_lin.result_register;
 
-                // Compute the multiplication as a logical shift
-                match self.get_computed_binary(r_lin.result_register, constant_register, &Operator::LShift) {
+                // Compute the multiplication
+                match self.get_computed_binary(r_lin.result_register, constant_register, &operator) {
                     Some(r) => offset_register = r,
                     _ => {
                         offset_register = self.get_register();
-                        let new_op = Binary(Operator::LShift, tt.clone(), offset_register, r_lin.result_register, constant_register);
-                        self.add_computed_binary((Operator::LShift, offset_register, r_lin.result_register, constant_register));
+                        let new_op = Binary(
+                            operator.clone(),
+                            tt.clone(),
+                            offset_register,
+                            r_lin.result_register,
+                            constant_register,
+                            Some(ast.source_ref.clone()),
+                        );
+                        self.add_computed_binary((operator, offset_register, r_lin.result_register, constant_register));
                         result.ir_list.push(new_op);
                     }
                 }
@@ -415,8 +552,15 @@ impl Lirgen {
                 Some(r) => sum_register = r,
                 _ => {
                     sum_register = self.get_register();
-                    let new_op = Binary(Operator::Plus, tt.clone(), sum_register, l_lin.result_register, offset_register);
-                    self.add_computed_binary((Operator::LShift, sum_register, l_lin.result_register, offset_register));
+                    let new_op = Binary(
+                        Operator::Plus,
+                        tt.clone(),
+                        sum_register,
+                        l_lin.result_register,
+                        offset_register,
+                        Some(ast.source_ref.clone()),
+                    );
+                    self.add_computed_binary((Operator::Plus, sum_register, l_lin.result_register, offset_register));
                     result.ir_list.push(new_op);
                 }
             }
@@ -426,7 +570,12 @@ impl Lirgen {
             // If we are interesetd in the value pointed by the address just computed, we load it
             if !get_address {
                 let result_register = self.get_register();
-                let load_value = LoadR(ast.type_ref.clone(), result_register, result.result_register);
+                let load_value = LoadR(
+                    ast.type_ref.clone(),
+                    result_register,
+                    result.result_register,
+                    Some(ast.source_ref.clone()),
+                );
                 result.result_register = result_register;
                 result.ir_list.push(load_value);
             }
@@ -452,6 +601,11 @@ impl Lirgen {
                     // In case of a scalar operator, we just linearize the expression and add the
                     // new operation to the list. No hash map has been implemented for unary
                     // operators
+                    if *op == Operator::Not && self.opt > 0 {
+                        if let Some(folded) = self.fold_not(expr) {
+                            return folded;
+                        }
+                    }
                     if *op == Operator::Not || *op == Operator::Minus || *op == Operator::Complement {
                         let mut exp_lin = self.linearize(&expr, false, 0, 0);
                         let result_register = self.get_register();
@@ -465,6 +619,10 @@ impl Lirgen {
                     // In case of an asterisk, we are sure (due to ast construction) that the
                     // expression is an lvalue. Its address correspond to the address of the
                     // pointed object (e.g. the content of the pointer).
+                    } else if *op == Operator::Asterisk && ast.type_ref.is_function_pointer() {
+                        // Dereferencing a pointer to function gives the address of the function,
+                        // that is the pointer itself
+                        return self.linearize(expr, false, 0, 0);
                     } else if *op == Operator::Asterisk {
                         let mut found_primary = false;
 
@@ -488,7 +646,7 @@ impl Lirgen {
                             // Load the address
                             let mut tt = ast.type_ref.clone();
                             tt.pointer += 1;
-                            let load_value = LoadR(tt, result_register, exp_lin.result_register);
+                            let load_value = LoadR(tt, result_register, exp_lin.result_register, Some(ast.source_ref.clone()));
                             result.ir_list.push(load_value);
                             result.result_register = result_register;
                         }
@@ -496,7 +654,12 @@ impl Lirgen {
                         // Load the value
                         if !get_address {
                             let result_register = self.get_register();
-                            let load_value = LoadR(ast.type_ref.clone(), result_register, result.result_register);
+                            let load_value = LoadR(
+                                ast.type_ref.clone(),
+                                result_register,
+                                result.result_register,
+                                Some(ast.source_ref.clone()),
+                            );
                             result.result_register = result_register;
                             result.ir_list.push(load_value);
                             result.result_register = result_register;
@@ -516,6 +679,55 @@ impl Lirgen {
         panic!("AstNode is not of type PrefixNode");
     }
 
+    /// Lirgen::fold_not
+    ///
+    /// Linearize the logical negation of an expression without a `Not` node, whenever possible:
+    /// the negation of a comparison is the opposite comparison (`!(a == b)` is `a != b`), while a
+    /// double negation is the normalization of its operand (`!!x` is `x != 0`), which is the
+    /// operand itself if it is a comparison
+    ///
+    /// @in expr[&AstNodeWrapper]: operand of the negation
+    /// @return [Option<LirgenResult>]: result of the conversion, None if the negation cannot be
+    /// folded
+    fn fold_not(&mut self, expr: &AstNodeWrapper) -> Option<LirgenResult> {
+        match &expr.node {
+            AstNode::BinaryNode(token, exp1, exp2) => {
+                let opposite = match token.tk {
+                    Tk::Operator(Operator::EqualCompare) => Operator::DiffCompare,
+                    Tk::Operator(Operator::DiffCompare) => Operator::EqualCompare,
+                    Tk::Operator(Operator::LTCompare) => Operator::GECompare,
+                    Tk::Operator(Operator::GECompare) => Operator::LTCompare,
+                    Tk::Operator(Operator::GTCompare) => Operator::LECompare,
+                    Tk::Operator(Operator::LECompare) => Operator::GTCompare,
+                    _ => return None,
+                };
+                let comparison = AstNodeWrapper {
+                    node: AstNode::BinaryNode(
+                        Token {
+                            tk: Tk::Operator(opposite),
+                            ..token.clone()
+                        },
+                        exp1.clone(),
+                        exp2.clone(),
+                    ),
+                    ..expr.clone()
+                };
+                Some(self.linearize(&comparison, false, 0, 0))
+            }
+            AstNode::PrefixNode(token, operand) if token.tk == Tk::Operator(Operator::Not) => {
+                if let AstNode::BinaryNode(token, ..) = &operand.node {
+                    if CompareType::from_token(token).is_some() {
+                        return Some(self.linearize(operand, false, 0, 0));
+                    }
+                }
+                let operand_lin = self.linearize(operand, false, 0, 0);
+                let operand_lin = self.promote_operand(operand, operand_lin);
+                Some(self.normalize_bool(operand, operand_lin))
+            }
+            _ => None,
+        }
+    }
+
     /// Lirgen::linearize_array_decl_node
     ///
     /// Linearize a node of type ArrayDeclNode
@@ -540,6 +752,8 @@ impl Lirgen {
                 init_register,
                 true,
                 name.tk.get_identifier(),
+                Radix::Decimal,
+                Some(ast.source_ref.clone()),
             );
 
             result.ir_list.append(&mut expression_lin.ir_list);
@@ -558,12 +772,15 @@ impl Lirgen {
                 size,
                 false,
                 name.tk.get_identifier(),
+                Radix::Decimal,
+                Some(ast.source_ref.clone()),
             );
             result.ir_list.push(store_node);
 
             result.result_register = result_register;
 
             self.add_pointer_variable(&name.tk.get_identifier(), result_register);
+            self.array_lengths.insert(result_register, init_register);
             return result;
         }
         panic!("AstNode is not of type ArrayDeclNode");
@@ -597,6 +814,9 @@ impl Lirgen {
             let for_next_label = self.get_label(); // Before the increment (used for continue)
             let for_end_label = self.get_label(); // End of loop (used for break)
 
+            // The variables declared in the first expression are visible only in the loop
+            let old_pointers = self.start_scope();
+
             // First expression is always evaluated, and it can use the context of the previous
             // instructions in terms of stored variables
             result.ir_list.push(IrNode::Label(for_label.clone()));
@@ -624,8 +844,9 @@ impl Lirgen {
                 .push(IrNode::Branch(CompareType::Always, ast.type_ref.clone(), 0, 0, for_start_label));
             result.ir_list.push(IrNode::Label(for_end_label.clone()));
 
-            // Complete the invalidation of the variables
+            // Complete the invalidation of the variables, and forget the ones declared in the loop
             self.end_invalidate(old_to_invalidate, old_constant_values, old_to_invalidate_variable);
+            self.end_scope(old_pointers);
             return result;
         }
         panic!("AstNode is not of type ForNode");
@@ -705,6 +926,14 @@ impl Lirgen {
             // {body_else}
             // L_if_end:
 
+            // With an optimization level greater than 1, an if whose arms assign the same variable
+            // is a selection without any branch
+            if self.opt > 1 {
+                if let Some(selection) = self.linearize_selection(expr, body, else_body) {
+                    return selection;
+                }
+            }
+
             let if_end_label = self.get_label();
 
             // The else label might correspond to the end label in case of an empty null body
@@ -758,9 +987,238 @@ impl Lirgen {
         panic!("AstNode is not of type IfNode");
     }
 
+    /// Lirgen::get_single_assignment
+    ///
+    /// Check whether the arm of an if statement is made of a single assignment to a variable,
+    /// possibly within a block
+    ///
+    /// @in body[&AstNodeWrapper]: arm to consider
+    /// @return [Option<(&AstNodeWrapper, &AstNodeWrapper, &AstNodeWrapper)>]: assignment, its
+    /// left operand and its right operand, None if the arm is not a single assignment
+    fn get_single_assignment(body: &AstNodeWrapper) -> Option<(&AstNodeWrapper, &AstNodeWrapper, &AstNodeWrapper)> {
+        match &body.node {
+            AstNode::CompoundNode(statements) if statements.len() == 1 => Self::get_single_assignment(&statements[0]),
+            AstNode::ExprStatementNode(expr) => {
+                if let AstNode::BinaryNode(token, exp1, exp2) = &expr.node {
+                    if token.tk == Tk::Operator(Operator::Assign) && matches!(exp1.node, AstNode::PrimaryNode(..)) {
+                        return Some((expr, exp1, exp2));
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Lirgen::is_pure
+    ///
+    /// Check whether an expression can be evaluated even if its value is not used: it cannot
+    /// contain assignments, calls, dereferences or accesses to arrays, nor divisions, whose divisor
+    /// might be zero when the value is not required
+    ///
+    /// @in ast[&AstNodeWrapper]: expression to consider
+    /// @return [bool]: whether the expression has no side effects
+    fn is_pure(ast: &AstNodeWrapper) -> bool {
+        match &ast.node {
+            AstNode::PrimaryNode(..) => true,
+            AstNode::CastNode(_, expr) => Self::is_pure(expr),
+            AstNode::PrefixNode(token, expr) => token.tk != Tk::Operator(Operator::Asterisk) && Self::is_pure(expr),
+            AstNode::BinaryNode(token, exp1, exp2) => {
+                !matches!(token.tk, Tk::Operator(Operator::Assign | Operator::Slash | Operator::Module)) && Self::is_pure(exp1) && Self::is_pure(exp2)
+            }
+            _ => false,
+        }
+    }
+
+    /// Lirgen::linearize_selection
+    ///
+    /// Linearize an if statement whose arms are single assignments to the same variable
+    /// (`if (c) x = a; else x = b;`) without branches. Both the values are computed, and the
+    /// condition `c`, normalized to 0 or 1, is turned into the mask `m = -c`, so that the value to
+    /// store is `b ^ ((a ^ b) & m)`. This is possible only if the right operands have no side
+    /// effects and do not require 64 bits
+    ///
+    /// @in expr[&AstNodeWrapper]: condition of the if statement
+    /// @in body[&AstNodeWrapper]: body of the if statement
+    /// @in else_body[&AstNodeWrapper]: else body of the if statement
+    /// @return [Option<LirgenResult>]: result of the conversion, None if the if statement is not a
+    /// selection
+    fn linearize_selection(&mut self, expr: &AstNodeWrapper, body: &AstNodeWrapper, else_body: &AstNodeWrapper) -> Option<LirgenResult> {
+        let (assign, lhs, then_value) = Self::get_single_assignment(body)?;
+        let (_, else_lhs, else_value) = Self::get_single_assignment(else_body)?;
+        let (id, else_id) = match (&lhs.node, &else_lhs.node) {
+            (AstNode::PrimaryNode(token), AstNode::PrimaryNode(else_token)) => (token.tk.get_identifier(), else_token.tk.get_identifier()),
+            _ => return None,
+        };
+        let tt = &assign.type_ref;
+        if id != else_id
+            || !TypeWrapper::are_compatible(tt, &else_lhs.type_ref)
+            || tt.get_size() == 8
+            || expr.type_ref.get_size() == 8
+            || !Self::is_pure(then_value)
+            || !Self::is_pure(else_value)
+        {
+            return None;
+        }
+
+        let mut result = LirgenResult { ..Default::default() };
+
+        // The condition is either 0 or 1
+        let mut cond_lin = self.linearize(expr, false, 0, 0);
+        let is_compare = matches!(&expr.node, AstNode::BinaryNode(token, ..) if CompareType::from_token(token).is_some());
+        if !is_compare {
+            cond_lin = self.promote_operand(expr, cond_lin);
+            let mut zero_lin = self.load_constant(&expr.type_ref, 0);
+            cond_lin.ir_list.append(&mut zero_lin.ir_list);
+            let value = cond_lin.result_register;
+            cond_lin.result_register = self.add_binary(
+                &mut cond_lin,
+                Operator::DiffCompare,
+                &expr.type_ref,
+                value,
+                zero_lin.result_register,
+                expr,
+            );
+        }
+        result.ir_list.append(&mut cond_lin.ir_list);
+
+        let mut then_lin = self.linearize(then_value, false, 0, 0);
+        let mut else_lin = self.linearize(else_value, false, 0, 0);
+        if tt.is_bool() {
+            then_lin = self.normalize_bool(then_value, then_lin);
+            else_lin = self.normalize_bool(else_value, else_lin);
+        }
+        result.ir_list.append(&mut then_lin.ir_list);
+        result.ir_list.append(&mut else_lin.ir_list);
+
+        // Mask with all the bits set if the condition holds
+        let mask = self.get_register();
+        result
+            .ir_list
+            .push(IrNode::Unary(tt.clone(), Operator::Minus, mask, cond_lin.result_register));
+
+        let difference = self.add_binary(
+            &mut result,
+            Operator::XorOp,
+            tt,
+            then_lin.result_register,
+            else_lin.result_register,
+            assign,
+        );
+        let selected = self.add_binary(&mut result, Operator::AndOp, tt, difference, mask, assign);
+        let value = self.add_binary(&mut result, Operator::XorOp, tt, else_lin.result_register, selected, assign);
+
+        let mut lhs_lin = self.linearize(lhs, true, 0, 0);
+        result.ir_list.append(&mut lhs_lin.ir_list);
+        result
+            .ir_list
+            .push(IrNode::Store(tt.clone(), lhs_lin.result_register, value, Some(assign.source_ref.clone())));
+
+        self.to_invalidate_variable.push(id.clone());
+        self.variable_values.insert(id, value);
+
+        Some(result)
+    }
+
+    /// Lirgen::linearize_switch_node
+    ///
+    /// Linearize a node of type SwitchNode
+    ///
+    /// @in ast[&AstNodeWrapper]: node to linearize
+    /// @in get_address[bool]: in case of an expression, whether we have to extract the address of
+    /// the operand (in case of an lvalue) or its value
+    /// @in continue_dest[u32]: in case of a loop, label to jump for continue instructions
+    /// @return [LirgenResult]: result of the conversion
+    fn linearize_switch_node(&mut self, ast: &AstNodeWrapper, get_address: bool, continue_dest: u32) -> LirgenResult {
+        if let AstNode::SwitchNode(expr, cases) = &ast.node {
+            let mut result = LirgenResult { ..Default::default() };
+
+            // A switch node is always linarized this way:
+            //
+            // {discriminant}
+            // v_case_1 = $value_1
+            // jeq v_discriminant, v_case_1 L_case_1
+            // ...
+            // j L_default (L_switch_end if there is no default)
+            // L_case_1:
+            // {body_1}
+            // ...
+            // L_switch_end:
+            //
+            // Each body falls through the following one, while a break jumps to L_switch_end
+
+            let switch_end_label = self.get_label();
+            let mut default_label = switch_end_label;
+            let mut case_labels: Vec<u32> = vec![];
+
+            // The discriminant is always evaluated, so it can rely on the previous context
+            let expr_lin = self.linearize(expr, false, 0, 0);
+            let mut expr_lin = self.promote_operand(expr, expr_lin);
+            result.ir_list.append(&mut expr_lin.ir_list);
+
+            // Compare the discriminant with the label of each case
+            for case in cases {
+                if let AstNode::CaseNode(label, _) = &case.node {
+                    let case_label = self.get_label();
+                    case_labels.push(case_label);
+
+                    // The default case is taken only if no other case matches
+                    if label.node == AstNode::NullNode {
+                        default_label = case_label;
+                        continue;
+                    }
+
+                    // The parser guarantees the labels to be integer constants, different from each other
+                    let value = label.eval_const_expr().unwrap();
+
+                    let value_register = self.get_register();
+                    result.ir_list.push(IrNode::MovC(expr.type_ref.clone(), value_register, value));
+                    result.ir_list.push(IrNode::Branch(
+                        CompareType::EQ,
+                        expr.type_ref.clone(),
+                        expr_lin.result_register,
+                        value_register,
+                        case_label,
+                    ));
+                } else {
+                    panic!("AstNode is not of type CaseNode");
+                }
+            }
+            result
+                .ir_list
+                .push(IrNode::Branch(CompareType::Always, ast.type_ref.clone(), 0, 0, default_label));
+
+            // Linearize the body of each case
+            for i in 0..cases.len() {
+                if let AstNode::CaseNode(_, statements) = &cases[i].node {
+                    // Start to invalidate, as a case can be reached both from the comparisons
+                    // and from the previous case
+                    let (old_to_invalidate, old_constant_values, old_to_invalidate_variable) = self.start_invalidate();
+
+                    result.ir_list.push(IrNode::Label(case_labels[i]));
+                    for statement in statements {
+                        let mut statement_lin = self.linearize(statement, get_address, switch_end_label, continue_dest);
+                        result.ir_list.append(&mut statement_lin.ir_list);
+                    }
+
+                    // End to invalidate
+                    self.end_invalidate(old_to_invalidate, old_constant_values, old_to_invalidate_variable);
+                }
+            }
+
+            // End label
+            result.ir_list.push(IrNode::Label(switch_end_label));
+
+            return result;
+        }
+
+        panic!("AstNode is not of type SwitchNode");
+    }
+
     /// Lirgen::linearize_procedure_node
     ///
-    /// Linearize a node of type ProcedureNode
+    /// Linearize a node of type ProcedureNode. A function identifier is called directly, while any
+    /// other expression is a pointer to function, whose address is called
     ///
     /// @in ast[&AstNodeWrapper]: node to linearize
     /// @return [LirgenResult]: result of the conversion
@@ -771,34 +1229,69 @@ impl Lirgen {
             // A call requires a list of paramenters which must be computed and stored in registers
             let mut list_params: Vec<u32> = vec![];
 
-            // The name of the procedure has to be an identifer (due to ast construction, there are
-            // no pointers to functions)
+            // The name of the function to call, if the procedure is a function identifier (a
+            // variable cannot have the name of a function, due to ast construction)
+            let mut name: Option<String> = None;
             if let AstNode::PrimaryNode(tk) = &primary.node {
-                // Get name of the functino to call
                 let id = tk.tk.get_identifier();
+                if self.functions.contains_key(&id) {
+                    name = Some(id);
+                }
+            }
 
-                // Compute the parameters and add their required instructions to the list
-                for p in params {
-                    let mut linearized = self.linearize(p, false, 0, 0);
+            if let Some(id) = &name {
+                // `__putchar(c)` is not a call, but the system call printing a character (number
+                // 11 in RARS and in the other simulators following its convention)
+                if id == "__putchar" {
+                    let mut linearized = self.linearize(&params[0], false, 0, 0);
                     result.ir_list.append(&mut linearized.ir_list);
-                    list_params.push(linearized.result_register);
+                    result.ir_list.push(Syscall(11, vec![linearized.result_register]));
+                    return result;
                 }
+            }
 
-                let result_register = if ast.type_ref.type_native == TypeNative::Void {
-                    0
-                } else {
-                    self.get_register()
-                };
-                result.ir_list.push(IrNode::Call(id, ast.type_ref.clone(), list_params, result_register));
+            // The address of the function is computed before the parameters
+            let mut target = 0;
+            if name.is_none() {
+                let mut linearized = self.linearize(primary, false, 0, 0);
+                result.ir_list.append(&mut linearized.ir_list);
+                target = linearized.result_register;
+            }
 
-                // We cannot say for sure what happens withing the function, thus we cannot rely on
-                // the stored values anymore
-                self.clear_variable_values();
-                result.result_register = result_register;
-                return result;
+            // Compute the parameters and add their required instructions to the list
+            for p in params {
+                let mut linearized = self.linearize(p, false, 0, 0);
+                result.ir_list.append(&mut linearized.ir_list);
+                list_params.push(linearized.result_register);
+            }
+
+            let result_register = if ast.type_ref.type_native == TypeNative::Void && ast.type_ref.pointer == 0 {
+                0
             } else {
-                panic!("AstNode is not an identifier when calling function");
+                self.get_register()
+            };
+            match name {
+                Some(id) => result.ir_list.push(IrNode::Call(
+                    id,
+                    ast.type_ref.clone(),
+                    list_params,
+                    result_register,
+                    Some(ast.source_ref.clone()),
+                )),
+                None => result.ir_list.push(IrNode::CallIndirect(
+                    ast.type_ref.clone(),
+                    target,
+                    list_params,
+                    result_register,
+                    Some(ast.source_ref.clone()),
+                )),
             }
+
+            // We cannot say for sure what happens withing the function, thus we cannot rely on
+            // the stored values anymore
+            self.clear_variable_values();
+            result.result_register = result_register;
+            return result;
         }
 
         panic!("AstNode is not of type ProcedureNode");
@@ -824,12 +1317,17 @@ impl Lirgen {
                     if expr.node != AstNode::NullNode {
                         let mut e_lin = self.linearize(expr, get_address, 0, 0);
                         result.ir_list.append(&mut e_lin.ir_list);
-                        result.ir_list.push(IrNode::Return(expr.type_ref.clone(), e_lin.result_register));
+                        result
+                            .ir_list
+                            .push(IrNode::Return(expr.type_ref.clone(), e_lin.result_register, Some(ast.source_ref.clone())));
                     } else {
-                        result.ir_list.push(IrNode::Return(expr.type_ref.clone(), 0));
+                        result
+                            .ir_list
+                            .push(IrNode::Return(expr.type_ref.clone(), 0, Some(ast.source_ref.clone())));
                     }
                 }
-                // A continue statement is a jump to the continue_dest label specified as input
+                // A continue statement is a jump to the continue_dest label specified as input (the
+                // parser rejects the statements which are not inside of a loop)
                 Tk::Keyword(Keyword::Continue) => {
                     result
                         .ir_list
@@ -879,12 +1377,17 @@ impl Lirgen {
         if let AstNode::CompoundNode(list) = &ast.node {
             let mut result = LirgenResult { ..Default::default() };
 
+            // A compound statement defines a new scope: the variables declared in it are not
+            // visible anymore at its end
+            let old_pointers = self.start_scope();
+
             // Linearize all the nodes
             for l in list {
                 let mut l_lin = self.linearize(l, get_address, break_dest, continue_dest);
                 result.ir_list.append(&mut l_lin.ir_list);
             }
 
+            self.end_scope(old_pointers);
             return result;
         }
 
@@ -911,6 +1414,34 @@ impl Lirgen {
         // functinos are executed before main.
 
         if let AstNode::DeclarationList(list) = &ast.node {
+            // Collect the names of the global variables, as they are linearized after the
+            // functions using them, together with the lengths of the global arrays
+            for elem in list {
+                match &elem.node {
+                    AstNode::VarDeclNode(_, id, _) => self.global_variables.push(id.tk.get_identifier()),
+                    AstNode::ArrayDeclNode(_, id, size) => {
+                        self.global_variables.push(id.tk.get_identifier());
+                        if let Some(length) = size.eval_const_expr() {
+                            self.global_array_lengths.insert(id.tk.get_identifier(), length);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            // Collect the signatures of the functions, so that each of them can be called before
+            // being linearized (mutual recursion)
+            for elem in list {
+                if let AstNode::FuncDeclNode(tt, id, args, _) = &elem.node {
+                    let arguments = args.iter().map(|a| a.type_ref.clone()).collect();
+                    self.functions.insert(id.tk.get_identifier(), (tt.type_ref.clone(), arguments));
+                }
+            }
+            // The built-in functions can be called as well, their code being added by the backend
+            for builtin in Declaration::builtins() {
+                self.functions.insert(builtin.name, (builtin.return_type, builtin.arguments));
+            }
+
             // Linearize all the functions
             for elem in list {
                 if let AstNode::FuncDeclNode(..) = &elem.node {
@@ -932,10 +1463,33 @@ impl Lirgen {
                 }
             }
 
-            // Add the call to main in the init function
-            var_decl.push(IrNode::Call("main".to_string(), TypeWrapper { ..Default::default() }, vec![], 0));
+            // Add the call to the entry function (main by default) in the init function. Its
+            // return value (if any) is the exit code of the program: it is stored in a global
+            // variable, where a runtime can read it
+            let entry_type = match self.functions.get(&self.entry) {
+                Some((tt, _)) if tt.type_native != TypeNative::Void => tt.clone(),
+                _ => TypeWrapper { ..Default::default() },
+            };
+            if entry_type.type_native == TypeNative::Null {
+                var_decl.push(IrNode::Call(self.entry.clone(), entry_type, vec![], 0, None));
+            } else {
+                let exit_code = self.get_register();
+                var_decl.push(IrNode::Call(self.entry.clone(), entry_type.clone(), vec![], exit_code, None));
+                let address = self.get_register();
+                var_decl.push(Alloc(
+                    entry_type,
+                    address,
+                    exit_code,
+                    true,
+                    1,
+                    false,
+                    EXIT_CODE.to_string(),
+                    Radix::Decimal,
+                    None,
+                ));
+            }
 
-            // After the call to main, add a jump to the instruction itself (endless loop
+            // After the call to the entry function, add a jump to the instruction itself (endless loop
             // representing the end of the execution)
             var_decl.push(Label(0));
             var_decl.push(Branch(CompareType::Always, TypeWrapper { ..Default::default() }, 0, 0, 0));
@@ -950,6 +1504,26 @@ impl Lirgen {
         panic!("AstNode is not of type DeclarationList");
     }
 
+    /// Lirgen::get_radix
+    ///
+    /// Get the base in which the initial value of a variable is written in the source, so that the
+    /// backend can print the data of a global variable in the same base. Only a literal, possibly
+    /// casted or negated, has a base different from the decimal one
+    ///
+    /// @in expression[&AstNodeWrapper]: initial value of the variable
+    /// @return [Radix]: base of the value
+    fn get_radix(expression: &AstNodeWrapper) -> Radix {
+        match &expression.node {
+            AstNode::PrimaryNode(Token {
+                tk: Tk::IntegerLiteral(_, radix),
+                ..
+            }) => radix.clone(),
+            AstNode::CastNode(_, exp) => Lirgen::get_radix(exp),
+            AstNode::PrefixNode(token, exp) if token.tk == Tk::Operator(Operator::Minus) => Lirgen::get_radix(exp),
+            _ => Radix::Decimal,
+        }
+    }
+
     /// Lirgen::linearize_var_recl_node
     ///
     /// Linearize a node of type VarDeclNode
@@ -963,6 +1537,10 @@ impl Lirgen {
             // if the right expression is not null, there is a value used for initialization
             let init_register = if expression.node != AstNode::NullNode {
                 let mut expression_lin = self.linearize(expression, false, 0, 0);
+                // A `bool` variable can only hold 0 or 1
+                if tt.type_ref.is_bool() {
+                    expression_lin = self.normalize_bool(expression, expression_lin);
+                }
                 result.ir_list.append(&mut expression_lin.ir_list);
                 // The value of the variable is stored in the list of variables
                 self.add_variable(&name.tk.get_identifier(), expression_lin.result_register);
@@ -981,6 +1559,8 @@ impl Lirgen {
                 1,
                 false,
                 name.tk.get_identifier(),
+                Lirgen::get_radix(expression),
+                Some(ast.source_ref.clone()),
             );
             result.ir_list.push(store_node);
             result.result_register = result_register;
@@ -1015,8 +1595,22 @@ impl Lirgen {
                             load_register = l;
                             result.result_register = l;
                         }
+                        // A function is not a variable: both its value and its address are the
+                        // address of its code
+                        None if self.functions.contains_key(id) => {
+                            let result_register = self.get_register();
+                            result.ir_list.push(LoadA(ast.type_ref.clone(), result_register, id.to_string()));
+                            result.result_register = result_register;
+                            return result;
+                        }
                         // If the variable is global, we have to load its address
                         None => {
+                            // The parser rejects undeclared identifiers with a located error
+                            debug_assert!(
+                                self.global_variables.contains(id),
+                                "internal error: identifier {} reached lirgen undeclared",
+                                id
+                            );
                             load_register = self.get_register();
                             let mut tt = ast.type_ref.clone();
                             tt.pointer += 1;
@@ -1037,7 +1631,7 @@ impl Lirgen {
                         }
                         None => {
                             let result_register = self.get_register();
-                            let load_node = LoadR(ast.type_ref.clone(), result_register, load_register);
+                            let load_node = LoadR(ast.type_ref.clone(), result_register, load_register, Some(ast.source_ref.clone()));
                             result.ir_list.push(load_node);
                             result.result_register = result_register;
                             self.add_variable(id, result_register);
@@ -1049,7 +1643,7 @@ impl Lirgen {
                 // In case of an integer, first we check if the constant was already stored in a
                 // register, and possibly we continue to use that register, otherwise we store
                 // it into a register
-                Tk::IntegerLiteral(num) => {
+                Tk::IntegerLiteral(num, _) => {
                     let mut result: LirgenResult = Default::default();
                     match self.get_constant(*num as u32) {
                         Some(l) => {
@@ -1083,6 +1677,316 @@ impl Lirgen {
         panic!("AstNode is not of type PrimaryNode");
     }
 
+    /// Lirgen::fold_constant
+    ///
+    /// Compute at compile time the value of an expression made of integer literals and binary
+    /// arithmetic operators. Only available for opt > 0
+    ///
+    /// @in ast[&AstNodeWrapper]: expression to evaluate
+    /// @return [Option<u32>]: value of the expression, None if it cannot be computed
+    fn fold_constant(&self, ast: &AstNodeWrapper) -> Option<u32> {
+        if self.opt == 0 {
+            return None;
+        }
+        match &ast.node {
+            AstNode::PrimaryNode(token) => {
+                if let Tk::IntegerLiteral(num, _) = token.tk {
+                    return Some(num as u32);
+                }
+                None
+            }
+            AstNode::BinaryNode(token, exp1, exp2) => {
+                let v1 = self.fold_constant(exp1)?;
+                let v2 = self.fold_constant(exp2)?;
+                let signed = ast.type_ref.is_signed();
+                let operator = match &token.tk {
+                    Tk::Operator(op) => op.clone(),
+                    _ => return None,
+                };
+                // Division by zero cannot be computed, neither at compile time nor at run time
+                if (operator == Operator::Slash || operator == Operator::Module) && v2 == 0 {
+                    panic!("Division by zero in constant expression");
+                }
+                // Shifts only consider the 5 least significant bits of the amount, as the
+                // hardware does
+                match operator {
+                    Operator::Plus => Some(v1.wrapping_add(v2)),
+                    Operator::Minus => Some(v1.wrapping_sub(v2)),
+                    Operator::Asterisk => Some(v1.wrapping_mul(v2)),
+                    Operator::Slash if signed => Some((v1 as i32).wrapping_div(v2 as i32) as u32),
+                    Operator::Slash => Some(v1 / v2),
+                    Operator::Module if signed => Some((v1 as i32).wrapping_rem(v2 as i32) as u32),
+                    Operator::Module => Some(v1 % v2),
+                    Operator::AndOp => Some(v1 & v2),
+                    Operator::OrOp => Some(v1 | v2),
+                    Operator::XorOp => Some(v1 ^ v2),
+                    Operator::LShift => Some(v1.wrapping_shl(v2)),
+                    Operator::RShift if signed => Some((v1 as i32).wrapping_shr(v2) as u32),
+                    Operator::RShift => Some(v1.wrapping_shr(v2)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Lirgen::reduce_strength
+    ///
+    /// Check whether a binary operation can be computed with a shift, which happens for
+    /// multiplications by a power of two and for unsigned divisions by a power of two (a signed
+    /// division rounds towards zero, while an arithmetic shift rounds towards minus infinity).
+    /// Only available for opt > 0
+    ///
+    /// @in ast[&AstNodeWrapper]: binary node to analyze
+    /// @return [Option<(&AstNodeWrapper, Operator, u32)>]: non constant operand, shift operator and
+    /// shift amount; None if the operation cannot be reduced
+    fn reduce_strength<'a>(&self, ast: &'a AstNodeWrapper) -> Option<(&'a AstNodeWrapper, Operator, u32)> {
+        if let AstNode::BinaryNode(token, exp1, exp2) = &ast.node {
+            let is_power_of_two = |v: Option<u32>| v.is_some() && v.unwrap() > 1 && v.unwrap().is_power_of_two();
+            let v1 = self.fold_constant(exp1);
+            let v2 = self.fold_constant(exp2);
+            match token.tk {
+                Tk::Operator(Operator::Asterisk) => {
+                    if is_power_of_two(v2) {
+                        return Some((exp1, Operator::LShift, v2.unwrap().trailing_zeros()));
+                    }
+                    if is_power_of_two(v1) {
+                        return Some((exp2, Operator::LShift, v1.unwrap().trailing_zeros()));
+                    }
+                }
+                Tk::Operator(Operator::Slash) if !ast.type_ref.is_signed() && is_power_of_two(v2) => {
+                    return Some((exp1, Operator::RShift, v2.unwrap().trailing_zeros()));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Lirgen::load_constant
+    ///
+    /// Get a register containing a constant value, either by reusing a register which already
+    /// stores it or by adding a MovC. A constant of 64 bits occupies a pair of registers in the
+    /// backend, thus it is never shared with the constants of smaller types
+    ///
+    /// @in tt[&TypeWrapper]: type of the constant
+    /// @in value[u32]: value of the constant
+    /// @return [LirgenResult]: result of the conversion
+    fn load_constant(&mut self, tt: &TypeWrapper, value: u32) -> LirgenResult {
+        let mut result: LirgenResult = Default::default();
+        if tt.get_size() == 8 {
+            result.result_register = self.get_register();
+            result.ir_list.push(MovC(tt.clone(), result.result_register, value));
+            return result;
+        }
+        match self.get_constant(value) {
+            Some(l) => {
+                result.result_register = l;
+            }
+            None => {
+                let result_register = self.get_register();
+                result.ir_list.push(MovC(tt.clone(), result_register, value));
+                result.result_register = result_register;
+                self.add_constant(result_register, value);
+            }
+        }
+        result
+    }
+
+    /// Lirgen::is_normalized_bool
+    ///
+    /// Check whether an expression of type `bool` is surely either 0 or 1. This is the case of
+    /// variables, whose values are normalized when they are stored, and of casts to `bool`. Other
+    /// expressions, such as `a + b` with `a` and `b` of type `bool`, might produce any value
+    ///
+    /// @in ast[&AstNodeWrapper]: expression to consider
+    /// @return [bool]: whether the value of the expression is either 0 or 1
+    fn is_normalized_bool(&self, ast: &AstNodeWrapper) -> bool {
+        if !ast.type_ref.is_bool() {
+            return false;
+        }
+        match &ast.node {
+            AstNode::PrimaryNode(token) => matches!(token.tk, Tk::Identifier(..)),
+            AstNode::CastNode(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Lirgen::normalize_bool
+    ///
+    /// Normalize a value to be stored as `bool`, by computing `value != 0`. If the expression is
+    /// already normalized, nothing is added
+    ///
+    /// @in ast[&AstNodeWrapper]: expression which was linearized
+    /// @in value[LirgenResult]: linearization of the expression
+    /// @return [LirgenResult]: result of the conversion, whose register is either 0 or 1
+    fn normalize_bool(&mut self, ast: &AstNodeWrapper, mut value: LirgenResult) -> LirgenResult {
+        if self.is_normalized_bool(ast) {
+            return value;
+        }
+        let mut zero_lin = self.load_constant(&ast.type_ref, 0);
+        let result_register = self.get_register();
+        value.ir_list.append(&mut zero_lin.ir_list);
+        value.ir_list.push(IrNode::Binary(
+            Operator::DiffCompare,
+            ast.type_ref.clone(),
+            result_register,
+            value.result_register,
+            zero_lin.result_register,
+            Some(ast.source_ref.clone()),
+        ));
+        value.result_register = result_register;
+        value
+    }
+
+    /// Lirgen::promote_operand
+    ///
+    /// An operation on a type smaller than 32 bits might leave the upper bits of its register
+    /// set (as `a + a` with `a` of type `u8` equal to 200), which is fine as long as the value is
+    /// stored or casted. Before being used by an operation which depends on all its bits (a
+    /// comparison, a division, a remainder or a right shift), the value is promoted to 32 bits by
+    /// a `Cast` to its own type, which truncates and extends it. Casts, comparisons and literals
+    /// never need it
+    ///
+    /// @in ast[&AstNodeWrapper]: operand to promote
+    /// @in value[LirgenResult]: linearization of the operand
+    /// @return [LirgenResult]: result of the conversion
+    fn promote_operand(&mut self, ast: &AstNodeWrapper, mut value: LirgenResult) -> LirgenResult {
+        let tt = &ast.type_ref;
+        if tt.pointer != 0 || tt.type_native == TypeNative::Void || tt.type_native == TypeNative::Null || tt.get_size() == 4 {
+            return value;
+        }
+        let is_extended = match &ast.node {
+            AstNode::CastNode(..) => true,
+            AstNode::PrimaryNode(token) => matches!(token.tk, Tk::IntegerLiteral(..) | Tk::Char(..)),
+            AstNode::BinaryNode(token, ..) => CompareType::from_token(token).is_some(),
+            _ => false,
+        };
+        if is_extended {
+            return value;
+        }
+        let result_register = self.get_register();
+        value
+            .ir_list
+            .push(IrNode::Cast(tt.clone(), tt.clone(), result_register, value.result_register));
+        value.result_register = result_register;
+        value
+    }
+
+    /// Lirgen::convert_operand
+    ///
+    /// Convert an operand of a comparison to the type in which the comparison is done, which
+    /// differs from the type of the operand when comparing integers of different types (see
+    /// `TypeWrapper::common_type`). An operand which already has that type is just promoted
+    ///
+    /// @in ast[&AstNodeWrapper]: operand to convert
+    /// @in value[LirgenResult]: linearization of the operand
+    /// @in tt[&TypeWrapper]: type of the comparison
+    /// @return [LirgenResult]: result of the conversion
+    fn convert_operand(&mut self, ast: &AstNodeWrapper, mut value: LirgenResult, tt: &TypeWrapper) -> LirgenResult {
+        if TypeWrapper::are_compatible(&ast.type_ref, tt) {
+            return self.promote_operand(ast, value);
+        }
+        let result_register = self.get_register();
+        value
+            .ir_list
+            .push(IrNode::Cast(tt.clone(), ast.type_ref.clone(), result_register, value.result_register));
+        value.result_register = result_register;
+        value
+    }
+
+    /// Lirgen::add_binary
+    ///
+    /// Add a binary operation to a result, unless the same operation was already computed
+    ///
+    /// @in result[&mut LirgenResult]: result to extend
+    /// @in operator[Operator]: operator to use
+    /// @in tt[&TypeWrapper]: type of the operation
+    /// @in src1[u32]: first operand
+    /// @in src2[u32]: second operand
+    /// @in ast[&AstNodeWrapper]: node the operation comes from
+    /// @return [u32]: register containing the result of the operation
+    fn add_binary(&mut self, result: &mut LirgenResult, operator: Operator, tt: &TypeWrapper, src1: u32, src2: u32, ast: &AstNodeWrapper) -> u32 {
+        if let Some(dest) = self.get_computed_binary(src1, src2, &operator) {
+            return dest;
+        }
+        let dest = self.get_register();
+        self.add_computed_binary((operator.clone(), dest, src1, src2));
+        result
+            .ir_list
+            .push(Binary(operator, tt.clone(), dest, src1, src2, Some(ast.source_ref.clone())));
+        dest
+    }
+
+    /// Lirgen::linearize_pointer_arithmetic
+    ///
+    /// Linearize an addition or a subtraction whose left operand is a pointer. The integer of
+    /// `ptr + int` and `ptr - int` is a number of elements, thus it is multiplied by the size of the
+    /// pointed type, while the difference of two pointers is divided by it (`&a[5] - &a[2]` is 3,
+    /// whatever the type of `a`). As sizes are powers of two, shifts are used. The sum of two
+    /// pointers is not scaled
+    ///
+    /// @in ast[&AstNodeWrapper]: node to linearize
+    /// @return [Option<LirgenResult>]: result of the conversion, None if the node does not require
+    /// any scaling
+    fn linearize_pointer_arithmetic(&mut self, ast: &AstNodeWrapper) -> Option<LirgenResult> {
+        let (operator, exp1, exp2) = match &ast.node {
+            AstNode::BinaryNode(token, exp1, exp2) => (token.tk.get_operator(), exp1, exp2),
+            _ => return None,
+        };
+        let is_difference = exp2.type_ref.pointer != 0;
+        if exp1.type_ref.pointer == 0 || (operator != Operator::Minus && (operator != Operator::Plus || is_difference)) {
+            return None;
+        }
+
+        let mut pointed = exp1.type_ref.clone();
+        pointed.pointer -= 1;
+        let size = pointed.get_size();
+
+        let mut result = LirgenResult { ..Default::default() };
+        let mut exp1_lin = self.linearize(exp1, false, 0, 0);
+        let exp2_lin = self.linearize(exp2, false, 0, 0);
+        let mut exp2_lin = self.promote_operand(exp2, exp2_lin);
+        result.ir_list.append(&mut exp1_lin.ir_list);
+        result.ir_list.append(&mut exp2_lin.ir_list);
+
+        // The offset is scaled before being used
+        if !is_difference {
+            let mut offset = exp2_lin.result_register;
+            if size > 1 {
+                let mut constant_lin = self.load_constant(&exp1.type_ref, size.trailing_zeros());
+                result.ir_list.append(&mut constant_lin.ir_list);
+                offset = self.add_binary(&mut result, Operator::LShift, &exp1.type_ref, offset, constant_lin.result_register, ast);
+            }
+            result.result_register = self.add_binary(&mut result, operator, &ast.type_ref, exp1_lin.result_register, offset, ast);
+            return Some(result);
+        }
+
+        // The difference is scaled after being computed
+        let difference = self.add_binary(
+            &mut result,
+            Operator::Minus,
+            &ast.type_ref,
+            exp1_lin.result_register,
+            exp2_lin.result_register,
+            ast,
+        );
+        result.result_register = difference;
+        if size > 1 {
+            let mut constant_lin = self.load_constant(&ast.type_ref, size.trailing_zeros());
+            result.ir_list.append(&mut constant_lin.ir_list);
+            result.result_register = self.add_binary(
+                &mut result,
+                Operator::RShift,
+                &ast.type_ref,
+                difference,
+                constant_lin.result_register,
+                ast,
+            );
+        }
+        Some(result)
+    }
+
     /// Lirgen::linearize_binary_node
     ///
     /// Linearize a node of type BinaryNode
@@ -1097,14 +2001,24 @@ impl Lirgen {
 
             // Case of an assignment
             if let Tk::Operator(Operator::Assign) = token.tk {
-                // We need the address of the left operand, the value of the right operand. By ast
-                // construction, we can be sure that the left operand is an lvalue we can extract
-                // the address from
-                let mut exp1_lin = self.linearize(&exp1, true, 0, 0);
-                let mut exp2_lin = self.linearize(&exp2, get_address, 0, 0);
+                // We need the address of the left operand, the value of the right operand (even when
+                // the assignment is itself used as an lvalue). By ast construction, we can be sure
+                // that the left operand is an lvalue we can extract the address from
+                let mut exp1_lin = self.linearize(exp1, true, 0, 0);
+                let mut exp2_lin = self.linearize(exp2, false, 0, 0);
+
+                // A `bool` lvalue can only hold 0 or 1
+                if ast.type_ref.is_bool() {
+                    exp2_lin = self.normalize_bool(exp2, exp2_lin);
+                }
 
                 // Store the result of the right expression into the address of the left expression
-                let new_op = IrNode::Store(ast.type_ref.clone(), exp1_lin.result_register, exp2_lin.result_register);
+                let new_op = IrNode::Store(
+                    ast.type_ref.clone(),
+                    exp1_lin.result_register,
+                    exp2_lin.result_register,
+                    Some(ast.source_ref.clone()),
+                );
 
                 result.ir_list.append(&mut exp1_lin.ir_list);
                 result.ir_list.append(&mut exp2_lin.ir_list);
@@ -1141,12 +2055,47 @@ impl Lirgen {
                 return result;
             }
 
+            // Additions and subtractions involving pointers count elements, not bytes
+            if let Some(result) = self.linearize_pointer_arithmetic(ast) {
+                return result;
+            }
+
+            // If both the operands are constant, the result is computed at compile time and stored
+            // in a register as a normal constant
+            if let Some(value) = self.fold_constant(ast) {
+                return self.load_constant(&ast.type_ref, value);
+            }
+
             // If the operand was not an assignment, we compute its operands and we add the
             // computation to the list. Since we keep track of what was already computed, it might
-            // be that we do not have to add the instruction if redundant
-            let mut exp1_lin = self.linearize(&exp1, get_address, 0, 0);
-            let mut exp2_lin = self.linearize(&exp2, get_address, 0, 0);
-            let operator = token.tk.get_operator();
+            // be that we do not have to add the instruction if redundant. Multiplications and
+            // unsigned divisions by a power of two are computed as shifts
+            let mut exp1_lin;
+            let mut exp2_lin;
+            let mut operator = token.tk.get_operator();
+            let is_compare = CompareType::from_token(token).is_some();
+            let is_promoted = matches!(operator, Operator::Slash | Operator::Module | Operator::RShift);
+            match self.reduce_strength(ast) {
+                Some((operand, shift_operator, amount)) => {
+                    exp1_lin = self.linearize(operand, get_address, 0, 0);
+                    exp2_lin = self.load_constant(&ast.type_ref, amount);
+                    if is_promoted {
+                        exp1_lin = self.promote_operand(operand, exp1_lin);
+                    }
+                    operator = shift_operator;
+                }
+                None => {
+                    exp1_lin = self.linearize(exp1, get_address, 0, 0);
+                    exp2_lin = self.linearize(exp2, get_address, 0, 0);
+                    if is_compare {
+                        exp1_lin = self.convert_operand(exp1, exp1_lin, &ast.type_ref);
+                        exp2_lin = self.convert_operand(exp2, exp2_lin, &ast.type_ref);
+                    } else if is_promoted {
+                        exp1_lin = self.promote_operand(exp1, exp1_lin);
+                        exp2_lin = self.promote_operand(exp2, exp2_lin);
+                    }
+                }
+            }
 
             if let Some(dest) = self.get_computed_binary(exp1_lin.result_register, exp2_lin.result_register, &operator) {
                 result.result_register = dest;
@@ -1163,6 +2112,7 @@ impl Lirgen {
                 result_register,
                 exp1_lin.result_register,
                 exp2_lin.result_register,
+                Some(ast.source_ref.clone()),
             );
 
             result.ir_list.append(&mut exp1_lin.ir_list);
@@ -1186,6 +2136,12 @@ impl Lirgen {
     /// @return [LirgenResult]: result of the conversion
     fn linearize_cast_node(&mut self, ast: &AstNodeWrapper, get_address: bool) -> LirgenResult {
         if let AstNode::CastNode(dest_type, exp) = &ast.node {
+            // An arithmetic operation on a type smaller than 32 bits is computed after the
+            // integer promotion, if its result is converted to a larger type
+            if let Some(promoted) = Lirgen::promote_arithmetic(dest_type, exp) {
+                return self.linearize(&promoted, get_address, 0, 0);
+            }
+
             let mut result = LirgenResult { ..Default::default() };
             let mut exp_lin = self.linearize(&exp, get_address, 0, 0);
 
@@ -1194,6 +2150,11 @@ impl Lirgen {
                 return exp_lin;
             }
 
+            // A cast to `bool` is a comparison with zero, done with the type of the operand
+            if dest_type.type_ref.is_bool() {
+                return self.normalize_bool(exp, exp_lin);
+            }
+
             // Just add the cast instruction specifying initial and final type
             let result_register = self.get_register();
             let new_cast = IrNode::Cast(dest_type.type_ref.clone(), exp.type_ref.clone(), result_register, exp_lin.result_register);
@@ -1208,6 +2169,76 @@ impl Lirgen {
         panic!("AstNode is not of type CastNode");
     }
 
+    /// Lirgen::promote_arithmetic
+    ///
+    /// As in C, the operands of an arithmetic operation on a type smaller than 32 bits are
+    /// promoted to `i32`, so that `(u32)(a + a)` with `a` of type `u8` equal to 200 is 400 and not
+    /// 144. The promotion is only visible when the result is converted to a larger type (by an
+    /// explicit cast, or implicitly by an assignment), which is when it is applied: the
+    /// operation is rebuilt on the operands casted to `i32`, and its result is converted to the
+    /// destination type
+    ///
+    /// @in dest_type[&AstNodeWrapper]: type node of the cast
+    /// @in exp[&AstNodeWrapper]: expression to cast
+    /// @return [Option<AstNodeWrapper>]: expression computing the promoted operation, None if the
+    /// promotion does not apply
+    fn promote_arithmetic(dest_type: &AstNodeWrapper, exp: &AstNodeWrapper) -> Option<AstNodeWrapper> {
+        let dest = &dest_type.type_ref;
+        let tt = &exp.type_ref;
+        if !dest.is_integer() || !tt.is_integer() || dest.is_bool() || tt.is_bool() || tt.get_size() >= 4 || dest.get_size() <= tt.get_size() {
+            return None;
+        }
+        let (token, exp1, exp2) = match &exp.node {
+            AstNode::BinaryNode(token, exp1, exp2) => (token, exp1, exp2),
+            _ => return None,
+        };
+        if !matches!(
+            token.tk,
+            Tk::Operator(
+                Operator::Plus
+                    | Operator::Minus
+                    | Operator::Asterisk
+                    | Operator::Slash
+                    | Operator::Module
+                    | Operator::AndOp
+                    | Operator::OrOp
+                    | Operator::XorOp
+                    | Operator::LShift
+                    | Operator::RShift
+            )
+        ) {
+            return None;
+        }
+
+        let i32_type = TypeWrapper {
+            type_native: TypeNative::I32,
+            pointer: 0,
+            constant: false,
+        };
+        let cast = |type_node: &AstNodeWrapper, node: &AstNodeWrapper| AstNodeWrapper {
+            node: AstNode::new_cast(type_node, node),
+            source_ref: node.source_ref.clone(),
+            type_ref: type_node.type_ref.clone(),
+            ..Default::default()
+        };
+        let i32_node = AstNodeWrapper {
+            node: AstNode::new_type(&i32_type),
+            source_ref: dest_type.source_ref.clone(),
+            type_ref: i32_type.clone(),
+            ..Default::default()
+        };
+        let promoted = AstNodeWrapper {
+            node: AstNode::new_binary(token, &cast(&i32_node, exp1), &cast(&i32_node, exp2)),
+            source_ref: exp.source_ref.clone(),
+            type_ref: i32_type.clone(),
+            ..Default::default()
+        };
+        if TypeWrapper::are_compatible(dest, &i32_type) {
+            return Some(promoted);
+        }
+        Some(cast(dest_type, &promoted))
+    }
+
     /// Lirgen::linearize_func_decl_node
     ///
     /// Linearize a node of type FuncDeclNode
@@ -1217,6 +2248,12 @@ impl Lirgen {
     fn linearize_func_decl_node(&mut self, ast: &AstNodeWrapper) -> LirgenResult {
         if let AstNode::FuncDeclNode(rt, name, params, body) = &ast.node {
             let mut result = LirgenResult { ..Default::default() };
+            // A prototype has no code: its signature was already collected, so that it can be
+            // called
+            if body.node == AstNode::NullNode {
+                return result;
+            }
+
             // List of types of the arguments
             let mut tt_list: Vec<TypeWrapper> = vec![];
             // List of nodes in the function
@@ -1241,6 +2278,8 @@ impl Lirgen {
                     1,
                     false,
                     name_param.clone(),
+                    Radix::Decimal,
+                    Some(params[i].source_ref.clone()),
                 );
 
                 // Save both pointer and value of the veriables
@@ -1255,6 +2294,12 @@ impl Lirgen {
             let mut body_lin = self.linearize(body, false, 0, 0);
             ir_list.append(&mut body_lin.ir_list);
 
+            // If the body does not end with a return statement, the function returns implicitly,
+            // without a value, so that the execution never goes past its end
+            if !matches!(ir_list.last(), Some(IrNode::Return(..))) {
+                ir_list.push(IrNode::Return(rt.type_ref.clone(), 0, Some(body.source_ref.clone())));
+            }
+
             let func_node = IrNode::FunctionDeclaration(name.tk.get_identifier(), rt.type_ref.clone(), tt_list, ir_list.clone());
             result.ir_list.push(func_node);
 
@@ -0,0 +1,1191 @@
+mod test {
+
+    /// test::get_parser
+    ///
+    /// Get a parser for the tokens of the input, which must be tokenized without errors
+    ///
+    /// @in input [&str]: source code to parse
+    /// @return [Parser]: parser ready to parse the input
+    #[cfg(test)]
+    fn get_parser(input: &str) -> crate::parser::parser::Parser {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let mut l = Lexer::new(String::from(input), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        Parser::new(tokens.unwrap(), String::from(""))
+    }
+
+    /// test::get_ast
+    ///
+    /// Get the ast of the input, which must be parsed without errors
+    ///
+    /// @in input [&str]: source code to parse
+    /// @return [AstNodeWrapper]: ast of the input
+    #[cfg(test)]
+    fn get_ast(input: &str) -> crate::ast::ast_node::AstNodeWrapper {
+        get_parser(input).parse().unwrap()
+    }
+
+    #[test]
+    fn lirgen_test_constant_folding() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            void main() {
+              u32 a = (3 + 4) * 2 - 1;
+              u32 b = 100 / 7 % 5 << 2 | 1;
+              u32 c = a + 13;
+              return;
+            }
+",
+        );
+
+        // With optimizations, the constant expressions are computed at compile time and the
+        // constants are reused
+        let expected_opt = String::from(
+            "
+function<null> init () {
+	v0 = call<null> main()
+
+	%L_0:
+	j %L_0
+}
+
+function<void> main () {
+	v1 = <u32> $13
+	v2 = alloc<u32> v1 	(@a)
+	v3 = <u32> $17
+	v4 = alloc<u32> v3 	(@b)
+	v5 = add <u32> v1, v1
+	v6 = alloc<u32> v5 	(@c)
+	return
+}
+",
+        );
+
+        let ast = get_ast(&input);
+
+        assert_eq!(expected_opt, Lirgen::new(1).linearize_ast(&ast).to_string());
+
+        // Without optimizations, each operation is kept
+        let ir = Lirgen::new(0).linearize_ast(&ast).to_string();
+        assert!(ir.contains("mul"));
+        assert!(ir.contains("div"));
+        assert!(ir.contains("rem"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn lirgen_test_constant_division_by_zero() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("void main() { u32 a = 10 / (3 - 3); return; }");
+
+        let ast = get_ast(&input);
+        Lirgen::new(1).linearize_ast(&ast);
+    }
+
+    #[test]
+    fn lirgen_test_strength_reduction() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 f(u32 x, i32 y) {
+              u32 a = x * 8;
+              u32 b = 4 * x;
+              u32 c = x / 16;
+              u32 d = x / 6;
+              i32 e = y / (i32)4;
+              return a;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        // Multiplications and unsigned divisions by a power of two become shifts, while the
+        // signed division and the division by a non power of two are kept
+        let expected_opt = String::from(
+            "
+function<u32> f (v1<u32>, v2<i32>) {
+	v3 = alloc<u32> v1 	(@x)
+	v4 = alloc<i32> v2 	(@y)
+	v5 = <u32> $3
+	v6 = sl <u32> v1, v5
+	v7 = alloc<u32> v6 	(@a)
+	v8 = <u32> $2
+	v9 = sl <u32> v1, v8
+	v10 = alloc<u32> v9 	(@b)
+	v11 = <u32> $4
+	v12 = sr <u32> v1, v11
+	v13 = alloc<u32> v12 	(@c)
+	v14 = <u32> $6
+	v15 = div <u32> v1, v14
+	v16 = alloc<u32> v15 	(@d)
+	v17 = <i32><u32> v11
+	v18 = div <i32> v2, v17
+	v19 = alloc<i32> v18 	(@e)
+	return<u32> v6
+}
+",
+        );
+
+        let ast = get_ast(&input);
+
+        assert!(Lirgen::new(1).linearize_ast(&ast).to_string().contains(&expected_opt));
+
+        // Without optimizations, no operation is reduced
+        let ir = Lirgen::new(0).linearize_ast(&ast).to_string();
+        assert!(!ir.contains("sl "));
+        assert!(!ir.contains("sr "));
+    }
+
+    #[test]
+    fn lirgen_test_selector_offset() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            void main() {
+              char c[4];
+              int i[4];
+              int k = 3;
+              c[k] = (char)'a';
+              i[k] = 7;
+              return;
+            }
+",
+        );
+
+        // The index of the char array is used as it is, while the one of the int array is
+        // multiplied by 4 through a shift by 2
+        let expected = String::from(
+            "
+function<void> main () {
+	v1 = <u32> $4
+	v2 = alloc<i8> [v1] 	(@c)
+	v3 = alloc<i8*> v2 	(@c)
+	v4 = alloc<u32> [v1] 	(@i)
+	v5 = alloc<u32*> v4 	(@i)
+	v6 = <u32> $3
+	v7 = alloc<u32> v6 	(@k)
+	v8 = load<i8*> v3
+	v9 = add <i8*> v8, v6
+	v10 = <u8> $97
+	v11 = <i8><u8> v10
+	store<i8> v9, v11
+	v12 = load<u32*> v5
+	v13 = load<u32> v7
+	v14 = <u32*> $2
+	v15 = sl <u32*> v13, v14
+	v16 = add <u32*> v12, v15
+	v17 = <u32> $7
+	store<u32> v16, v17
+	return
+}
+",
+        );
+
+        let ast = get_ast(&input);
+
+        assert!(Lirgen::new(1).linearize_ast(&ast).to_string().contains(&expected));
+    }
+
+    #[test]
+    fn lirgen_test_selector_reuse() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 f(u32* a, u32 k) {
+              return a[k] + a[k];
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        // The address of `a[k]` is computed once and used by both the loads
+        let expected = String::from(
+            "
+function<u32> f (v1<u32*>, v2<u32>) {
+	v3 = alloc<u32*> v1 	(@a)
+	v4 = alloc<u32> v2 	(@k)
+	v5 = <u32*> $2
+	v6 = sl <u32*> v2, v5
+	v7 = add <u32*> v1, v6
+	v8 = load<u32> v7
+	v9 = load<u32> v7
+	v10 = add <u32> v8, v9
+	return<u32> v10
+}
+",
+        );
+
+        let ast = get_ast(&input);
+
+        assert!(Lirgen::new(1).linearize_ast(&ast).to_string().contains(&expected));
+    }
+
+    #[test]
+    fn lirgen_test_for_scope() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 main() {
+              u32 s = 0;
+              for (u32 i = 0; i < 3; i = i + 1) { s = s + i; }
+              for (u32 i = 10; i < 12; i = i + 1) { s = s + i; }
+              return s;
+            }
+",
+        );
+
+        // Each loop allocates its own `i`, and the second one does not use the value of the first
+        let expected = String::from(
+            "
+function<null> init () {
+	v1 = call<u32> main()
+	v2 = alloc<u32> v1  !global 	(@init.exit_code)
+
+	%L_0:
+	j %L_0
+}
+
+function<u32> main () {
+	v1 = <u32> $0
+	v2 = alloc<u32> v1 	(@s)
+
+	%L_1:
+	v3 = alloc<u32> v1 	(@i)
+
+	%L_2:
+	v4 = load<u32> v3
+	v5 = <u32> $3
+	jge<u32> v4, v5 %L_4
+	v6 = load<u32> v2
+	v7 = add <u32> v6, v4
+	store<u32> v2, v7
+
+	%L_3:
+	v8 = <u32> $1
+	v9 = add <u32> v4, v8
+	store<u32> v3, v9
+	j %L_2
+
+	%L_4:
+
+	%L_5:
+	v10 = <u32> $10
+	v11 = alloc<u32> v10 	(@i)
+
+	%L_6:
+	v12 = load<u32> v11
+	v13 = <u32> $12
+	jge<u32> v12, v13 %L_8
+	v14 = load<u32> v2
+	v15 = add <u32> v14, v12
+	store<u32> v2, v15
+
+	%L_7:
+	v16 = <u32> $1
+	v17 = add <u32> v12, v16
+	store<u32> v11, v17
+	j %L_6
+
+	%L_8:
+	v18 = load<u32> v2
+	return<u32> v18
+}
+",
+        );
+
+        let ast = get_ast(&input);
+        assert_eq!(expected, Lirgen::new(1).linearize_ast(&ast).to_string());
+    }
+
+    #[test]
+    fn lirgen_test_block_scope() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 main() {
+              u32 s = 1;
+              if (s > 0) { u32 a = s + 2; s = a * 2; }
+              { u32 a = 5; s = s + a; }
+              return s;
+            }
+",
+        );
+
+        // Each block allocates its own `a`, which is forgotten at the end of the block
+        let expected = String::from(
+            "
+function<null> init () {
+	v1 = call<u32> main()
+	v2 = alloc<u32> v1  !global 	(@init.exit_code)
+
+	%L_0:
+	j %L_0
+}
+
+function<u32> main () {
+	v1 = <u32> $1
+	v2 = alloc<u32> v1 	(@s)
+	v3 = <u32> $0
+	jle<u32> v1, v3 %L_1
+	v4 = <u32> $2
+	v5 = add <u32> v1, v4
+	v6 = alloc<u32> v5 	(@a)
+	v7 = sl <u32> v5, v1
+	store<u32> v2, v7
+
+	%L_1:
+	v8 = <u32> $5
+	v9 = alloc<u32> v8 	(@a)
+	v10 = load<u32> v2
+	v11 = add <u32> v10, v8
+	store<u32> v2, v11
+	return<u32> v11
+}
+",
+        );
+
+        let ast = get_ast(&input);
+        assert_eq!(expected, Lirgen::new(1).linearize_ast(&ast).to_string());
+    }
+
+    #[test]
+    fn lirgen_test_forward_call() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 main() { return f(3); }
+            u32 f(u32 a) { return a + 1; }
+            ",
+        );
+
+        // `main` calls `f` before it is linearized
+        let expected = String::from(
+            "
+function<null> init () {
+	v1 = call<u32> main()
+	v2 = alloc<u32> v1  !global 	(@init.exit_code)
+
+	%L_0:
+	j %L_0
+}
+
+function<u32> main () {
+	v1 = <u32> $3
+	v2 = call<u32> f(v1)
+	return<u32> v2
+}
+
+function<u32> f (v1<u32>) {
+	v2 = alloc<u32> v1 	(@a)
+	v3 = <u32> $1
+	v4 = add <u32> v1, v3
+	return<u32> v4
+}
+",
+        );
+
+        let ast = get_ast(&input);
+        assert_eq!(expected, Lirgen::new(1).linearize_ast(&ast).to_string());
+    }
+
+    #[test]
+    fn lirgen_test_implicit_return() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            void f(u32 *p) { *p = 3; }
+            u32 g(u32 a) { if (a > 2) { return 5; } }
+            u32 main() { u32 x = 1; f(&x); return x + g(7); }
+            ",
+        );
+
+        // Both `f` and `g` fall off the end of their bodies, so a return is added, while `main`
+        // already ends with one
+        let expected = String::from(
+            "
+function<null> init () {
+	v1 = call<u32> main()
+	v2 = alloc<u32> v1  !global 	(@init.exit_code)
+
+	%L_0:
+	j %L_0
+}
+
+function<void> f (v1<u32*>) {
+	v2 = alloc<u32*> v1 	(@p)
+	v3 = <u32> $3
+	store<u32> v1, v3
+	return
+}
+
+function<u32> g (v1<u32>) {
+	v2 = alloc<u32> v1 	(@a)
+	v3 = <u32> $2
+	jle<u32> v1, v3 %L_2
+	v4 = <u32> $5
+	return<u32> v4
+
+	%L_2:
+	return
+}
+
+function<u32> main () {
+	v1 = <u32> $1
+	v2 = alloc<u32> v1 	(@x)
+	v0 = call<void> f(v2)
+	v3 = load<u32> v2
+	v4 = <u32> $7
+	v5 = call<u32> g(v4)
+	v6 = add <u32> v3, v5
+	return<u32> v6
+}
+",
+        );
+
+        let ast = get_ast(&input);
+        assert_eq!(expected, Lirgen::new(1).linearize_ast(&ast).to_string());
+    }
+
+    #[test]
+    fn lirgen_test_bounds_check() {
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("u32 main() { u32 a[4]; u32 i = 2; a[i] = 1; return a[i]; }");
+
+        // Each access compares the index with the length of the array, calling `__trap` when it is
+        // out of range
+        let expected = String::from(
+            "
+function<null> init () {
+	v1 = call<u32> main()
+	v2 = alloc<u32> v1  !global 	(@init.exit_code)
+
+	%L_0:
+	j %L_0
+}
+
+function<u32> main () {
+	v1 = <u32> $4
+	v2 = alloc<u32> [v1] 	(@a)
+	v3 = alloc<u32*> v2 	(@a)
+	v4 = <u32> $2
+	v5 = alloc<u32> v4 	(@i)
+	v6 = load<u32*> v3
+	jlt<u32> v4, v1 %L_1
+	v0 = call<void> __trap()
+
+	%L_1:
+	v7 = sl <u32*> v4, v4
+	v8 = add <u32*> v6, v7
+	v9 = <u32> $1
+	store<u32> v8, v9
+	v10 = load<u32*> v3
+	v11 = load<u32> v5
+	jlt<u32> v11, v1 %L_2
+	v0 = call<void> __trap()
+
+	%L_2:
+	v12 = sl <u32*> v11, v4
+	v13 = add <u32*> v10, v12
+	v14 = load<u32> v13
+	return<u32> v14
+}
+",
+        );
+
+        let ast = get_ast(&input);
+        let mut lirgen = Lirgen::new(1);
+        lirgen.set_bounds_check(true);
+        assert_eq!(expected, lirgen.linearize_ast(&ast).to_string());
+
+        // Without the option, no check is added
+        let mut lirgen = Lirgen::new(1);
+        assert!(!lirgen.linearize_ast(&ast).to_string().contains("__trap"));
+
+        // The length of a global array of constant size is known, thus its accesses are checked
+        // from any function
+        let ast = get_ast("u32 g[4]; u32 main() { u32 i = 5; g[i] = 1; return 0; }");
+        let mut lirgen = Lirgen::new(1);
+        lirgen.set_bounds_check(true);
+        let ir = lirgen.linearize_ast(&ast).to_string();
+        let main = &ir[ir.find("function<u32> main").unwrap()..];
+        assert!(
+            main.contains("\tv5 = <u32> $4\n\tjlt<u32> v1, v5 %L_1\n\tv0 = call<void> __trap()\n"),
+            "{}",
+            main
+        );
+    }
+
+    #[test]
+    fn lirgen_test_trap() {
+        use crate::compiler::compile_str;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("u32 f(u32 *a, u32 i) { if (i > 3) { __trap(); } return 0; } u32 main() { u32 a[4]; a[1] = 2; return a[1]; }");
+
+        // `__trap()` is a call to the built-in routine, which is called by the bounds checks as well
+        let ast = get_ast(&input);
+        let mut lirgen = Lirgen::new(1);
+        lirgen.set_bounds_check(true);
+        let ir = lirgen.linearize_ast(&ast).to_string();
+        let f = &ir[ir.find("function<u32> f").unwrap()..ir.find("function<u32> main").unwrap()];
+        let main = &ir[ir.find("function<u32> main").unwrap()..];
+        assert_eq!(f.matches("call<void> __trap()").count(), 1);
+        assert_eq!(main.matches("call<void> __trap()").count(), 2);
+
+        // The backend adds the routine once per program, no matter how many functions call it
+        for opt in 0..=3 {
+            let code = compile_str(&input, opt, "rv32im").unwrap();
+            assert_eq!(code.matches("__trap:").count(), 1);
+            assert!(code.matches("call\t__trap").count() >= 1);
+        }
+    }
+
+    #[test]
+    fn lirgen_test_source_ref() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("u32 main() {\n  u32 a = 1;\n  u32 *p = &a;\n  return *p + 2;\n}");
+
+        let ast = get_ast(&input);
+
+        // The nodes of main keep the line of the source code they come from
+        if let IrNode::Program(functions) = Lirgen::new(1).linearize_ast(&ast) {
+            if let IrNode::FunctionDeclaration(_, _, _, nodes) = &functions[1] {
+                let lines: Vec<(String, u32)> = nodes
+                    .iter()
+                    .filter_map(|n| n.get_source_ref().map(|s| (n.to_string().trim().to_string(), s.init_line)))
+                    .collect();
+                assert!(lines.contains(&("v2 = alloc<u32> v1 \t(@a)".to_string(), 2)));
+                assert!(lines.contains(&("v3 = alloc<u32*> v2 \t(@p)".to_string(), 3)));
+                assert!(lines.contains(&("v4 = load<u32> v2".to_string(), 4)));
+                assert!(lines.contains(&("return<u32> v6".to_string(), 4)));
+                assert_eq!(lines.len(), 5);
+                return;
+            }
+        }
+        panic!("Linearization of the program is not valid");
+    }
+
+    #[test]
+    fn lirgen_test_ir_api() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("u32 f(u32 a) { return a + 1; } u32 main() { return f(2); }");
+
+        let ast = get_ast(&input);
+        let ir = Lirgen::new(1).linearize_ast(&ast);
+
+        // The functions of the program can be walked in order, `init` being the first one
+        let names: Vec<Option<&str>> = ir.functions().iter().map(|f| f.get_name()).collect();
+        assert_eq!(names, vec![Some("init"), Some("f"), Some("main")]);
+
+        // The nodes of a function can be walked in order
+        let f = &ir.functions()[1];
+        assert_eq!(f.instructions().len(), 4);
+        assert!(matches!(f.instructions()[0], IrNode::Alloc(..)));
+        assert!(matches!(f.instructions().last(), Some(IrNode::Return(..))));
+
+        // A program prints its functions one after the other, and a function its nodes
+        assert_eq!(ir.to_string(), ir.functions().iter().map(|f| f.to_string()).collect::<String>());
+        assert_eq!(
+            f.to_string(),
+            format!(
+                "\nfunction<u32> f (v1<u32>) {{\n{}}}\n",
+                f.instructions().iter().map(|n| n.to_string()).collect::<String>()
+            )
+        );
+
+        // The helpers give nothing for the other nodes
+        assert!(f.functions().is_empty());
+        assert!(ir.instructions().is_empty());
+        assert_eq!(f.instructions()[0].get_name(), None);
+    }
+
+    #[test]
+    fn lirgen_test_bool_normalization() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 main() {
+              bool a = (bool) 5;
+              bool b;
+              b = a + a;
+              b = a;
+              return 0;
+            }
+",
+        );
+
+        let ast = get_ast(&input);
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+        let nodes = ir.functions()[1].instructions();
+
+        // Register written by the `!=` comparison preceding a node, if any
+        let normalized = |i: usize| match &nodes[i - 1] {
+            IrNode::Binary(Operator::DiffCompare, _, dest, ..) => Some(*dest),
+            _ => None,
+        };
+
+        // The cast compares with zero, and the initialization of `a` does not compare again
+        let (i, init) = nodes
+            .iter()
+            .enumerate()
+            .find_map(|(i, n)| if let IrNode::Alloc(_, _, init, ..) = n { Some((i, *init)) } else { None })
+            .unwrap();
+        assert_eq!(normalized(i), Some(init));
+        assert_eq!(
+            nodes[..i]
+                .iter()
+                .filter(|n| matches!(n, IrNode::Binary(Operator::DiffCompare, ..)))
+                .count(),
+            1
+        );
+
+        // A value which might not be 0 or 1 is normalized before being stored, while the value of
+        // a `bool` variable is stored as it is
+        let stores: Vec<(usize, u32)> = nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, n)| if let IrNode::Store(_, _, src, ..) = n { Some((i, *src)) } else { None })
+            .collect();
+        assert_eq!(stores.len(), 2);
+        assert_eq!(normalized(stores[0].0), Some(stores[0].1));
+        assert_eq!(normalized(stores[1].0), None);
+    }
+
+    #[test]
+    fn lirgen_test_unary_plus() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from("i32 main() { return +(-3); }");
+
+        let ast = get_ast(&input);
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+        let nodes = ir.functions()[1].instructions();
+
+        // The unary plus gives its operand as it is, so the negation is the value returned
+        assert!(!nodes.iter().any(|n| matches!(n, IrNode::Unary(_, Operator::Plus, ..))));
+        match nodes {
+            [IrNode::MovC(_, c, 3), IrNode::Unary(_, Operator::Minus, neg, src), IrNode::Return(_, ret, ..)] => {
+                assert_eq!(src, c);
+                assert_eq!(ret, neg);
+            }
+            _ => panic!("Unexpected nodes {:?}", nodes),
+        }
+    }
+
+    #[test]
+    fn lirgen_test_operand_promotion() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 f(u8 a, u8 b) { if ((a + b) / (u8) 2 < b) { return 1; } return 0; }
+            u32 g(u8 a) { return (u32) a + (u32) a; }
+            void main() { return; }
+",
+        );
+
+        let ast = get_ast(&input);
+        let ir = Lirgen::new(1).linearize_ast(&ast);
+
+        // Source of the cast of a value to its own type writing a register, if any
+        let promoted = |nodes: &[IrNode], register: u32| {
+            nodes.iter().find_map(|n| match n {
+                IrNode::Cast(ttd, tts, dest, src) if ttd == tts && *dest == register => Some(*src),
+                _ => None,
+            })
+        };
+
+        // The sum might be larger than 8 bits, so it is promoted before the division, while the
+        // casted constant is not. Both the operands of the comparison are promoted as well
+        let nodes = ir.functions()[1].instructions();
+        let sum = nodes.iter().find_map(|n| {
+            if let IrNode::Binary(Operator::Plus, _, dest, ..) = n {
+                Some(*dest)
+            } else {
+                None
+            }
+        });
+        let division = nodes.iter().find(|n| matches!(n, IrNode::Binary(Operator::Slash, ..))).unwrap();
+        if let IrNode::Binary(_, _, dest, src1, src2, _) = division {
+            assert_eq!(promoted(nodes, *src1), sum);
+            assert_eq!(promoted(nodes, *src2), None);
+            let branch = nodes.iter().find(|n| matches!(n, IrNode::Branch(..))).unwrap();
+            if let IrNode::Branch(_, _, src1, src2, _) = branch {
+                assert_eq!(promoted(nodes, *src1), Some(*dest));
+                assert_eq!(promoted(nodes, *src2), Some(2));
+            }
+        }
+
+        // Operations on 32 bits never need it
+        let nodes = ir.functions()[2].instructions();
+        assert_eq!(nodes.iter().filter(|n| matches!(n, IrNode::Cast(ttd, tts, ..) if ttd == tts)).count(), 0);
+    }
+
+    #[test]
+    fn lirgen_test_empty_statements() {
+        use crate::ast::ast_node::AstNode;
+        use crate::lirgen::irnode::{CompareType, IrNode};
+        use crate::lirgen::lirgen::Lirgen;
+
+        // Stray semicolons and empty blocks produce no IR, also after a return
+        let reference = get_ast("u32 main() { u32 x = 1; return x; }");
+        let input = get_ast("void f() {} u32 main() { ;;; u32 x = 1; { ; } {} ; return x; ; }");
+        for opt in 0..=3 {
+            let reference = Lirgen::new(opt).linearize_ast(&reference);
+            let ir = Lirgen::new(opt).linearize_ast(&input);
+            let to_string = |nodes: &[IrNode]| nodes.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+            assert!(matches!(ir.functions()[1].instructions()[..], [IrNode::Return(_, 0, _)]));
+            assert_eq!(
+                to_string(ir.functions()[2].instructions()),
+                to_string(reference.functions()[1].instructions())
+            );
+        }
+
+        // The empty blocks are kept as empty compound nodes
+        let ast = get_ast("u32 main() { u32 x = 0; while (x) {} for(;;){ ; } }");
+        if let AstNode::DeclarationList(functions) = &ast.node {
+            if let AstNode::FuncDeclNode(_, _, _, body) = &functions[0].node {
+                if let AstNode::CompoundNode(statements) = &body.node {
+                    match (&statements[1].node, &statements[2].node) {
+                        (AstNode::WhileNode(_, while_body), AstNode::ForNode(_, _, _, for_body)) => {
+                            assert_eq!(while_body.node, AstNode::CompoundNode(vec![]));
+                            assert_eq!(for_body.node, AstNode::CompoundNode(vec![]));
+                        }
+                        _ => panic!("Unexpected statements {:?}", statements),
+                    }
+                }
+            }
+        }
+
+        // The loop without condition has no exit, while the other one leaves when `x` is zero
+        for opt in 0..=3 {
+            let ir = Lirgen::new(opt).linearize_ast(&ast);
+            let branches: Vec<&CompareType> = ir.functions()[1]
+                .instructions()
+                .iter()
+                .filter_map(|n| if let IrNode::Branch(ct, ..) = n { Some(ct) } else { None })
+                .collect();
+            assert_eq!(branches, vec![&CompareType::NS, &CompareType::Always, &CompareType::Always]);
+        }
+    }
+
+    #[test]
+    fn lirgen_test_fold_not() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 f(u32 a, u32 b) { return !(a == b); }
+            u32 g(u32 a) { return !!a; }
+            u32 h(u32 a, u32 b) { return !!(a < b); }
+            u32 k(u32 a) { return !a; }
+            void main() { return; }
+",
+        );
+
+        let ast = get_ast(&input);
+
+        // Operators of the binary and unary nodes of a function
+        let operators = |ir: &IrNode, index: usize| {
+            ir.functions()[index]
+                .instructions()
+                .iter()
+                .filter_map(|n| match n {
+                    IrNode::Binary(op, ..) | IrNode::Unary(_, op, ..) => Some(op.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<Operator>>()
+        };
+
+        // Without optimizations, each negation is a `Not` node
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+        assert_eq!(operators(&ir, 1), vec![Operator::EqualCompare, Operator::Not]);
+        assert_eq!(operators(&ir, 2), vec![Operator::Not, Operator::Not]);
+        assert_eq!(operators(&ir, 3), vec![Operator::LTCompare, Operator::Not, Operator::Not]);
+
+        // Otherwise, the negation of a comparison is the opposite comparison, while a double
+        // negation is either a normalization or nothing at all for a comparison
+        let ir = Lirgen::new(1).linearize_ast(&ast);
+        assert_eq!(operators(&ir, 1), vec![Operator::DiffCompare]);
+        assert_eq!(operators(&ir, 2), vec![Operator::DiffCompare]);
+        assert_eq!(operators(&ir, 3), vec![Operator::LTCompare]);
+        assert_eq!(operators(&ir, 4), vec![Operator::Not]);
+    }
+
+    #[test]
+    fn lirgen_test_entry() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::{Lirgen, EXIT_CODE};
+
+        let parse = |input: &str, entry: Option<&str>| {
+            let mut p = get_parser(input);
+            if let Some(entry) = entry {
+                p.set_entry(entry.to_string());
+            }
+            p.parse()
+        };
+
+        // The entry function must be defined and it cannot have parameters, while `main` is not
+        // required anymore
+        let input = "u32 start() { return 7; }";
+        assert!(parse(input, None).is_none());
+        assert!(parse("u32 start(u32 a) { return a; }", Some("start")).is_none());
+        let ast = parse(input, Some("start")).unwrap();
+
+        // Init calls the entry function, storing its result as exit code
+        let mut lirgen = Lirgen::new(0);
+        lirgen.set_entry("start".to_string());
+        let ir = lirgen.linearize_ast(&ast);
+        match &ir.functions()[0].instructions()[..2] {
+            [IrNode::Call(name, _, _, ret, _), IrNode::Alloc(_, _, src, true, _, _, label, ..)] => {
+                assert_eq!(name, "start");
+                assert_eq!(ret, src);
+                assert_eq!(label, EXIT_CODE);
+            }
+            nodes => panic!("Unexpected nodes {:?}", nodes),
+        }
+    }
+
+    #[test]
+    fn lirgen_test_pointer_arithmetic() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        // Value of the constant in a register, if any
+        let constant = |nodes: &[IrNode], register: u32| {
+            nodes.iter().find_map(|n| match n {
+                IrNode::MovC(_, dest, value) if *dest == register => Some(*value),
+                _ => None,
+            })
+        };
+
+        // The difference of two pointers is divided by the size of an element, so that
+        // `&a[5] - &a[2]` is 3 whatever the type, while the integer subtracted from a pointer is
+        // multiplied by it
+        for (tt, shift) in [("u8", 0), ("u16", 1), ("u32", 2), ("u8*", 2)] {
+            let ast = get_ast(&format!("i32 main() {{ {} a[8]; {} *p = &a[5] - 3; return &a[5] - &a[2]; }}", tt, tt));
+            for opt in 0..=1 {
+                let ir = Lirgen::new(opt).linearize_ast(&ast);
+                let nodes = ir.functions()[1].instructions();
+                let binary: Vec<(Operator, u32, u32, u32)> = nodes
+                    .iter()
+                    .filter_map(|n| match n {
+                        IrNode::Binary(op, _, dest, src1, src2, _) => Some((op.clone(), *dest, *src1, *src2)),
+                        _ => None,
+                    })
+                    .collect();
+                let subtractions: Vec<&(Operator, u32, u32, u32)> = binary.iter().filter(|b| b.0 == Operator::Minus).collect();
+                assert_eq!(subtractions.len(), 2);
+
+                // The offset of `&a[5] - 3` is shifted first
+                let offset = subtractions[0].3;
+                match binary.iter().find(|b| b.1 == offset) {
+                    Some((Operator::LShift, _, src1, amount)) => {
+                        assert_eq!(constant(nodes, *src1), Some(3));
+                        assert_eq!(constant(nodes, *amount), Some(shift));
+                    }
+                    _ => assert_eq!((shift, constant(nodes, offset)), (0, Some(3))),
+                }
+
+                // The difference of the pointers is shifted afterwards
+                let difference = subtractions[1].1;
+                let ret = match nodes.last() {
+                    Some(IrNode::Return(_, ret, _)) => *ret,
+                    _ => panic!("Unexpected nodes {:?}", nodes),
+                };
+                if shift == 0 {
+                    assert_eq!(ret, difference);
+                } else {
+                    let scaled = binary.iter().find(|b| b.0 == Operator::RShift && b.2 == difference).unwrap();
+                    assert_eq!(ret, scaled.1);
+                    assert_eq!(constant(nodes, scaled.3), Some(shift));
+                }
+            }
+        }
+
+        // Only pointers of the same type can be subtracted, a pointer cannot be subtracted from an
+        // integer and the difference of two pointers is not a pointer
+        assert!(get_parser("i32 main() { u32 a[2]; u16 b[2]; return &a[1] - &b[1]; }").parse().is_none());
+        assert!(get_parser("u32 main() { u32 a[2]; return 3 - &a[1]; }").parse().is_none());
+        assert!(get_parser("u32 main() { u32 a[2]; u32 *p = &a[1] - &a[0]; return 0; }").parse().is_none());
+    }
+
+    #[test]
+    fn lirgen_test_remainder_associativity() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        // `%` is left associative, as `*` and `/` at the same level: `a % b % c` is `(a % b) % c`,
+        // thus the result of the first remainder is the dividend of the second one
+        let input = "u32 f(u32 a, u32 b, u32 c) { return a % b % c; } u32 main() { return f(7, 5, 2); }";
+        let ast = get_ast(input);
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+
+        let remainders: Vec<(u32, u32)> = ir.functions()[1]
+            .instructions()
+            .iter()
+            .filter_map(|node| match node {
+                IrNode::Binary(Operator::Module, _, dest, src1, _, _) => Some((*dest, *src1)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(remainders.len(), 2);
+        assert_eq!(remainders[1].1, remainders[0].0);
+        assert_eq!(ir.to_string().matches(" rem ").count(), 2);
+    }
+
+    #[test]
+    fn lirgen_test_prototypes() {
+        use crate::backend::codegen_riscv::Codegen;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = "
+            u32 f(u32 a);
+            u32 ext(u32 a);
+            u32 main() { return f(4) + ext(1); }
+            u32 f(u32 a) { return a * 3; }
+";
+        let ast = get_ast(input);
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+
+        // The prototypes produce no function, while the calls use their signatures
+        let names: Vec<String> = ir
+            .functions()
+            .iter()
+            .map(|f| match f {
+                IrNode::FunctionDeclaration(name, ..) => name.clone(),
+                _ => panic!("Invalid program"),
+            })
+            .collect();
+        assert_eq!(names, vec![format!("init"), format!("main"), format!("f")]);
+        assert!(ir.to_string().contains("call<u32> ext("));
+
+        // Only the functions with a body have a label
+        let code: String = Codegen::new(0, true).generate_code(&ir).unwrap().iter().map(|i| i.to_string()).collect();
+        assert_eq!(code.matches("\nf:").count(), 1);
+        assert!(!code.contains("\next:"));
+        assert!(code.contains("\tcall\text"));
+    }
+
+    #[test]
+    fn lirgen_test_selection() {
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 g;
+            u32 f(u32 c, u32 a, u32 b) { u32 x; if (c) { x = a + 1; } else { x = b; } return x; }
+            u32 h(u32 c, u32 a) { u32 x; if (c) { x = a; } else { x = (g = a); } return x; }
+            u32 k(u32 c, u32 a, u32 b) { u32 x; u32 y; if (c) { x = a; } else { y = b; } return x; }
+            void main() { return; }
+",
+        );
+
+        let ast = get_ast(&input);
+
+        let branches = |ir: &IrNode, index: usize| {
+            ir.functions()[index]
+                .instructions()
+                .iter()
+                .filter(|n| matches!(n, IrNode::Branch(..)))
+                .count()
+        };
+
+        // Assigning the same variable in both the arms produces a selection
+        let ir = Lirgen::new(2).linearize_ast(&ast);
+        assert_eq!(branches(&ir, 1), 0);
+        let operators: Vec<Operator> = ir.functions()[1]
+            .instructions()
+            .iter()
+            .filter_map(|n| match n {
+                IrNode::Binary(op, ..) | IrNode::Unary(_, op, ..) => Some(op.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            operators,
+            vec![
+                Operator::DiffCompare,
+                Operator::Plus,
+                Operator::Minus,
+                Operator::XorOp,
+                Operator::AndOp,
+                Operator::XorOp
+            ]
+        );
+
+        // Arms with side effects or assigning different variables keep their branches
+        assert_eq!(branches(&ir, 2), 2);
+        assert_eq!(branches(&ir, 3), 2);
+
+        // Without optimizations, branches are always used
+        let ir = Lirgen::new(1).linearize_ast(&ast);
+        assert_eq!(branches(&ir, 1), 2);
+    }
+
+    #[test]
+    fn lirgen_test_chained_assignment() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 f(u32 c) { u32 a; u32 b; a = b = c; return a; }
+            u32 g(u32 c) { u32 a; u32 b; (a = b) = c; return a; }
+            void main() { return; }
+",
+        );
+
+        let ast = get_ast(&input);
+
+        for opt in 0..=1 {
+            let ir = Lirgen::new(opt).linearize_ast(&ast);
+            let stores = |index: usize| {
+                ir.functions()[index]
+                    .instructions()
+                    .iter()
+                    .filter_map(|n| {
+                        if let IrNode::Store(_, address, value, _) = n {
+                            Some((*address, *value))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<(u32, u32)>>()
+            };
+            let allocs = |index: usize| {
+                ir.functions()[index]
+                    .instructions()
+                    .iter()
+                    .filter_map(|n| if let IrNode::Alloc(_, register, ..) = n { Some(*register) } else { None })
+                    .collect::<Vec<u32>>()
+            };
+
+            // `b` is assigned first, then `a` gets the same value
+            let (f_stores, f_allocs) = (stores(1), allocs(1));
+            assert_eq!(f_stores.len(), 2);
+            assert_eq!(f_stores[0].0, f_allocs[2]);
+            assert_eq!(f_stores[1].0, f_allocs[1]);
+            assert_eq!(f_stores[0].1, f_stores[1].1);
+
+            // An assignment used as an lvalue stores the value of its right operand, never its
+            // address
+            let (g_stores, g_allocs) = (stores(2), allocs(2));
+            assert_eq!(g_stores.len(), 2);
+            assert!(g_stores
+                .iter()
+                .all(|(address, value)| *address == g_allocs[1] && !g_allocs.contains(value)));
+        }
+    }
+
+    #[test]
+    fn lirgen_test_empty_parameter_list() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        // A function declared with `()` or `(void)` has no parameters, thus no argument is
+        // allocated on the stack and the two spellings give the same IR
+        let mut results: Vec<Vec<String>> = vec![];
+        for list in ["()", "(void)"] {
+            let input = format!("u32 f{} {{ return 7; }} u32 main{} {{ return f(); }}", list, list);
+            let ast = get_ast(&input);
+
+            let ir = Lirgen::new(0).linearize_ast(&ast);
+            let f = ir.functions().iter().find(|f| f.get_name() == Some("f")).unwrap();
+            assert!(matches!(f, IrNode::FunctionDeclaration(_, _, args, _) if args.is_empty()));
+            assert!(!f.instructions().iter().any(|n| matches!(n, IrNode::Alloc(..))));
+            results.push(ir.functions().iter().map(|n| n.to_string()).collect());
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn lirgen_test_function_pointers() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+
+        let input = String::from(
+            "
+            u32 add(u32 a, u32 b) { return a + b; }
+            u32 apply(u32 (*op)(u32, u32), u32 x) { return (*op)(x, x) + add(x, x); }
+            u32 main() { return apply(&add, 2); }
+",
+        );
+
+        let ast = get_ast(&input);
+
+        for opt in 0..=2 {
+            let ir = Lirgen::new(opt).linearize_ast(&ast);
+            let apply = ir.functions().iter().find(|f| f.get_name() == Some("apply")).unwrap();
+            let main = ir.functions().iter().find(|f| f.get_name() == Some("main")).unwrap();
+
+            // The address of `add` is loaded through its label, and passed as argument
+            let address = main.instructions().iter().find_map(|n| {
+                if let IrNode::LoadA(tt, dest, label) = n {
+                    Some((tt.is_function_pointer(), *dest, label.clone()))
+                } else {
+                    None
+                }
+            });
+            assert_eq!(
+                address.clone().map(|(is_function_pointer, _, label)| (is_function_pointer, label)),
+                Some((true, "add".to_string()))
+            );
+            assert!(main
+                .instructions()
+                .iter()
+                .any(|n| matches!(n, IrNode::Call(name, _, arguments, ..) if name == "apply" && arguments[0] == address.clone().unwrap().1)));
+
+            // The pointer is called indirectly through a register, while `add` is still called by
+            // name
+            let target = apply.instructions().iter().find_map(|n| {
+                if let IrNode::CallIndirect(_, target, arguments, ..) = n {
+                    Some((*target, arguments.len()))
+                } else {
+                    None
+                }
+            });
+            assert!(matches!(target, Some((target, 2)) if target != 0));
+            assert!(apply.instructions().iter().any(|n| matches!(n, IrNode::Call(name, ..) if name == "add")));
+            assert!(apply.to_string().contains(&format!(" = call<u32> *v{}(", target.unwrap().0)));
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod irnode;
 pub mod lirgen;
+pub mod lirgen_test;
+pub mod verifier;
+pub mod verifier_test;
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use crate::lirgen::irnode::IrNode;
+use crate::optimizer::cfg::Cfg;
+
+use IrNode::*;
+
+/// verify
+///
+/// Check the invariants the backend relies on for each function of a program: every branch jumps
+/// to a label of the same function, and no register is read before being written along any path
+/// from the beginning of the function. The arguments of a function are in the registers from 1
+/// to the number of arguments, thus they are written on entry
+///
+/// @in ir [&IrNode]: program to verify
+/// @return [Result<(), String>]: description of the first violation found, if any
+pub fn verify(ir: &IrNode) -> Result<(), String> {
+    let functions_list = if let Program(functions_list) = ir {
+        functions_list
+    } else {
+        return Err("Provided node to `verify` not of type Program".to_string());
+    };
+
+    for function in functions_list {
+        if let FunctionDeclaration(name, _, arguments, nodes) = function {
+            verify_function(name, arguments.len() as u32, nodes)?;
+        } else {
+            return Err("Provided node to `verify` not of type FunctionDeclaration".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// get_read_registers
+///
+/// Get the registers read by a node. Differently from IrNode::get_src, the address register of a
+/// store is read as well, and register 0 (no register) is skipped
+///
+/// @in node [&IrNode]: node to consider
+/// @return [Vec<u32>]: registers read by the node
+fn get_read_registers(node: &IrNode) -> Vec<u32> {
+    let mut result = node.get_src();
+    if let Store(_, address, ..) = node {
+        result.push(*address);
+    }
+    result.retain(|r| *r != 0);
+    result
+}
+
+/// get_written_register
+///
+/// Get the register written by a node, if any. Differently from IrNode::get_dest, a store does not
+/// write any register
+///
+/// @in node [&IrNode]: node to consider
+/// @return [Option<u32>]: register written by the node
+fn get_written_register(node: &IrNode) -> Option<u32> {
+    match node {
+        Store(..) => None,
+        _ if node.get_dest() != 0 => Some(node.get_dest()),
+        _ => None,
+    }
+}
+
+/// verify_function
+///
+/// Verify a single function. The registers which are surely written at the beginning of each block
+/// are computed by iterating until a fixed point is reached: for each block, they are the
+/// intersection of the registers written at the end of its predecessors (the arguments for the
+/// first block). Blocks which cannot be reached do not constrain their successors
+///
+/// @in name [&str]: name of the function
+/// @in n_arguments [u32]: number of arguments of the function
+/// @in nodes [&[IrNode]]: nodes of the function
+/// @return [Result<(), String>]: description of the first violation found, if any
+fn verify_function(name: &str, n_arguments: u32, nodes: &[IrNode]) -> Result<(), String> {
+    let cfg = Cfg::new(name, nodes);
+
+    // Each branch must have a target in the function
+    for block in &cfg.blocks {
+        if let Some(Branch(_, _, _, _, label)) = block.nodes.last() {
+            if block.branch_target.is_none() {
+                return Err(format!("in function `{}`, branch to label L{} which does not exist", name, label));
+            }
+        }
+    }
+
+    // Predecessors of each block
+    let mut predecessors: Vec<Vec<usize>> = vec![vec![]; cfg.blocks.len()];
+    for (i, block) in cfg.blocks.iter().enumerate() {
+        for successor in [block.fallthrough, block.branch_target].into_iter().flatten() {
+            predecessors[successor].push(i);
+        }
+    }
+
+    // Registers written at the end of each block, None if the block was not reached yet
+    let mut written_out: Vec<Option<HashSet<u32>>> = vec![None; cfg.blocks.len()];
+    let entry: HashSet<u32> = (1..=n_arguments).collect();
+
+    let get_written_in = |i: usize, written_out: &Vec<Option<HashSet<u32>>>| -> Option<HashSet<u32>> {
+        if i == 0 {
+            return Some(entry.clone());
+        }
+        let mut result: Option<HashSet<u32>> = None;
+        for p in &predecessors[i] {
+            if let Some(written) = &written_out[*p] {
+                result = match result {
+                    None => Some(written.clone()),
+                    Some(r) => Some(r.intersection(written).cloned().collect()),
+                };
+            }
+        }
+        result
+    };
+
+    let mut is_changed = true;
+    while is_changed {
+        is_changed = false;
+        for i in 0..cfg.blocks.len() {
+            let mut written = match get_written_in(i, &written_out) {
+                Some(written) => written,
+                None => continue,
+            };
+            for node in &cfg.blocks[i].nodes {
+                if let Some(dest) = get_written_register(node) {
+                    written.insert(dest);
+                }
+            }
+            if written_out[i].as_ref() != Some(&written) {
+                written_out[i] = Some(written);
+                is_changed = true;
+            }
+        }
+    }
+
+    // Check the reads of each reachable block
+    for i in 0..cfg.blocks.len() {
+        let mut written = match get_written_in(i, &written_out) {
+            Some(written) => written,
+            None => continue,
+        };
+        for node in &cfg.blocks[i].nodes {
+            for src in get_read_registers(node) {
+                if !written.contains(&src) {
+                    return Err(format!(
+                        "in function `{}`, register v{} is read before being written in `{}`",
+                        name,
+                        src,
+                        node.to_string().trim()
+                    ));
+                }
+            }
+            if let Some(dest) = get_written_register(node) {
+                written.insert(dest);
+            }
+        }
+    }
+
+    Ok(())
+}
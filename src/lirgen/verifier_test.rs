@@ -0,0 +1,109 @@
+mod test {
+
+    #[test]
+    fn verifier_test_valid() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lirgen::lirgen::Lirgen;
+        use crate::lirgen::verifier::verify;
+        use crate::optimizer::optimizer::Optimizer;
+        use crate::parser::parser::Parser;
+
+        // The IR produced by lirgen and by the optimizer satisfies the invariants, whatever the
+        // control flow is
+        let input = String::from(
+            "
+            u32 g = 4;
+            u32 f(u32 a, u32 b) {
+              u32 r = 0;
+              for (u32 i = 0; i < a; i = i + 1) {
+                if (i > b) { r = r + i; } else { continue; }
+                while (r > 100) { r = r / 2; }
+              }
+              switch (r) {
+                case 1: return g;
+                case 2: r = 3;
+                default: break;
+              }
+              return r;
+            }
+            u32 main() {
+              u32 arr[3];
+              arr[1] = f(3, 1);
+              u32 x = arr[1];
+              if (x > 2) { return x + g; }
+              return f(1, 2);
+            }
+",
+        );
+
+        let mut l = Lexer::new(input.clone(), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast = p.parse().unwrap();
+
+        for opt in 0..=3 {
+            for bounds_check in [false, true] {
+                let mut lirgen = Lirgen::new(opt);
+                lirgen.set_bounds_check(bounds_check);
+                let mut ir = lirgen.linearize_ast(&ast);
+                assert_eq!(verify(&ir), Ok(()));
+                if opt > 1 {
+                    ir = Optimizer::new(opt).optimize(ir);
+                    assert_eq!(verify(&ir), Ok(()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn verifier_test_invalid() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::lirgen::irnode::{CompareType, IrNode::*};
+        use crate::lirgen::verifier::verify;
+
+        let tt = TypeWrapper {
+            type_native: TypeNative::U32,
+            pointer: 0,
+            constant: false,
+        };
+        let function = |nodes| Program(vec![FunctionDeclaration("f".to_string(), tt.clone(), vec![tt.clone()], nodes)]);
+
+        // The argument is written on entry, and a register written on both paths can be read
+        let ir = function(vec![
+            Branch(CompareType::S, tt.clone(), 1, 0, 1),
+            MovC(tt.clone(), 2, 5),
+            Branch(CompareType::Always, tt.clone(), 0, 0, 2),
+            Label(1),
+            MovC(tt.clone(), 2, 6),
+            Label(2),
+            Return(tt.clone(), 2, None),
+        ]);
+        assert_eq!(verify(&ir), Ok(()));
+
+        // A register written on a single path cannot be read once the paths join
+        let ir = function(vec![
+            Branch(CompareType::S, tt.clone(), 1, 0, 1),
+            MovC(tt.clone(), 2, 5),
+            Label(1),
+            Return(tt.clone(), 2, None),
+        ]);
+        assert!(verify(&ir).unwrap_err().contains("register v2 is read before being written"));
+
+        // A register cannot be read before the node which writes it
+        let ir = function(vec![
+            Binary(crate::lexer::token::Operator::Plus, tt.clone(), 3, 1, 2, None),
+            MovC(tt.clone(), 2, 5),
+            Return(tt.clone(), 3, None),
+        ]);
+        assert!(verify(&ir).unwrap_err().contains("register v2 is read before being written"));
+
+        // The address of a store is read as well
+        let ir = function(vec![Store(tt.clone(), 4, 1, None), Return(tt.clone(), 1, None)]);
+        assert!(verify(&ir).unwrap_err().contains("register v4 is read before being written"));
+
+        // Each branch must jump to a label of the function
+        let ir = function(vec![Branch(CompareType::Always, tt.clone(), 0, 0, 7), Return(tt.clone(), 1, None)]);
+        assert!(verify(&ir).unwrap_err().contains("branch to label L7 which does not exist"));
+    }
+}
@@ -1,32 +1,33 @@
-mod ast;
-mod backend;
-mod lexer;
-mod lirgen;
-mod optimizer;
-mod parser;
 use std::fs;
 use std::io::prelude::*;
+use std::io::{BufWriter, ErrorKind};
+use std::time::{Duration, Instant};
 
-use backend::codegen_riscv::Codegen as cg_riscv;
 use clap::Parser as ClapParser;
-use lexer::lexer::Lexer;
-use lirgen::lirgen::Lirgen;
-use optimizer::optimizer::Optimizer;
-use parser::parser::Parser;
+use dummy_cc::backend::codegen_riscv::Codegen as cg_riscv;
+use dummy_cc::backend::riscv_isa::Syntax;
+use dummy_cc::compiler::optimize;
+use dummy_cc::diagnostic::{Diagnostic, Reporter};
+use dummy_cc::lexer::lexer::Lexer;
+use dummy_cc::lexer::token::Token;
+use dummy_cc::lirgen::lirgen::Lirgen;
+use dummy_cc::lirgen::verifier::verify;
+use dummy_cc::optimizer::cfg::Cfg;
+use dummy_cc::parser::parser::Parser;
 
 #[derive(ClapParser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    /// Path of the file to compile
+    /// Path of the file to compile (`-` for the standard input)
     #[arg(short, long)]
     file_name: String,
 
-    /// Path of the result file
+    /// Path of the result file (`-` for the standard output)
     #[arg(short, long, default_value_t = format!("out.asm"))]
     o: String,
 
     /// Required level of optimization
-    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=2))]
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=3))]
     opt: u32,
 
     /// Show result of parsing
@@ -37,55 +38,238 @@ struct Cli {
     #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
     print_lir: bool,
 
+    /// Stop the compilation after a phase, without producing the assembly (`parse`: after the
+    /// parser, `lir`: after lirgen and the optimizer), e.g. to inspect the output of `--print-ast`
+    /// or `--print-lir`
+    #[arg(long, value_parser = ["parse", "lir"])]
+    stop_after: Option<String>,
+
     /// Target architecture
-    #[arg(short, long, default_value_t = format!("rv32im"), value_parser = ["rv32im"])]
+    #[arg(short, long, default_value_t = format!("rv32im"), value_parser = ["rv32im", "rv32i"])]
     arch: String,
+
+    /// Print an intermediate result on stdout and stop the compilation (`tokens`: result of the
+    /// lexer as JSON, `cfg`: control flow graph of the IR in the DOT language)
+    #[arg(long, value_parser = ["tokens", "cfg"])]
+    emit: Option<String>,
+
+    /// Check at runtime that each access to a local array or to a global array of constant size is
+    /// in range, stopping the execution otherwise (accesses through pointers are not checked)
+    #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
+    bounds_check: bool,
+
+    /// Print on stderr the time spent in each phase of the compilation
+    #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
+    print_timings: bool,
+
+    /// Keep the comments describing the generated instructions (`false` for a terse assembly)
+    #[arg(long, action = clap::ArgAction::Set, default_value_t = true)]
+    keep_comments: bool,
+
+    /// Syntax of the assembly (`gas`: directives and ABI register names for the GNU assembler,
+    /// `raw`: bare instructions with numeric register names)
+    #[arg(long, default_value_t = format!("gas"), value_parser = ["gas", "raw"])]
+    syntax: String,
+
+    /// Name of the function called at the beginning of the execution, after the initialization of
+    /// the global variables
+    #[arg(long, default_value_t = format!("main"))]
+    entry: String,
+
+    /// Emit the `.file` and `.loc` directives mapping the instructions back to the lines of the
+    /// source, for debugging
+    #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
+    debug_lines: bool,
+
+    /// Stop parsing after this number of errors, instead of reporting all of them
+    #[arg(long)]
+    max_errors: Option<u32>,
+
+    /// Treat the warnings as errors, so that the compilation fails if any of them is found
+    #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
+    werror: bool,
+}
+
+/// print_timings
+///
+/// Print on stderr the duration of each phase of the compilation, together with their sum
+///
+/// @in timings [&[(&str, Duration)]]: name and duration of each phase
+fn print_timings(timings: &[(&str, Duration)]) {
+    let total: Duration = timings.iter().map(|(_, duration)| *duration).sum();
+    eprintln!("\x1b[34mtimings:\x1b[0m");
+    for (phase, duration) in timings.iter().chain([("total", total)].iter()) {
+        eprintln!("  {:<14}{:>10.3} ms", phase, duration.as_secs_f64() * 1000.0);
+    }
+}
+
+/// exit_on_broken_pipe
+///
+/// Stop the compiler with success if the output was closed by its reader, as with
+/// `--emit cfg | head`: the rest of the output is not required by anybody
+///
+/// @in result [std::io::Result<()>]: result of a write on the output
+/// @return [std::io::Result<()>]: the same result, if it is not a closed pipe
+fn exit_on_broken_pipe(result: std::io::Result<()>) -> std::io::Result<()> {
+    match result {
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        result => result,
+    }
 }
 
 fn main() -> std::io::Result<()> {
     let args = Cli::parse();
 
-    let mut l = Lexer::new(args.file_name.clone(), true).unwrap();
+    // Read the program from the standard input if required
+    let mut source: Option<String> = None;
+    if args.file_name == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        source = Some(input);
+    }
+
+    // The intermediate results are printed on the standard output, buffered as the assembly is.
+    // Each of them is flushed at once, so that it comes before the assembly on `-o -`
+    let mut stdout: BufWriter<Box<dyn Write>> = BufWriter::new(Box::new(std::io::stdout()));
+
+    // Duration of each phase of the compilation
+    let mut timings: Vec<(&str, Duration)> = vec![];
+
+    let start = Instant::now();
+    let mut l = match &source {
+        Some(input) => Lexer::new_from_source("<stdin>".to_string(), input.clone()).unwrap(),
+        None => Lexer::new(args.file_name.clone(), true).unwrap(),
+    };
     let tokens = l.tokenize();
+    timings.push(("lexing", start.elapsed()));
     if tokens.is_none() {
+        std::process::exit(1);
+    }
+    let tokens = tokens.unwrap();
+
+    if args.emit.as_deref() == Some("tokens") {
+        exit_on_broken_pipe(writeln!(stdout, "{}", Token::list_to_json(&tokens)).and_then(|_| stdout.flush()))?;
         return Ok(());
     }
 
-    let mut p = Parser::new(tokens.unwrap(), args.file_name.clone());
+    // The diagnostics found after the lexer are printed by the reporter, which shows the lines of
+    // the source they refer to
+    let reporter = match &source {
+        Some(input) => Reporter::new("<stdin>".to_string(), input, args.werror),
+        None => Reporter::new(args.file_name.clone(), &fs::read_to_string(&args.file_name)?, args.werror),
+    };
+
+    let start = Instant::now();
+    let mut p = match source {
+        Some(input) => {
+            let mut p = Parser::new(tokens, "<stdin>".to_string());
+            p.set_source(input);
+            p
+        }
+        None => Parser::new(tokens, args.file_name.clone()),
+    };
+    p.set_entry(args.entry.clone());
+    if let Some(max_errors) = args.max_errors {
+        p.set_max_errors(max_errors);
+    }
     let ast_wrapped = p.parse();
+    timings.push(("parsing", start.elapsed()));
 
-    if ast_wrapped.is_none() {
-        return Ok(());
+    // The later phases are never run on a program with errors, including the warnings treated
+    // as errors
+    let warning_errors = reporter.report(p.get_diagnostics());
+    if ast_wrapped.is_none() || p.get_errors_counter() > 0 {
+        std::process::exit(1);
+    }
+    if warning_errors > 0 {
+        eprintln!("\x1b[91mFailed parsing with {} warnings treated as errors\x1b[0m", warning_errors);
+        std::process::exit(1);
     }
     let ast = ast_wrapped.unwrap();
 
     if args.print_ast {
-        println!("{}", ast.to_string(0));
+        exit_on_broken_pipe(writeln!(stdout, "{}", ast.to_string_typed(0)).and_then(|_| stdout.flush()))?;
     }
 
+    if args.stop_after.as_deref() == Some("parse") {
+        if args.print_timings {
+            print_timings(&timings);
+        }
+        return Ok(());
+    }
+
+    let start = Instant::now();
     let mut i = Lirgen::new(args.opt);
+    i.set_bounds_check(args.bounds_check);
+    i.set_entry(args.entry.clone());
     let mut ir = i.linearize_ast(&ast);
+    timings.push(("lirgen", start.elapsed()));
 
-    if args.opt > 1 {
-        let mut opt = Optimizer::new(args.opt);
-        ir = opt.optimize(ir);
-    }
+    let start = Instant::now();
+    ir = optimize(ir, args.opt);
+    timings.push(("optimization", start.elapsed()));
 
     if args.print_lir {
-        println!("{}", ir.to_string());
+        exit_on_broken_pipe(writeln!(stdout, "{}", ir.to_string()).and_then(|_| stdout.flush()))?;
     }
 
-    let code = match args.arch.as_str() {
-        "rv32im" => {
-            let mut codegen = cg_riscv::new();
-            codegen.generate_code(&ir)
+    if args.stop_after.as_deref() == Some("lir") {
+        if args.print_timings {
+            print_timings(&timings);
         }
+        return Ok(());
+    }
+
+    if args.emit.as_deref() == Some("cfg") {
+        exit_on_broken_pipe(writeln!(stdout, "{}", Cfg::program_to_dot(&ir)).and_then(|_| stdout.flush()))?;
+        return Ok(());
+    }
+
+    // An invalid IR is a bug of the compiler: report it before the backend fails on it
+    if let Err(error) = verify(&ir) {
+        reporter.report(&[Diagnostic::error("verifier", error, None)]);
+        std::process::exit(1);
+    }
+
+    let start = Instant::now();
+    let mut codegen = match args.arch.as_str() {
+        "rv32im" => cg_riscv::new(args.opt, true),
+        "rv32i" => cg_riscv::new(args.opt, false),
         _ => panic!("Unsupported architecture: {}", args.arch),
     };
+    if args.debug_lines {
+        codegen.set_debug_lines(if args.file_name == "-" {
+            "<stdin>".to_string()
+        } else {
+            args.file_name.clone()
+        });
+    }
+    let code_result = codegen.generate_code(&ir);
+    timings.push(("codegen", start.elapsed()));
 
-    let mut outpfile = fs::File::create(args.o)?;
+    if args.print_timings {
+        print_timings(&timings);
+    }
+
+    // A failure in codegen is due to an invalid IR: report it and stop
+    let code = match code_result {
+        Ok(code) => code,
+        Err(error) => {
+            reporter.report(&[Diagnostic::error("codegen", error.to_string(), None)]);
+            std::process::exit(1);
+        }
+    };
+
+    // The instructions are buffered, so that they are not written one at a time
+    let mut outpfile: BufWriter<Box<dyn Write>> = if args.o == "-" {
+        BufWriter::new(Box::new(std::io::stdout()))
+    } else {
+        BufWriter::new(Box::new(fs::File::create(args.o)?))
+    };
+    let syntax = if args.syntax == "raw" { Syntax::Raw } else { Syntax::Gas };
     for e in code {
-        outpfile.write_all(e.to_string().as_bytes())?;
+        exit_on_broken_pipe(write!(outpfile, "{}", e.format(&syntax, args.keep_comments)))?;
     }
+    exit_on_broken_pipe(outpfile.flush())?;
     return Ok(());
 }
@@ -0,0 +1,135 @@
+use crate::lirgen::irnode::{CompareType, IrNode};
+
+use IrNode::*;
+
+/// struct BasicBlock
+///
+/// Sequence of IR nodes which are always executed one after the other. A block might start with
+/// a label and might end with a branch or a return. Its successors are the block which is executed
+/// when its last node does not jump (fallthrough) and the destination of its branch, if any
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct BasicBlock {
+    pub nodes: Vec<IrNode>,
+    pub fallthrough: Option<usize>,
+    pub branch_target: Option<usize>,
+}
+
+/// struct Cfg
+///
+/// Control flow graph of a function, made of its basic blocks. The first block is the entry point
+/// of the function
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Cfg {
+    pub name: String,
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl Cfg {
+    /// Cfg::new
+    ///
+    /// Build the control flow graph of a function. A new block starts at each label and after each
+    /// branch or return. Then, the edges are added: a conditional branch has both a fallthrough
+    /// and a target, an unconditional one only the target, a return has no successors
+    ///
+    /// @in name [&str]: name of the function
+    /// @in nodes [&[IrNode]]: nodes of the function
+    /// @return [Cfg]: control flow graph of the function
+    pub fn new(name: &str, nodes: &[IrNode]) -> Cfg {
+        let mut blocks: Vec<BasicBlock> = vec![BasicBlock::default()];
+
+        // Split the nodes in blocks
+        for node in nodes {
+            if let Label(_) = node {
+                if !blocks.last().unwrap().nodes.is_empty() {
+                    blocks.push(BasicBlock::default());
+                }
+            }
+            blocks.last_mut().unwrap().nodes.push(node.clone());
+            if let Branch(..) | Return(..) = node {
+                blocks.push(BasicBlock::default());
+            }
+        }
+
+        // The last block might be empty if the function ends with a branch or a return
+        if blocks.len() > 1 && blocks.last().unwrap().nodes.is_empty() {
+            blocks.pop();
+        }
+
+        // Add the edges
+        for i in 0..blocks.len() {
+            let next_block = if i + 1 < blocks.len() { Some(i + 1) } else { None };
+            match blocks[i].nodes.last().cloned() {
+                Some(Branch(ct, _, _, _, label)) => {
+                    blocks[i].branch_target = blocks.iter().position(|b| b.nodes.first() == Some(&Label(label)));
+                    if ct != CompareType::Always {
+                        blocks[i].fallthrough = next_block;
+                    }
+                }
+                Some(Return(..)) => {}
+                _ => blocks[i].fallthrough = next_block,
+            }
+        }
+
+        Cfg {
+            name: name.to_string(),
+            blocks,
+        }
+    }
+
+    /// Cfg::to_dot
+    ///
+    /// Represent the graph as a cluster of a Graphviz graph. Each block is labeled with its nodes,
+    /// while the edges are labeled as `branch` or `fallthrough`
+    ///
+    /// @return [String]: graph in the DOT language
+    pub fn to_dot(&self) -> String {
+        let mut result = format!("  subgraph \"cluster_{}\" {{\n    label=\"{}\";\n", self.name, self.name);
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for node in &block.nodes {
+                label += &format!(
+                    "{}\\l",
+                    node.to_string().trim().replace('\t', " ").replace('\\', "\\\\").replace('"', "\\\"")
+                );
+            }
+            result += &format!("    \"{}_{}\" [label=\"{}\"];\n", self.name, i, label);
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            if let Some(next) = block.fallthrough {
+                result += &format!("    \"{}_{}\" -> \"{}_{}\" [label=\"fallthrough\"];\n", self.name, i, self.name, next);
+            }
+            if let Some(target) = block.branch_target {
+                result += &format!("    \"{}_{}\" -> \"{}_{}\" [label=\"branch\"];\n", self.name, i, self.name, target);
+            }
+        }
+
+        result + "  }\n"
+    }
+
+    /// Cfg::program_to_dot
+    ///
+    /// Represent the control flow graphs of all the functions of a program as a Graphviz graph,
+    /// with one cluster for each function
+    ///
+    /// @in ir [&IrNode]: program to represent
+    /// @return [String]: graph in the DOT language
+    pub fn program_to_dot(ir: &IrNode) -> String {
+        let functions_list = if let Program(functions_list) = ir {
+            functions_list
+        } else {
+            panic!("Provided node to `program_to_dot` not of type Program")
+        };
+
+        let mut result = "digraph cfg {\n  node [shape=box, fontname=\"monospace\"];\n".to_string();
+        for function in functions_list {
+            if let FunctionDeclaration(name, _, _, nodes) = function {
+                result += &Cfg::new(name, nodes).to_dot();
+            } else {
+                panic!("Provided node to `program_to_dot` not of type FunctionDeclaration")
+            }
+        }
+        result + "}\n"
+    }
+}
@@ -0,0 +1,71 @@
+mod test {
+
+    #[test]
+    fn cfg_test_blocks() {
+        use crate::lexer::lexer::Lexer;
+        use crate::lirgen::irnode::IrNode;
+        use crate::lirgen::lirgen::Lirgen;
+        use crate::optimizer::cfg::Cfg;
+        use crate::parser::parser::Parser;
+
+        let input = String::from(
+            "
+            u32 main() {
+              u32 s = 0;
+              u32 i;
+              for (i = 0; i < 10; i = i + 1) {
+                if (i > 5) { s = s + i; }
+              }
+              return s;
+            }
+",
+        );
+
+        let mut l = Lexer::new(input.clone(), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast = p.parse().unwrap();
+        let ir = Lirgen::new(0).linearize_ast(&ast);
+
+        let (name, nodes) = if let IrNode::Program(functions_list) = &ir {
+            if let IrNode::FunctionDeclaration(name, _, _, nodes) = &functions_list[1] {
+                (name, nodes)
+            } else {
+                panic!("Expected a function declaration")
+            }
+        } else {
+            panic!("Expected a program")
+        };
+        let cfg = Cfg::new(name, nodes);
+
+        // Blocks: entry, loop initialization, loop condition, if condition, if body, end of the if,
+        // loop increment, exit
+        assert_eq!(cfg.blocks.len(), 8);
+        let edges: Vec<(Option<usize>, Option<usize>)> = cfg.blocks.iter().map(|b| (b.fallthrough, b.branch_target)).collect();
+        assert_eq!(
+            edges,
+            vec![
+                (Some(1), None),
+                (Some(2), None),
+                (Some(3), Some(7)),
+                (Some(4), Some(5)),
+                (Some(5), None),
+                (Some(6), None),
+                (None, Some(2)),
+                (None, None),
+            ]
+        );
+        // All the nodes are in a block
+        assert_eq!(cfg.blocks.iter().map(|b| b.nodes.len()).sum::<usize>(), nodes.len());
+
+        let dot = Cfg::program_to_dot(&ir);
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(dot.contains("  subgraph \"cluster_init\" {\n"));
+        assert!(dot.contains("  subgraph \"cluster_main\" {\n"));
+        assert!(dot.contains("    \"main_6\" -> \"main_2\" [label=\"branch\"];\n"));
+        assert!(dot.contains("    \"main_2\" -> \"main_3\" [label=\"fallthrough\"];\n"));
+        assert!(dot.contains("    \"main_7\" [label=\"%L_4:\\lv17 = load<u32> v2\\lreturn<u32> v17\\l\"];\n"));
+        assert!(!dot.contains("\t"));
+    }
+}
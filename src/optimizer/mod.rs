@@ -1 +1,4 @@
+pub mod cfg;
+pub mod cfg_test;
 pub mod optimizer;
+pub mod optimizer_test;
@@ -1,7 +1,19 @@
-use crate::lirgen::irnode::IrNode;
+use crate::ast::type_wrapper::TypeWrapper;
+use crate::lexer::token::Operator;
+use crate::lirgen::irnode::{CompareType, IrNode};
+use crate::optimizer::cfg::Cfg;
+use std::collections::{BTreeMap, HashMap};
 
 use IrNode::*;
 
+/// Maximum number of passes of the optimizer when it runs to a fixed point, so that the
+/// compilation terminates even if the algorithms do not converge
+pub const MAX_OPTIMIZER_PASSES: u32 = 100;
+
+/// Binary operations (operator, type and operands) whose result is available, with the register
+/// holding it
+type AvailableExpressions = BTreeMap<(Operator, TypeWrapper, u32, u32), u32>;
+
 /// struct Optimizer
 ///
 /// Object which allows the optimization of the linear IR
@@ -33,6 +45,11 @@ impl Optimizer {
         // Apply the algorithms until nothing changes anymore. At that point, the final result is
         // provided back.
         loop {
+            (ir, _) = self.constant_propagation(ir.clone());
+            (ir, _) = self.algebraic_simplification(ir.clone());
+            (ir, _) = self.common_subexpression_elimination(ir.clone());
+            (ir, _) = self.copy_propagation(ir.clone());
+            (ir, _) = self.unreachable_code_removal(ir.clone());
             (ir, _) = self.dead_code_removal(ir.clone());
             (ir, is_changed) = self.control_flow_removal(ir.clone());
             if !is_changed {
@@ -42,6 +59,732 @@ impl Optimizer {
         return ir;
     }
 
+    /// Optimizer::optimize_pass
+    ///
+    /// Run each of the optimizations algorithms once
+    ///
+    /// @in ir [IrNode]: program to be optimized
+    /// @result [(IrNode, bool)]: optimized program, whether some changes have been done or not
+    pub fn optimize_pass(&mut self, mut ir: IrNode) -> (IrNode, bool) {
+        let is_changed_constants: bool;
+        let is_changed_algebraic: bool;
+        let is_changed_subexpressions: bool;
+        let is_changed_copies: bool;
+        let is_changed_unreachable: bool;
+        let is_changed_dead_code: bool;
+        let is_changed_control_flow: bool;
+
+        (ir, is_changed_constants) = self.constant_propagation(ir);
+        (ir, is_changed_algebraic) = self.algebraic_simplification(ir);
+        (ir, is_changed_subexpressions) = self.common_subexpression_elimination(ir);
+        (ir, is_changed_copies) = self.copy_propagation(ir);
+        (ir, is_changed_unreachable) = self.unreachable_code_removal(ir);
+        (ir, is_changed_dead_code) = self.dead_code_removal(ir);
+        (ir, is_changed_control_flow) = self.control_flow_removal(ir);
+        (
+            ir,
+            is_changed_constants
+                || is_changed_algebraic
+                || is_changed_subexpressions
+                || is_changed_copies
+                || is_changed_unreachable
+                || is_changed_dead_code
+                || is_changed_control_flow,
+        )
+    }
+
+    /// Optimizer::copy_propagation
+    ///
+    /// Iterate over all the nodes of a function, keeping track, for each address register, of the
+    /// register containing the value in memory at that address. The value is known after a store
+    /// (or an allocation with an initial value) and after a load. When a load from an address
+    /// whose value is known is found, the load is removed and its destination is replaced with
+    /// the known register in all the following nodes, as in the following case
+    ///
+    ///     store v1, v2
+    ///     v3 = load v1        <- removed
+    ///     v4 = add v3, v3     <- becomes `v4 = add v2, v2`
+    ///
+    /// The analysis is limited to a basic block, thus everything is forgotten at labels and
+    /// branches. Different local allocations cannot overlap, and a local allocation whose address
+    /// is only used to load and store its value (private) cannot be modified through a pointer.
+    /// Thus:
+    ///     - a store to a private allocation only modifies the value at its address;
+    ///     - a store to another local allocation might also modify the addresses which are not
+    ///       local allocations;
+    ///     - any other store, as well as a call, might modify anything but private allocations.
+    /// Values stored with a type smaller than 32 bits are not considered, since the load would
+    /// not provide the same value of the stored register.
+    ///
+    /// @in ir [IrNode] -> Program to optimize
+    /// @return [(IrNode, bool)] -> Program optimized, whether something has changed or not
+    fn copy_propagation(&mut self, ir: IrNode) -> (IrNode, bool) {
+        let mut is_changed = false;
+
+        // The initial node is always a program made of function declarations. The algorithm runs
+        // on each function declaration individually
+        if let Program(functions_list) = ir {
+            let mut new_functions_list: Vec<IrNode> = vec![];
+            for function in functions_list {
+                if let FunctionDeclaration(n, t, args, nodes) = function {
+                    // Do not optimize `init` function
+                    if n == "init" {
+                        new_functions_list.push(FunctionDeclaration(n, t, args, nodes.clone()));
+                        continue;
+                    }
+
+                    // Registers which are the destination of a local allocation
+                    let local_alloc_references: Vec<u32> = nodes
+                        .iter()
+                        .filter(|node| matches!(node, Alloc(_, _, _, false, ..)))
+                        .map(|node| node.get_dest())
+                        .collect();
+
+                    // Registers which are the destination of a private local allocation: they are
+                    // only used as the address of loads and stores
+                    let private_references: Vec<u32> = local_alloc_references
+                        .iter()
+                        .filter(|reference| {
+                            !nodes.iter().any(|node| match node {
+                                Store(_, _, src, _) => src == *reference,
+                                LoadR(..) => false,
+                                _ => node.get_src().contains(reference),
+                            })
+                        })
+                        .cloned()
+                        .collect();
+
+                    // For each address, type of the value in memory and register containing it
+                    let mut known_values: HashMap<u32, (TypeWrapper, u32)> = HashMap::new();
+
+                    // Registers to be replaced, with their replacement
+                    let mut replacements: HashMap<u32, u32> = HashMap::new();
+
+                    let mut new_nodes: Vec<IrNode> = vec![];
+
+                    for node in nodes {
+                        let node = Optimizer::replace_sources(&node, &replacements);
+                        match &node {
+                            LoadR(tt, dest, address, _) => {
+                                if let Some((known_tt, src)) = known_values.get(address) {
+                                    if TypeWrapper::are_compatible(known_tt, tt) {
+                                        replacements.insert(*dest, *src);
+                                        is_changed = true;
+                                        continue;
+                                    }
+                                }
+                                known_values.insert(*address, (tt.clone(), *dest));
+                            }
+                            Store(tt, address, src, _) | Alloc(tt, address, src, false, _, false, ..) => {
+                                if private_references.contains(address) {
+                                    known_values.remove(address);
+                                } else if local_alloc_references.contains(address) {
+                                    known_values.retain(|a, _| local_alloc_references.contains(a));
+                                    known_values.remove(address);
+                                } else {
+                                    known_values.retain(|a, _| private_references.contains(a));
+                                }
+                                if *src != 0 && tt.get_size() == 4 {
+                                    known_values.insert(*address, (tt.clone(), *src));
+                                }
+                            }
+                            Call(..) | CallIndirect(..) => known_values.retain(|a, _| private_references.contains(a)),
+                            Label(..) | Branch(..) => known_values.clear(),
+                            _ => {}
+                        }
+                        new_nodes.push(node);
+                    }
+
+                    new_functions_list.push(FunctionDeclaration(n, t, args, new_nodes));
+                } else {
+                    panic!("Provided node to `copy_propagation` not of type FunctionDeclaration")
+                }
+            }
+
+            return (Program(new_functions_list), is_changed);
+        }
+        panic!("Provided node to `copy_propagation` not of type Program")
+    }
+
+    /// Optimizer::replace_sources
+    ///
+    /// Replace the source registers of a node according to a map
+    ///
+    /// @in node [&IrNode]: node to modify
+    /// @in replacements [&HashMap<u32, u32>]: registers to replace, with their replacement
+    /// @return [IrNode]: node with the new sources
+    fn replace_sources(node: &IrNode, replacements: &HashMap<u32, u32>) -> IrNode {
+        let r = |register: &u32| *replacements.get(register).unwrap_or(register);
+        match node {
+            Return(tt, src, sr) => Return(tt.clone(), r(src), sr.clone()),
+            Alloc(tt, dest, src, is_global, size, from_reg, name, radix, sr) => {
+                let size = if *from_reg { r(size) } else { *size };
+                Alloc(
+                    tt.clone(),
+                    *dest,
+                    r(src),
+                    *is_global,
+                    size,
+                    *from_reg,
+                    name.clone(),
+                    radix.clone(),
+                    sr.clone(),
+                )
+            }
+            Cast(ttd, tts, dest, src) => Cast(ttd.clone(), tts.clone(), *dest, r(src)),
+            Store(tt, address, src, sr) => Store(tt.clone(), r(address), r(src), sr.clone()),
+            LoadR(tt, dest, address, sr) => LoadR(tt.clone(), *dest, r(address), sr.clone()),
+            Call(name, tt, arguments, ret, sr) => Call(name.clone(), tt.clone(), arguments.iter().map(r).collect(), *ret, sr.clone()),
+            CallIndirect(tt, target, arguments, ret, sr) => CallIndirect(tt.clone(), r(target), arguments.iter().map(r).collect(), *ret, sr.clone()),
+            Branch(ct, tt, src1, src2, label) => Branch(ct.clone(), tt.clone(), r(src1), r(src2), *label),
+            Unary(tt, op, dest, src) => Unary(tt.clone(), op.clone(), *dest, r(src)),
+            Syscall(number, arguments) => Syscall(*number, arguments.iter().map(r).collect()),
+            Binary(op, tt, dest, src1, src2, sr) => Binary(op.clone(), tt.clone(), *dest, r(src1), r(src2), sr.clone()),
+            _ => node.clone(),
+        }
+    }
+
+    /// Optimizer::unreachable_code_removal
+    ///
+    /// The algorithm is in charge of removing the nodes which can never be executed, since they
+    /// follow an unconditional jump or a return and they are not preceded by any label, as in the
+    /// following case
+    ///
+    ///     ...
+    ///     jmp L_x
+    ///     v1 = add v2, v3     <- removed
+    ///     store v4, v1        <- removed
+    /// L_y:
+    ///     ....
+    ///
+    /// Such situations are mostly generated by the `constant_propagation` algorithm, when the
+    /// condition of a branch is known at compile time.
+    ///
+    /// @in ir [IrNode]: program to optimize
+    /// @result [(IrNode, bool)]: result of the optimization, whether some changes have been done
+    /// or not.
+    fn unreachable_code_removal(&mut self, ir: IrNode) -> (IrNode, bool) {
+        let mut is_changed = false;
+
+        // The initial node is always a program made of function declarations. The algorithm runs
+        // on each function declaration individually
+        if let Program(functions_list) = ir {
+            let mut new_functions_list: Vec<IrNode> = vec![];
+            for function in functions_list {
+                if let FunctionDeclaration(n, t, args, nodes) = function {
+                    // Do not optimize `init` function
+                    if n == "init" {
+                        new_functions_list.push(FunctionDeclaration(n, t, args, nodes.clone()));
+                        continue;
+                    }
+
+                    // New list of nodes for the current function
+                    let mut new_nodes: Vec<IrNode> = vec![];
+
+                    // Whether the current node can be reached or not
+                    let mut is_reachable = true;
+
+                    for node in nodes {
+                        // A label can always be reached through a jump
+                        if let Label(..) = node {
+                            is_reachable = true;
+                        }
+
+                        if !is_reachable {
+                            is_changed = true;
+                            continue;
+                        }
+
+                        match node {
+                            Branch(CompareType::Always, ..) | Return(..) => is_reachable = false,
+                            _ => {}
+                        }
+                        new_nodes.push(node);
+                    }
+
+                    new_functions_list.push(FunctionDeclaration(n, t, args, new_nodes));
+                } else {
+                    panic!("Provided node to `unreachable_code_removal` not of type FunctionDeclaration")
+                }
+            }
+
+            return (Program(new_functions_list), is_changed);
+        }
+        panic!("Provided node to `unreachable_code_removal` not of type Program")
+    }
+
+    /// Optimizer::constant_propagation
+    ///
+    /// Iterate over all the nodes of a function, keeping track of the registers whose value is
+    /// a known constant (destinations of `MovC` operations):
+    ///     - a unary or binary operation whose sources are all known is replaced by a `MovC` of
+    ///       its result, so that its destination becomes known as well (the same holds for a
+    ///       cast, which truncates and extends the value as the target would do);
+    ///     - a branch whose sources are both known is either replaced by an unconditional branch,
+    ///       if it is always taken, or removed;
+    ///     - stores (as any other node) then use a register whose value is computed at compile
+    ///       time.
+    ///
+    /// The value of a register is forgotten when the register is redefined by another node, and
+    /// all the values are forgotten after a call. Divisions and remainders by zero are not
+    /// computed, so that their behaviour is the one of the target. Constants are 32 bits wide,
+    /// thus the values of 64 bits types are never known.
+    /// At the end, the `MovC` nodes whose destination is not used anymore are removed.
+    ///
+    /// @in ir [IrNode] -> Program to optimize
+    /// @return [(IrNode, bool)] -> Program optimized, whether something has changed or not
+    fn constant_propagation(&mut self, ir: IrNode) -> (IrNode, bool) {
+        let mut is_changed = false;
+
+        // The initial node is always a program made of function declarations. The algorithm runs
+        // on each function declaration individually
+        if let Program(functions_list) = ir {
+            let mut new_functions_list: Vec<IrNode> = vec![];
+            for function in functions_list {
+                if let FunctionDeclaration(n, t, args, nodes) = function {
+                    // Do not optimize `init` function
+                    if n == "init" {
+                        new_functions_list.push(FunctionDeclaration(n, t, args, nodes.clone()));
+                        continue;
+                    }
+
+                    // Registers whose value is known
+                    let mut known_values: HashMap<u32, u32> = HashMap::new();
+                    let mut new_nodes: Vec<IrNode> = vec![];
+
+                    for node in nodes {
+                        let mut new_node = Some(node.clone());
+                        match &node {
+                            Binary(op, tt, dest, src1, src2, _) => {
+                                if let (Some(v1), Some(v2)) = (known_values.get(src1), known_values.get(src2)) {
+                                    if let Some(value) = Optimizer::fold_binary(op, tt, *v1, *v2) {
+                                        new_node = Some(MovC(tt.clone(), *dest, value));
+                                    }
+                                }
+                            }
+                            Unary(tt, op, dest, src) => {
+                                if let Some(v) = known_values.get(src) {
+                                    let value = match op {
+                                        Operator::Minus => Some(v.wrapping_neg()),
+                                        Operator::Complement => Some(!v),
+                                        Operator::Not => Some((Optimizer::fold_cast(tt, tt, *v) == 0) as u32),
+                                        _ => None,
+                                    };
+                                    if let Some(value) = value {
+                                        new_node = Some(MovC(tt.clone(), *dest, value));
+                                    }
+                                }
+                            }
+                            Cast(ttd, tts, dest, src) if ttd.get_size() != 8 => {
+                                if let Some(v) = known_values.get(src) {
+                                    new_node = Some(MovC(ttd.clone(), *dest, Optimizer::fold_cast(ttd, tts, *v)));
+                                }
+                            }
+                            Branch(ct, tt, src1, src2, label) if *ct != CompareType::Always => {
+                                let v1 = known_values.get(src1);
+                                let v2 = if *ct == CompareType::S || *ct == CompareType::NS {
+                                    Some(&0)
+                                } else {
+                                    known_values.get(src2)
+                                };
+                                if let (Some(v1), Some(v2)) = (v1, v2) {
+                                    new_node = if Optimizer::fold_compare(ct, tt, *v1, *v2) {
+                                        Some(Branch(CompareType::Always, tt.clone(), 0, 0, *label))
+                                    } else {
+                                        None
+                                    };
+                                }
+                            }
+                            _ => {}
+                        }
+
+                        if new_node.as_ref() != Some(&node) {
+                            is_changed = true;
+                        }
+
+                        // Update the known values according to the new node
+                        if let Some(new_node) = new_node {
+                            if let Call(..) | CallIndirect(..) = new_node {
+                                known_values.clear();
+                            } else if new_node.get_dest() != 0 {
+                                known_values.remove(&new_node.get_dest());
+                            }
+                            if let MovC(tt, dest, value) = &new_node {
+                                if tt.get_size() != 8 {
+                                    known_values.insert(*dest, *value);
+                                }
+                            }
+                            new_nodes.push(new_node);
+                        }
+                    }
+
+                    // Remove the constants which are not used anymore
+                    let used_registers: Vec<u32> = new_nodes.iter().flat_map(|node| node.get_src()).collect();
+                    let nodes_count = new_nodes.len();
+                    new_nodes.retain(|node| !matches!(node, MovC(_, dest, _) if !used_registers.contains(dest)));
+                    if new_nodes.len() != nodes_count {
+                        is_changed = true;
+                    }
+
+                    new_functions_list.push(FunctionDeclaration(n, t, args, new_nodes));
+                } else {
+                    panic!("Provided node to `constant_propagation` not of type FunctionDeclaration")
+                }
+            }
+
+            return (Program(new_functions_list), is_changed);
+        }
+        panic!("Provided node to `constant_propagation` not of type Program")
+    }
+
+    /// Optimizer::algebraic_simplification
+    ///
+    /// Iterate over all the nodes of a function, keeping track of the registers whose value is
+    /// a known constant (destinations of `MovC` operations), and simplify the binary operations
+    /// having a constant operand which is either an identity or an annihilator:
+    ///     - `x + 0`, `0 + x`, `x - 0`, `x * 1`, `1 * x`, `x / 1`, `x | 0`, `0 | x`, `x ^ 0`,
+    ///       `0 ^ x`, `x << 0` and `x >> 0` are copies of `x`: the node is removed and its
+    ///       destination is replaced with `x` in all the following nodes;
+    ///     - `x * 0`, `0 * x`, `x & 0` and `0 & x` are replaced by a `MovC` of 0.
+    /// As each register is written once, `x` is defined wherever the destination of the removed
+    /// node was read, as in the following case
+    ///
+    ///     v2 = 0
+    ///     v3 = add v1, v2     <- removed
+    ///     v4 = mul v3, v3     <- becomes `v4 = mul v1, v1`
+    ///
+    /// @in ir [IrNode] -> Program to optimize
+    /// @return [(IrNode, bool)] -> Program optimized, whether something has changed or not
+    fn algebraic_simplification(&mut self, ir: IrNode) -> (IrNode, bool) {
+        let mut is_changed = false;
+
+        // The initial node is always a program made of function declarations. The algorithm runs
+        // on each function declaration individually
+        if let Program(functions_list) = ir {
+            let mut new_functions_list: Vec<IrNode> = vec![];
+            for function in functions_list {
+                if let FunctionDeclaration(n, t, args, nodes) = function {
+                    // Do not optimize `init` function
+                    if n == "init" {
+                        new_functions_list.push(FunctionDeclaration(n, t, args, nodes.clone()));
+                        continue;
+                    }
+
+                    // Registers whose value is known
+                    let mut known_values: HashMap<u32, u32> = HashMap::new();
+
+                    // Registers to be replaced, with their replacement
+                    let mut replacements: HashMap<u32, u32> = HashMap::new();
+
+                    let mut new_nodes: Vec<IrNode> = vec![];
+
+                    for node in nodes {
+                        let node = Optimizer::replace_sources(&node, &replacements);
+                        if let Binary(op, tt, dest, src1, src2, _) = &node {
+                            let (v1, v2) = (known_values.get(src1).cloned(), known_values.get(src2).cloned());
+                            let is_copy_of = match (op, v1, v2) {
+                                (Operator::Plus | Operator::OrOp | Operator::XorOp, Some(0), _) => Some(*src2),
+                                (Operator::Asterisk, Some(1), _) => Some(*src2),
+                                (Operator::Plus | Operator::Minus | Operator::OrOp | Operator::XorOp, _, Some(0)) => Some(*src1),
+                                (Operator::LShift | Operator::RShift, _, Some(0)) => Some(*src1),
+                                (Operator::Asterisk | Operator::Slash, _, Some(1)) => Some(*src1),
+                                _ => None,
+                            };
+                            let is_zero = matches!(
+                                (op, v1, v2),
+                                (Operator::Asterisk | Operator::AndOp, Some(0), _) | (Operator::Asterisk | Operator::AndOp, _, Some(0))
+                            );
+
+                            if let Some(src) = is_copy_of {
+                                replacements.insert(*dest, src);
+                                is_changed = true;
+                                continue;
+                            }
+                            if is_zero {
+                                known_values.insert(*dest, 0);
+                                new_nodes.push(MovC(tt.clone(), *dest, 0));
+                                is_changed = true;
+                                continue;
+                            }
+                        }
+
+                        if let MovC(_, dest, value) = &node {
+                            known_values.insert(*dest, *value);
+                        } else if node.get_dest() != 0 {
+                            known_values.remove(&node.get_dest());
+                        }
+                        new_nodes.push(node);
+                    }
+
+                    new_functions_list.push(FunctionDeclaration(n, t, args, new_nodes));
+                } else {
+                    panic!("Provided node to `algebraic_simplification` not of type FunctionDeclaration")
+                }
+            }
+
+            return (Program(new_functions_list), is_changed);
+        }
+        panic!("Provided node to `algebraic_simplification` not of type Program")
+    }
+
+    /// Optimizer::common_subexpression_elimination
+    ///
+    /// Remove the binary operations whose result is already available in a register on every path
+    /// reaching them. The expressions available at the beginning of each block are computed by
+    /// iterating until a fixed point is reached: they are the expressions available at the end of
+    /// all its predecessors in the same register. An expression stops being available when one of
+    /// its operands or its destination is written again, and at each store and call. A removed
+    /// node has its destination replaced with the register holding the expression, as in the
+    /// following case
+    ///
+    ///     v3 = mul v1, v2
+    ///     jle v1, v2 L_1
+    ///     ...
+    /// L_1:
+    ///     v7 = mul v1, v2     <- removed
+    ///     v8 = add v7, v7     <- becomes `v8 = add v3, v3`
+    ///
+    /// Differently from the binary operation cache of lirgen, the analysis is not limited by the
+    /// branches.
+    ///
+    /// @in ir [IrNode] -> Program to optimize
+    /// @return [(IrNode, bool)] -> Program optimized, whether something has changed or not
+    fn common_subexpression_elimination(&mut self, ir: IrNode) -> (IrNode, bool) {
+        let mut is_changed = false;
+
+        // The initial node is always a program made of function declarations. The algorithm runs
+        // on each function declaration individually
+        if let Program(functions_list) = ir {
+            let mut new_functions_list: Vec<IrNode> = vec![];
+            for function in functions_list {
+                if let FunctionDeclaration(n, t, args, nodes) = function {
+                    // Do not optimize `init` function
+                    if n == "init" {
+                        new_functions_list.push(FunctionDeclaration(n, t, args, nodes.clone()));
+                        continue;
+                    }
+
+                    let cfg = Cfg::new(&n, &nodes);
+
+                    // Predecessors of each block
+                    let mut predecessors: Vec<Vec<usize>> = vec![vec![]; cfg.blocks.len()];
+                    for (i, block) in cfg.blocks.iter().enumerate() {
+                        for successor in [block.fallthrough, block.branch_target].into_iter().flatten() {
+                            predecessors[successor].push(i);
+                        }
+                    }
+
+                    // Expressions available at the end of each block, None if the block was not
+                    // reached yet
+                    let mut available_out: Vec<Option<AvailableExpressions>> = vec![None; cfg.blocks.len()];
+
+                    let get_available_in = |i: usize, available_out: &Vec<Option<AvailableExpressions>>| -> Option<AvailableExpressions> {
+                        if i == 0 {
+                            return Some(AvailableExpressions::new());
+                        }
+                        let mut result: Option<AvailableExpressions> = None;
+                        for p in &predecessors[i] {
+                            if let Some(available) = &available_out[*p] {
+                                result = match result {
+                                    None => Some(available.clone()),
+                                    Some(mut r) => {
+                                        r.retain(|expression, register| available.get(expression) == Some(register));
+                                        Some(r)
+                                    }
+                                };
+                            }
+                        }
+                        result
+                    };
+
+                    let mut is_changed_analysis = true;
+                    while is_changed_analysis {
+                        is_changed_analysis = false;
+                        for i in 0..cfg.blocks.len() {
+                            let mut available = match get_available_in(i, &available_out) {
+                                Some(available) => available,
+                                None => continue,
+                            };
+                            for node in &cfg.blocks[i].nodes {
+                                Optimizer::update_available_expressions(&mut available, node);
+                            }
+                            if available_out[i].as_ref() != Some(&available) {
+                                available_out[i] = Some(available);
+                                is_changed_analysis = true;
+                            }
+                        }
+                    }
+
+                    // Registers to be replaced, with their replacement
+                    let mut replacements: HashMap<u32, u32> = HashMap::new();
+
+                    let mut new_nodes: Vec<IrNode> = vec![];
+
+                    for i in 0..cfg.blocks.len() {
+                        // Blocks which cannot be reached are left as they are
+                        let mut available = match get_available_in(i, &available_out) {
+                            Some(available) => available,
+                            None => {
+                                new_nodes.extend(cfg.blocks[i].nodes.iter().cloned());
+                                continue;
+                            }
+                        };
+                        for node in &cfg.blocks[i].nodes {
+                            if let Binary(op, tt, dest, src1, src2, _) = node {
+                                if let Some(register) = available.get(&(op.clone(), tt.clone(), *src1, *src2)) {
+                                    replacements.insert(*dest, *register);
+                                    is_changed = true;
+                                    continue;
+                                }
+                            }
+                            Optimizer::update_available_expressions(&mut available, node);
+                            new_nodes.push(node.clone());
+                        }
+                    }
+
+                    // A register might be replaced with the result of a removed node
+                    for register in replacements.clone().keys() {
+                        let mut replacement = replacements[register];
+                        while let Some(next) = replacements.get(&replacement) {
+                            replacement = *next;
+                        }
+                        replacements.insert(*register, replacement);
+                    }
+
+                    let new_nodes = new_nodes.iter().map(|node| Optimizer::replace_sources(node, &replacements)).collect();
+                    new_functions_list.push(FunctionDeclaration(n, t, args, new_nodes));
+                } else {
+                    panic!("Provided node to `common_subexpression_elimination` not of type FunctionDeclaration")
+                }
+            }
+
+            return (Program(new_functions_list), is_changed);
+        }
+        panic!("Provided node to `common_subexpression_elimination` not of type Program")
+    }
+
+    /// Optimizer::update_available_expressions
+    ///
+    /// Update the expressions available after a node: a store or a call makes all of them
+    /// unavailable, a node writing a register makes unavailable the ones which read or write it,
+    /// and a binary operation makes its expression available in its destination, unless it was
+    /// already available in another register
+    ///
+    /// @in available [&mut AvailableExpressions]: expressions available before the node
+    /// @in node [&IrNode]: node to consider
+    fn update_available_expressions(available: &mut AvailableExpressions, node: &IrNode) {
+        match node {
+            Store(..) | Call(..) | CallIndirect(..) => available.clear(),
+            _ if node.get_dest() != 0 => {
+                let dest = node.get_dest();
+                available.retain(|(_, _, src1, src2), register| *src1 != dest && *src2 != dest && *register != dest);
+            }
+            _ => {}
+        }
+        if let Binary(op, tt, dest, src1, src2, _) = node {
+            if *src1 != *dest && *src2 != *dest {
+                available.entry((op.clone(), tt.clone(), *src1, *src2)).or_insert(*dest);
+            }
+        }
+    }
+
+    /// Optimizer::fold_binary
+    ///
+    /// Compute the result of a binary operation between two constants, as the target would do
+    ///
+    /// @in op [&Operator]: operator of the binary operation
+    /// @in tt [&TypeWrapper]: type of the operation
+    /// @in v1 [u32]: value of the first operand
+    /// @in v2 [u32]: value of the second operand
+    /// @return [Option<u32>]: result of the operation, None if it cannot be computed
+    fn fold_binary(op: &Operator, tt: &TypeWrapper, v1: u32, v2: u32) -> Option<u32> {
+        let signed = tt.is_signed();
+
+        // Shifts only consider the 5 least significant bits of the amount, as the hardware does
+        match op {
+            Operator::Plus => Some(v1.wrapping_add(v2)),
+            Operator::Minus => Some(v1.wrapping_sub(v2)),
+            Operator::Asterisk => Some(v1.wrapping_mul(v2)),
+            Operator::Slash | Operator::Module if v2 == 0 => None,
+            Operator::Slash if signed => Some((v1 as i32).wrapping_div(v2 as i32) as u32),
+            Operator::Slash => Some(v1 / v2),
+            Operator::Module if signed => Some((v1 as i32).wrapping_rem(v2 as i32) as u32),
+            Operator::Module => Some(v1 % v2),
+            Operator::AndOp => Some(v1 & v2),
+            Operator::OrOp => Some(v1 | v2),
+            Operator::XorOp => Some(v1 ^ v2),
+            Operator::LShift => Some(v1.wrapping_shl(v2)),
+            Operator::RShift if signed => Some((v1 as i32).wrapping_shr(v2) as u32),
+            Operator::RShift => Some(v1.wrapping_shr(v2)),
+            Operator::EqualCompare => Some(Optimizer::fold_compare(&CompareType::EQ, tt, v1, v2) as u32),
+            Operator::DiffCompare => Some(Optimizer::fold_compare(&CompareType::NE, tt, v1, v2) as u32),
+            Operator::LTCompare => Some(Optimizer::fold_compare(&CompareType::LT, tt, v1, v2) as u32),
+            Operator::GTCompare => Some(Optimizer::fold_compare(&CompareType::GT, tt, v1, v2) as u32),
+            Operator::LECompare => Some(Optimizer::fold_compare(&CompareType::LE, tt, v1, v2) as u32),
+            Operator::GECompare => Some(Optimizer::fold_compare(&CompareType::GE, tt, v1, v2) as u32),
+            _ => None,
+        }
+    }
+
+    /// Optimizer::fold_cast
+    ///
+    /// Compute the result of a cast of a constant, as the target would do: the value is truncated
+    /// to the smallest of the two sizes, and then sign extended if a signed source is widened or
+    /// if the destination is signed and not larger than the source. Casting a value to its own
+    /// type removes the upper bits which might be set by an operation which overflowed
+    ///
+    /// @in ttd [&TypeWrapper]: destination type of the cast
+    /// @in tts [&TypeWrapper]: source type of the cast
+    /// @in value [u32]: value to cast
+    /// @return [u32]: result of the cast
+    fn fold_cast(ttd: &TypeWrapper, tts: &TypeWrapper, value: u32) -> u32 {
+        let bits = ttd.get_size().min(tts.get_size()) * 8;
+        let is_signed = if ttd.get_size() <= tts.get_size() {
+            ttd.is_signed()
+        } else {
+            tts.is_signed()
+        };
+        if bits == 32 {
+            return value;
+        }
+        let value = value & ((1 << bits) - 1);
+        if is_signed {
+            return (((value << (32 - bits)) as i32) >> (32 - bits)) as u32;
+        }
+        value
+    }
+
+    /// Optimizer::fold_compare
+    ///
+    /// Compute the result of a comparison between two constants
+    ///
+    /// @in ct [&CompareType]: comparison to compute
+    /// @in tt [&TypeWrapper]: type of the operands
+    /// @in v1 [u32]: value of the first operand
+    /// @in v2 [u32]: value of the second operand
+    /// @return [bool]: whether the comparison holds
+    fn fold_compare(ct: &CompareType, tt: &TypeWrapper, v1: u32, v2: u32) -> bool {
+        let (s1, s2) = (v1 as i32, v2 as i32);
+        let is_ordered = matches!(ct, CompareType::GT | CompareType::GE | CompareType::LT | CompareType::LE);
+        let signed = is_ordered && tt.is_signed();
+
+        match ct {
+            CompareType::Always => true,
+            CompareType::GT if signed => s1 > s2,
+            CompareType::GT => v1 > v2,
+            CompareType::GE if signed => s1 >= s2,
+            CompareType::GE => v1 >= v2,
+            CompareType::LT if signed => s1 < s2,
+            CompareType::LT => v1 < v2,
+            CompareType::LE if signed => s1 <= s2,
+            CompareType::LE => v1 <= v2,
+            CompareType::EQ => v1 == v2,
+            CompareType::NE => v1 != v2,
+            CompareType::S => v1 != v2,
+            CompareType::NS => v1 == v2,
+        }
+    }
+
     /// Optimizer::control_flow_removal
     ///
     /// The algorithm is in charge of removing useless jumps, in which the label of the destination
@@ -52,10 +795,11 @@ impl Optimizer {
     /// L_x:
     ///     ....
     ///
-    /// Since each jump is associate to a single label and viceversa, when a jump is removed the
-    /// corresponding label can be removed as well. The algorithm makes sense only if some
-    /// instructions were removed by the `dead_code_removal` algorithm. The IR generator does not
-    /// generate such situations.
+    /// Since many jumps might have the same destination (as with `break` and `continue`), the
+    /// label is not removed together with the jump. Then, the labels which are not the
+    /// destination of any jump are removed, as they only split the code in useless blocks.
+    /// The algorithm makes sense only if some instructions were removed by the other algorithms.
+    /// The IR generator does not generate such situations.
     ///
     /// @in ir [IrNode]: program to optimize
     /// @result [(IrNode, bool)]: result of the optimization, whether some changes have been done
@@ -91,7 +835,6 @@ impl Optimizer {
                                 if let Label(l) = &nodes[j] {
                                     if *l == label {
                                         to_remove[i] = true;
-                                        to_remove[j] = true;
                                         is_changed = true;
                                         break;
                                     }
@@ -104,6 +847,26 @@ impl Optimizer {
                         }
                     }
 
+                    // Labels which are the destination of the remaining jumps
+                    let mut used_labels: Vec<u32> = vec![];
+                    for i in 0..nodes.len() {
+                        if let Branch(_, _, _, _, label) = nodes[i] {
+                            if !to_remove[i] {
+                                used_labels.push(label);
+                            }
+                        }
+                    }
+
+                    // Mark the unused labels as to be removed
+                    for i in 0..nodes.len() {
+                        if let Label(label) = nodes[i] {
+                            if !used_labels.contains(&label) {
+                                to_remove[i] = true;
+                                is_changed = true;
+                            }
+                        }
+                    }
+
                     // Create a new list of nodes containing the nodes which cannot be removed
                     for i in 0..nodes.len() {
                         if !to_remove[i] {
@@ -215,9 +978,9 @@ impl Optimizer {
                             }
 
                             match node {
-                                // return nodes, call nodes, branch nodes and label nodes are
-                                // always critical
-                                Return(..) | Call(..) | Branch(..) | Label(..) => {
+                                // return nodes, call nodes, system calls, branch nodes and label
+                                // nodes are always critical
+                                Return(..) | Call(..) | CallIndirect(..) | Syscall(..) | Branch(..) | Label(..) => {
                                     // Add the sources to the critical registers
                                     critical_registers.append(&mut node.get_src());
                                     is_node_critical[i] = true;
@@ -0,0 +1,478 @@
+mod test {
+
+    /// test::get_ir
+    ///
+    /// Get the IR of the input, which must be parsed without errors, before any optimization
+    ///
+    /// @in input [&str]: source code to compile
+    /// @in opt [u32]: level of optimization of lirgen
+    /// @return [IrNode]: IR of the program
+    #[cfg(test)]
+    fn get_ir(input: &str, opt: u32) -> crate::lirgen::irnode::IrNode {
+        use crate::lexer::lexer::Lexer;
+        use crate::lirgen::lirgen::Lirgen;
+        use crate::parser::parser::Parser;
+
+        let mut l = Lexer::new(String::from(input), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        Lirgen::new(opt).linearize_ast(&p.parse().unwrap())
+    }
+
+    #[test]
+    fn optimizer_test_fixed_point() {
+        use crate::optimizer::optimizer::{Optimizer, MAX_OPTIMIZER_PASSES};
+
+        let input = String::from(
+            "
+            u32 main() {
+              u32 s = 0;
+              u32 i;
+              u32 unused = 3;
+              for (i = 0; i < 10; i = i + 1) {
+                if (i > 5) { s = s + i; }
+                unused = unused + i;
+              }
+              return s;
+            }
+",
+        );
+
+        let mut ir = get_ir(&input, 3);
+        let mut optimizer = Optimizer::new(3);
+
+        // The first pass changes the program, and the fixed point is reached after some passes
+        let mut is_changed: bool;
+        (ir, is_changed) = optimizer.optimize_pass(ir);
+        assert!(is_changed);
+        let mut passes = 1;
+        while is_changed {
+            (ir, is_changed) = optimizer.optimize_pass(ir);
+            passes += 1;
+        }
+        assert!(passes < MAX_OPTIMIZER_PASSES);
+
+        // Once the fixed point is reached, the program does not change anymore
+        let (new_ir, is_changed) = optimizer.optimize_pass(ir.clone());
+        assert!(!is_changed);
+        assert_eq!(new_ir, ir);
+    }
+
+    #[test]
+    fn optimizer_test_constant_propagation() {
+        use crate::lirgen::irnode::{CompareType, IrNode};
+        use crate::optimizer::optimizer::Optimizer;
+
+        let get_nodes = |input: &str, optimize: bool| {
+            let mut ir = get_ir(input, 2);
+            if optimize {
+                ir = Optimizer::new(2).optimize(ir);
+            }
+            if let IrNode::Program(functions) = ir {
+                if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    return nodes.clone();
+                }
+            }
+            panic!("Invalid program");
+        };
+
+        // `b = a + 3` is computed at compile time
+        let input = "
+            u32 main() {
+              u32 a = 2;
+              u32 b = a + 3;
+              return b;
+            }
+";
+        let nodes = get_nodes(input, false);
+        assert!(nodes.iter().any(|node| matches!(node, IrNode::Binary(..))));
+        let nodes = get_nodes(input, true);
+        assert!(!nodes.iter().any(|node| matches!(node, IrNode::Binary(..))));
+        assert_eq!(nodes.iter().filter(|node| matches!(node, IrNode::MovC(..))).count(), 1);
+        assert!(nodes.iter().any(|node| matches!(node, IrNode::MovC(_, _, 5))));
+
+        // Known conditions remove the branches which are never taken
+        let input = "
+            u32 main() {
+              i32 a = -2;
+              u32 b = 0;
+              if (a < -1) { b = 1; }
+              if (a > -1) { b = 2; }
+              return b;
+            }
+";
+        let nodes = get_nodes(input, false);
+        assert!(nodes
+            .iter()
+            .any(|node| matches!(node, IrNode::Branch(ct, ..) if *ct != CompareType::Always)));
+        let nodes = get_nodes(input, true);
+        assert!(!nodes
+            .iter()
+            .any(|node| matches!(node, IrNode::Branch(ct, ..) if *ct != CompareType::Always)));
+    }
+
+    #[test]
+    fn optimizer_test_signed_division() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        // Value returned by `main`, if it is computed at compile time
+        let get_result = |input: String| {
+            let ir = Optimizer::new(2).optimize(get_ir(&input, 2));
+            if let IrNode::Program(functions) = ir {
+                if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    let returned = nodes
+                        .iter()
+                        .find_map(|node| if let IrNode::Return(_, src, _) = node { Some(*src) } else { None });
+                    return nodes.iter().find_map(|node| match node {
+                        IrNode::MovC(_, dest, v) if Some(*dest) == returned => Some(*v),
+                        _ => None,
+                    });
+                }
+            }
+            panic!("Invalid program");
+        };
+        let signed = |a: &str, op: &str, b: &str| get_result(format!("i32 main() {{ i32 a = {}; i32 b = {}; return a {} b; }}", a, b, op));
+        let unsigned = |a: &str, op: &str, b: &str| get_result(format!("u32 main() {{ u32 a = {}; u32 b = {}; return a {} b; }}", a, b, op));
+
+        // Signed divisions truncate towards zero, and the remainder has the sign of the dividend
+        assert_eq!(signed("-7", "/", "(i32) 2"), Some(-3_i32 as u32));
+        assert_eq!(signed("-7", "%", "(i32) 2"), Some(-1_i32 as u32));
+        assert_eq!(signed("(i32) 7", "%", "-2"), Some(1));
+        assert_eq!(signed("(i32) 7", "/", "-2"), Some(-3_i32 as u32));
+
+        // The same operands are different values when unsigned
+        assert_eq!(unsigned("(u32) -7", "/", "2"), Some(0x7FFFFFFC));
+        assert_eq!(unsigned("(u32) -7", "%", "2"), Some(1));
+
+        // The overflowing division gives the dividend, as the target does
+        assert_eq!(signed("(i32) 0x80000000", "/", "-1"), Some(0x80000000));
+        assert_eq!(signed("(i32) 0x80000000", "%", "-1"), Some(0));
+
+        // A division by zero is left to the target
+        assert_eq!(signed("(i32) 7", "/", "(i32) (2 - 2)"), None);
+    }
+
+    #[test]
+    fn optimizer_test_not() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        // Value returned by `main`, if it is computed at compile time
+        let get_result = |input: String| {
+            let ir = Optimizer::new(2).optimize(get_ir(&input, 2));
+            if let IrNode::Program(functions) = ir {
+                if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    let returned = nodes
+                        .iter()
+                        .find_map(|node| if let IrNode::Return(_, src, _) = node { Some(*src) } else { None });
+                    return nodes.iter().find_map(|node| match node {
+                        IrNode::MovC(_, dest, v) if Some(*dest) == returned => Some(*v),
+                        _ => None,
+                    });
+                }
+            }
+            panic!("Invalid program");
+        };
+
+        // `!x` is 1 if `x` is zero and 0 otherwise, whatever the width of `x` is. An overflow
+        // leaves `x` equal to zero when only its lower bits are considered
+        let matrix = vec![
+            ("u8", "0", "0", 1),
+            ("u8", "1", "0", 0),
+            ("u8", "255", "0", 0),
+            ("u8", "255", "1", 1),
+            ("i8", "0", "0", 1),
+            ("i8", "127", "1", 0),
+            ("i8", "128", "128", 1),
+            ("i32", "0", "0", 1),
+            ("i32", "5", "0", 0),
+            ("i32", "0xFFFFFFFF", "1", 1),
+        ];
+        for (tt, a, b, expected) in matrix {
+            let input = format!(
+                "u32 main() {{ {} a = ({}) {}; {} b = a + ({}) {}; return (u32) !b; }}",
+                tt, tt, a, tt, tt, b
+            );
+            assert_eq!(get_result(input), Some(expected));
+        }
+
+        // Casts truncate the value before it is checked, and sign extension does not change
+        // whether the value is zero
+        let matrix = vec![
+            ("(u8) (u32) 256", 1),
+            ("(i8) (u32) 0x100", 1),
+            ("(i8) (u32) 0x180", 0),
+            ("(i32) (i8) (u32) 0x100", 1),
+            ("(u32) (i8) (i32) -1", 0),
+            ("(i32) (u8) 255", 0),
+        ];
+        for (expression, expected) in matrix {
+            let input = format!("u32 main() {{ return (u32) !({}); }}", expression);
+            assert_eq!(get_result(input), Some(expected));
+        }
+    }
+
+    #[test]
+    fn optimizer_test_dead_code() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        let get_nodes = |input: &str, optimize: bool| {
+            let mut ir = get_ir(input, 2);
+            if optimize {
+                ir = Optimizer::new(2).optimize(ir);
+            }
+            if let IrNode::Program(functions) = ir {
+                if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    return nodes.clone();
+                }
+            }
+            panic!("Invalid program");
+        };
+
+        // The computation of `b` is never used
+        let input = "
+            u32 main() { return 0; }
+            u32 g(u32 a) {
+              u32 b = a * 7 + 3;
+              return a;
+            }
+";
+        let nodes = get_nodes(input, false);
+        assert!(nodes.iter().any(|node| matches!(node, IrNode::Binary(..))));
+        let nodes = get_nodes(input, true);
+        assert!(!nodes.iter().any(|node| matches!(node, IrNode::Binary(..))));
+        assert!(!nodes.iter().any(|node| matches!(node, IrNode::MovC(..))));
+
+        // The call cannot be removed, even if its result is not used
+        let input = "
+            u32 f(u32 a) { return a; }
+            u32 main() { return 0; }
+            u32 g(u32 a) {
+              u32 b = f(a) + 1;
+              return a;
+            }
+";
+        let nodes = get_nodes(input, true);
+        assert!(nodes.iter().any(|node| matches!(node, IrNode::Call(..))));
+        assert!(!nodes.iter().any(|node| matches!(node, IrNode::Binary(..))));
+
+        // The body of the second `if` follows an unconditional jump, thus it is removed
+        let input = "
+            u32 main() { return 0; }
+            u32 g(u32 a) {
+              i32 c = -2;
+              u32 b = a;
+              if (c < -1) { b = b * 3; }
+              if (c > -1) { b = b * 5; }
+              return b;
+            }
+";
+        let nodes = get_nodes(input, true);
+        assert_eq!(nodes.iter().filter(|node| matches!(node, IrNode::Binary(..))).count(), 1);
+        assert!(!nodes.iter().any(|node| matches!(node, IrNode::Branch(..))));
+    }
+
+    #[test]
+    fn optimizer_test_control_flow() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        let input = "
+            u32 main() {
+              u32 s = 0;
+              u32 i;
+              for (i = 0; i < 10; i = i + 1) {
+                if (i == 5) { break; }
+                s = s + i;
+              }
+              return s;
+            }
+";
+
+        let ir = Optimizer::new(2).optimize(get_ir(input, 2));
+        let nodes = if let IrNode::Program(functions) = ir {
+            if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                nodes.clone()
+            } else {
+                panic!("Invalid program");
+            }
+        } else {
+            panic!("Invalid program");
+        };
+
+        // Jumps to the following label are removed, and so are the labels which are not the
+        // destination of any jump (the start of the `for`, the `if` and the `for` increment)
+        assert_eq!(
+            nodes.iter().map(|node| node.to_string()).collect::<String>(),
+            "\tv1 = <u32> $0
+\tv2 = alloc<u32> v1 \t(@s)
+\tv3 = alloc<u32> \t(@i)
+\tstore<u32> v3, v1
+
+\t%L_2:
+\tv4 = load<u32> v3
+\tv5 = <u32> $10
+\tjge<u32> v4, v5 %L_4
+\tv6 = <u32> $5
+\tjne<u32> v4, v6 %L_5
+\tj %L_4
+
+\t%L_5:
+\tv7 = load<u32> v2
+\tv8 = add <u32> v7, v4
+\tstore<u32> v2, v8
+\tv9 = <u32> $1
+\tv10 = add <u32> v4, v9
+\tstore<u32> v3, v10
+\tj %L_2
+
+\t%L_4:
+\tv11 = load<u32> v2
+\treturn<u32> v11
+"
+        );
+    }
+
+    #[test]
+    fn optimizer_test_copy_propagation() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        let input = "
+            u32 main() { return 0; }
+            u32 g(u32* p, u32* q) {
+              u32 a = *p;
+              u32 b = *p + a;
+              *p = b;
+              u32 c = *p * 3;
+              *q = c;
+              return *p + c;
+            }
+";
+
+        let ir = Optimizer::new(2).optimize(get_ir(input, 2));
+        let nodes = if let IrNode::Program(functions) = ir {
+            if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                nodes.clone()
+            } else {
+                panic!("Invalid program");
+            }
+        } else {
+            panic!("Invalid program");
+        };
+
+        // The second load of `*p` uses the value of the first one, the third one uses the stored
+        // value, while the last one is required since `q` might be equal to `p`
+        assert_eq!(
+            nodes.iter().map(|node| node.to_string()).collect::<String>(),
+            "\tv5 = load<u32> v1
+\tv8 = add <u32> v5, v5
+\tstore<u32> v1, v8
+\tv12 = <u32> $3
+\tv13 = mul <u32> v8, v12
+\tstore<u32> v2, v13
+\tv17 = load<u32> v1
+\tv19 = add <u32> v17, v13
+\treturn<u32> v19
+"
+        );
+    }
+
+    #[test]
+    fn optimizer_test_algebraic_simplification() {
+        use crate::lirgen::irnode::IrNode;
+        use crate::optimizer::optimizer::Optimizer;
+
+        let get_nodes = |input: &str| {
+            let ir = Optimizer::new(2).optimize(get_ir(input, 2));
+            if let IrNode::Program(functions) = ir {
+                if let IrNode::FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    return nodes.iter().map(|node| node.to_string()).collect::<String>();
+                }
+            }
+            panic!("Invalid program");
+        };
+
+        // Once the constants are propagated, the identities are copies of the other operand,
+        // which is then used by the following nodes
+        let input = "
+            u32 main() { return 0; }
+            u32 f(u32 x, u32 y) {
+              u32 z = 0;
+              u32 o = 1;
+              u32 a = (x + z) * o;
+              u32 b = ((y << z) | z) - z;
+              return (a ^ z) / o + (b + z) * b;
+            }
+";
+        assert_eq!(
+            get_nodes(input),
+            "\tv19 = mul <u32> v2, v2
+\tv20 = add <u32> v1, v19
+\treturn<u32> v20
+"
+        );
+
+        // The annihilators give zero, which is propagated as well
+        let input = "
+            u32 main() { return 0; }
+            u32 f(u32 x, u32 y) {
+              u32 z = 0;
+              return (x * z) + (y & z) + x;
+            }
+";
+        assert_eq!(get_nodes(input), "\treturn<u32> v1\n");
+    }
+
+    #[test]
+    fn optimizer_test_common_subexpression_elimination() {
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::lexer::token::Operator;
+        use crate::lirgen::irnode::{CompareType, IrNode, IrNode::*};
+        use crate::optimizer::optimizer::Optimizer;
+
+        let tt = TypeWrapper {
+            type_native: TypeNative::U32,
+            pointer: 0,
+            constant: false,
+        };
+        let get_nodes = |nodes: Vec<IrNode>| {
+            let function = FunctionDeclaration("f".to_string(), tt.clone(), vec![tt.clone(), tt.clone()], nodes);
+            if let Program(functions) = Optimizer::new(2).optimize(Program(vec![function])) {
+                if let FunctionDeclaration(_, _, _, nodes) = functions.last().unwrap() {
+                    return nodes.iter().map(|node| node.to_string()).collect::<String>();
+                }
+            }
+            panic!("Invalid program");
+        };
+
+        // `a * b` is available in v3 after the branch, thus the second multiplication is removed
+        let nodes = |call: Vec<IrNode>| {
+            let mut nodes = vec![
+                Binary(Operator::Asterisk, tt.clone(), 3, 1, 2, None),
+                Branch(CompareType::LE, tt.clone(), 1, 2, 1),
+                Binary(Operator::Plus, tt.clone(), 4, 3, 1, None),
+                Return(tt.clone(), 4, None),
+                Label(1),
+            ];
+            nodes.extend(call);
+            nodes.extend(vec![
+                Binary(Operator::Asterisk, tt.clone(), 5, 1, 2, None),
+                Binary(Operator::Plus, tt.clone(), 6, 5, 3, None),
+                Return(tt.clone(), 6, None),
+            ]);
+            nodes
+        };
+        let result = get_nodes(nodes(vec![]));
+        assert_eq!(result.matches("mul").count(), 1);
+        assert!(result.contains("v6 = add <u32> v3, v3"));
+
+        // A call in between makes the expression unavailable, thus it is computed twice
+        let result = get_nodes(nodes(vec![Call("g".to_string(), tt.clone(), vec![], 7, None)]));
+        assert_eq!(result.matches("mul").count(), 2);
+        assert!(result.contains("v6 = add <u32> v5, v3"));
+    }
+}
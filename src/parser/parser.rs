@@ -1,5 +1,6 @@
 use crate::ast::ast_node::{AstNode, AstNodeWrapper, SourceReference};
 use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+use crate::diagnostic::Diagnostic;
 use crate::lexer::token::{Bracket, Keyword, Operator, Tk, Token};
 use crate::parser::symbol_table::{Declaration, SymbolTable};
 use std::string::String;
@@ -13,10 +14,15 @@ pub struct Parser {
     token_list: Vec<Token>,    // List of tokens from the lexer
     current_position: usize,   // Current position in the above list
     errors_counter: u32,       // How many errors have been found while parsing (fail if >0)
+    max_errors: Option<u32>,   // Number of errors after which parsing stops, unbounded if None
     file_name: String,         // Name of file under analysis (to print error messages)
+    source: Option<String>,    // Content of the source, if it is not a file (to print error messages)
     symbol_table: SymbolTable, // Global symbol table
+    predeclared: Vec<String>,  // Functions added to the symbol table before parsing their body
     skip_erorrs: bool,         // In certain situations, it is worth to skip the erorr messages
-                               // (when performing backtracking)
+    // (when performing backtracking)
+    diagnostics: Vec<Diagnostic>, // Warnings found while parsing
+    entry: String,                // Function called by init
 }
 
 // ParserResult
@@ -41,6 +47,7 @@ enum ParserError {
     RedefintionError(String),                   // Identifier already defined
     TokenError(String),                         // Token is wrong
     NodeError(AstNodeWrapper, String),          // AST node is wrong
+    DeclarationError(String),                   // Required declaration is missing
 }
 
 use AstNode::*;
@@ -64,12 +71,75 @@ impl Parser {
             token_list,
             current_position: 0,
             errors_counter: 0,
+            max_errors: None,
             file_name,
+            source: None,
             symbol_table: SymbolTable::new(),
+            predeclared: Vec::new(),
             skip_erorrs: false,
+            diagnostics: Vec::new(),
+            entry: "main".to_string(),
         }
     }
 
+    /// Parser::set_entry
+    ///
+    /// Set the name of the entry function of the program (`main` by default), which is called by
+    /// `init`: it must be defined and it cannot have parameters
+    ///
+    /// @in entry [String]: name of the entry function
+    pub fn set_entry(&mut self, entry: String) {
+        self.entry = entry;
+    }
+
+    /// Parser::set_max_errors
+    ///
+    /// Stop parsing as soon as a given number of errors is found, instead of recovering and
+    /// reporting all of them (which might just be propagations of the first ones)
+    ///
+    /// @in max_errors [u32]: number of errors after which parsing stops
+    pub fn set_max_errors(&mut self, max_errors: u32) {
+        self.max_errors = Some(max_errors);
+    }
+
+    /// Parser::get_errors_counter
+    ///
+    /// Get the number of errors found while parsing
+    ///
+    /// @return [u32]: number of errors reported so far
+    pub fn get_errors_counter(&self) -> u32 {
+        self.errors_counter
+    }
+
+    /// Parser::get_diagnostics
+    ///
+    /// Get the warnings found while parsing, which are not printed by the parser but by a
+    /// `Reporter`, once parsing is over. Without a file name, the errors are collected as well
+    ///
+    /// @return [&Vec<Diagnostic>]: diagnostics found so far
+    pub fn get_diagnostics(&self) -> &Vec<Diagnostic> {
+        &self.diagnostics
+    }
+
+    /// Parser::is_error_limit_reached
+    ///
+    /// Check whether the errors found so far reached the limit set with `set_max_errors`
+    ///
+    /// @return [bool]: true if parsing is to be stopped
+    fn is_error_limit_reached(&self) -> bool {
+        self.max_errors.is_some_and(|max| self.errors_counter >= max)
+    }
+
+    /// Parser::set_source
+    ///
+    /// Provide the content of the source under analysis, when it is not a file (such as the
+    /// standard input), so that the error messages can show its lines
+    ///
+    /// @in source [String]: content of the source
+    pub fn set_source(&mut self, source: String) {
+        self.source = Some(source);
+    }
+
     /// Parser::get_current
     ///
     /// Get the current token under exam
@@ -119,6 +189,9 @@ impl Parser {
     /// @return [Option<AstNode>]: return the AST in case of success, None if an error occurred in the
     /// process
     pub fn parse(&mut self) -> Option<AstNodeWrapper> {
+        // Functions can be called before their definition
+        self.collect_functions();
+
         // Match a list of declarations
         match self.external_declaration_list() {
             Match(node) => {
@@ -126,6 +199,9 @@ impl Parser {
                 // spurious tokens are present.
                 if self.get_current() != Tk::EOF {
                     self.parser_error(TokenError(format!("EOF")));
+                // The entry point of the program is always called by init, so it must be defined
+                } else if self.symbol_table.search_definition(&self.entry).map_or(true, |d| !d.is_function) {
+                    self.parser_error(DeclarationError(format!("function `{}` is not defined", self.entry)));
                 // Else, parsing is considered to be successfull if no errors where found in the process
                 } else if self.errors_counter == 0 {
                     return Some(node);
@@ -134,10 +210,75 @@ impl Parser {
             _ => {}
         }
 
+        if self.is_error_limit_reached() {
+            eprintln!("\x1b[91mToo many errors, parsing stopped after {} of them\x1b[0m", self.errors_counter);
+        }
         eprintln!("\x1b[91mFailed parsing with {} errors\x1b[0m", self.errors_counter);
         return None;
     }
 
+    /// Parser::collect_functions
+    ///
+    /// Add to the global scope the signature of all the functions defined in the program, before
+    /// parsing any of them. In this way, a function can be called before being defined, and
+    /// mutually recursive functions are possible. The headers are found by trying to parse a
+    /// function signature at each type outside of curly brackets, without reporting errors, as
+    /// they will be found while parsing the whole program
+    fn collect_functions(&mut self) {
+        let mut depth = 0;
+        self.skip_erorrs = true;
+        while self.get_current() != Tk::EOF {
+            match self.get_current() {
+                Tk::Bracket(LCurly) => depth += 1,
+                Tk::Bracket(RCurly) => depth -= 1,
+                Tk::Keyword(Const) if depth == 0 => self.function_signature(),
+                ref t if depth == 0 && t.is_type() => self.function_signature(),
+                _ => {}
+            }
+            self.advance();
+        }
+        self.current_position = 0;
+        self.skip_erorrs = false;
+    }
+
+    /// Parser::function_signature
+    ///
+    /// Try to parse a function signature starting from the current token, defined as
+    ///
+    /// Function_signature -> Pointer_type identifier ( Parameter_list ) {
+    ///
+    /// If it succeeds, the function is added to the global scope. The current position is not
+    /// modified
+    fn function_signature(&mut self) {
+        let start_position = self.current_position;
+        if let Match(type_node) = self.pointer_type() {
+            if let Tk::Identifier(id) = self.get_current() {
+                self.advance();
+                if self.get_current() == Tk::Bracket(LBracket) {
+                    self.advance();
+                    if let Match(list) = self.parameter_list() {
+                        self.advance();
+                        if let (Tk::Bracket(LCurly), AstNode::FuncDeclNode(_, _, params, _)) = (self.get_current(), list.node) {
+                            let arguments = params.iter().map(|p| p.type_ref.clone()).collect();
+                            let res = self.symbol_table.add_definition(&Declaration {
+                                name: id.clone(),
+                                return_type: type_node.type_ref.clone(),
+                                is_function: true,
+                                arguments,
+                            });
+                            // In case of redefinition, the error is reported while parsing the
+                            // second definition
+                            if res.is_some() {
+                                self.predeclared.push(id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.current_position = start_position;
+    }
+
     /// Parser::external_declaration_list
     ///
     /// Parse an external_declaration_list, defined as
@@ -173,7 +314,10 @@ impl Parser {
     /// Declaration ->  Pointer_type identifier stop
     ///              |  Pointer_type identifier =  Expression stop
     ///              |  Pointer_type identifier ( Parameter_list ) Compound_statement
+    ///              |  Pointer_type identifier ( Parameter_list ) stop
     ///              |  Pointer_type identifier [ Expression ] stop
+    ///              |  Pointer_type Function_pointer_declarator stop
+    ///              |  Pointer_type Function_pointer_declarator = Expression stop
     ///
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
@@ -182,6 +326,10 @@ impl Parser {
         match self.pointer_type() {
             // After the type we expect an identifier
             Match(type_node) => {
+                // A left bracket starts the declarator of a pointer to function
+                if self.get_current() == Tk::Bracket(LBracket) {
+                    return self.function_pointer_declaration(type_node);
+                }
                 match self.get_current() {
                     Tk::Identifier(id) => {
                         let id_token = self.get_current_token(true);
@@ -219,6 +367,7 @@ impl Parser {
                                         if self.get_current() != Tk::Semicolon {
                                             return self.parser_error(TokenError(";".to_string()));
                                         }
+                                        let expr = self.implicit_conversion(expr, &type_node.type_ref);
                                         let token = self.get_current_token(true);
                                         let source_ref = SourceReference::merge(&type_node.source_ref, &SourceReference::from_token(&token));
                                         let result = AstNodeWrapper {
@@ -228,7 +377,9 @@ impl Parser {
                                         };
                                         // Type of the declaration and type of the expression must
                                         // be compatible in order for the assignment to be valid
-                                        if !TypeWrapper::are_compatible(&expr.type_ref, &type_node.type_ref) {
+                                        if !TypeWrapper::are_compatible(&expr.type_ref, &type_node.type_ref)
+                                            || TypeWrapper::discards_const(&type_node.type_ref, &expr.type_ref)
+                                        {
                                             return self.parser_error(NodeError(
                                                 result,
                                                 String::from(format!(
@@ -266,11 +417,14 @@ impl Parser {
                                             return self.parser_error(TokenError(")".to_string()));
                                         }
                                         self.advance();
+                                        // A semicolon in place of the body makes the declaration
+                                        // a prototype
+                                        let is_prototype = self.get_current() == Tk::Semicolon;
                                         // The parameter list returns a function declaration node
                                         // containing the parameters
                                         if let AstNode::FuncDeclNode(_, _, ref params, _) = list.node {
-                                            if id == "main" && params.len() != 0 {
-                                                return self.parser_error(NodeError(list, format!("Function main cannot have parameters")));
+                                            if id == self.entry && !params.is_empty() {
+                                                return self.parser_error(NodeError(list, format!("Function {} cannot have parameters", id)));
                                             }
                                             let mut parameters_st = Vec::new();
                                             // For each parameter, get type and name and add it to
@@ -280,7 +434,9 @@ impl Parser {
                                                 if let AstNode::ParameterNode(name, t_n) = &elem.node {
                                                     if let AstNode::TypeNode(t) = &t_n.node {
                                                         parameters_st.push(t.clone());
-                                                        if let Tk::Identifier(n) = &name.tk {
+                                                        // The parameters of a prototype have no
+                                                        // scope, as there is no body
+                                                        if let (false, Tk::Identifier(n)) = (is_prototype, &name.tk) {
                                                             let res = self.symbol_table.add_to_next_scope(&Declaration {
                                                                 name: n.to_string(),
                                                                 return_type: t.clone(),
@@ -296,9 +452,36 @@ impl Parser {
                                             }
                                             // Add definition to the symbol table. As this is done
                                             // before handling the body of the function, recursion
-                                            // is possible
+                                            // is possible. If the function was already added by
+                                            // `collect_functions`, its declaration is used
                                             params_g = params.clone();
-                                            if let AstNode::TypeNode(t) = &type_node.node {
+                                            if is_prototype {
+                                                // A prototype declares a function which is either
+                                                // defined in the program (thus already collected)
+                                                // or provided externally. The signatures of all
+                                                // the declarations must be the same
+                                                match self.symbol_table.search_definition(&id) {
+                                                    Ok(d) if d.is_function && d.return_type == type_node.type_ref && d.arguments == parameters_st => {
+                                                    }
+                                                    Ok(d) if d.is_function => {
+                                                        return self.parser_error(NodeError(
+                                                            list,
+                                                            format!("Declaration of function {} does not match its previous declaration", id),
+                                                        ));
+                                                    }
+                                                    Ok(_) => return self.parser_error(RedefintionError(id)),
+                                                    Err(_) => {
+                                                        self.symbol_table.add_definition(&Declaration {
+                                                            name: id.clone(),
+                                                            return_type: type_node.type_ref.clone(),
+                                                            is_function: true,
+                                                            arguments: parameters_st,
+                                                        });
+                                                    }
+                                                }
+                                            } else if let Some(index) = self.predeclared.iter().position(|f| *f == id) {
+                                                self.predeclared.remove(index);
+                                            } else if let AstNode::TypeNode(t) = &type_node.node {
                                                 let res = self.symbol_table.add_definition(&Declaration {
                                                     name: id.clone(),
                                                     return_type: t.clone(),
@@ -311,31 +494,44 @@ impl Parser {
                                                 }
                                             }
                                         }
-                                        match self.compound_statement(false, &type_node.type_ref) {
+                                        if is_prototype {
+                                            let token = self.get_current_token(true);
+                                            return Match(AstNodeWrapper {
+                                                node: AstNode::new_func_decl(
+                                                    &type_node,
+                                                    &id_token,
+                                                    &params_g,
+                                                    &AstNodeWrapper { ..Default::default() },
+                                                ),
+                                                source_ref: SourceReference::merge(&type_node.source_ref, &SourceReference::from_token(&token)),
+                                                ..Default::default()
+                                            });
+                                        }
+                                        let errors_before_body = self.errors_counter;
+                                        match self.compound_statement(false, false, &type_node.type_ref) {
                                             Match(node) => {
-                                                // A return statement must be found having the
-                                                // correct expression type
+                                                // A function which does not end with a return
+                                                // statement returns implicitly at the end of its
+                                                // body. As no value is returned, this is worth a
+                                                // warning if the function is not void (unless the
+                                                // return statement was skipped due to an error)
                                                 if let AstNode::CompoundNode(list) = &node.node {
-                                                    // List of statements is empty
-                                                    if list.len() == 0 {
-                                                        return self.parser_error(NodeError(
-                                                            node.clone(),
-                                                            String::from(format!("Missing return statement",)),
-                                                        ));
-                                                    }
                                                     let mut found_ret = false;
                                                     // Last statement
-                                                    if let JumpNode(ref tk, _) = list[&list.len() - 1].clone().node {
+                                                    if let Some(JumpNode(ref tk, _)) = list.last().map(|n| n.node.clone()) {
                                                         if let Tk::Keyword(Return) = tk.tk {
                                                             found_ret = true;
                                                         }
                                                     }
                                                     // The return statement was not found
-                                                    if !found_ret {
-                                                        return self.parser_error(NodeError(
-                                                            list[&list.len() - 1].clone(),
+                                                    if !found_ret
+                                                        && type_node.type_ref.type_native != TypeNative::Void
+                                                        && self.errors_counter == errors_before_body
+                                                    {
+                                                        self.parser_warning(NodeError(
+                                                            list.last().unwrap_or(&node).clone(),
                                                             String::from(format!(
-                                                                "Last statement must be return with type {}",
+                                                                "Missing return statement with type {}, the returned value is undefined",
                                                                 type_node.type_ref.to_string()
                                                             )),
                                                         ));
@@ -360,7 +556,31 @@ impl Parser {
                                 self.advance();
                                 // Expression to get the size of the array
                                 match self.expression() {
-                                    Match(node) => {
+                                    Match(mut node) => {
+                                        // A constant size can be given with any integer literal
+                                        // (such as a character), and it is converted to `u32`
+                                        if node.type_ref.pointer == 0
+                                            && node.type_ref.type_native != TypeNative::U32
+                                            && node.eval_const_expr().is_some()
+                                        {
+                                            let u32_type = TypeWrapper {
+                                                type_native: TypeNative::U32,
+                                                pointer: 0,
+                                                constant: false,
+                                            };
+                                            let type_node = AstNodeWrapper {
+                                                node: AstNode::new_type(&u32_type),
+                                                source_ref: node.source_ref.clone(),
+                                                type_ref: u32_type.clone(),
+                                                ..Default::default()
+                                            };
+                                            node = AstNodeWrapper {
+                                                node: AstNode::new_cast(&type_node, &node),
+                                                source_ref: node.source_ref.clone(),
+                                                type_ref: u32_type,
+                                                ..Default::default()
+                                            };
+                                        }
                                         if self.get_current() != Tk::Bracket(RSquare) {
                                             return self.parser_error(TokenError("]".to_string()));
                                         }
@@ -412,7 +632,11 @@ impl Parser {
     ///
     /// Parse a parameter_list, defined as
     ///
-    /// Parameter_list ->   {Pointer_type identifier {, Pointer_type identifier}* }
+    /// Parameter_list ->   {Parameter {, Parameter}* }
+    ///                 |   void
+    ///
+    /// Parameter -> Pointer_type identifier
+    ///            | Pointer_type Function_pointer_declarator
     ///
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
@@ -421,35 +645,52 @@ impl Parser {
         let mut result: Vec<AstNodeWrapper> = Vec::new();
         let mut source_ref_g: SourceReference = Default::default();
 
+        // `(void)` is an empty list, as `()`
+        self.skip_void_list();
+
         // Handle case of empty list: loop over types only if current is not right bracket
         if self.get_current() != Tk::Bracket(RBracket) {
             source_ref_g = SourceReference::from_token(&self.get_current_token(false));
             loop {
                 match self.pointer_type() {
-                    Match(type_node) => match self.get_current() {
-                        Identifier(_) => {
-                            let token = self.get_current_token(true);
-                            let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &type_node.source_ref);
-                            result.push(AstNodeWrapper {
-                                node: AstNode::new_parameter(&token, &type_node),
-                                source_ref,
-                                type_ref: type_node.type_ref.clone(),
-                                ..Default::default()
-                            });
-                            // Iterate again if next is comma
-                            if self.get_current() == Tk::Operator(Operator::Comma) {
-                                self.advance();
-                            // Error if next is not right bracket
-                            } else if self.get_current() != Tk::Bracket(Bracket::RBracket) {
-                                return self.parser_error(TokenError(")".to_string()));
-                            // If right bracket, stop loop
-                            } else {
-                                source_ref_g = SourceReference::merge(&source_ref_g, &SourceReference::from_token(&self.get_current_token(false)));
-                                break;
+                    Match(type_node) => {
+                        match self.get_current() {
+                            Identifier(_) => {
+                                let token = self.get_current_token(true);
+                                let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &type_node.source_ref);
+                                // Each argument is passed in a single register
+                                if type_node.type_ref.is_wide() {
+                                    return self.parser_error(NodeError(
+                                        type_node.clone(),
+                                        format!("parameters of type {} are not supported yet", type_node.type_ref.to_string()),
+                                    ));
+                                }
+                                result.push(AstNodeWrapper {
+                                    node: AstNode::new_parameter(&token, &type_node),
+                                    source_ref,
+                                    type_ref: type_node.type_ref.clone(),
+                                    ..Default::default()
+                                });
                             }
+                            // A parameter can be a pointer to function
+                            Tk::Bracket(LBracket) => match self.function_pointer_declarator(&type_node) {
+                                Match(node) => result.push(node),
+                                _ => return Fail,
+                            },
+                            _ => return self.parser_error(TokenError("identifier".to_string())),
                         }
-                        _ => return self.parser_error(TokenError("identifier".to_string())),
-                    },
+                        // Iterate again if next is comma
+                        if self.get_current() == Tk::Operator(Operator::Comma) {
+                            self.advance();
+                        // Error if next is not right bracket
+                        } else if self.get_current() != Tk::Bracket(Bracket::RBracket) {
+                            return self.parser_error(TokenError(")".to_string()));
+                        // If right bracket, stop loop
+                        } else {
+                            source_ref_g = SourceReference::merge(&source_ref_g, &SourceReference::from_token(&self.get_current_token(false)));
+                            break;
+                        }
+                    }
                     _ => return Fail,
                 }
             }
@@ -466,6 +707,153 @@ impl Parser {
         });
     }
 
+    /// Parser::skip_void_list
+    ///
+    /// Skip the keyword `void` if it is the only element of a list of parameters, so that the
+    /// list is parsed as an empty one. A parameter of type `void*` is not skipped
+    fn skip_void_list(&mut self) {
+        if self.get_current() == Tk::Keyword(Void) {
+            self.advance();
+            if self.get_current() != Tk::Bracket(RBracket) {
+                self.previous();
+            }
+        }
+    }
+
+    /// Parser::function_pointer_declarator
+    ///
+    /// Parse the declarator of a pointer to function, following the return type of the function,
+    /// defined as
+    ///
+    /// Function_pointer_declarator -> ( * identifier ) ( {Pointer_type {, Pointer_type}*} )
+    ///                              |  ( * identifier ) ( void )
+    ///
+    /// @in return_type [&AstNodeWrapper]: type node preceding the declarator
+    /// @return [ParseResult]: return Match with a ParameterNode made by the identifier and the type
+    /// of the pointer in case of success, Fail in case of error
+    fn function_pointer_declarator(&mut self, return_type: &AstNodeWrapper) -> ParserResult {
+        let mut source_ref = return_type.source_ref.clone();
+        for expected in [Tk::Bracket(LBracket), Tk::Operator(Asterisk)] {
+            if self.get_current() != expected {
+                return self.parser_error(TokenError(expected.to_string()));
+            }
+            self.advance();
+        }
+        let id_token = match self.get_current() {
+            Tk::Identifier(_) => self.get_current_token(true),
+            _ => return self.parser_error(TokenError("identifier".to_string())),
+        };
+        for expected in [Tk::Bracket(RBracket), Tk::Bracket(LBracket)] {
+            if self.get_current() != expected {
+                return self.parser_error(TokenError(expected.to_string()));
+            }
+            self.advance();
+        }
+
+        // Types of the arguments, possibly none (either `()` or `(void)`)
+        let mut arguments: Vec<TypeWrapper> = vec![];
+        self.skip_void_list();
+        while self.get_current() != Tk::Bracket(RBracket) {
+            match self.pointer_type() {
+                // Each argument is passed in a single register
+                Match(type_node) if type_node.type_ref.is_wide() => {
+                    return self.parser_error(NodeError(
+                        type_node.clone(),
+                        format!("parameters of type {} are not supported yet", type_node.type_ref.to_string()),
+                    ));
+                }
+                Match(type_node) => arguments.push(type_node.type_ref),
+                _ => return Fail,
+            }
+            if self.get_current() == Tk::Operator(Comma) {
+                self.advance();
+            } else if self.get_current() != Tk::Bracket(RBracket) {
+                return self.parser_error(TokenError(")".to_string()));
+            }
+        }
+        source_ref = SourceReference::merge(&source_ref, &SourceReference::from_token(&self.get_current_token(true)));
+
+        let type_ref = TypeWrapper {
+            type_native: TypeNative::Function(Box::new(return_type.type_ref.clone()), arguments),
+            pointer: 1,
+            constant: false,
+        };
+        let type_node = AstNodeWrapper {
+            node: AstNode::new_type(&type_ref),
+            source_ref: source_ref.clone(),
+            type_ref: type_ref.clone(),
+            ..Default::default()
+        };
+        Match(AstNodeWrapper {
+            node: AstNode::new_parameter(&id_token, &type_node),
+            source_ref,
+            type_ref,
+            ..Default::default()
+        })
+    }
+
+    /// Parser::function_pointer_declaration
+    ///
+    /// Parse the declaration of a pointer to function, defined as
+    ///
+    /// Declaration ->  Pointer_type Function_pointer_declarator stop
+    ///              |  Pointer_type Function_pointer_declarator = Expression stop
+    ///
+    /// @in return_type [AstNodeWrapper]: type node preceding the declarator
+    /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
+    /// case of error
+    fn function_pointer_declaration(&mut self, return_type: AstNodeWrapper) -> ParserResult {
+        let (id_token, type_node) = match self.function_pointer_declarator(&return_type) {
+            Match(AstNodeWrapper {
+                node: AstNode::ParameterNode(id_token, type_node),
+                ..
+            }) => (id_token, *type_node),
+            _ => return Fail,
+        };
+        let id = id_token.tk.get_identifier();
+
+        // Optional initialization
+        let mut expr = AstNodeWrapper { ..Default::default() };
+        if self.get_current() == Tk::Operator(Assign) {
+            self.advance();
+            match self.expression() {
+                Match(node) => expr = node,
+                _ => return Fail,
+            }
+        }
+        if self.get_current() != Tk::Semicolon {
+            return self.parser_error(TokenError(";".to_string()));
+        }
+        let token = self.get_current_token(true);
+        let source_ref = SourceReference::merge(&return_type.source_ref, &SourceReference::from_token(&token));
+        let result = AstNodeWrapper {
+            node: AstNode::new_var_decl(&type_node, &id_token, &expr),
+            source_ref,
+            ..Default::default()
+        };
+
+        // The initialization must be a pointer to a function with the same signature
+        if expr.node != AstNode::NullNode && !TypeWrapper::are_compatible(&expr.type_ref, &type_node.type_ref) {
+            return self.parser_error(NodeError(
+                result,
+                format!(
+                    "mismatched type at assignment: expected {}, found {}",
+                    type_node.type_ref.to_string(),
+                    expr.type_ref.to_string()
+                ),
+            ));
+        }
+        let res = self.symbol_table.add_definition(&Declaration {
+            name: id.clone(),
+            return_type: type_node.type_ref.clone(),
+            ..Default::default()
+        });
+        if res.is_none() {
+            return self.parser_error(RedefintionError(id));
+        }
+        Match(result)
+    }
+
     /// Parser::compound_statement
     ///
     /// Parse a compound_statement, defined as
@@ -473,10 +861,11 @@ impl Parser {
     /// Compound_statement ->  { {statement}* }
     ///
     /// @in in_loop [bool]: whether the statement is currently in a loop or not
+    /// @in in_switch [bool]: whether the statement is currently in a switch or not
     /// @in return_type [TypeWrapper]: expected return type
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
-    fn compound_statement(&mut self, in_loop: bool, return_type: &TypeWrapper) -> ParserResult {
+    fn compound_statement(&mut self, in_loop: bool, in_switch: bool, return_type: &TypeWrapper) -> ParserResult {
         let mut result: Vec<AstNodeWrapper> = Vec::new();
         if self.get_current() != Tk::Bracket(LCurly) {
             return self.parser_error(TokenError("{".to_string()));
@@ -487,9 +876,10 @@ impl Parser {
         self.symbol_table.add_scope();
         while self.get_current() != Tk::Bracket(RCurly) {
             // Match a statement as long as possible
-            match self.statement(in_loop, &return_type) {
+            match self.statement(in_loop, in_switch, return_type) {
                 Match(node) => {
-                    if found_jump {
+                    // An empty statement (stray semicolon) is absorbed without any warning
+                    if found_jump && node.node != AstNode::NullNode {
                         self.parser_warning(NodeError(node.clone(), "Statement cannot be reached".to_string()));
                     }
                     // Add to the list of node if different from null (empty semicolon)
@@ -502,11 +892,20 @@ impl Parser {
                         }
                     }
                 }
-                // In case of error, we skip the tokens until a new semicolon or a curly bracket
+                // In case of error, we skip the tokens until a new semicolon or a curly bracket,
+                // unless too many errors were found or the source is over
                 _ => {
-                    while self.get_current() != Tk::Bracket(RCurly) && self.get_current() != Tk::Semicolon {
+                    if self.is_error_limit_reached() {
+                        self.symbol_table.remove_scope();
+                        return Fail;
+                    }
+                    while ![Tk::Bracket(RCurly), Tk::Semicolon, Tk::EOF].contains(&self.get_current()) {
                         self.advance();
                     }
+                    if self.get_current() == Tk::EOF {
+                        self.symbol_table.remove_scope();
+                        return self.parser_error(TokenError("}".to_string()));
+                    }
                     if self.get_current() == Tk::Semicolon {
                         self.advance();
                     }
@@ -539,13 +938,15 @@ impl Parser {
     ///            |    Compound_statement
     ///            |    Selection_statement
     ///            |    Iteration_statement
+    ///            |    Switch_statement
     ///            |    Jump_statement
     ///
     /// @in in_loop [bool]: whether the statement is currently in a loop or not
+    /// @in in_switch [bool]: whether the statement is currently in a switch or not
     /// @in return_type [TypeWrapper]: expected return type
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
-    fn statement(&mut self, in_loop: bool, return_type: &TypeWrapper) -> ParserResult {
+    fn statement(&mut self, in_loop: bool, in_switch: bool, return_type: &TypeWrapper) -> ParserResult {
         // A declaration starts with a type or with a the `const` keyword
         if self.get_current().is_type() || self.get_current() == Tk::Keyword(Const) {
             match self.declaration() {
@@ -557,24 +958,32 @@ impl Parser {
 
         match self.get_current() {
             // If left curly, then we have a compound statement
-            Tk::Bracket(LCurly) => match self.compound_statement(in_loop, &return_type) {
+            Tk::Bracket(LCurly) => match self.compound_statement(in_loop, in_switch, return_type) {
                 Match(node) => return Match(node),
                 _ => return Fail,
             },
             // If `if` token, then we have an if statement
-            Tk::Keyword(Keyword::If) => match self.selection_statement(in_loop, &return_type) {
+            Tk::Keyword(Keyword::If) => match self.selection_statement(in_loop, in_switch, return_type) {
                 Match(node) => return Match(node),
                 _ => return Fail,
             },
-            // If `break` or `continue` token, then we have jump statement
-            Tk::Keyword(Break) | Tk::Keyword(Continue) => match self.jump_statement(&return_type) {
+            // If `break` token, then we have jump statement
+            Tk::Keyword(Break) => match self.jump_statement(return_type) {
+                Match(node) => {
+                    // Such jump is allowed only in loop or in switch
+                    if !in_loop && !in_switch {
+                        return self.parser_error(NodeError(node.clone(), String::from("Cannot use break outside of loop or switch")));
+                    }
+                    return Match(node);
+                }
+                _ => return Fail,
+            },
+            // If `continue` token, then we have jump statement
+            Tk::Keyword(Continue) => match self.jump_statement(return_type) {
                 Match(node) => {
                     // Such jump is allowed only in loop
                     if !in_loop {
-                        return self.parser_error(NodeError(
-                            node.clone(),
-                            String::from(format!("Cannot use break or continue outside of loop",)),
-                        ));
+                        return self.parser_error(NodeError(node.clone(), String::from("Cannot use continue outside of loop")));
                     }
                     return Match(node);
                 }
@@ -592,6 +1001,11 @@ impl Parser {
                 Match(node) => return Match(node),
                 _ => return Fail,
             },
+            // If `switch` token, then we have a switch statement
+            Tk::Keyword(Switch) => match self.switch_statement(in_loop, return_type) {
+                Match(node) => return Match(node),
+                _ => return Fail,
+            },
             _ => {}
         }
 
@@ -650,8 +1064,12 @@ impl Parser {
             source_ref,
             ..Default::default()
         };
-        // Type is not void and mismatch between types was found
-        if return_type.type_native != TypeNative::Void && result_expr.type_ref != *return_type {
+        // Type is not void and mismatch between types was found. Only return statements carry an
+        // expression to be checked against the return type of the function
+        if token.tk == Tk::Keyword(Return)
+            && return_type.type_native != TypeNative::Void
+            && (!TypeWrapper::are_compatible(&result_expr.type_ref, return_type) || TypeWrapper::discards_const(return_type, &result_expr.type_ref))
+        {
             return self.parser_error(NodeError(
                 result,
                 String::from(format!(
@@ -668,7 +1086,7 @@ impl Parser {
     /// Parse an iteration statement, defined as
     ///
     /// Iteration_statement ->  while ( Expression ) Compound_statement
-    ///                      |  for ( Optional_expression stop Optional_expression stop Optional_expression ) Compound_statement
+    ///                      |  for ( For_init Optional_expression stop Optional_expression ) Compound_statement
     ///
     /// @in return_type [TypeWrapper]: expected return type
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
@@ -676,43 +1094,12 @@ impl Parser {
     fn iteration_statement(&mut self, return_type: &TypeWrapper) -> ParserResult {
         match self.get_current() {
             Tk::Keyword(For) => {
-                let token = self.get_current_token(true);
-                if self.get_current() != Tk::Bracket(LBracket) {
-                    return self.parser_error(TokenError("(".to_string()));
-                }
-                self.advance();
-                // Match first expression
-                if let Match(expr1) = self.optional_expression() {
-                    if self.get_current() != Tk::Semicolon {
-                        return self.parser_error(TokenError(";".to_string()));
-                    }
-                    self.advance();
-                    // Match second expression
-                    if let Match(expr2) = self.optional_expression() {
-                        if self.get_current() != Tk::Semicolon {
-                            return self.parser_error(TokenError(";".to_string()));
-                        }
-                        self.advance();
-                        // Match third expression
-                        if let Match(expr3) = self.optional_expression() {
-                            if self.get_current() != Tk::Bracket(RBracket) {
-                                return self.parser_error(TokenError(")".to_string()));
-                            }
-                            self.advance();
-                            // Match body of the for loop
-                            if let Match(body) = self.compound_statement(true, &return_type) {
-                                let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &body.source_ref);
-                                let result = AstNodeWrapper {
-                                    node: AstNode::new_for(&expr1, &expr2, &expr3, &body),
-                                    source_ref,
-                                    ..Default::default()
-                                };
-                                return Match(result);
-                            }
-                        }
-                    }
-                }
-                return Fail;
+                // The variables declared in the first clause are visible only in the loop, thus
+                // a new scope is added
+                self.symbol_table.add_scope();
+                let result = self.for_statement(return_type);
+                self.symbol_table.remove_scope();
+                result
             }
             Tk::Keyword(While) => {
                 let token = self.get_current_token(true);
@@ -720,10 +1107,18 @@ impl Parser {
                     self.advance();
                     // Match expression of while
                     if let Match(expr) = self.expression() {
+                        self.check_assignment_condition(&expr);
+                        // A condition is compared with zero, which is not supported on 64 bits yet
+                        if expr.type_ref.is_wide() {
+                            return self.parser_error(NodeError(
+                                expr.clone(),
+                                format!("type {} cannot be used as a condition yet", expr.type_ref.to_string()),
+                            ));
+                        }
                         if let Tk::Bracket(RBracket) = self.get_current() {
                             self.advance();
                             // Match body
-                            if let Match(body) = self.compound_statement(true, return_type) {
+                            if let Match(body) = self.compound_statement(true, false, return_type) {
                                 let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &body.source_ref);
                                 return Match(AstNodeWrapper {
                                     node: AstNode::new_while(&expr, &body),
@@ -749,6 +1144,235 @@ impl Parser {
         }
     }
 
+    /// Parser::for_statement
+    /// Parse a for statement, defined as
+    ///
+    /// For_statement ->  for ( For_init Optional_expression stop Optional_expression ) Compound_statement
+    ///
+    /// For_init ->   Optional_expression stop
+    ///           |   Declaration
+    ///
+    /// The scope of the variables declared in the first clause is handled by the caller
+    ///
+    /// @in return_type [TypeWrapper]: expected return type
+    /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
+    /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
+    fn for_statement(&mut self, return_type: &TypeWrapper) -> ParserResult {
+        let token = self.get_current_token(true);
+        if self.get_current() != Tk::Bracket(LBracket) {
+            return self.parser_error(TokenError("(".to_string()));
+        }
+        self.advance();
+        // Match first clause, either a declaration (including its semicolon) or an expression
+        let expr1 = if self.get_current().is_type() || self.get_current() == Tk::Keyword(Const) {
+            match self.declaration() {
+                Match(decl) => {
+                    if let FuncDeclNode(..) = decl.node {
+                        return self.parser_error(NodeError(decl, String::from("Only variables can be declared in a for statement")));
+                    }
+                    decl
+                }
+                _ => return Fail,
+            }
+        } else {
+            match self.optional_expression() {
+                Match(expr1) => {
+                    if self.get_current() != Tk::Semicolon {
+                        return self.parser_error(TokenError(";".to_string()));
+                    }
+                    self.advance();
+                    expr1
+                }
+                _ => return Fail,
+            }
+        };
+        // Match second expression
+        if let Match(expr2) = self.optional_expression() {
+            self.check_assignment_condition(&expr2);
+            // A condition is compared with zero, which is not supported on 64 bits yet
+            if expr2.type_ref.is_wide() {
+                return self.parser_error(NodeError(
+                    expr2.clone(),
+                    format!("type {} cannot be used as a condition yet", expr2.type_ref.to_string()),
+                ));
+            }
+            if self.get_current() != Tk::Semicolon {
+                return self.parser_error(TokenError(";".to_string()));
+            }
+            self.advance();
+            // Match third expression
+            if let Match(expr3) = self.optional_expression() {
+                if self.get_current() != Tk::Bracket(RBracket) {
+                    return self.parser_error(TokenError(")".to_string()));
+                }
+                self.advance();
+                // Match body of the for loop
+                if let Match(body) = self.compound_statement(true, false, return_type) {
+                    let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &body.source_ref);
+                    let result = AstNodeWrapper {
+                        node: AstNode::new_for(&expr1, &expr2, &expr3, &body),
+                        source_ref,
+                        ..Default::default()
+                    };
+                    return Match(result);
+                }
+            }
+        }
+        Fail
+    }
+
+    /// Parser::switch_statement
+    ///
+    /// Parse a switch_statement, defined as
+    ///
+    /// Switch_statement -> switch ( Expression ) { {Case_label {Statement}*}* }
+    ///
+    /// Case_label ->   case Expression :
+    ///             |   default :
+    ///
+    /// @in in_loop [bool]: whether the statement is currently in a loop or not
+    /// @in return_type [TypeWrapper]: expected return type
+    /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
+    /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
+    fn switch_statement(&mut self, in_loop: bool, return_type: &TypeWrapper) -> ParserResult {
+        // Must match switch token
+        if self.get_current() != Tk::Keyword(Switch) {
+            return self.parser_error(TokenError("switch".to_string()));
+        }
+        let token = self.get_current_token(true);
+        if self.get_current() != Tk::Bracket(LBracket) {
+            return self.parser_error(TokenError("(".to_string()));
+        }
+        self.advance();
+        // Match the discriminant, which must be an integer
+        let expr = match self.expression() {
+            Match(expr) => expr,
+            _ => return Fail,
+        };
+        if expr.type_ref.pointer != 0 || expr.type_ref.type_native == TypeNative::Void || expr.type_ref.is_wide() {
+            return self.parser_error(NodeError(
+                expr.clone(),
+                format!(
+                    "Switch expression must be an integer of at most 32 bits, but {} was found",
+                    expr.type_ref.to_string()
+                ),
+            ));
+        }
+        if self.get_current() != Tk::Bracket(RBracket) {
+            return self.parser_error(TokenError(")".to_string()));
+        }
+        self.advance();
+        if self.get_current() != Tk::Bracket(LCurly) {
+            return self.parser_error(TokenError("{".to_string()));
+        }
+        self.advance();
+
+        let mut cases: Vec<AstNodeWrapper> = vec![];
+        let mut case_values: Vec<u32> = vec![];
+        let mut found_default = false;
+        // The body of the switch is a single scope, shared by all the cases
+        self.symbol_table.add_scope();
+        while self.get_current() != Tk::Bracket(RCurly) {
+            let case_token = self.get_current_token(true);
+            // Match the label of the case, which is null for the default one
+            let label = match case_token.tk {
+                Tk::Keyword(Case) => match self.expression() {
+                    // The label must be an integer constant, different from the previous ones
+                    Match(label) => match label.eval_const_expr() {
+                        Some(value) if !case_values.contains(&value) => {
+                            case_values.push(value);
+                            label
+                        }
+                        Some(value) => {
+                            self.symbol_table.remove_scope();
+                            return self.parser_error(NodeError(label, format!("Duplicate case value {}", value)));
+                        }
+                        None => {
+                            self.symbol_table.remove_scope();
+                            return self.parser_error(NodeError(label, String::from("Case label must be an integer constant")));
+                        }
+                    },
+                    _ => {
+                        self.symbol_table.remove_scope();
+                        return Fail;
+                    }
+                },
+                Tk::Keyword(DefaultCase) => {
+                    if found_default {
+                        self.previous();
+                        self.symbol_table.remove_scope();
+                        return self.parser_error(TokenError("case".to_string()));
+                    }
+                    found_default = true;
+                    AstNodeWrapper { ..Default::default() }
+                }
+                _ => {
+                    self.previous();
+                    self.symbol_table.remove_scope();
+                    return self.parser_error(TokenError("case".to_string()));
+                }
+            };
+            if self.get_current() != Tk::Colon {
+                self.symbol_table.remove_scope();
+                return self.parser_error(TokenError(":".to_string()));
+            }
+            let colon = self.get_current_token(true);
+            let mut source_ref = SourceReference::merge(&SourceReference::from_token(&case_token), &SourceReference::from_token(&colon));
+
+            // Match statements until the next label or the end of the switch
+            let mut statements: Vec<AstNodeWrapper> = vec![];
+            let mut found_jump = false;
+            while self.get_current() != Tk::Bracket(RCurly)
+                && self.get_current() != Tk::Keyword(Case)
+                && self.get_current() != Tk::Keyword(DefaultCase)
+            {
+                match self.statement(in_loop, true, return_type) {
+                    Match(node) => {
+                        // An empty statement (stray semicolon) is absorbed without any warning
+                        if found_jump && node.node != AstNode::NullNode {
+                            self.parser_warning(NodeError(node.clone(), "Statement cannot be reached".to_string()));
+                        }
+                        // Add to the list of node if different from null (empty semicolon)
+                        if node.node != AstNode::NullNode && !found_jump {
+                            source_ref = SourceReference::merge(&source_ref, &node.source_ref);
+                            statements.push(node.clone());
+                            // If the statement is of type JumpNode, the code which follow in the
+                            // same case cannot be reached
+                            if let JumpNode(..) = node.node {
+                                found_jump = true;
+                            }
+                        }
+                    }
+                    // In case of error, we skip the tokens until a new semicolon or a curly bracket
+                    _ => {
+                        while self.get_current() != Tk::Bracket(RCurly) && self.get_current() != Tk::Semicolon {
+                            self.advance();
+                        }
+                        if self.get_current() == Tk::Semicolon {
+                            self.advance();
+                        }
+                    }
+                }
+            }
+
+            cases.push(AstNodeWrapper {
+                node: AstNode::new_case(&label, &statements),
+                source_ref,
+                ..Default::default()
+            });
+        }
+        // Remove scope
+        self.symbol_table.remove_scope();
+
+        let token_r = self.get_current_token(true);
+        let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &SourceReference::from_token(&token_r));
+        Match(AstNodeWrapper {
+            node: AstNode::new_switch(&expr, &cases),
+            source_ref,
+            ..Default::default()
+        })
+    }
+
     /// Parser::selection_statement
     ///
     /// Parse a selection_statement, defined as
@@ -756,10 +1380,11 @@ impl Parser {
     /// Selection_statement ->  if ( Expression ) Compound_statement Else_statement
     ///
     /// @in in_loop [bool]: whether the statement is currently in a loop or not
+    /// @in in_switch [bool]: whether the statement is currently in a switch or not
     /// @in return_type [TypeWrapper]: expected return type
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
-    fn selection_statement(&mut self, in_loop: bool, return_type: &TypeWrapper) -> ParserResult {
+    fn selection_statement(&mut self, in_loop: bool, in_switch: bool, return_type: &TypeWrapper) -> ParserResult {
         // Must match if token
         if let Tk::Keyword(If) = self.get_current() {
             let token = self.get_current_token(true);
@@ -769,13 +1394,21 @@ impl Parser {
             self.advance();
             // Match expression in the if condition
             if let Match(expr) = self.expression() {
+                self.check_assignment_condition(&expr);
+                // A condition is compared with zero, which is not supported on 64 bits yet
+                if expr.type_ref.is_wide() {
+                    return self.parser_error(NodeError(
+                        expr.clone(),
+                        format!("type {} cannot be used as a condition yet", expr.type_ref.to_string()),
+                    ));
+                }
                 if self.get_current() != Tk::Bracket(RBracket) {
                     self.parser_error(TokenError(")".to_string()));
                 }
                 self.advance();
                 // Match body of the if statement
-                match self.compound_statement(in_loop, &return_type) {
-                    Match(body) => match self.else_statement(in_loop, &return_type) {
+                match self.compound_statement(in_loop, in_switch, return_type) {
+                    Match(body) => match self.else_statement(in_loop, in_switch, return_type) {
                         // Match else statement
                         Match(else_body) => {
                             let source_ref = SourceReference::merge(&SourceReference::from_token(&token), &body.source_ref);
@@ -805,17 +1438,18 @@ impl Parser {
     ///                 |   else Compound_statement
     ///
     /// @in in_loop [bool]: whether the statement is currently in a loop or not
+    /// @in in_switch [bool]: whether the statement is currently in a switch or not
     /// @in return_type [TypeWrapper]: expected return type
     /// @return [ParseResult]: return Match with the corresponding AST in case of success, Fail in
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
-    fn else_statement(&mut self, in_loop: bool, return_type: &TypeWrapper) -> ParserResult {
+    fn else_statement(&mut self, in_loop: bool, in_switch: bool, return_type: &TypeWrapper) -> ParserResult {
         // If else is not present, return an empty node
         if self.get_current() != Tk::Keyword(Else) {
             return Match(AstNodeWrapper { ..Default::default() });
         }
         // Otherwise, parse the following compound statement
         let token = self.get_current_token(true);
-        match self.compound_statement(in_loop, &return_type) {
+        match self.compound_statement(in_loop, in_switch, return_type) {
             Match(mut node) => {
                 node.source_ref = SourceReference::merge(&SourceReference::from_token(&token), &node.source_ref);
                 Match(node)
@@ -913,6 +1547,7 @@ impl Parser {
                         let token = self.get_current_token(true);
                         match self.expression() {
                             Match(node) => {
+                                let node = self.implicit_conversion(node, &node_unary.type_ref);
                                 let source_ref = SourceReference::merge(&node_unary.source_ref, &node.source_ref);
                                 let result = AstNodeWrapper {
                                     node: AstNode::new_binary(&token, &node_unary, &node),
@@ -924,7 +1559,9 @@ impl Parser {
                                 };
                                 // Type of the lvalue and type of the right expression must be
                                 // compatible
-                                if !TypeWrapper::are_compatible(&node.type_ref, &node_unary.type_ref) {
+                                if !TypeWrapper::are_compatible(&node.type_ref, &node_unary.type_ref)
+                                    || TypeWrapper::discards_const(&node_unary.type_ref, &node.type_ref)
+                                {
                                     return self.parser_error(NodeError(
                                         result,
                                         String::from(format!(
@@ -944,8 +1581,11 @@ impl Parser {
             }
             _ => {}
         }
-        // Backtack and match logical expression (skip errors is now false)
+        // Backtack and match logical expression (skip errors is now false). The warnings already
+        // found are kept, as the same nodes are going to be parsed again
+        let diagnostics = std::mem::take(&mut self.diagnostics);
         *self = state_parser;
+        self.diagnostics = diagnostics;
         return self.logical_expression();
     }
 
@@ -981,6 +1621,9 @@ impl Parser {
                     op_stack.push(self.get_current_token(true));
                     match self.equality_expression() {
                         Match(node) => {
+                            if self.check_wide_operands(op_stack.last().unwrap(), &[&first, &node]) {
+                                return Fail;
+                            }
                             // Cannot apply the operation on pointer type
                             if node.type_ref.pointer != 0 {
                                 return self.parser_error(NodeError(
@@ -1022,6 +1665,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                 {
@@ -1057,8 +1701,11 @@ impl Parser {
                     op_stack.push(self.get_current_token(true));
                     match self.relational_expression() {
                         Match(node) => {
-                            // Cannot apply the operation on pointer type
-                            if node.type_ref != first.type_ref {
+                            if self.check_wide_operands(op_stack.last().unwrap(), &[&first, &node]) {
+                                return Fail;
+                            }
+                            // Pointers can only be compared with pointers of the same type
+                            if node.type_ref != first.type_ref && !(node.type_ref.is_integer() && first.type_ref.is_integer()) {
                                 return self.parser_error(NodeError(
                                     node.clone(),
                                     String::from(format!(
@@ -1068,8 +1715,8 @@ impl Parser {
                                     )),
                                 ));
                             }
-                            // Types must be compatible
-                            if !TypeWrapper::are_compatible(&node.type_ref, &first.type_ref) {
+                            // Types must be comparable
+                            if !TypeWrapper::are_comparable(&node.type_ref, &first.type_ref) {
                                 return self.parser_error(NodeError(
                                     node.clone(),
                                     String::from(format!(
@@ -1084,7 +1731,7 @@ impl Parser {
                         _ => return Fail,
                     }
                 }
-                // Handle nodes from right to left
+                // Handle nodes from right to left. The operands are compared in their common type
                 let mut result = node_stack.remove(0);
                 let mut source_ref = result.source_ref.clone();
                 while node_stack.len() != 0 {
@@ -1092,9 +1739,9 @@ impl Parser {
                     let new_operand = node_stack.remove(0);
                     source_ref = SourceReference::merge(&source_ref, &new_operand.source_ref);
                     result = AstNodeWrapper {
+                        type_ref: TypeWrapper::common_type(&result.type_ref, &new_operand.type_ref),
                         node: AstNode::new_binary(&op, &result, &new_operand),
                         source_ref: source_ref.clone(),
-                        type_ref: first.type_ref.clone(),
                         ..Default::default()
                     };
                 }
@@ -1102,6 +1749,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                     && self.get_current() != Tk::Operator(AndOp)
@@ -1148,6 +1796,9 @@ impl Parser {
                     op_stack.push(self.get_current_token(true));
                     match self.shift_expression() {
                         Match(node) => {
+                            if self.check_wide_operands(op_stack.last().unwrap(), &[&first, &node]) {
+                                return Fail;
+                            }
                             // Cannot apply the operation on pointer type
                             if node.type_ref.pointer != 0 {
                                 return self.parser_error(NodeError(
@@ -1155,8 +1806,8 @@ impl Parser {
                                     String::from(format!("type {} cannot be used in current expression", node.type_ref.to_string())),
                                 ));
                             }
-                            // Types must be compatible
-                            if !TypeWrapper::are_compatible(&node.type_ref, &first.type_ref) {
+                            // Types must be comparable
+                            if !TypeWrapper::are_comparable(&node.type_ref, &first.type_ref) {
                                 return self.parser_error(NodeError(
                                     node.clone(),
                                     String::from(format!(
@@ -1171,7 +1822,7 @@ impl Parser {
                         _ => return Fail,
                     }
                 }
-                // Handle nodes from right to left
+                // Handle nodes from right to left. The operands are compared in their common type
                 let mut result = node_stack.remove(0);
                 let mut source_ref = result.source_ref.clone();
                 while node_stack.len() != 0 {
@@ -1179,9 +1830,9 @@ impl Parser {
                     let new_operand = node_stack.remove(0);
                     source_ref = SourceReference::merge(&source_ref, &new_operand.source_ref);
                     result = AstNodeWrapper {
+                        type_ref: TypeWrapper::common_type(&result.type_ref, &new_operand.type_ref),
                         node: AstNode::new_binary(&op, &result, &new_operand),
                         source_ref: source_ref.clone(),
-                        type_ref: first.type_ref.clone(),
                         ..Default::default()
                     };
                 }
@@ -1189,6 +1840,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                     && self.get_current() != Tk::Operator(AndOp)
@@ -1231,6 +1883,9 @@ impl Parser {
                     op_stack.push(self.get_current_token(true));
                     match self.additive_expression() {
                         Match(node) => {
+                            if self.check_wide_operands(op_stack.last().unwrap(), &[&first, &node]) {
+                                return Fail;
+                            }
                             // Cannot apply the operation on pointer type
                             if node.type_ref.pointer != 0 {
                                 return self.parser_error(NodeError(
@@ -1249,6 +1904,18 @@ impl Parser {
                                     )),
                                 ));
                             }
+                            // Only the lower 5 bits of the amount are used, so a larger constant
+                            // amount is most likely a mistake
+                            let amount = match &node.node {
+                                AstNode::CastNode(_, expr) => expr.eval_const_expr(),
+                                _ => node.eval_const_expr(),
+                            };
+                            if let Some(amount) = amount.filter(|amount| *amount > 31) {
+                                self.parser_warning(NodeError(
+                                    node.clone(),
+                                    format!("shift amount {} is larger than 31, only its lower 5 bits are used", amount),
+                                ));
+                            }
                             node_stack.push(node)
                         }
                         _ => return Fail,
@@ -1272,6 +1939,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                     && self.get_current() != Tk::Operator(AndOp)
@@ -1309,27 +1977,50 @@ impl Parser {
         let mut op_stack: Vec<Token> = Vec::new();
         let mut node_stack: Vec<AstNodeWrapper> = Vec::new();
 
+        // Type of the expression after each operand
+        let mut type_stack: Vec<TypeWrapper> = Vec::new();
+
         // Match following expression
         match self.multiplicative_expression() {
             Match(first) => {
                 node_stack.push(first.clone());
+                let mut current_type = first.type_ref.clone();
                 // Continue parsing expression until one of these tokens is found
                 while self.get_current() == Tk::Operator(Plus) || self.get_current() == Tk::Operator(Minus) {
-                    op_stack.push(self.get_current_token(true));
+                    let op = self.get_current_token(true);
+                    op_stack.push(op.clone());
                     match self.multiplicative_expression() {
                         Match(node) => {
-                            // Types must be compatible
-                            if !TypeWrapper::are_compatible(&node.type_ref, &first.type_ref) {
+                            let is_integer = node.type_ref.pointer == 0
+                                && node.type_ref.type_native != TypeNative::Void
+                                && node.type_ref.type_native != TypeNative::Null;
+                            let is_compatible = TypeWrapper::are_compatible(&node.type_ref, &current_type);
+                            // A function has no size, thus a pointer to function cannot be moved
+                            if current_type.is_function_pointer() {
+                                return self.parser_error(NodeError(node.clone(), String::from("Cannot use a pointer to function in arithmetic")));
+                            }
+                            // An integer can be added to or subtracted from a pointer, while the
+                            // difference of two pointers is the number of elements between them
+                            let is_offset = current_type.pointer != 0 && is_integer;
+                            let is_difference = current_type.pointer != 0 && op.tk == Tk::Operator(Minus) && is_compatible;
+                            if is_difference {
+                                current_type = TypeWrapper {
+                                    type_native: TypeNative::I32,
+                                    ..Default::default()
+                                };
+                            // Otherwise, types must be compatible
+                            } else if !is_offset && !is_compatible {
                                 return self.parser_error(NodeError(
                                     node.clone(),
                                     String::from(format!(
                                         "type {} cannot be used in current expression; expected {}",
                                         node.type_ref.to_string(),
-                                        first.type_ref.to_string()
+                                        current_type.to_string()
                                     )),
                                 ));
                             }
-                            node_stack.push(node)
+                            node_stack.push(node);
+                            type_stack.push(current_type.clone());
                         }
                         _ => return Fail,
                     }
@@ -1344,7 +2035,7 @@ impl Parser {
                     result = AstNodeWrapper {
                         node: AstNode::new_binary(&op, &result, &new_operand),
                         source_ref: source_ref.clone(),
-                        type_ref: first.type_ref.clone(),
+                        type_ref: type_stack.remove(0),
                         ..Default::default()
                     };
                 }
@@ -1352,6 +2043,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                     && self.get_current() != Tk::Operator(AndOp)
@@ -1404,6 +2096,12 @@ impl Parser {
                     op_stack.push(self.get_current_token(true));
                     match self.cast_expression() {
                         Match(node) => {
+                            // Only multiplications are supported on values of 64 bits
+                            if op_stack.last().unwrap().tk != Tk::Operator(Asterisk)
+                                && self.check_wide_operands(op_stack.last().unwrap(), &[&first, &node])
+                            {
+                                return Fail;
+                            }
                             // Cannot apply the operation on pointer type
                             if node.type_ref.pointer != 0 {
                                 return self.parser_error(NodeError(
@@ -1422,6 +2120,10 @@ impl Parser {
                                     )),
                                 ));
                             }
+                            // A division by a literal zero is detected at compile time
+                            if op_stack.last().unwrap().tk != Tk::Operator(Asterisk) && Parser::is_literal_zero(&node) {
+                                return self.parser_error(NodeError(node.clone(), String::from("division by zero")));
+                            }
                             node_stack.push(node);
                         }
                         _ => return Fail,
@@ -1445,6 +2147,7 @@ impl Parser {
                 // Only these tokens are valid as next nodes
                 if self.get_current() != Tk::Bracket(RBracket)
                     && self.get_current() != Tk::Semicolon
+                    && self.get_current() != Tk::Colon
                     && self.get_current() != Tk::Bracket(RSquare)
                     && self.get_current() != Tk::Operator(Comma)
                     && self.get_current() != Tk::Operator(AndOp)
@@ -1500,7 +2203,12 @@ impl Parser {
                                             type_ref: type_node.type_ref.clone(),
                                             ..Default::default()
                                         };
-                                        if node.type_ref.type_native == TypeNative::Null || node.type_ref.type_native == TypeNative::Void {
+                                        // A value of 64 bits would be compared with zero, which is not supported yet
+                                        let is_wide_to_bool = node.type_ref.is_wide() && type_node.type_ref.is_bool();
+                                        if node.type_ref.type_native == TypeNative::Null
+                                            || node.type_ref.type_native == TypeNative::Void
+                                            || is_wide_to_bool
+                                        {
                                             return self.parser_error(NodeError(
                                                 result,
                                                 format!("Cannot cast {} into {}", node.type_ref.to_string(), type_node.type_ref.to_string()),
@@ -1557,7 +2265,7 @@ impl Parser {
                     source_ref = SourceReference::merge(&source_ref, &SourceReference::from_token(&self.get_current_token(true)));
                 }
                 match self.get_current() {
-                    Tk::Identifier(_) | Tk::Bracket(RBracket) => {}
+                    Tk::Identifier(_) | Tk::Bracket(RBracket) | Tk::Bracket(LBracket) | Tk::Operator(Comma) => {}
                     _ => return self.parser_error(TokenError("".to_string())),
                 }
                 // Return type node
@@ -1638,14 +2346,21 @@ impl Parser {
                             ..Default::default()
                         };
                         let mut type_ref = node.type_ref.clone();
+                        // Dereferencing a pointer to function gives the function, which is
+                        // used through its address, thus the pointer itself
+                        if token.tk == Tk::Operator(Asterisk) && type_ref.is_function_pointer() {
+                            result.is_lvalue = false;
                         // Dereferencing can be done only on pointers
-                        if let Tk::Operator(Asterisk) = token.tk {
+                        } else if let Tk::Operator(Asterisk) = token.tk {
                             if type_ref.pointer == 0 {
                                 return self.parser_error(NodeError(result, String::from("Cannot dereference non-pointer type")));
                             }
                             type_ref.pointer -= 1;
                             result.is_lvalue = true;
                         // Address can be extracted only from lvalues
+                        // The address of a function is the pointer to function itself
+                        } else if token.tk == Tk::Operator(AndOp) && !node.is_lvalue && type_ref.is_function_pointer() {
+                            result.is_lvalue = false;
                         } else if let Tk::Operator(AndOp) = token.tk {
                             if !node.is_lvalue {
                                 return self.parser_error(NodeError(result, String::from("Cannot extract address from rvalue")));
@@ -1658,9 +2373,10 @@ impl Parser {
                                 return self.parser_error(NodeError(result, String::from("Cannot apply unary operator on pointer type")));
                             }
                             result.is_lvalue = false;
-                            // When using the minus operator, convert the node to I32 type
+                            // When using the minus operator, convert the node to I32 type (I64 for
+                            // the operands of 64 bits)
                             if let Tk::Operator(Minus) = token.tk {
-                                type_ref.type_native = TypeNative::I32;
+                                type_ref.type_native = if type_ref.get_size() == 8 { TypeNative::I64 } else { TypeNative::I32 };
                             }
                         };
                         result.type_ref = type_ref;
@@ -1704,6 +2420,9 @@ impl Parser {
                                     if type_ref.pointer == 0 {
                                         return self.parser_error(NodeError(node, String::from("Cannot dereference non-pointer type")));
                                     }
+                                    if type_ref.is_function_pointer() {
+                                        return self.parser_error(NodeError(node, String::from("Cannot index a pointer to function")));
+                                    }
                                     type_ref.pointer -= 1;
                                     node.type_ref = type_ref;
                                 }
@@ -1797,6 +2516,7 @@ impl Parser {
             }
             Tk::Bracket(RBracket)
             | Tk::Semicolon
+            | Tk::Colon
             | Tk::Bracket(RSquare)
             | Tk::Operator(Comma)
             | Tk::Operator(AndOp)
@@ -1835,7 +2555,7 @@ impl Parser {
     /// case of error, Unmatch in case of non correspondant parse way, whenever it is possible
     fn primary_expression(&mut self) -> ParserResult {
         match self.get_current() {
-            Tk::Identifier(_) | Tk::IntegerLiteral(_) | Tk::Char(_) => {
+            Tk::Identifier(_) | Tk::IntegerLiteral(..) | Tk::Char(_) => {
                 let token = self.get_current_token(true);
                 let source_ref = SourceReference::from_token(&token);
                 let node = AstNode::new_primary(&token);
@@ -1845,7 +2565,7 @@ impl Parser {
                     ..Default::default()
                 };
                 // Integrals are always u32
-                if let Tk::IntegerLiteral(_) = token.tk {
+                if let Tk::IntegerLiteral(..) = token.tk {
                     result.type_ref = TypeWrapper {
                         type_native: TypeNative::U32,
                         ..Default::default()
@@ -1865,8 +2585,16 @@ impl Parser {
                         return self.parser_error(ParserError::ScopeError(name.to_string(), message, result));
                     }
                     let declaration = result_search.unwrap();
-                    result.is_lvalue = true;
-                    result.type_ref = declaration.return_type;
+                    // A built-in function has no address, thus it can only be called
+                    if declaration.is_function
+                        && self.get_current() != Tk::Bracket(LBracket)
+                        && Declaration::builtins().iter().any(|b| b.name == declaration.name)
+                    {
+                        return self.parser_error(NodeError(result, format!("Built-in function {} can only be called", name)));
+                    }
+                    // A function is not an lvalue, and its identifier is its address
+                    result.is_lvalue = !declaration.is_function;
+                    result.type_ref = declaration.get_type();
                 }
                 return Match(result);
             }
@@ -1879,6 +2607,7 @@ impl Parser {
                             let token_r = self.get_current_token(true);
                             let source_ref = SourceReference::merge(&SourceReference::from_token(&token_l), &SourceReference::from_token(&token_r));
                             node.source_ref = source_ref;
+                            node.is_parenthesized = true;
                             return Match(node);
                         }
                         _ => return self.parser_error(TokenError(")".to_string())),
@@ -1938,10 +2667,26 @@ impl Parser {
     /// @in error [ParserError]: type of error to handle
     /// @return [ParseResult]: Always Fail
     fn parser_error(&mut self, error: ParserError) -> ParserResult {
-        if self.skip_erorrs {
+        // Once the limit is reached, the following errors are not reported
+        if self.skip_erorrs || self.is_error_limit_reached() {
             return Fail;
         }
         self.errors_counter += 1;
+        // No source file is associated to the parser, thus its lines cannot be shown: the error
+        // is added to the diagnostics, for the caller to report it
+        if self.file_name.is_empty() {
+            let current = SourceReference::from_token(&self.token_list[self.current_position]);
+            let (message, source_ref) = match error {
+                ScopeError(found, expected, node) => (format!("identifier `{}` not found, did you mean `{}`?", found, expected), node.source_ref),
+                RedefintionError(id) => (format!("redefinition of identifier `{}`", id), current),
+                DeclarationError(message) => (message, current),
+                TokenError(expected) if !expected.is_empty() => (format!("expected `{}`, found `{}`", expected, self.get_current()), current),
+                TokenError(_) => (format!("unexpected token `{}`", self.get_current()), current),
+                NodeError(node, message) => (message, node.source_ref),
+            };
+            self.diagnostics.push(Diagnostic::error("parser", message, Some(source_ref)));
+            return Fail;
+        }
         let line_number = self.token_list[self.current_position].line_number;
         let last_character = self.token_list[self.current_position].last_character;
         let first_character = self.token_list[self.current_position].first_character;
@@ -1988,6 +2733,9 @@ impl Parser {
             RedefintionError(message) => {
                 eprintln!("\x1b[91merror parser: \x1b[0mredefinition of identifier `\x1b[34m{}\x1b[0m`", message);
             }
+            DeclarationError(message) => {
+                eprintln!("\x1b[91merror parser: \x1b[0m{}", message);
+            }
             TokenError(expected) => {
                 if expected.len() != 0 {
                     eprintln!(
@@ -2051,65 +2799,162 @@ impl Parser {
         return Fail;
     }
 
-    /// Parser::parser_warning
+    /// Parser::is_literal_zero
     ///
-    /// Generate a warning from the parser
+    /// Check whether an expression is a literal zero, possibly casted
     ///
-    /// @in error [ParserError]: type of error to handle
-    fn parser_warning(&mut self, error: ParserError) {
-        let line_number = self.token_list[self.current_position].line_number;
-        let first_character = self.token_list[self.current_position].first_character;
-        let file_lines = self.read_lines(&self.file_name);
+    /// @in node [&AstNodeWrapper]: expression to check
+    /// @return [bool]: true if the expression is a literal zero
+    fn is_literal_zero(node: &AstNodeWrapper) -> bool {
+        match &node.node {
+            AstNode::PrimaryNode(token) => matches!(token.tk, Tk::IntegerLiteral(0, _)),
+            AstNode::CastNode(_, expr) => Parser::is_literal_zero(expr),
+            _ => false,
+        }
+    }
 
-        eprint!("\x1b[34m{}:{}:{}: \x1b[0m", self.file_name, line_number, first_character);
+    /// Parser::implicit_conversion
+    ///
+    /// Convert an integer value assigned to an integer destination of a different type, wrapping
+    /// it into a cast. A conversion to a smaller type might change the value, thus it gives a
+    /// warning, unless the value is a constant which fits the destination
+    ///
+    /// @in expr [AstNodeWrapper]: value to assign
+    /// @in dest [&TypeWrapper]: type of the destination
+    /// @return [AstNodeWrapper]: value converted to the type of the destination, or unchanged if
+    /// no conversion is required
+    fn implicit_conversion(&mut self, expr: AstNodeWrapper, dest: &TypeWrapper) -> AstNodeWrapper {
+        if TypeWrapper::are_compatible(&expr.type_ref, dest) || !expr.type_ref.is_integer() || !dest.is_integer() {
+            return expr;
+        }
+        let dest = TypeWrapper {
+            constant: false,
+            ..dest.clone()
+        };
 
-        match error {
-            NodeError(node, string) => {
-                eprintln!("\x1b[33mwarning parser: \x1b[0m{}", string);
-                println!("");
-                for line_number in node.source_ref.init_line..=node.source_ref.last_line {
-                    let line = &file_lines[line_number as usize - 1];
-                    eprint!("{}\t| {}\n\t| ", line_number, line);
-                    for i in 0..line.len() {
-                        if node.source_ref.init_line == node.source_ref.last_line {
-                            if i >= node.source_ref.init_char as usize - 1 && i <= node.source_ref.last_char as usize - 1 {
-                                eprint!("\x1b[33m^\x1b[0m");
-                            } else {
-                                eprint!(" ");
-                            }
-                        } else if line_number == node.source_ref.init_line {
-                            if i < node.source_ref.init_char as usize - 1 {
-                                eprint!(" ");
-                            } else {
-                                eprint!("\x1b[33m^\x1b[0m");
-                            }
-                        } else if line_number == node.source_ref.last_line {
-                            if i <= node.source_ref.last_char as usize - 1 {
-                                eprint!("\x1b[33m^\x1b[0m");
-                            } else {
-                                eprint!(" ");
-                            }
-                        } else {
-                            eprint!("\x1b[33m^\x1b[0m");
-                        }
+        // A value of 64 bits would be compared with zero, which is not supported yet
+        if dest.is_bool() && expr.type_ref.is_wide() {
+            self.parser_error(NodeError(
+                expr.clone(),
+                format!("Cannot convert {} into bool yet", expr.type_ref.to_string()),
+            ));
+        }
+
+        if dest.get_size() < expr.type_ref.get_size() {
+            let fits = match expr.eval_const_expr() {
+                Some(value) => {
+                    let value = if expr.type_ref.is_signed() { value as i32 as i64 } else { value as i64 };
+                    let bits = dest.get_size() * 8;
+                    if dest.is_bool() {
+                        value == 0 || value == 1
+                    } else if dest.is_signed() {
+                        value >= -(1 << (bits - 1)) && value < (1 << (bits - 1))
+                    } else {
+                        value >= 0 && value < (1 << bits)
                     }
-                    eprintln!("");
                 }
+                None => false,
+            };
+            if !fits {
+                self.parser_warning(NodeError(
+                    expr.clone(),
+                    format!(
+                        "implicit conversion from {} to {} may change the value, add an explicit cast if it is intended",
+                        expr.type_ref.to_string(),
+                        dest.to_string()
+                    ),
+                ));
+            }
+        }
+
+        let type_node = AstNodeWrapper {
+            node: AstNode::new_type(&dest),
+            source_ref: expr.source_ref.clone(),
+            type_ref: dest.clone(),
+            ..Default::default()
+        };
+        AstNodeWrapper {
+            node: AstNode::new_cast(&type_node, &expr),
+            source_ref: expr.source_ref.clone(),
+            type_ref: dest,
+            ..Default::default()
+        }
+    }
+
+    /// Parser::check_wide_operands
+    ///
+    /// The backend supports `+`, `-`, `*`, the unary operators and the casts on values of 64 bits,
+    /// but not the other binary operators yet. Give an error if one of the operands of such an
+    /// operator is of 64 bits, so that the program is rejected with the location of the operand
+    ///
+    /// @in op [&Token]: operator of the binary expression
+    /// @in operands [&[&AstNodeWrapper]]: operands of the expression
+    /// @return [bool]: whether an error was given
+    fn check_wide_operands(&mut self, op: &Token, operands: &[&AstNodeWrapper]) -> bool {
+        match operands.iter().find(|operand| operand.type_ref.is_wide()) {
+            Some(operand) => {
+                self.parser_error(NodeError(
+                    (*operand).clone(),
+                    format!(
+                        "operator {} is not supported on values of type {} yet",
+                        op.tk,
+                        operand.type_ref.to_string()
+                    ),
+                ));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parser::check_assignment_condition
+    ///
+    /// Warn if the condition of a statement is an assignment which is not parenthesized, as
+    /// `if (a = b)` is most likely a typo for `if (a == b)`. The assignment is still valid, and
+    /// the warning is not given for `while ((c = f()))`
+    ///
+    /// @in condition [&AstNodeWrapper]: condition to check
+    fn check_assignment_condition(&mut self, condition: &AstNodeWrapper) {
+        if let AstNode::BinaryNode(token, ..) = &condition.node {
+            if token.tk == Tk::Operator(Assign) && !condition.is_parenthesized {
+                self.parser_warning(NodeError(
+                    condition.clone(),
+                    String::from("assignment used as a condition, add parentheses if it is intended"),
+                ));
+            }
+        }
+    }
+
+    /// Parser::parser_warning
+    ///
+    /// Add a warning to the diagnostics of the parser
+    ///
+    /// @in error [ParserError]: type of error to handle
+    fn parser_warning(&mut self, error: ParserError) {
+        if let NodeError(node, string) = error {
+            let diagnostic = Diagnostic::warning("parser", string, Some(node.source_ref));
+            // Due to backtracking, a node might be parsed more than once: each warning is added once
+            if !self.diagnostics.contains(&diagnostic) {
+                self.diagnostics.push(diagnostic);
             }
-            _ => {}
         }
     }
 
     /// Parser::read_lines
     ///
-    /// Get all the linest from a required source file
+    /// Get all the linest from a required source file, or from the source provided with
+    /// `set_source`
     ///
     /// @in expected [&str]: file name
     /// @return [Vec<String>]: vector of all the strings in the file
     fn read_lines(&self, filename: &str) -> Vec<String> {
         let mut result = Vec::new();
 
-        for line in read_to_string(filename).unwrap().lines() {
+        let content = match &self.source {
+            Some(source) => source.clone(),
+            None => read_to_string(filename).unwrap(),
+        };
+        for line in content.lines() {
             result.push(line.to_string())
         }
 
@@ -2,7 +2,7 @@ mod test {
 
     #[test]
     fn parser_test_1() {
-        use crate::lexer::lexer_impl::Lexer;
+        use crate::lexer::lexer::Lexer;
         use crate::parser::parser::Parser;
 
         let input = String::from(
@@ -95,4 +95,960 @@ void main(){
             assert!(false);
         }
     }
+
+    #[test]
+    fn parser_test_jump_outside_loop() {
+        use crate::diagnostic::Severity;
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            "void main() { break; return; }",
+            "void main() { continue; return; }",
+            "void main() { u32 a = 0; if (a) { break; } return; }",
+            "u32 f() { { continue; } return 0; }",
+            "void main() { switch (1) { case 1: continue; } return; }",
+            "void main() { switch (1) { default: break; default: break; } return; }",
+        ];
+
+        for input in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+
+        // Without a source file, the error is still reported as a diagnostic with its location
+        let mut l = Lexer::new(String::from("void main() {\n  break;\n  return;\n}"), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        assert!(p.parse().is_none());
+        let diagnostics = p.get_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].message.contains("Cannot use break outside of loop"));
+        let source_ref = diagnostics[0].source_ref.clone().unwrap();
+        assert_eq!((source_ref.init_line, source_ref.init_char), (2, 3));
+    }
+
+    #[test]
+    fn parser_test_jump_inside_loop() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            "void main() { while (1) { break; } return; }",
+            "void main() { for (;;) { continue; } return; }",
+            "u32 f() { u32 a = 0; while (a) { if (a) { break; } else { continue; } } return a; } void main() { return; }",
+            "u8* f() { for (;;) { while (1) { break; } break; } return (u8*)0; } void main() { return; }",
+            "void main() { switch (1) { case 1: break; } return; }",
+            "void main() { while (1) { switch (1) { case 1: continue; default: break; } } return; }",
+        ];
+
+        for input in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_some());
+        }
+    }
+
+    #[test]
+    fn parser_test_switch() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let input = String::from(
+            "
+            u32 f(u32 x) {
+              u32 r = 0;
+              switch (x + 1) {
+                case 1:
+                case 2:
+                  r = 10;
+                  break;
+                case 'a':
+                  u32 k = 3;
+                  r = k;
+                default:
+                  r = r + 1;
+              }
+              return r;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let expected = String::from(
+            "u32 f(u32 x){
+  u32 r = 0;
+  switch((x + 1)){
+    case 1:
+    case 2:
+      (r = 10);
+      break;
+    case 'a':
+      u32 k = 3;
+      (r = k);
+    default:
+      (r = (r + 1));
+  }
+  return r;
+}
+void main(){
+  return;
+}
+",
+        );
+
+        let mut l = Lexer::new(input.clone(), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast_wrapped = p.parse();
+        assert!(ast_wrapped.is_some());
+        assert_eq!(expected, ast_wrapped.unwrap().to_string(0));
+    }
+
+    #[test]
+    fn parser_test_switch_labels() {
+        use crate::diagnostic::Severity;
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Labels which are not integer constants, or which repeat a previous value, are reported
+        // as errors on the label itself
+        let inputs = vec![
+            (
+                "u32 f(u32 x) {\n  switch (x) {\n    case x:\n      break;\n  }\n  return 0;\n}",
+                "integer constant",
+                3,
+                10,
+            ),
+            (
+                "u32 f(u32 x) {\n  switch (x) {\n    case 1:\n    case 1:\n      break;\n  }\n  return 0;\n}",
+                "Duplicate case value 1",
+                4,
+                10,
+            ),
+        ];
+
+        for (input, message, line, character) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none(), "{}", input);
+            // Recovering from the error might lead to further ones, the first is the one of the label
+            let diagnostics = p.get_diagnostics();
+            assert!(!diagnostics.is_empty(), "{}", input);
+            assert_eq!(diagnostics[0].severity, Severity::Error);
+            assert!(diagnostics[0].message.contains(message), "{}", diagnostics[0].message);
+            let source_ref = diagnostics[0].source_ref.clone().unwrap();
+            assert_eq!((source_ref.init_line, source_ref.init_char), (line, character), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_constant_expressions() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Case labels and sizes of arrays accept each form of literal: decimal, hexadecimal,
+        // binary, octal and characters (converted to u32 for the size of an array)
+        let input = String::from(
+            "
+            u32 f(u32 x) {
+              u8 a[0x10];
+              u8 b[0b101];
+              u8 c[017];
+              u8 d['A'];
+              switch (x) {
+                case 0x41: return 1;
+                case 0b11: return 2;
+                case 010: return 3;
+                case 'a': return 4;
+                case -5: return 5;
+              }
+              return 0;
+            }
+
+            void main() {
+              return;
+            }
+",
+        );
+
+        let expected = String::from(
+            "u32 f(u32 x){
+  u8 a[16];
+  u8 b[5];
+  u8 c[15];
+  u8 d[((u32)'A')];
+  switch(x){
+    case 65:
+      return 1;
+    case 3:
+      return 2;
+    case 8:
+      return 3;
+    case 'a':
+      return 4;
+    case (-5):
+      return 5;
+  }
+  return 0;
+}
+void main(){
+  return;
+}
+",
+        );
+
+        let mut l = Lexer::new(input.clone(), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast = p.parse().unwrap();
+        assert_eq!(expected, ast.to_string(0));
+
+        // Labels must be constants different from each other, even if written in different forms
+        let invalid_inputs = vec![
+            "u32 main() { u32 x = 2; switch (x) { case x: return 1; } return 0; }",
+            "u32 main() { switch (2) { case 'A': return 1; case 0x41: return 2; } return 0; }",
+            "u32 main() { switch (2) { case 8: return 1; case 010: return 2; } return 0; }",
+            "u32 main() { u8 n = (u8) 3; u32 a[n]; return 0; }",
+        ];
+
+        for input in invalid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+    }
+
+    #[test]
+    fn parser_test_types() {
+        use crate::ast::ast_node::AstNode;
+        use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Type errors are detected while building the ast
+        let inputs = vec![
+            "void main() { u32 a; (a + 1) = 2; return; }",
+            "void main() { u32 a; 3 = a; return; }",
+            "void main() { const u32 a = 3; a = 4; return; }",
+            "void main() { u8* b = 3; return; }",
+            "void main() { u32 a; u8* b; a = b; return; }",
+        ];
+
+        for input in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+
+        // Each expression node carries its type and whether it is an lvalue
+        let mut l = Lexer::new(String::from("void main() { u32* p; u32 a; a = p[0]; return; }"), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast = p.parse().unwrap();
+
+        let u32_type = TypeWrapper {
+            type_native: TypeNative::U32,
+            pointer: 0,
+            constant: false,
+        };
+
+        if let AstNode::DeclarationList(functions) = &ast.node {
+            if let AstNode::FuncDeclNode(_, _, _, body) = &functions[0].node {
+                if let AstNode::CompoundNode(statements) = &body.node {
+                    if let AstNode::ExprStatementNode(expr) = &statements[2].node {
+                        if let AstNode::BinaryNode(_, left, right) = &expr.node {
+                            assert_eq!(expr.type_ref, u32_type);
+                            assert!(left.is_lvalue);
+                            assert_eq!(left.type_ref, u32_type);
+                            assert!(right.is_lvalue);
+                            assert_eq!(right.type_ref, u32_type);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        panic!("The assignment was not found in the ast");
+    }
+
+    #[test]
+    fn parser_test_undeclared() {
+        use crate::diagnostic::Severity;
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            "void main() { a = 3; return; }",
+            "void main() { { u32 a; } a = 3; return; }",
+            "void f() { u32 x; return; } void main() { x = 1; return; }",
+            "void main() { g(); return; }",
+            "u32 a = b; void main() { return; }",
+        ];
+
+        for input in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+
+        // The error is a diagnostic with the location of the identifier, thus lirgen never sees it
+        let mut l = Lexer::new(String::from("u32 main() {\n  u32 b = 1;\n  return a + b;\n}"), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        assert!(p.parse().is_none());
+        let diagnostics = p.get_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].message.contains("identifier `a` not found"));
+        let source_ref = diagnostics[0].source_ref.clone().unwrap();
+        assert_eq!((source_ref.init_line, source_ref.init_char), (3, 10));
+    }
+
+    #[test]
+    fn parser_test_division_by_zero() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Dividing by a literal zero, even if casted, is an error
+        let invalid_inputs = vec![
+            "u32 main() { u32 a = 7; return a / 0; }",
+            "u32 main() { u32 a = 7; return a % 0; }",
+            "i32 main() { i32 a = -7; return a / (i32) 0; }",
+        ];
+
+        // The divisor is known only at runtime, or it is not zero
+        let valid_inputs = vec![
+            "u32 main() { u32 a = 7; u32 b = 0; return a / b; }",
+            "u32 main() { u32 a = 7; return a * 0 + a / 1; }",
+            "i32 main() { i32 a = -7; return a % (i32) 2; }",
+        ];
+
+        for input in invalid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+
+        for input in valid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_some());
+        }
+    }
+
+    #[test]
+    fn parser_test_forward_call() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Functions can be called before their definition, and can be mutually recursive
+        let valid_inputs = vec![
+            "u32 main() { return f(3); } u32 f(u32 a) { return a + 1; }",
+            "u32 even(u32 n) { if (n == 0) { return 1; } return odd(n - 1); }
+             u32 odd(u32 n) { if (n == 0) { return 0; } return even(n - 1); }
+             u32 main() { return even(10); }",
+            "u32 x = f(); u32 f() { return 2; } u32 main() { return x; }",
+            // A function returns implicitly at the end of its body
+            "void f() {} u32 main() { f(); return 0; }",
+            "u32 f(u32 a) { if (a > 2) { return 5; } } u32 main() { return f(3); }",
+        ];
+
+        // Signatures are still checked, and a function cannot be defined twice
+        let invalid_inputs = vec![
+            "u32 main() { return f(3, 4); } u32 f(u32 a) { return a; }",
+            "u32 main() { u32 *p = f(3); return 0; } u32 f(u32 a) { return a; }",
+            "u32 main() { return f(3); } u32 f(u32 a) { return a; } u32 f(u32 b) { return b; }",
+            "u32 f; u32 main() { return f(3); } u32 f(u32 a) { return a; }",
+        ];
+
+        for input in valid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_some());
+        }
+
+        for input in invalid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+    }
+
+    #[test]
+    fn parser_test_builtin() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // `__strcmp` can be called without being defined
+        let valid_inputs = vec![
+            "i32 main() { u8 a[4]; u8 b[4]; return __strcmp(a, b); }",
+            "i32 main() { u8 a[4]; u8 *p = &a[1]; return __strcmp(p, &a[0]) + __strcmp(a, a); }",
+        ];
+
+        // Its signature is checked, and it cannot be redefined
+        let invalid_inputs = vec![
+            "i32 main() { u8 a[4]; return __strcmp(a); }",
+            "i32 main() { u32 a[4]; return __strcmp(a, a); }",
+            "u32 main() { u8 a[4]; return __strcmp(a, a); }",
+            "i32 __strcmp(u8 *a, u8 *b) { return (i32) 0; } i32 main() { return (i32) 0; }",
+            "i32 main() { u32 __strcmp = 0; return (i32) __strcmp; }",
+        ];
+
+        for input in valid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_some());
+        }
+
+        for input in invalid_inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none());
+        }
+    }
+
+    #[test]
+    fn parser_test_declarations() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            ("void main() { return; }", true),
+            ("void f() { return; }", false),
+            ("u32 main; void f() { return; }", false),
+            ("void main() { return; } void main() { return; }", false),
+            ("void f() { return; } u32 f() { return 0; } void main() { return; }", false),
+            ("u32 f; void f() { return; } void main() { return; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid);
+        }
+    }
+
+    #[test]
+    fn parser_test_procedure_arguments() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            ("void main() { u8 c; f(1, &c); return; }", true),
+            ("void main() { f(1); return; }", false),
+            ("void main() { u8 c; f(1, &c, 2); return; }", false),
+            ("void main() { u8 c; f(c, &c); return; }", false),
+            ("void main() { u32 c; f(1, &c); return; }", false),
+            ("void main() { g(); return; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let program = format!("u32 g; u32 f(u32 a, u8* b) {{ return a; }} {}", input);
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid);
+        }
+    }
+
+    #[test]
+    fn parser_test_for_declaration() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            ("void main() { for (u32 i = 0; i < 3; i = i + 1) {} return; }", true),
+            (
+                "void main() { for (u32 i; i < 3; i = i + 1) {} for (u32 i = 1; i < 3; i = i + 1) {} return; }",
+                true,
+            ),
+            ("void main() { for (u32 i = 0; i < 3; i = i + 1) {} i = 3; return; }", false),
+            ("void main() { u32 i; for (u32 i = 0; i < 3; i = i + 1) {} return; }", false),
+            ("void main() { for (u32 i = 0 i < 3; i = i + 1) {} return; }", false),
+            ("void main() { for (u32 f() { return 0; } ; ) {} return; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid);
+        }
+    }
+
+    #[test]
+    fn parser_test_for_step() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let inputs = vec![
+            ("void f(u32 a) { return; } void main() { for (u32 i = 0; i < 3; f(i)) {} return; }", true),
+            (
+                "u32 f(u32 a) { return a; } void main() { for (u32 i = 0; i < 3; f(i = i + 1)) {} return; }",
+                true,
+            ),
+            ("void main() { u32 a; u32 b; for (a = 0; a < 3; b = a = a + 1) {} return; }", true),
+            ("void main() { u32 a; for (a = 0; a < 3; a + 1) {} return; }", true),
+            ("void main() { for (u32 i = 0; i < 3; u32 j = 0) {} return; }", false),
+            ("void main() { for (u32 i = 0; i < 3; i = i + 1;) {} return; }", false),
+            ("void main() { for (u32 i = 0; i < 3; return) {} return; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid);
+        }
+    }
+
+    #[test]
+    fn parser_test_assignment_lvalue() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Only identifiers, dereferences and subscripts can be on the left side of `=`
+        let inputs = vec![
+            ("x = 3;", true),
+            ("*p = 3;", true),
+            ("a[1] = 3;", true),
+            ("(a[1]) = 3;", true),
+            ("*&x = 3;", true),
+            ("x = a[0] = 3;", true),
+            ("3 = x;", false),
+            ("(x + 1) = 3;", false),
+            ("x = 3 = x;", false),
+            ("-x = 3;", false),
+            ("&x = p;", false),
+            ("f() = 3;", false),
+            ("(x < 1) = 3;", false),
+        ];
+
+        for (input, valid) in inputs {
+            let program = format!(
+                "u32 f() {{ return 1; }} void main() {{ u32 x; u32 a[2]; u32 *p = &x; {} return; }}",
+                input
+            );
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_const() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // A constant is initialized at its declaration and read as any other value, but it cannot
+        // be modified, not even through a pointer
+        let inputs = vec![
+            ("const u32 x = 3; return x;", true),
+            ("const u32 x = 3; u32 y = x; y = 4; return y;", true),
+            ("const u32 x = 3; const u32 *p = &x; return *p;", true),
+            ("const u32 x = 3; return h(&x);", true),
+            ("u32 y = 1; const u32 *p = &y; return *p;", true),
+            ("const u32 x = 3; u32 *p = (u32*) &x; return *p;", true),
+            ("const u32 x = 3; x = 4; return x;", false),
+            ("const u32 a[2]; a[0] = 1; return 0;", false),
+            ("u32 y = 1; const u32 *p = &y; *p = 3; return 0;", false),
+            ("const u32 x = 3; u32 *p = &x; return 0;", false),
+            ("const u32 x = 3; u32 *p; p = &x; return 0;", false),
+            ("const u32 x = 3; return g(&x);", false),
+            ("const u32 a[2]; return g(a);", false),
+        ];
+
+        for (input, valid) in inputs {
+            let program = format!(
+                "u32 g(u32 *p) {{ return *p; }} u32 h(const u32 *p) {{ return *p; }} u32 main() {{ {} }}",
+                input
+            );
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+
+        // A pointer to a constant cannot be returned as a pointer to a variable
+        let input = "u32 *k(const u32 *p) { return p; } void main() { return; }";
+        let mut l = Lexer::new(String::from(input), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        assert!(p.parse().is_none());
+    }
+
+    #[test]
+    fn parser_test_assignment_condition() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // An assignment is a valid condition, whose value is the assigned one, both parenthesized
+        // and not (in which case a warning is given)
+        let inputs = vec![
+            ("while ((c = next())) { s = s + c; }", true),
+            ("while (c = next()) { s = s + c; }", true),
+            ("if ((s = c)) { s = 1; }", true),
+            ("if (s = c) { s = 1; }", true),
+            ("for (c = 0; (s = next()); c = c + 1) { }", true),
+            ("if (s + 1 = c) { s = 1; }", false),
+            ("while ((next() = c)) { }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let program = format!("u32 next() {{ return 0; }} u32 main() {{ u32 c = 0; u32 s = 0; {} return s; }}", input);
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_implicit_conversions() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // An integer is converted to the type of the variable it is assigned to, with a warning if
+        // the conversion might change its value (that is, unless it is explicitly casted, it is
+        // not narrowing or it is a constant fitting the variable)
+        let inputs = vec![
+            ("u8 c = 300;", true),
+            ("u8 c = (u8) 300;", false),
+            ("u8 c = 200;", false),
+            ("i8 c = 200;", true),
+            ("i8 c = -5;", false),
+            ("u8 c = -5;", true),
+            ("bool c = 1;", false),
+            ("bool c = 2;", true),
+            ("u8 c; c = x;", true),
+            ("u8 c; c = (u8) x;", false),
+            ("u8 c; u8* p = &c; *p = x;", true),
+            ("u64 c = x;", false),
+            ("i32 c = x;", false),
+            ("u32 c = 'a';", false),
+            ("u32 c; u64 w; c = w;", true),
+        ];
+
+        for (input, warning) in inputs {
+            let program = format!("u32 x = 5; u32 main() {{ {} return 0; }}", input);
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_some(), "{}", input);
+            let warnings: Vec<_> = p.get_diagnostics().iter().filter(|d| d.message.contains("implicit conversion")).collect();
+            assert_eq!(warnings.len(), warning as usize, "{}", input);
+        }
+
+        // The warning cites the location and both types, while the value is wrapped in a cast
+        let mut l = Lexer::new(String::from("void main() {\n  u8 c = 300;\n  return;\n}"), false).unwrap();
+        let tokens = l.tokenize();
+        assert!(tokens.is_some());
+        let mut p = Parser::new(tokens.unwrap(), String::from(""));
+        let ast = p.parse().unwrap();
+        let diagnostics = p.get_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("from u32 to u8"));
+        let source_ref = diagnostics[0].source_ref.clone().unwrap();
+        assert_eq!((source_ref.init_line, source_ref.init_char), (2, 10));
+        assert!(ast.to_string(0).contains("u8 c = ((u8)300)"), "{}", ast.to_string(0));
+    }
+
+    #[test]
+    fn parser_test_prototypes() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // A prototype declares a function defined later or provided externally, which can then be
+        // called. All the declarations of a function must have the same signature
+        let inputs = vec![
+            ("u32 f(u32 a); u32 main() { return f(1); } u32 f(u32 a) { return a; }", true),
+            ("u32 main() { return f(1); } u32 f(u32 a) { return a; } u32 f(u32 b);", true),
+            ("u32 ext(u8 *p, u32 n); u32 main() { u8 c; return ext(&c, 1); }", true),
+            ("u32 f(u32 a); u32 f(u32 a); u32 main() { return f(1); } u32 f(u32 a) { return a; }", true),
+            ("i32 __strcmp(u8 *a, u8 *b); u32 main() { return 0; }", true),
+            ("u32 f(u32 a); u32 main() { return f(1); } u32 f(u8 a) { return 0; }", false),
+            ("u32 f(u32 a); u32 main() { return f(1); } i32 f(u32 a) { return (i32) a; }", false),
+            ("u32 f; u32 f(u32 a); u32 main() { return 0; }", false),
+            ("u32 f(u32 a); u32 main() { return a; }", false),
+            ("u32 ext(u32 a); u32 main() { return ext(); }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_function_pointers() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // The address of a function is a pointer to function, which can be stored, passed and
+        // called with the same signature of the function
+        let inputs = vec![
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; u8 c; return (*fp)(1, &c); }", true),
+            (
+                "u32 main() { u32 (*fp)(u32, u8*); fp = f; u8 c; return fp(1, &c) + (**fp)(2, &c); }",
+                true,
+            ),
+            ("u32 (*gp)(u32, u8*) = &f; u32 main() { u8 c; return (*gp)(1, &c); }", true),
+            (
+                "u32 apply(u32 (*op)(u32, u8*), u8 *p) { return (*op)(0, p); } u32 main() { u8 c; return apply(&f, &c); }",
+                true,
+            ),
+            ("u32 main() { void (*fp)() = &g; (*fp)(); return 0; }", true),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; return (u32) (fp == &f); }", true),
+            ("u32 main() { u32 (*fp)(u32, u8) = &f; return 0; }", false),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; return (*fp)(1); }", false),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; u8 c; u8* d = (*fp)(1, &c); return 0; }", false),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; fp = fp + 1; return 0; }", false),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; return fp[0]; }", false),
+            ("u32 main() { u32 (*fp)(u32, u8*) = &f; *fp = &f; return 0; }", false),
+            ("u32 main() { u32 x = f; return x; }", false),
+            ("u32 main() { u32 x = 0; return x(); }", false),
+            ("u32 main() { void (*fp)(u8) = &__putchar; return 0; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let program = format!("u32 f(u32 a, u8* b) {{ return a; }} void g() {{ return; }} {}", input);
+            let mut l = Lexer::new(program, false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+
+        // A wrong number of arguments is reported on the called expression, both for direct and
+        // indirect calls
+        let inputs = vec![
+            ("u32 f(u32 a, u8* b) {\n  return a;\n}\nu32 main() {\n  return f(1);\n}", 5, 10),
+            (
+                "u32 f(u32 a, u8* b) {\n  return a;\n}\nu32 main() {\n  u32 (*fp)(u32, u8*) = &f;\n  return fp(1);\n}",
+                6,
+                10,
+            ),
+        ];
+
+        for (input, line, character) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert!(p.parse().is_none(), "{}", input);
+            let diagnostics = p.get_diagnostics();
+            assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+            assert_eq!(diagnostics[0].message, "expected 2 arguments, found 1");
+            let source_ref = diagnostics[0].source_ref.clone().unwrap();
+            assert_eq!((source_ref.init_line, source_ref.init_char), (line, character), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_empty_parameter_list() {
+        use crate::ast::ast_node::AstNode;
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        // Both `()` and `(void)` declare a function without parameters
+        for list in ["()", "(void)"] {
+            let input = format!(
+                "u32 f{}; u32 f{} {{ return 1; }} u32 main{} {{ u32 (*fp){} = &f; return f() + fp(); }}",
+                list, list, list, list
+            );
+            let mut l = Lexer::new(input.clone(), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            let ast = p.parse().unwrap();
+            if let AstNode::DeclarationList(functions) = &ast.node {
+                assert_eq!(functions.len(), 3);
+                for function in functions {
+                    assert!(
+                        matches!(&function.node, AstNode::FuncDeclNode(_, _, params, _) if params.is_empty()),
+                        "{}",
+                        input
+                    );
+                }
+            } else {
+                panic!("The program is not a list of declarations");
+            }
+        }
+
+        // `void` is only allowed alone, while `void*` is a type as any other
+        let inputs = vec![
+            ("u32 f(void* p) { return 0; } u32 main() { return f((void*) 0); }", true),
+            ("u32 f(u8 (*g)(void), void* p) { return 0; } u32 main() { return 0; }", true),
+            ("u32 f(void) { return 0; } u32 main() { return f(1); }", false),
+            ("u32 f(void) { return 0; } u32 f(u32 a); u32 main() { return 0; }", false),
+            ("u32 f(void, u32 a) { return 0; } u32 main() { return 0; }", false),
+            ("u32 f(u32 a, void) { return 0; } u32 main() { return 0; }", false),
+        ];
+
+        for (input, valid) in inputs {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let tokens = l.tokenize();
+            assert!(tokens.is_some());
+            let mut p = Parser::new(tokens.unwrap(), String::from(""));
+            assert_eq!(p.parse().is_some(), valid, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parser_test_max_errors() {
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let input = "u32 main() { u32 a = ; u32 b = ; u32 c = ; u32 d = ; return 0; }";
+        let parse = |max_errors: Option<u32>| {
+            let mut l = Lexer::new(String::from(input), false).unwrap();
+            let mut p = Parser::new(l.tokenize().unwrap(), String::from(""));
+            if let Some(max_errors) = max_errors {
+                p.set_max_errors(max_errors);
+            }
+            assert!(p.parse().is_none());
+            p.get_errors_counter()
+        };
+
+        // Without a limit each statement is reported, otherwise parsing stops at the limit
+        assert_eq!(parse(None), 4);
+        assert_eq!(parse(Some(2)), 2);
+        assert_eq!(parse(Some(1)), 1);
+        assert_eq!(parse(Some(10)), 4);
+
+        // A source which ends while recovering from an error fails without terminating the process
+        let mut l = Lexer::new(String::from("u32 main() { u32 a = ; u32 b = 1;"), false).unwrap();
+        let mut p = Parser::new(l.tokenize().unwrap(), String::from(""));
+        assert!(p.parse().is_none());
+    }
+
+    #[test]
+    fn parser_test_wide_operations() {
+        use crate::diagnostic::Severity;
+        use crate::lexer::lexer::Lexer;
+        use crate::parser::parser::Parser;
+
+        let parse = |input: &str| {
+            let program = format!("u64 w = (u64)1; u64 z = (u64)2; {}", input);
+            let mut l = Lexer::new(program, false).unwrap();
+            let mut p = Parser::new(l.tokenize().unwrap(), String::from(""));
+            let result = p.parse();
+            (result, p.get_diagnostics().to_vec())
+        };
+
+        // Additions, subtractions, multiplications, unary operators and casts are supported on
+        // values of 64 bits, as well as returning them and pointing to them
+        let inputs = vec![
+            "u32 main() { u64 a = w + z * w - z; a = -a; a = ~a; a = !a; return (u32)a; }",
+            "u64 f(u64 *p) { return *p * (u64)3; } u32 main() { return (u32)f(&w); }",
+            "u32 main() { i64 a = (i64)-1; i32 b = (i32)a; return (u32)b; }",
+        ];
+        for input in inputs {
+            let (result, diagnostics) = parse(input);
+            assert!(result.is_some(), "{}", input);
+            assert!(diagnostics.iter().all(|d| d.severity != Severity::Error), "{}", input);
+        }
+
+        // The backend cannot translate the other operators, the conditions and the arguments of
+        // 64 bits yet, so they are rejected with an error instead of failing at code generation
+        let inputs = vec![
+            ("u32 main() { return (u32)(w < z); }", "operator < is not supported on values of type u64"),
+            ("u32 main() { return (u32)(w == (u64)2); }", "operator == is not supported"),
+            ("u32 main() { u32 a = 1; return (u32)(w != a); }", "operator != is not supported"),
+            ("u32 main() { return (u32)(w << z); }", "operator << is not supported"),
+            ("u32 main() { return (u32)(w >> z); }", "operator >> is not supported"),
+            ("u32 main() { return (u32)(w / z); }", "operator / is not supported"),
+            ("u32 main() { return (u32)(w % z); }", "operator % is not supported"),
+            ("u32 main() { return (u32)(w & z); }", "operator & is not supported"),
+            ("u32 main() { return (u32)(w | z); }", "operator | is not supported"),
+            ("u32 main() { return (u32)(w ^ z); }", "operator ^ is not supported"),
+            ("u32 main() { if (w) { return 1; } return 0; }", "type u64 cannot be used as a condition"),
+            (
+                "u32 main() { while (w) { w = w - (u64)1; } return 0; }",
+                "type u64 cannot be used as a condition",
+            ),
+            (
+                "u32 main() { for (; w; ) { w = w - (u64)1; } return 0; }",
+                "type u64 cannot be used as a condition",
+            ),
+            ("u32 main() { switch (w) { case 1: return 1; } return 0; }", "at most 32 bits"),
+            ("u32 main() { bool b = (bool)w; return (u32)b; }", "Cannot cast u64 into bool"),
+            ("u32 main() { bool b = w; return (u32)b; }", "Cannot convert u64 into bool"),
+            ("u64 id(u64 a) { return a; } u32 main() { return (u32)id(w); }", "parameters of type u64"),
+            ("u32 f(i64 a); u32 main() { return 0; }", "parameters of type i64"),
+            ("u32 main() { u32 (*p)(u64); return 0; }", "parameters of type u64"),
+        ];
+        for (input, message) in inputs {
+            let (result, diagnostics) = parse(input);
+            assert!(result.is_none(), "{}", input);
+            assert!(
+                diagnostics.iter().any(|d| d.severity == Severity::Error && d.message.contains(message)),
+                "{}: {:?}",
+                input,
+                diagnostics
+            );
+        }
+
+        // The error refers to the operand of 64 bits
+        let mut l = Lexer::new(
+            String::from("u32 main() {\n  u32 a = 1;\n  u64 b = (u64)2;\n  return (u32)(a < b);\n}"),
+            false,
+        )
+        .unwrap();
+        let mut p = Parser::new(l.tokenize().unwrap(), String::from(""));
+        assert!(p.parse().is_none());
+        let diagnostics = p.get_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let source_ref = diagnostics[0].source_ref.clone().unwrap();
+        assert_eq!((source_ref.init_line, source_ref.init_char), (4, 20));
+    }
 }
@@ -1,8 +1,8 @@
 use edit_distance;
 
 use crate::ast::ast_node::{AstNode, AstNodeWrapper};
-use crate::ast::type_wrapper::TypeWrapper;
-use crate::lexer::token::Tk;
+use crate::ast::type_wrapper::{TypeNative, TypeWrapper};
+use crate::lexer::token::{Tk, Token};
 
 /// Declaration
 ///
@@ -15,6 +15,81 @@ pub struct Declaration {
     pub arguments: Vec<TypeWrapper>, // Types of arguments
 }
 
+impl Declaration {
+    /// Declaration::builtins
+    ///
+    /// Get the functions which are available without being defined in the program. Their code is
+    /// added by the backend, once, if they are called:
+    /// - `i32 __strcmp(u8 *a, u8 *b)`: compare two strings terminated by zero, returning 0 if they
+    ///   are equal, a negative value if `a` comes first and a positive value otherwise.
+    /// - `void __trap()`: stop the execution because of a runtime error, with an endless loop. It is
+    ///   also called by the accesses out of range with `--bounds-check`.
+    /// - `void __putchar(u8 c)`: print a character through the system call 11 of the simulator
+    ///   (`ecall` with the number in `a7` and the character in `a0`). It is not a call.
+    ///
+    /// @return [Vec<Declaration>]: declarations of the built-in functions
+    pub fn builtins() -> Vec<Declaration> {
+        let string = TypeWrapper {
+            type_native: TypeNative::U8,
+            pointer: 1,
+            constant: false,
+        };
+        vec![
+            Declaration {
+                name: "__strcmp".to_string(),
+                is_function: true,
+                return_type: TypeWrapper {
+                    type_native: TypeNative::I32,
+                    pointer: 0,
+                    constant: false,
+                },
+                arguments: vec![string.clone(), string],
+            },
+            Declaration {
+                name: "__trap".to_string(),
+                is_function: true,
+                return_type: TypeWrapper {
+                    type_native: TypeNative::Void,
+                    pointer: 0,
+                    constant: false,
+                },
+                arguments: vec![],
+            },
+            Declaration {
+                name: "__putchar".to_string(),
+                is_function: true,
+                return_type: TypeWrapper {
+                    type_native: TypeNative::Void,
+                    pointer: 0,
+                    constant: false,
+                },
+                arguments: vec![TypeWrapper {
+                    type_native: TypeNative::U8,
+                    pointer: 0,
+                    constant: false,
+                }],
+            },
+        ]
+    }
+
+    /// Declaration::get_type
+    ///
+    /// Get the type of the identifier in an expression: the type of a variable, or a pointer to
+    /// function for a function, as a function identifier which is not called is its address
+    ///
+    /// @return [TypeWrapper]: type of the identifier
+    pub fn get_type(&self) -> TypeWrapper {
+        if !self.is_function {
+            return self.return_type.clone();
+        }
+        TypeWrapper {
+            type_native: TypeNative::Function(Box::new(self.return_type.clone()), self.arguments.clone()),
+            pointer: 1,
+            constant: false,
+        }
+    }
+}
+
 // Symbol table
 //
 // Stores the symbols in the current scope
@@ -37,6 +112,8 @@ impl SymbolTable {
             to_add: Vec::new(),
         };
         res.add_scope();
+        // The built-in functions are part of the global scope
+        res.matrix[0].append(&mut Declaration::builtins());
         res
     }
 
@@ -130,7 +207,9 @@ impl SymbolTable {
 
     /// SymbolTable::check_procedure
     ///
-    /// Check whether the procedure invoked is valid
+    /// Check whether the procedure invoked is valid. The procedure is either a function identifier
+    /// or an expression of type pointer to function (such as `fp` or `(*fp)`), whose signature is
+    /// the one of its type
     ///
     /// @in primary [&AstNodeWrapper]: primary node, left side of the procedure postfix operator
     /// @in args [&Vec<AstNodeWrapper>]: List of arguments
@@ -138,39 +217,48 @@ impl SymbolTable {
     /// which caused the error together with an error message. Otherwise it returns the declaration
     /// of the function
     pub fn check_procedure(&self, primary: &AstNodeWrapper, args: &Vec<AstNodeWrapper>) -> Result<Declaration, (AstNodeWrapper, String, String)> {
-        // Primary must be an indentifier
-        let mut identifier = "";
-        if let AstNode::PrimaryNode(n) = &primary.node {
-            if let Tk::Identifier(id) = &n.tk {
-                identifier = id;
+        let decl = if let TypeNative::Function(return_type, arguments) = &primary.type_ref.type_native {
+            if !primary.type_ref.is_function_pointer() {
+                return Err((
+                    primary.clone(),
+                    String::from("function"),
+                    format!("type {}", primary.type_ref.to_string()),
+                ));
             }
-        }
-        if identifier == "" {
-            return Err((primary.clone(), String::from("function identifier"), String::from("expression")));
-        }
-
-        // As an identifier, it must be a function
-        let decl = self.search_definition(&identifier.to_string()).unwrap();
-        if !decl.is_function {
+            // A function identifier is called directly (the name of the declaration is the one of
+            // the function), otherwise the call is indirect (the name is empty)
+            let mut name = String::from("");
+            if let AstNode::PrimaryNode(Token { tk: Tk::Identifier(id), .. }) = &primary.node {
+                if self.search_definition(id).is_ok_and(|d| d.is_function) {
+                    name = id.to_string();
+                }
+            }
+            Declaration {
+                name,
+                is_function: true,
+                return_type: *return_type.clone(),
+                arguments: arguments.clone(),
+            }
+        } else if let AstNode::PrimaryNode(Token { tk: Tk::Identifier(_), .. }) = &primary.node {
             return Err((primary.clone(), String::from("function identifier"), String::from("variable identifier")));
-        }
+        } else {
+            return Err((primary.clone(), String::from("function identifier"), String::from("expression")));
+        };
 
         // Number of arguments must be appropriate
         if decl.arguments.len() != args.len() {
-            return Err((
-                primary.clone(),
-                String::from(format!("{} arguments", decl.arguments.len())),
-                String::from(format!("{}", args.len())),
-            ));
+            return Err((primary.clone(), format!("{} arguments", decl.arguments.len()), args.len().to_string()));
         }
 
         // Type of arguments must match
         for i in 0..decl.arguments.len() {
-            if !TypeWrapper::are_compatible(&decl.arguments[i], &args[i].type_ref) {
+            if !TypeWrapper::are_compatible(&decl.arguments[i], &args[i].type_ref)
+                || TypeWrapper::discards_const(&decl.arguments[i], &args[i].type_ref)
+            {
                 return Err((
                     args[i].clone(),
-                    String::from(format!("type {}", decl.arguments[i].to_string())),
-                    String::from(format!("type {}", args[i].type_ref.to_string())),
+                    format!("type {}", decl.arguments[i].to_string()),
+                    format!("type {}", args[i].type_ref.to_string()),
                 ));
             }
         }